@@ -28,6 +28,7 @@ defmodule LeaxerCore.Application do
   15. **LeaxerCore.Workers.StableDiffusionServer** - Singleton HTTP server mode (keeps model in VRAM)
   16. **LeaxerCore.Workers.LLM** - Text generation via llama.cpp (depends on ProcessTracker)
   17. **LeaxerCoreWeb.Endpoint** - Phoenix HTTP/WebSocket server (depends on PubSub)
+  18. **LeaxerCore.Control** - Control channel to the desktop shell (only when started by it)
 
   ### Restart Behavior
 
@@ -67,6 +68,8 @@ defmodule LeaxerCore.Application do
     # Initialize ETS table for stateful nodes (Counter, RoundRobin, etc.)
     :ets.new(:node_state, [:set, :public, :named_table])
 
+    LeaxerCore.Control.notify("progress", %{message: "Starting services..."})
+
     children = [
       LeaxerCoreWeb.Telemetry,
       {DNSCluster, query: Application.get_env(:leaxer_core, :dns_cluster_query) || :ignore},
@@ -108,6 +111,9 @@ defmodule LeaxerCore.Application do
       LeaxerCoreWeb.Endpoint
     ]
 
+    # Reports readiness to the desktop shell, so it has to come after the endpoint
+    children = if LeaxerCore.Control.enabled?(), do: children ++ [LeaxerCore.Control], else: children
+
     # See https://hexdocs.pm/elixir/Supervisor.html
     # for other strategies and supported options
     opts = [strategy: :one_for_one, name: LeaxerCore.Supervisor]
@@ -0,0 +1,185 @@
+defmodule LeaxerCore.Control do
+  @moduledoc """
+  JSON line control channel to the desktop shell over stdin/stdout.
+
+  Enabled when the shell starts the release with `LEAXER_CONTROL=stdio`.
+
+  ## Protocol
+
+  The shell writes one JSON object per line to stdin:
+
+      {"id": 1, "cmd": "status"}
+
+  Supported commands are `status`, `shutdown`, `reload_config`, `set_device_tokens` and
+  `set_extensions`.
+  Replies and notifications are written to stdout as lines starting with
+  `LEAXER_CONTROL `, so the shell can tell them apart from log output:
+
+      LEAXER_CONTROL {"type": "reply", "id": 1, "ok": true, "result": {...}}
+      LEAXER_CONTROL {"type": "event", "event": "ready"}
+
+  The backend asks the shell in turn with `ask/3`, which writes a `request` line and
+  waits for the shell's `reply` line on stdin:
+
+      LEAXER_CONTROL {"type": "request", "id": 1, "cmd": "pair", "args": {...}}
+      {"type": "reply", "id": 1, "ok": true, "result": {...}}
+
+  When stdin closes the shell is gone, and the backend stops instead of lingering as
+  an orphan.
+  """
+
+  use GenServer
+  require Logger
+
+  @prefix "LEAXER_CONTROL "
+
+  @doc """
+  Whether the shell started this backend with a control channel.
+  """
+  def enabled?, do: System.get_env("LEAXER_CONTROL") == "stdio"
+
+  @doc """
+  Sends a notification to the shell. No-op without a control channel.
+  """
+  def notify(event, payload \\ %{}) do
+    if enabled?() do
+      write(Map.merge(payload, %{type: "event", event: event}))
+    end
+
+    :ok
+  end
+
+  @doc """
+  Sends `cmd` to the shell and waits up to `timeout` ms for its result. Returns
+  `{:error, :unavailable}` without a control channel.
+  """
+  def ask(cmd, args \\ %{}, timeout \\ 10_000) do
+    if enabled?() and Process.whereis(__MODULE__) do
+      try do
+        GenServer.call(__MODULE__, {:ask, cmd, args, timeout}, timeout + 1_000)
+      catch
+        :exit, _ -> {:error, "The desktop app did not answer"}
+      end
+    else
+      {:error, :unavailable}
+    end
+  end
+
+  def start_link(opts) do
+    GenServer.start_link(__MODULE__, opts, name: __MODULE__)
+  end
+
+  @impl true
+  def init(_opts) do
+    parent = self()
+    # Reading stdin blocks, so it happens in a linked process
+    spawn_link(fn -> read_loop(parent) end)
+    # Started after the endpoint, so everything is up by now
+    notify("ready")
+    {:ok, %{pending: %{}, next_id: 1}}
+  end
+
+  @impl true
+  def handle_call({:ask, cmd, args, timeout}, from, state) do
+    id = state.next_id
+    write(%{type: "request", id: id, cmd: cmd, args: args})
+    Process.send_after(self(), {:expire, id}, timeout)
+    {:noreply, %{state | pending: Map.put(state.pending, id, from), next_id: id + 1}}
+  end
+
+  defp read_loop(parent) do
+    case IO.read(:stdio, :line) do
+      line when is_binary(line) ->
+        send(parent, {:line, line})
+        read_loop(parent)
+
+      _eof_or_error ->
+        send(parent, :closed)
+    end
+  end
+
+  @impl true
+  def handle_info({:line, line}, state) do
+    case Jason.decode(line) do
+      {:ok, %{"type" => "reply", "id" => id} = reply} ->
+        {from, pending} = Map.pop(state.pending, id)
+        if from, do: GenServer.reply(from, reply_result(reply))
+        {:noreply, %{state | pending: pending}}
+
+      {:ok, %{"cmd" => cmd} = request} ->
+        reply(request["id"], handle_command(cmd, request["args"] || %{}))
+        {:noreply, state}
+
+      _ ->
+        Logger.warning("[Control] Ignoring malformed message: #{inspect(line)}")
+        {:noreply, state}
+    end
+  end
+
+  def handle_info({:expire, id}, state) do
+    {from, pending} = Map.pop(state.pending, id)
+    if from, do: GenServer.reply(from, {:error, "The desktop app did not answer"})
+    {:noreply, %{state | pending: pending}}
+  end
+
+  def handle_info(:closed, state) do
+    Logger.info("[Control] Shell closed the control channel, stopping")
+    System.stop()
+    {:noreply, state}
+  end
+
+  @doc false
+  def handle_command("status", _args) do
+    {wall_clock_ms, _} = :erlang.statistics(:wall_clock)
+
+    {:ok,
+     %{
+       version: to_string(Application.spec(:leaxer_core, :vsn)),
+       uptime_ms: wall_clock_ms,
+       memory_bytes: :erlang.memory(:total),
+       processes: :erlang.system_info(:process_count)
+     }}
+  end
+
+  def handle_command("shutdown", _args) do
+    Logger.info("[Control] Shutdown requested")
+
+    Task.Supervisor.start_child(LeaxerCore.TaskSupervisor, fn ->
+      # Give time for the reply to be written
+      Process.sleep(200)
+      System.stop()
+    end)
+
+    {:ok, %{}}
+  end
+
+  def handle_command("reload_config", _args) do
+    # Settings are read from config.json on every access; only derived state is cached
+    LeaxerCore.ComputeBackend.clear_cache()
+    {:ok, %{}}
+  end
+
+  def handle_command("set_device_tokens", %{"hashes" => hashes}) when is_list(hashes) do
+    LeaxerCore.Pairing.set_token_hashes(hashes)
+    {:ok, %{}}
+  end
+
+  def handle_command("set_extensions", %{"urls" => urls}) when is_map(urls) do
+    LeaxerCore.Extensions.set_urls(urls)
+    {:ok, %{}}
+  end
+
+  def handle_command(cmd, _args), do: {:error, "Unknown command: #{cmd}"}
+
+  @doc false
+  def reply_result(%{"ok" => true} = reply), do: {:ok, reply["result"]}
+  def reply_result(reply), do: {:error, reply["error"] || "The desktop app reported an error"}
+
+  defp reply(nil, _result), do: :ok
+
+  defp reply(id, {:ok, result}), do: write(%{type: "reply", id: id, ok: true, result: result})
+
+  defp reply(id, {:error, error}), do: write(%{type: "reply", id: id, ok: false, error: error})
+
+  defp write(message), do: IO.puts(@prefix <> Jason.encode!(message))
+end
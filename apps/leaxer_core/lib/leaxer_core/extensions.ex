@@ -0,0 +1,55 @@
+defmodule LeaxerCore.Extensions do
+  @moduledoc """
+  Sidecar extensions run by the desktop shell.
+
+  The shell starts the programs listed under `extensions` in its config.json and only
+  announces the ones that are up and answer their health check: `LEAXER_EXTENSIONS`
+  (a JSON object of name to base URL) at start, and the `set_extensions` control command
+  whenever that set changes. Without the shell there are none.
+  """
+
+  require Logger
+
+  @key {__MODULE__, :urls}
+
+  @doc """
+  Replaces the known extensions with `urls`, a map of name to base URL.
+  """
+  def set_urls(urls) when is_map(urls) do
+    urls = for {name, url} <- urls, is_binary(name) and is_binary(url), into: %{}, do: {name, url}
+    :persistent_term.put(@key, urls)
+  end
+
+  @doc """
+  Name to base URL of every extension that can be reached now.
+  """
+  def urls do
+    case :persistent_term.get(@key, nil) do
+      nil ->
+        set_urls(from_env())
+        :persistent_term.get(@key)
+
+      urls ->
+        urls
+    end
+  end
+
+  @doc """
+  Base URL of the extension called `name`, or nil when it isn't running.
+  """
+  def url(name), do: Map.get(urls(), name)
+
+  defp from_env do
+    with json when is_binary(json) <- System.get_env("LEAXER_EXTENSIONS"),
+         {:ok, urls} when is_map(urls) <- Jason.decode(json) do
+      urls
+    else
+      nil ->
+        %{}
+
+      _ ->
+        Logger.warning("[Extensions] Ignoring malformed LEAXER_EXTENSIONS")
+        %{}
+    end
+  end
+end
@@ -0,0 +1,62 @@
+defmodule LeaxerCore.Pairing do
+  @moduledoc """
+  Devices paired with a backend exposed on the network.
+
+  The desktop shell runs the pairing: it shows the user a short-lived PIN, checks the PIN
+  a device sends to `POST /api/pairing` (relayed with `LeaxerCore.Control.ask/3`), and
+  issues the device a token. The backend only knows the SHA-256 hashes of those tokens,
+  from `LEAXER_DEVICE_TOKENS` at start and the shell's `set_device_tokens` command later.
+
+  With `LEAXER_PAIRING=required`, set by the shell when it exposes the backend, other
+  machines need a paired device's token; requests from this machine never do.
+  """
+
+  @key {__MODULE__, :token_hashes}
+
+  @doc """
+  Whether other machines need a device token.
+  """
+  def required?, do: System.get_env("LEAXER_PAIRING") == "required"
+
+  @doc """
+  Replaces the hashes of the paired devices' tokens.
+  """
+  def set_token_hashes(hashes) when is_list(hashes) do
+    :persistent_term.put(@key, MapSet.new(hashes, &String.downcase/1))
+  end
+
+  @doc """
+  Whether `token` belongs to a paired device.
+  """
+  def authorized?(token) when is_binary(token) and token != "" do
+    hash = :crypto.hash(:sha256, token) |> Base.encode16(case: :lower)
+    MapSet.member?(token_hashes(), hash)
+  end
+
+  def authorized?(_token), do: false
+
+  @doc """
+  Whether a request from `remote_ip` carrying `token` may reach the backend.
+  """
+  def allowed?(remote_ip, token) do
+    not required?() or loopback?(remote_ip) or authorized?(token)
+  end
+
+  def loopback?({127, _, _, _}), do: true
+  def loopback?({0, 0, 0, 0, 0, 0, 0, 1}), do: true
+  def loopback?(_), do: false
+
+  defp token_hashes do
+    case :persistent_term.get(@key, nil) do
+      nil ->
+        (System.get_env("LEAXER_DEVICE_TOKENS") || "")
+        |> String.split(",", trim: true)
+        |> set_token_hashes()
+
+        :persistent_term.get(@key)
+
+      hashes ->
+        hashes
+    end
+  end
+end
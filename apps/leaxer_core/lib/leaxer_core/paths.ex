@@ -2,12 +2,14 @@ defmodule LeaxerCore.Paths do
   @moduledoc """
   Manages user data directory paths for Leaxer.
 
-  By default, user data is stored in `~/Documents/Leaxer/`. This can be
-  overridden by setting the `LEAXER_USER_DIR` environment variable.
+  By default, user data is stored in the platform's data directory
+  (`%LOCALAPPDATA%\\Leaxer`, `~/Library/Application Support/Leaxer` or
+  `$XDG_DATA_HOME/Leaxer`). This can be overridden by setting the
+  `LEAXER_USER_DIR` environment variable.
 
   ## Directory Structure
 
-      ~/Documents/Leaxer/
+      <user data dir>/
       ├── custom_nodes/     # Custom node plugins (.ex files)
       ├── models/           # ML models (Stable Diffusion, LLMs, etc.)
       ├── workflows/        # Saved workflows (.json files)
@@ -23,7 +25,7 @@ defmodule LeaxerCore.Paths do
   3. Restart Leaxer
 
   Example custom node structure:
-      ~/Documents/Leaxer/custom_nodes/
+      <user data dir>/custom_nodes/
       ├── my_custom_node.ex
       └── leaxer-community-pack/
           ├── image_effects.ex
@@ -47,8 +49,11 @@ defmodule LeaxerCore.Paths do
   def default_user_dir do
     case :os.type() do
       {:unix, :darwin} ->
-        # macOS: ~/Documents/Leaxer
-        Path.expand("~/Documents/Leaxer")
+        # macOS: ~/Library/Application Support/Leaxer
+        with_legacy_fallback(
+          Path.expand("~/Library/Application Support/Leaxer"),
+          Path.expand("~/Documents/Leaxer")
+        )
 
       {:unix, _} ->
         # Linux: prefer XDG spec, fallback to ~/.local/share/Leaxer
@@ -56,12 +61,23 @@ defmodule LeaxerCore.Paths do
         Path.join(xdg_data, "Leaxer")
 
       {:win32, _} ->
-        # Windows: ~/Documents/Leaxer
+        # Windows: %LOCALAPPDATA%\Leaxer
         user_profile = System.get_env("USERPROFILE") || Path.expand("~")
-        Path.join([user_profile, "Documents", "Leaxer"])
+        local_app_data = System.get_env("LOCALAPPDATA") || Path.join([user_profile, "AppData", "Local"])
+
+        with_legacy_fallback(
+          Path.join(local_app_data, "Leaxer"),
+          Path.join([user_profile, "Documents", "Leaxer"])
+        )
     end
   end
 
+  # Older versions kept data in Documents/Leaxer. The desktop app moves it on launch;
+  # until then it stays in use.
+  defp with_legacy_fallback(dir, legacy) do
+    if not File.dir?(dir) and File.dir?(legacy), do: legacy, else: dir
+  end
+
   @doc """
   Returns the directory for custom node plugins.
   """
@@ -99,7 +99,8 @@ defmodule LeaxerCore.Settings do
       local_ips: get_local_ips(),
       current_binding: get_current_binding(),
       backend_port: get_port(),
-      frontend_port: get_frontend_port()
+      frontend_port: get_frontend_port(),
+      tls: get_tls()
     }
   end
 
@@ -196,6 +197,14 @@ defmodule LeaxerCore.Settings do
     Application.get_env(:leaxer_core, LeaxerCoreWeb.Endpoint)[:http][:port] || 4000
   end
 
+  # HTTPS listener for other devices, when the desktop shell gave the backend a certificate
+  defp get_tls do
+    case Application.get_env(:leaxer_core, LeaxerCoreWeb.Endpoint)[:https] do
+      nil -> nil
+      https -> %{port: https[:port], fingerprint: System.get_env("LEAXER_TLS_FINGERPRINT")}
+    end
+  end
+
   defp get_frontend_port do
     # Frontend port - defaults to 8888 for Vite dev server
     # Can be overridden via UI_PORT env var
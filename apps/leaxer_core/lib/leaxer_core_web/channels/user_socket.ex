@@ -7,9 +7,17 @@ defmodule LeaxerCoreWeb.UserSocket do
   channel "hardware:*", LeaxerCoreWeb.HardwareChannel
   channel "chat:*", LeaxerCoreWeb.ChatChannel
 
+  # Sockets skip the endpoint's plugs, so paired devices are checked here too
+  # (see `LeaxerCoreWeb.Plugs.DevicePairing`)
   @impl true
-  def connect(_params, socket, _connect_info) do
-    {:ok, socket}
+  def connect(params, socket, connect_info) do
+    remote_ip = get_in(connect_info, [:peer_data, :address])
+
+    if LeaxerCore.Pairing.allowed?(remote_ip, params["device_token"]) do
+      {:ok, socket}
+    else
+      :error
+    end
   end
 
   @impl true
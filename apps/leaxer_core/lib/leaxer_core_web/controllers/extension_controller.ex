@@ -0,0 +1,13 @@
+defmodule LeaxerCoreWeb.ExtensionController do
+  use LeaxerCoreWeb, :controller
+
+  @doc """
+  GET /api/extensions
+
+  The desktop shell's sidecar extensions that can be reached now, as
+  `{"extensions": {"<name>": "<base URL>"}}`.
+  """
+  def index(conn, _params) do
+    json(conn, %{extensions: LeaxerCore.Extensions.urls()})
+  end
+end
@@ -0,0 +1,38 @@
+defmodule LeaxerCoreWeb.PairingController do
+  use LeaxerCoreWeb, :controller
+
+  alias LeaxerCore.Control
+
+  @doc """
+  POST /api/pairing
+
+  Pairs the calling device with the PIN shown in the desktop app.
+
+  Body: `{"pin": "123456", "name": "Kitchen tablet"}`. Answers `{"id", "token"}`; the
+  device sends the token with every request (see `LeaxerCoreWeb.Plugs.DevicePairing`).
+  """
+  def pair(conn, %{"pin" => pin} = params) when is_binary(pin) do
+    case Control.ask("pair", %{pin: pin, name: params["name"] || ""}) do
+      {:ok, %{"token" => token, "id" => id}} ->
+        conn
+        |> put_status(:ok)
+        |> json(%{id: id, token: token})
+
+      {:error, :unavailable} ->
+        conn
+        |> put_status(:service_unavailable)
+        |> json(%{error: "Pairing needs the Leaxer desktop app"})
+
+      {:error, reason} ->
+        conn
+        |> put_status(:forbidden)
+        |> json(%{error: reason})
+    end
+  end
+
+  def pair(conn, _params) do
+    conn
+    |> put_status(:bad_request)
+    |> json(%{error: "pin is required"})
+  end
+end
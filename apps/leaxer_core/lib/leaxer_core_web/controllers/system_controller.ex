@@ -3,11 +3,15 @@ defmodule LeaxerCoreWeb.SystemController do
   REST API controller for system operations.
 
   Provides endpoints for server management operations like restart and cleanup.
+  They are only accepted from the local machine, and from the desktop shell when it
+  started the server (see `LeaxerCoreWeb.Plugs.ShellToken`); the UI asks the shell.
   """
   use LeaxerCoreWeb, :controller
 
   require Logger
 
+  alias LeaxerCoreWeb.Plugs.ShellToken
+
   @doc """
   POST /api/system/restart
 
@@ -15,6 +19,10 @@ defmodule LeaxerCoreWeb.SystemController do
   Sends response first, then restarts after a short delay.
   """
   def restart(conn, _params) do
+    if from_local_shell?(conn), do: do_restart(conn), else: forbidden(conn, "Restart")
+  end
+
+  defp do_restart(conn) do
     # Schedule restart after response is sent
     Task.Supervisor.start_child(LeaxerCore.TaskSupervisor, fn ->
       # Give time for response to be sent
@@ -31,6 +39,45 @@ defmodule LeaxerCoreWeb.SystemController do
     })
   end
 
+  @doc """
+  POST /api/system/shutdown
+
+  Stops the VM gracefully (applications shut down in order, so pending
+  writes are flushed). Used by the desktop shell before it falls back to
+  killing the process.
+  """
+  def shutdown(conn, _params) do
+    if from_local_shell?(conn) do
+      Logger.info("[SystemController] Shutdown requested")
+
+      Task.Supervisor.start_child(LeaxerCore.TaskSupervisor, fn ->
+        # Give time for response to be sent
+        Process.sleep(200)
+        System.stop()
+      end)
+
+      conn
+      |> put_status(:ok)
+      |> json(%{message: "Server shutdown initiated"})
+    else
+      forbidden(conn, "Shutdown")
+    end
+  end
+
+  defp from_local_shell?(conn) do
+    loopback?(conn.remote_ip) and ShellToken.from_shell?(conn)
+  end
+
+  defp forbidden(conn, operation) do
+    conn
+    |> put_status(:forbidden)
+    |> json(%{error: "#{operation} is only allowed from the desktop app on this machine"})
+  end
+
+  defp loopback?({127, _, _, _}), do: true
+  defp loopback?({0, 0, 0, 0, 0, 0, 0, 1}), do: true
+  defp loopback?(_), do: false
+
   @doc """
   POST /api/system/cleanup
 
@@ -41,6 +88,10 @@ defmodule LeaxerCoreWeb.SystemController do
   - Triggers cache cleanup
   """
   def cleanup(conn, _params) do
+    if from_local_shell?(conn), do: do_cleanup(conn), else: forbidden(conn, "Cleanup")
+  end
+
+  defp do_cleanup(conn) do
     Logger.info("[SystemController] Starting system cleanup...")
 
     # 0. Cancel any running job first to avoid stale state
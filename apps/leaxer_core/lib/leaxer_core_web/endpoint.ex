@@ -7,8 +7,8 @@ defmodule LeaxerCoreWeb.Endpoint do
   @session_options [
     store: :cookie,
     key: "_leaxer_core_key",
-    # Will be overridden by runtime config for security
-    signing_salt: "LeaxerSession",
+    # Per installation, from SIGNING_SALT (see config/runtime.exs)
+    signing_salt: {__MODULE__, :signing_salt, []},
     same_site: "Lax"
   ]
 
@@ -19,9 +19,11 @@ defmodule LeaxerCoreWeb.Endpoint do
       # Check connection every 30 seconds
       check_origin: false,
       # Increase max frame size for file attachments (1MB)
-      max_frame_size: 1_048_576
+      max_frame_size: 1_048_576,
+      connect_info: [:peer_data]
     ],
-    longpoll: false
+    # Used by the desktop shell's socket proxy, which can't carry WebSockets
+    longpoll: [check_origin: false, connect_info: [:peer_data]]
 
   # Serve at "/" the static files from "priv/static" directory.
   #
@@ -53,6 +55,12 @@ defmodule LeaxerCoreWeb.Endpoint do
   plug Plug.Head
   plug Plug.Session, @session_options
 
+  # Tells the desktop shell's requests apart from other local programs'
+  plug LeaxerCoreWeb.Plugs.ShellToken
+
+  # Other machines need a paired device's token when the shell requires pairing
+  plug LeaxerCoreWeb.Plugs.DevicePairing
+
   # Private Network Access - required for Tauri desktop app
   plug LeaxerCoreWeb.Plugs.PrivateNetworkAccess
 
@@ -60,4 +68,9 @@ defmodule LeaxerCoreWeb.Endpoint do
   plug CORSPlug
 
   plug LeaxerCoreWeb.Router
+
+  @doc false
+  def signing_salt do
+    Application.fetch_env!(:leaxer_core, __MODULE__)[:live_view][:signing_salt]
+  end
 end
@@ -0,0 +1,43 @@
+defmodule LeaxerCoreWeb.Plugs.DevicePairing do
+  @moduledoc """
+  Refuses requests from other machines without a paired device's token when pairing is
+  required (see `LeaxerCore.Pairing`).
+
+  The token goes in an `authorization: Bearer <token>` header or, where headers can't be
+  set, a `device_token` query parameter. The health check and pairing itself stay open.
+  """
+
+  import Plug.Conn
+
+  alias LeaxerCore.Pairing
+
+  @open_paths [["api", "health"], ["api", "pairing"]]
+
+  def init(opts), do: opts
+
+  def call(conn, _opts) do
+    cond do
+      conn.path_info in @open_paths ->
+        conn
+
+      Pairing.allowed?(conn.remote_ip, device_token(conn)) ->
+        conn
+
+      true ->
+        conn
+        |> put_resp_content_type("application/json")
+        |> send_resp(401, ~s({"error":"pairing_required"}))
+        |> halt()
+    end
+  end
+
+  @doc """
+  The device token a request carries, if any.
+  """
+  def device_token(conn) do
+    case get_req_header(conn, "authorization") do
+      ["Bearer " <> token | _] -> token
+      _ -> fetch_query_params(conn).query_params["device_token"]
+    end
+  end
+end
@@ -0,0 +1,54 @@
+defmodule LeaxerCoreWeb.Plugs.ShellToken do
+  @moduledoc """
+  Recognizes requests from the desktop shell.
+
+  The shell spawns the backend with a random `LEAXER_SHELL_TOKEN` and sends it in the
+  `x-leaxer-shell-token` header of its own requests (health checks, shutdown). A request
+  with a wrong token is refused, so a shell never takes another server for its backend,
+  and `from_shell?/1` lets controllers keep shell-only operations to the shell.
+
+  Without the variable (a standalone server) there is no shell to tell apart, and every
+  caller counts as one.
+  """
+
+  import Plug.Conn
+
+  @header "x-leaxer-shell-token"
+
+  def init(opts), do: opts
+
+  def call(conn, _opts) do
+    case {expected_token(), get_req_header(conn, @header)} do
+      {nil, _} ->
+        conn
+
+      {_token, []} ->
+        conn
+
+      {token, [given | _]} ->
+        if Plug.Crypto.secure_compare(given, token) do
+          assign(conn, :from_shell, true)
+        else
+          conn
+          |> put_resp_content_type("application/json")
+          |> send_resp(401, ~s({"error":"Invalid shell token"}))
+          |> halt()
+        end
+    end
+  end
+
+  @doc """
+  Whether the request carried the shell's token, or there is no shell.
+  """
+  def from_shell?(conn) do
+    expected_token() == nil or conn.assigns[:from_shell] == true
+  end
+
+  defp expected_token do
+    case System.get_env("LEAXER_SHELL_TOKEN") do
+      nil -> nil
+      "" -> nil
+      token -> token
+    end
+  end
+end
@@ -11,6 +11,12 @@ defmodule LeaxerCoreWeb.Router do
     # Health check for startup readiness
     get "/health", HealthController, :check
 
+    # Device pairing, with the PIN shown in the desktop app
+    post "/pairing", PairingController, :pair
+
+    # Sidecar extensions run by the desktop app
+    get "/extensions", ExtensionController, :index
+
     # Node registry
     get "/nodes", NodeController, :index
     get "/nodes/:type", NodeController, :show
@@ -61,6 +67,7 @@ defmodule LeaxerCoreWeb.Router do
     # System management
     post "/system/restart", SystemController, :restart
     post "/system/cleanup", SystemController, :cleanup
+    post "/system/shutdown", SystemController, :shutdown
 
     # User settings
     get "/settings", SettingsController, :index
@@ -0,0 +1,32 @@
+defmodule LeaxerCore.ControlTest do
+  use ExUnit.Case, async: true
+
+  alias LeaxerCore.Control
+
+  describe "handle_command/2" do
+    test "status reports the VM" do
+      assert {:ok, status} = Control.handle_command("status", %{})
+      assert is_binary(status.version)
+      assert status.memory_bytes > 0
+      assert status.processes > 0
+    end
+
+    test "rejects unknown commands" do
+      assert {:error, "Unknown command: explode"} = Control.handle_command("explode", %{})
+    end
+  end
+
+  test "replies from the shell carry results or errors" do
+    assert {:ok, %{"token" => "t"}} = Control.reply_result(%{"ok" => true, "result" => %{"token" => "t"}})
+    assert {:error, "Wrong pairing PIN"} = Control.reply_result(%{"ok" => false, "error" => "Wrong pairing PIN"})
+  end
+
+  test "ask is unavailable without a control channel" do
+    assert {:error, :unavailable} = Control.ask("pair")
+  end
+
+  test "notify is a no-op without a control channel" do
+    refute Control.enabled?()
+    assert :ok = Control.notify("ready")
+  end
+end
@@ -0,0 +1,23 @@
+defmodule LeaxerCore.ExtensionsTest do
+  use ExUnit.Case, async: false
+
+  alias LeaxerCore.Extensions
+
+  setup do
+    on_exit(fn -> Extensions.set_urls(%{}) end)
+  end
+
+  test "the shell's set_extensions replaces the known extensions" do
+    urls = %{"ocr" => "http://127.0.0.1:4101"}
+    assert {:ok, %{}} = LeaxerCore.Control.handle_command("set_extensions", %{"urls" => urls})
+    assert Extensions.url("ocr") == "http://127.0.0.1:4101"
+
+    assert {:ok, %{}} = LeaxerCore.Control.handle_command("set_extensions", %{"urls" => %{}})
+    assert Extensions.url("ocr") == nil
+  end
+
+  test "entries that aren't name to URL are dropped" do
+    Extensions.set_urls(%{"ocr" => 4101, "tts" => "http://127.0.0.1:4102"})
+    assert Extensions.urls() == %{"tts" => "http://127.0.0.1:4102"}
+  end
+end
@@ -0,0 +1,24 @@
+defmodule LeaxerCore.PairingTest do
+  use ExUnit.Case, async: false
+
+  alias LeaxerCore.Pairing
+
+  setup do
+    on_exit(fn -> Pairing.set_token_hashes([]) end)
+  end
+
+  test "only tokens whose hashes the shell sent are authorized" do
+    hash = :crypto.hash(:sha256, "device-token") |> Base.encode16()
+    assert {:ok, %{}} = LeaxerCore.Control.handle_command("set_device_tokens", %{"hashes" => [hash]})
+
+    assert Pairing.authorized?("device-token")
+    refute Pairing.authorized?("other-token")
+    refute Pairing.authorized?(nil)
+  end
+
+  test "requests from this machine need no token" do
+    assert Pairing.loopback?({127, 0, 0, 1})
+    assert Pairing.loopback?({0, 0, 0, 0, 0, 0, 0, 1})
+    refute Pairing.loopback?({192, 168, 1, 20})
+  end
+end
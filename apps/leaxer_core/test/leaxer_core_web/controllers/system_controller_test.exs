@@ -0,0 +1,15 @@
+defmodule LeaxerCoreWeb.SystemControllerTest do
+  use LeaxerCoreWeb.ConnCase, async: false
+
+  setup do
+    System.put_env("LEAXER_SHELL_TOKEN", "shell-token")
+    on_exit(fn -> System.delete_env("LEAXER_SHELL_TOKEN") end)
+  end
+
+  test "restart and cleanup are refused without the shell token", %{conn: conn} do
+    for path <- ["/api/system/restart", "/api/system/cleanup", "/api/system/shutdown"] do
+      assert %{"error" => error} = conn |> post(path) |> json_response(403)
+      assert error =~ "only allowed from the desktop app"
+    end
+  end
+end
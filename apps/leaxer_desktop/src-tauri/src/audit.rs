@@ -0,0 +1,132 @@
+//! Append-only record of privileged shell operations, for machines shared by several people.
+//!
+//! Secret and keychain access, file access grants, extension approvals, elevated operations,
+//! network exposure changes, device pairing, and backend restarts and cleanups each add a
+//! JSON line to `audit.log` in the base Leaxer dir, e.g.
+//! `{"time": 1760000000000, "action": "secret_get", "target": "HF_TOKEN", "ok": true}`.
+//! Secret values are never written. The file is only ever appended to, readable only by
+//! the user, and read back with `get_audit_log`.
+
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::logging::log_to_file;
+
+const AUDIT_FILE: &str = "audit.log";
+
+/// Entries `get_audit_log` returns when not asked for a number
+const DEFAULT_LIMIT: usize = 200;
+
+/// Keeps lines from concurrent commands whole
+static APPEND: Mutex<()> = Mutex::new(());
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AuditEntry {
+    /// Unix time in milliseconds
+    pub time: u64,
+    pub action: String,
+    /// What the operation was about: a secret's name, a path, a setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn audit_path() -> Option<PathBuf> {
+    crate::paths::base_leaxer_dir().map(|dir| dir.join(AUDIT_FILE))
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn append_to(path: &Path, entry: &AuditEntry) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let line = format!("{}\n", serde_json::to_string(entry).map_err(std::io::Error::other)?);
+    let _guard = APPEND.lock().unwrap();
+    options.open(path)?.write_all(line.as_bytes())
+}
+
+/// The last `limit` entries in `path`, newest first. Lines that don't parse are skipped.
+fn read_from(path: &Path, limit: usize) -> Vec<AuditEntry> {
+    let Ok(file) = std::fs::File::open(path) else {
+        return Vec::new();
+    };
+    let entries: Vec<AuditEntry> = std::io::BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect();
+    entries.into_iter().rev().take(limit).collect()
+}
+
+/// Record `action` on `target` and whether it succeeded
+pub fn record<T>(action: &str, target: Option<&str>, result: &Result<T, String>) {
+    let entry = AuditEntry {
+        time: now_millis(),
+        action: action.to_string(),
+        target: target.map(str::to_string),
+        ok: result.is_ok(),
+        error: result.as_ref().err().cloned(),
+    };
+    let written = audit_path()
+        .ok_or_else(|| std::io::Error::other("no data directory"))
+        .and_then(|path| append_to(&path, &entry));
+    if let Err(e) = written {
+        log_to_file(&format!("[Leaxer] Failed to write the audit log ({}): {:?}", e, entry));
+    }
+}
+
+/// The most recent privileged operations, newest first
+#[tauri::command]
+pub fn get_audit_log(limit: Option<usize>) -> Vec<AuditEntry> {
+    audit_path()
+        .map(|path| read_from(&path, limit.unwrap_or(DEFAULT_LIMIT)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(time: u64, action: &str) -> AuditEntry {
+        AuditEntry {
+            time,
+            action: action.to_string(),
+            target: None,
+            ok: true,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn appends_and_reads_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(AUDIT_FILE);
+        append_to(&path, &entry(1, "secret_get")).unwrap();
+        append_to(&path, &entry(2, "backend_restart")).unwrap();
+        std::fs::OpenOptions::new().append(true).open(&path).unwrap().write_all(b"garbage\n").unwrap();
+        append_to(&path, &entry(3, "keychain_set")).unwrap();
+
+        assert_eq!(
+            read_from(&path, 10),
+            vec![entry(3, "keychain_set"), entry(2, "backend_restart"), entry(1, "secret_get")]
+        );
+        assert_eq!(read_from(&path, 1), vec![entry(3, "keychain_set")]);
+        assert!(read_from(&dir.path().join("missing.log"), 10).is_empty());
+    }
+}
@@ -0,0 +1,316 @@
+//! Backend release updates, independent of the shell installer.
+//!
+//! The manifest at `backend_update_url` (config.json) describes the latest release:
+//! `{"version": "0.1.4", "url": "https://.../leaxer_core.zip", "sha256": "<hex>", "signature": "<base64>"}`.
+//! The signature is an Ed25519 signature over the archive's SHA-256 digest, checked with
+//! the public key compiled in from `LEAXER_BACKEND_UPDATE_PUBKEY`; builds without a key
+//! can't install updates.
+//!
+//! Releases are unpacked into `<Leaxer dir>/backend/<version>`, and the file
+//! `<Leaxer dir>/backend/current` names the one to run. It is replaced with a rename, so
+//! an interrupted update leaves the old release selected. `locate_backend` prefers the
+//! selected release over the bundled one as long as this shell is compatible with it.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+
+use crate::checksum::{sha256_file, to_hex};
+use crate::logging::log_to_file;
+
+/// Base64 Ed25519 public key that update archives must be signed with
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("LEAXER_BACKEND_UPDATE_PUBKEY");
+
+const CURRENT_FILE: &str = "current";
+
+/// Where downloaded releases live; shared by all profiles
+pub fn updates_dir() -> Option<PathBuf> {
+    crate::paths::base_leaxer_dir().map(|dir| dir.join("backend"))
+}
+
+/// Version selected in `dir` and its launcher, if it is installed and compatible
+fn installed_release(dir: &Path) -> Option<(String, PathBuf)> {
+    let version = fs::read_to_string(dir.join(CURRENT_FILE)).ok()?.trim().to_string();
+    let launcher = dir.join(&version).join(crate::process::backend_relative_path());
+    (launcher.exists() && crate::compat::is_compatible_version(&version)).then_some((version, launcher))
+}
+
+/// Launcher of an updated backend release, preferred over the bundled one
+pub fn installed_backend() -> Option<PathBuf> {
+    updates_dir().and_then(|dir| installed_release(&dir)).map(|(_, launcher)| launcher)
+}
+
+/// Check the archive against the manifest's digest and the digest's signature
+fn verify_archive(path: &Path, sha256: &str, signature: &str, public_key: &str) -> Result<(), String> {
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    let digest = sha256_file(path).map_err(|e| format!("Failed to hash the update: {}", e))?;
+    if !to_hex(&digest).eq_ignore_ascii_case(sha256.trim()) {
+        return Err("The update archive is corrupt (checksum mismatch)".to_string());
+    }
+
+    let decode = |value: &str| base64::engine::general_purpose::STANDARD.decode(value.trim());
+    let key: [u8; 32] = decode(public_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("The update public key is invalid")?;
+    let key = VerifyingKey::from_bytes(&key).map_err(|_| "The update public key is invalid")?;
+    let signature: [u8; 64] = decode(signature)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or("The update signature is malformed")?;
+    key.verify(&digest, &Signature::from_bytes(&signature))
+        .map_err(|_| "The update signature is not valid".to_string())
+}
+
+/// Unpack a zip archive into `dest`, refusing entries that point outside of it
+fn extract(archive: &Path, dest: &Path) -> Result<(), String> {
+    let file = fs::File::open(archive).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Invalid update archive: {}", e))?;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).map_err(|e| e.to_string())?;
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| format!("Unsafe path in update archive: {}", entry.name()))?;
+        let path = dest.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&path).map_err(|e| e.to_string())?;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut out = fs::File::create(&path).map_err(|e| e.to_string())?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| e.to_string())?;
+        #[cfg(unix)]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&path, fs::Permissions::from_mode(mode));
+        }
+    }
+    Ok(())
+}
+
+/// Make `version` the release to run, then remove all others but the one it replaces
+fn select(dir: &Path, version: &str) -> std::io::Result<()> {
+    let previous = fs::read_to_string(dir.join(CURRENT_FILE)).ok().map(|v| v.trim().to_string());
+    let tmp = dir.join(format!("{}.tmp", CURRENT_FILE));
+    fs::write(&tmp, version)?;
+    fs::rename(&tmp, dir.join(CURRENT_FILE))?;
+
+    for entry in fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let keep = name == version || Some(&name) == previous.as_ref();
+        if entry.path().is_dir() && !keep {
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Verify a downloaded archive and install it as the release to run
+fn install(dir: &Path, archive: &Path, manifest: &Manifest, public_key: &str) -> Result<(), String> {
+    verify_archive(archive, &manifest.sha256, &manifest.signature, public_key)?;
+
+    // Unpacked next to its final place, so moving it there is a rename
+    let staging = dir.join(format!("{}.partial", manifest.version));
+    let _ = fs::remove_dir_all(&staging);
+    extract(archive, &staging)?;
+    if !staging.join(crate::process::backend_relative_path()).exists() {
+        let _ = fs::remove_dir_all(&staging);
+        return Err("The update archive does not contain a backend release".to_string());
+    }
+    let target = dir.join(&manifest.version);
+    let _ = fs::remove_dir_all(&target);
+    fs::rename(&staging, &target).map_err(|e| e.to_string())?;
+    select(dir, &manifest.version).map_err(|e| e.to_string())
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct Manifest {
+    pub version: String,
+    pub url: String,
+    pub sha256: String,
+    pub signature: String,
+}
+
+/// Release version of the backend that would be started now
+fn current_version(app: &tauri::AppHandle) -> Option<String> {
+    let launcher = crate::process::locate_backend(app)?;
+    let root = launcher.parent()?.parent()?;
+    crate::process::release_versions(root).map(|(_, release)| release)
+}
+
+#[cfg(feature = "http")]
+async fn fetch_manifest(app: &tauri::AppHandle, client: &tauri_plugin_http::reqwest::Client) -> Result<Manifest, String> {
+    use tauri::Manager;
+
+    let config = app.state::<crate::config::ConfigStore>().load();
+    let url = config
+        .get("backend_update_url")
+        .and_then(serde_json::Value::as_str)
+        .ok_or("No backend_update_url is configured")?;
+    let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Update check failed with status {}", response.status()));
+    }
+    let body = response.bytes().await.map_err(|e| e.to_string())?;
+    serde_json::from_slice(&body).map_err(|e| format!("Invalid update manifest: {}", e))
+}
+
+/// Whether `manifest` is newer than `current` and runs with this shell
+fn is_update(manifest: &Manifest, current: Option<&str>) -> bool {
+    let newer = match (crate::compat::parse_version(&manifest.version), current.and_then(crate::compat::parse_version)) {
+        (Some(available), Some(current)) => available > current,
+        (Some(_), None) => true,
+        _ => false,
+    };
+    newer && crate::compat::is_compatible_version(&manifest.version)
+}
+
+/// The available backend update, or None when the current release is the latest one
+/// this shell can run
+#[cfg(feature = "http")]
+#[tauri::command]
+pub async fn check_backend_update(app: tauri::AppHandle) -> Result<Option<Manifest>, String> {
+    let client = tauri_plugin_http::reqwest::Client::new();
+    let manifest = fetch_manifest(&app, &client).await?;
+    Ok(is_update(&manifest, current_version(&app).as_deref()).then_some(manifest))
+}
+
+/// Download, verify and install the available backend update. It is used from the next
+/// backend start on (e.g. `restart_backend`). Progress is reported as a task.
+#[cfg(feature = "http")]
+#[tauri::command]
+pub async fn install_backend_update(app: tauri::AppHandle) -> Result<String, String> {
+    use tokio::io::AsyncWriteExt;
+
+    let public_key = UPDATE_PUBLIC_KEY.ok_or("Backend updates are not enabled in this build")?;
+    let dir = updates_dir().ok_or("No data directory")?;
+    let client = tauri_plugin_http::reqwest::Client::new();
+    let manifest = fetch_manifest(&app, &client).await?;
+    if !is_update(&manifest, current_version(&app).as_deref()) {
+        return Err("No compatible backend update is available".to_string());
+    }
+    log_to_file(&format!("[Leaxer] Installing backend update {}", manifest.version));
+
+    let task = crate::commands::tasks::register_task(&app, "backend-update", &manifest.version);
+    let archive = dir.join(format!("{}.zip.part", manifest.version));
+    let result = async {
+        tokio::fs::create_dir_all(&dir).await.map_err(|e| e.to_string())?;
+        let mut response = client.get(&manifest.url).send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("Download failed with status {}", response.status()));
+        }
+        let total = response.content_length();
+        let mut file = tokio::fs::File::create(&archive).await.map_err(|e| e.to_string())?;
+        let mut received = 0;
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            if task.is_cancelled() {
+                return Err("Update cancelled".to_string());
+            }
+            file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+            received += chunk.len() as u64;
+            task.progress(received, total);
+        }
+        file.flush().await.map_err(|e| e.to_string())?;
+
+        let (dir, archive, manifest) = (dir.clone(), archive.clone(), manifest.clone());
+        tauri::async_runtime::spawn_blocking(move || install(&dir, &archive, &manifest, public_key))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&archive).await;
+    task.finish(&result);
+
+    match &result {
+        Ok(()) => log_to_file(&format!("[Leaxer] Backend {} installed", manifest.version)),
+        Err(e) => log_to_file(&format!("[Leaxer] Backend update failed: {}", e)),
+    }
+    result.map(|()| manifest.version)
+}
+
+#[cfg(not(feature = "http"))]
+#[tauri::command]
+pub async fn check_backend_update() -> Result<Option<Manifest>, String> {
+    Err(crate::features::unavailable("Backend updates", &["http"]))
+}
+
+#[cfg(not(feature = "http"))]
+#[tauri::command]
+pub async fn install_backend_update() -> Result<String, String> {
+    Err(crate::features::unavailable("Backend updates", &["http"]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::io::Write;
+
+    fn write_release(path: &Path, entries: &[&str]) {
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        for name in entries {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(b"#!/bin/sh\n").unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn sign(path: &Path) -> (Manifest, String) {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let digest = sha256_file(path).unwrap();
+        let encode = |bytes: &[u8]| base64::engine::general_purpose::STANDARD.encode(bytes);
+        let manifest = Manifest {
+            version: "0.1.5".to_string(),
+            url: String::new(),
+            sha256: to_hex(&digest),
+            signature: encode(&key.sign(&digest).to_bytes()),
+        };
+        (manifest, encode(key.verifying_key().as_bytes()))
+    }
+
+    #[test]
+    fn installs_a_signed_release_and_selects_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("update.zip");
+        let launcher = crate::process::backend_relative_path();
+        write_release(&archive, &[launcher.to_str().unwrap()]);
+        let (manifest, public_key) = sign(&archive);
+
+        install(dir.path(), &archive, &manifest, &public_key).unwrap();
+        let (version, installed) = installed_release(dir.path()).unwrap();
+        assert_eq!(version, "0.1.5");
+        assert_eq!(installed, dir.path().join("0.1.5").join(launcher));
+    }
+
+    #[test]
+    fn rejects_tampered_or_unsigned_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("update.zip");
+        write_release(&archive, &["leaxer_core/bin/leaxer_core"]);
+        let (manifest, public_key) = sign(&archive);
+
+        let other_key = base64::engine::general_purpose::STANDARD.encode([9u8; 32]);
+        assert!(verify_archive(&archive, &manifest.sha256, &manifest.signature, &other_key).is_err());
+
+        fs::OpenOptions::new().append(true).open(&archive).unwrap().write_all(b"x").unwrap();
+        assert!(verify_archive(&archive, &manifest.sha256, &manifest.signature, &public_key).is_err());
+        assert!(installed_release(dir.path()).is_none());
+    }
+
+    #[test]
+    fn offers_only_newer_compatible_releases() {
+        let manifest = |version: &str| Manifest {
+            version: version.to_string(),
+            url: String::new(),
+            sha256: String::new(),
+            signature: String::new(),
+        };
+        assert!(is_update(&manifest("0.1.5"), Some("0.1.0")));
+        assert!(!is_update(&manifest("0.1.0"), Some("0.1.0")));
+        assert!(!is_update(&manifest("9.0.0"), Some("0.1.0")));
+    }
+}
@@ -0,0 +1,246 @@
+//! Chunked SHA-256 verification for large files.
+//!
+//! Files are hashed as fixed-size chunks on all cores and the chunk hashes are combined
+//! into a merkle root. A manifest carries both, so verifying a multi-gigabyte model takes
+//! seconds instead of minutes, and a mismatch names the corrupt chunks so only those
+//! have to be downloaded again.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+use crate::stream::read_exact_at;
+
+/// Size of the buffer each worker reads through
+const READ_BUFFER_BYTES: usize = 1024 * 1024;
+
+type Hash = [u8; 32];
+
+/// Expected hashes for a file, as hex strings
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ChunkManifest {
+    pub size: u64,
+    pub chunk_size: u64,
+    pub chunks: Vec<String>,
+    pub root: String,
+}
+
+pub fn to_hex(hash: &Hash) -> String {
+    hash.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// SHA-256 of a whole file, read sequentially
+pub fn sha256_file(path: &Path) -> io::Result<Hash> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; READ_BUFFER_BYTES];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Combine chunk hashes pairwise until one remains; an odd hash is carried up unchanged
+pub fn merkle_root(chunks: &[Hash]) -> Hash {
+    if chunks.is_empty() {
+        return Sha256::digest(b"").into();
+    }
+    let mut level = chunks.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => {
+                    let mut hasher = Sha256::new();
+                    hasher.update(left);
+                    hasher.update(right);
+                    hasher.finalize().into()
+                }
+                [single] => *single,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+fn hash_range(file: &File, start: u64, len: u64, buf: &mut [u8]) -> io::Result<Hash> {
+    let mut hasher = Sha256::new();
+    let mut offset = start;
+    let end = start + len;
+    while offset < end {
+        let n = (end - offset).min(buf.len() as u64) as usize;
+        read_exact_at(file, &mut buf[..n], offset)?;
+        hasher.update(&buf[..n]);
+        offset += n as u64;
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Hash `path` in `chunk_size` pieces, spread over all available cores
+pub fn hash_chunks(path: &Path, chunk_size: u64) -> io::Result<Vec<Hash>> {
+    if chunk_size == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "chunk size must not be zero"));
+    }
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let count = size.div_ceil(chunk_size) as usize;
+
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(count.max(1));
+    let next = AtomicUsize::new(0);
+    let hashes = Mutex::new(vec![[0u8; 32]; count]);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| -> io::Result<()> {
+                    let mut buf = vec![0u8; READ_BUFFER_BYTES];
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        if index >= count {
+                            return Ok(());
+                        }
+                        let start = index as u64 * chunk_size;
+                        let len = chunk_size.min(size - start);
+                        let hash = hash_range(&file, start, len, &mut buf)?;
+                        hashes.lock().unwrap()[index] = hash;
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .try_for_each(|handle| handle.join().unwrap_or_else(|_| Err(io::Error::other("hash worker panicked"))))
+    })?;
+
+    Ok(hashes.into_inner().unwrap())
+}
+
+/// Build the manifest for a file
+pub fn build_manifest(path: &Path, chunk_size: u64) -> io::Result<ChunkManifest> {
+    let chunks = hash_chunks(path, chunk_size)?;
+    Ok(ChunkManifest {
+        size: std::fs::metadata(path)?.len(),
+        chunk_size,
+        chunks: chunks.iter().map(to_hex).collect(),
+        root: to_hex(&merkle_root(&chunks)),
+    })
+}
+
+/// Check `path` against `manifest`. Returns the indices of chunks that don't match;
+/// a size mismatch or an inconsistent manifest is an error.
+pub fn verify(path: &Path, manifest: &ChunkManifest) -> io::Result<Vec<usize>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let size = std::fs::metadata(path)?.len();
+    if size != manifest.size {
+        return Err(invalid(format!("expected {} bytes, found {}", manifest.size, size)));
+    }
+    let actual = hash_chunks(path, manifest.chunk_size)?;
+    if actual.len() != manifest.chunks.len() {
+        return Err(invalid(format!(
+            "manifest lists {} chunks, file has {}",
+            manifest.chunks.len(),
+            actual.len()
+        )));
+    }
+
+    let bad: Vec<usize> = actual
+        .iter()
+        .zip(&manifest.chunks)
+        .enumerate()
+        .filter(|(_, (hash, expected))| !to_hex(hash).eq_ignore_ascii_case(expected))
+        .map(|(index, _)| index)
+        .collect();
+
+    // Chunks can only be trusted if they add up to the published root
+    if bad.is_empty() && !to_hex(&merkle_root(&actual)).eq_ignore_ascii_case(&manifest.root) {
+        return Err(invalid("chunk hashes don't match the manifest root".to_string()));
+    }
+    Ok(bad)
+}
+
+/// Byte range `[start, end]` covered by a chunk
+pub fn chunk_range(manifest: &ChunkManifest, index: usize) -> (u64, u64) {
+    let start = index as u64 * manifest.chunk_size;
+    let end = (start + manifest.chunk_size).min(manifest.size) - 1;
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_pattern(path: &Path, len: usize) {
+        let data: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+        std::fs::write(path, data).unwrap();
+    }
+
+    #[test]
+    fn chunk_hashes_match_sequential_hashing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.bin");
+        write_pattern(&path, 10_000);
+
+        let chunks = hash_chunks(&path, 4096).unwrap();
+        assert_eq!(chunks.len(), 3);
+
+        let data = std::fs::read(&path).unwrap();
+        let expected: Hash = Sha256::digest(&data[8192..]).into();
+        assert_eq!(chunks[2], expected);
+        assert_eq!(sha256_file(&path).unwrap(), <Hash>::from(Sha256::digest(&data)));
+    }
+
+    #[test]
+    fn merkle_root_carries_odd_hashes() {
+        let a: Hash = Sha256::digest(b"a").into();
+        let b: Hash = Sha256::digest(b"b").into();
+        let c: Hash = Sha256::digest(b"c").into();
+        assert_eq!(merkle_root(&[a]), a);
+
+        let ab = merkle_root(&[a, b]);
+        assert_eq!(merkle_root(&[a, b, c]), merkle_root(&[ab, c]));
+    }
+
+    #[test]
+    fn verify_pinpoints_corrupt_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.bin");
+        write_pattern(&path, 10_000);
+        let manifest = build_manifest(&path, 1024).unwrap();
+        assert_eq!(verify(&path, &manifest).unwrap(), Vec::<usize>::new());
+
+        let mut data = std::fs::read(&path).unwrap();
+        data[5000] ^= 0xff;
+        std::fs::write(&path, &data).unwrap();
+        assert_eq!(verify(&path, &manifest).unwrap(), vec![4]);
+        assert_eq!(chunk_range(&manifest, 4), (4096, 5119));
+        assert_eq!(chunk_range(&manifest, 9), (9216, 9999));
+    }
+
+    #[test]
+    fn verify_rejects_wrong_size_and_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.bin");
+        write_pattern(&path, 3000);
+        let mut manifest = build_manifest(&path, 1024).unwrap();
+
+        manifest.root = "00".repeat(32);
+        assert!(verify(&path, &manifest).is_err());
+
+        write_pattern(&path, 2000);
+        assert!(verify(&path, &build_manifest(&path, 1024).unwrap()).is_ok());
+        assert!(verify(&path, &manifest).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+//! Per-user registration of the `leaxer://` scheme and `.leaxer` file association.
+
+use std::process::Command;
+
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "macos")]
+use std::path::PathBuf;
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+use crate::process::CREATE_NO_WINDOW;
+use crate::logging::log_to_file;
+
+/// URL scheme handled by Leaxer (leaxer://...)
+const URL_SCHEME: &str = "leaxer";
+
+/// File extension (without dot) opened by Leaxer
+const FILE_EXTENSION: &str = "leaxer";
+
+/// Desktop entry written for per-user associations on Linux
+#[cfg(target_os = "linux")]
+const LINUX_DESKTOP_FILE: &str = "leaxer-handler.desktop";
+
+/// MIME type registered for .leaxer files on Linux
+#[cfg(target_os = "linux")]
+const LINUX_MIME_TYPE: &str = "application/x-leaxer";
+
+#[derive(serde::Serialize)]
+pub struct AssociationStatus {
+    protocol_registered: bool,
+    file_association_registered: bool,
+    /// Path of the executable the associations point to
+    executable: String,
+}
+
+/// Run a helper command, returning whether it exited successfully
+fn run_quiet(cmd: &mut Command) -> bool {
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd.output().map(|out| out.status.success()).unwrap_or(false)
+}
+
+/// Windows: per-user associations under HKCU\Software\Classes
+#[cfg(target_os = "windows")]
+fn set_associations(exe: &std::path::Path, register: bool) -> Result<(), String> {
+    let scheme_key = format!("HKCU\\Software\\Classes\\{}", URL_SCHEME);
+    let ext_key = format!("HKCU\\Software\\Classes\\.{}", FILE_EXTENSION);
+    let prog_key = "HKCU\\Software\\Classes\\Leaxer.Document";
+
+    if !register {
+        for key in [scheme_key.as_str(), ext_key.as_str(), prog_key] {
+            run_quiet(Command::new("reg").args(["delete", key, "/f"]));
+        }
+        return Ok(());
+    }
+
+    let open_command = format!("\"{}\" \"%1\"", exe.display());
+    let entries: [(String, Option<&str>, &str); 6] = [
+        (scheme_key.clone(), None, "URL:Leaxer Protocol"),
+        (scheme_key.clone(), Some("URL Protocol"), ""),
+        (format!("{}\\shell\\open\\command", scheme_key), None, &open_command),
+        (ext_key, None, "Leaxer.Document"),
+        (prog_key.to_string(), None, "Leaxer Document"),
+        (format!("{}\\shell\\open\\command", prog_key), None, &open_command),
+    ];
+
+    for (key, name, value) in entries {
+        let mut cmd = Command::new("reg");
+        cmd.args(["add", &key, "/f", "/d", value]);
+        match name {
+            Some(name) => cmd.args(["/v", name]),
+            None => cmd.arg("/ve"),
+        };
+        if !run_quiet(&mut cmd) {
+            return Err(format!("Failed to write registry key {}", key));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn query_associations(exe: &std::path::Path) -> (bool, bool) {
+    let points_to_exe = |key: String| {
+        let mut cmd = Command::new("reg");
+        cmd.args(["query", &key, "/ve"]).creation_flags(CREATE_NO_WINDOW);
+        cmd.output()
+            .map(|out| String::from_utf8_lossy(&out.stdout).contains(&exe.display().to_string()))
+            .unwrap_or(false)
+    };
+    (
+        points_to_exe(format!("HKCU\\Software\\Classes\\{}\\shell\\open\\command", URL_SCHEME)),
+        points_to_exe("HKCU\\Software\\Classes\\Leaxer.Document\\shell\\open\\command".to_string()),
+    )
+}
+
+/// Linux: a desktop entry plus a shared-mime-info package in the user's XDG data dir
+#[cfg(target_os = "linux")]
+fn set_associations(exe: &std::path::Path, register: bool) -> Result<(), String> {
+    let data_dir = dirs::data_dir().ok_or("No XDG data directory")?;
+    let desktop_path = data_dir.join("applications").join(LINUX_DESKTOP_FILE);
+    let mime_dir = data_dir.join("mime");
+    let mime_path = mime_dir.join("packages").join("leaxer.xml");
+
+    if register {
+        let desktop_entry = format!(
+            "[Desktop Entry]\nType=Application\nName=Leaxer\nExec=\"{}\" %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};{};\n",
+            exe.display(),
+            URL_SCHEME,
+            LINUX_MIME_TYPE
+        );
+        let mime_package = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<mime-info xmlns=\"http://www.freedesktop.org/standards/shared-mime-info\">\n  <mime-type type=\"{}\">\n    <comment>Leaxer document</comment>\n    <glob pattern=\"*.{}\"/>\n  </mime-type>\n</mime-info>\n",
+            LINUX_MIME_TYPE, FILE_EXTENSION
+        );
+
+        for (path, content) in [(&desktop_path, desktop_entry), (&mime_path, mime_package)] {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::write(path, content).map_err(|e| e.to_string())?;
+        }
+
+        run_quiet(Command::new("xdg-mime").args([
+            "default",
+            LINUX_DESKTOP_FILE,
+            &format!("x-scheme-handler/{}", URL_SCHEME),
+            LINUX_MIME_TYPE,
+        ]));
+    } else {
+        let _ = fs::remove_file(&desktop_path);
+        let _ = fs::remove_file(&mime_path);
+    }
+
+    run_quiet(Command::new("update-mime-database").arg(&mime_dir));
+    run_quiet(Command::new("update-desktop-database").arg(data_dir.join("applications")));
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn query_associations(_exe: &std::path::Path) -> (bool, bool) {
+    let is_default = |mime: String| {
+        Command::new("xdg-mime")
+            .args(["query", "default", &mime])
+            .output()
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim() == LINUX_DESKTOP_FILE)
+            .unwrap_or(false)
+    };
+    (
+        is_default(format!("x-scheme-handler/{}", URL_SCHEME)),
+        is_default(LINUX_MIME_TYPE.to_string()),
+    )
+}
+
+/// Path of LaunchServices' registration tool
+#[cfg(target_os = "macos")]
+const LSREGISTER: &str = "/System/Library/Frameworks/CoreServices.framework/Frameworks/LaunchServices.framework/Support/lsregister";
+
+/// macOS: associations are declared in the bundle's Info.plist, so (un)registering means
+/// asking LaunchServices to (un)register the enclosing .app bundle
+#[cfg(target_os = "macos")]
+fn app_bundle(exe: &std::path::Path) -> Option<PathBuf> {
+    exe.ancestors()
+        .find(|p| p.extension().map(|e| e == "app").unwrap_or(false))
+        .map(|p| p.to_path_buf())
+}
+
+#[cfg(target_os = "macos")]
+fn set_associations(exe: &std::path::Path, register: bool) -> Result<(), String> {
+    let bundle = app_bundle(exe).ok_or("Leaxer is not running from an app bundle")?;
+    let flag = if register { "-f" } else { "-u" };
+    if run_quiet(Command::new(LSREGISTER).arg(flag).arg(&bundle)) {
+        Ok(())
+    } else {
+        Err("LaunchServices registration failed".to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn query_associations(exe: &std::path::Path) -> (bool, bool) {
+    let bundle = match app_bundle(exe) {
+        Some(bundle) => bundle,
+        None => return (false, false),
+    };
+    let registered = Command::new(LSREGISTER)
+        .arg("-dump")
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).contains(&bundle.display().to_string()))
+        .unwrap_or(false);
+    (registered, registered)
+}
+
+fn association_status() -> Result<AssociationStatus, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let (protocol_registered, file_association_registered) = query_associations(&exe);
+    Ok(AssociationStatus {
+        protocol_registered,
+        file_association_registered,
+        executable: exe.display().to_string(),
+    })
+}
+
+/// Register the `leaxer://` scheme and `.leaxer` files for the current user.
+/// Portable builds can't rely on the installer for this.
+#[tauri::command]
+pub async fn register_associations() -> Result<AssociationStatus, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        log_to_file(&format!("[Leaxer] Registering protocol and file associations for {:?}", exe));
+        set_associations(&exe, true)?;
+        association_status()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Remove the per-user `leaxer://` and `.leaxer` associations
+#[tauri::command]
+pub async fn unregister_associations() -> Result<AssociationStatus, String> {
+    tauri::async_runtime::spawn_blocking(|| {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        log_to_file("[Leaxer] Removing protocol and file associations");
+        set_associations(&exe, false)?;
+        association_status()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Report whether the scheme and file association currently point at this executable
+#[tauri::command]
+pub async fn get_association_status() -> Result<AssociationStatus, String> {
+    tauri::async_runtime::spawn_blocking(association_status)
+        .await
+        .map_err(|e| e.to_string())?
+}
@@ -0,0 +1,341 @@
+//! System-wide "capture selection" hotkey.
+//!
+//! Pressing the hotkey grabs the selected text in whatever app has focus and opens a
+//! small popup window, which runs the configured action (summarize, translate) through
+//! the backend's chat channel and shows the result. On Linux the selection is read from
+//! the PRIMARY selection; elsewhere a copy keystroke is simulated and the previous
+//! clipboard text restored afterwards.
+//!
+//! Needs the `global-shortcut` feature; without it the commands report that capture is
+//! unavailable in this build.
+
+#[cfg(feature = "global-shortcut")]
+use {
+    crate::config::ConfigStore,
+    crate::logging::log_to_file,
+    std::sync::Mutex,
+    tauri::{Emitter, Manager},
+    tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState},
+};
+
+/// Hotkey used when config.json doesn't set `capture_hotkey`; `null` there disables it
+#[cfg(feature = "global-shortcut")]
+const DEFAULT_CAPTURE_HOTKEY: &str = "CommandOrControl+Shift+Space";
+
+/// Actions the popup knows how to run on a capture
+const CAPTURE_ACTIONS: &[&str] = &["summarize", "translate"];
+
+/// Label of the popup window that shows capture results
+#[cfg(feature = "global-shortcut")]
+const CAPTURE_WINDOW: &str = "capture";
+
+/// Selections longer than this are truncated before they reach the model
+#[cfg(feature = "global-shortcut")]
+const CAPTURE_MAX_TEXT_BYTES: usize = 64 * 1024;
+
+/// How long to wait for the focused app to answer the simulated copy
+#[cfg(all(feature = "global-shortcut", not(target_os = "linux")))]
+const COPY_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(500);
+
+#[derive(Clone, serde::Serialize)]
+pub struct Capture {
+    text: String,
+    action: String,
+    truncated: bool,
+}
+
+/// The registered hotkey and the capture waiting for the popup to pick it up
+#[cfg(feature = "global-shortcut")]
+#[derive(Default)]
+pub struct CaptureState {
+    hotkey: Mutex<Option<Shortcut>>,
+    pending: Mutex<Option<Capture>>,
+}
+
+fn validate_action(action: &str) -> Result<(), String> {
+    if CAPTURE_ACTIONS.contains(&action) {
+        Ok(())
+    } else {
+        Err(format!("Unknown capture action: {} (expected one of {})", action, CAPTURE_ACTIONS.join(", ")))
+    }
+}
+
+/// Configured hotkey: a missing key means the default, `null` means disabled
+#[cfg(feature = "global-shortcut")]
+fn configured_hotkey(config: &serde_json::Value) -> Option<String> {
+    match config.get("capture_hotkey") {
+        None => Some(DEFAULT_CAPTURE_HOTKEY.to_string()),
+        Some(value) => value.as_str().map(str::to_string),
+    }
+}
+
+#[cfg(feature = "global-shortcut")]
+fn configured_action(config: &serde_json::Value) -> String {
+    config
+        .get("capture_action")
+        .and_then(|v| v.as_str())
+        .filter(|action| CAPTURE_ACTIONS.contains(action))
+        .unwrap_or(CAPTURE_ACTIONS[0])
+        .to_string()
+}
+
+/// Read the current selection of the focused app
+#[cfg(all(feature = "global-shortcut", target_os = "linux"))]
+fn read_selection() -> Result<String, String> {
+    use arboard::{GetExtLinux, LinuxClipboardKind};
+
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+    clipboard
+        .get()
+        .clipboard(LinuxClipboardKind::Primary)
+        .text()
+        .map_err(|_| "Nothing is selected".to_string())
+}
+
+#[cfg(all(feature = "global-shortcut", not(target_os = "linux")))]
+fn read_selection() -> Result<String, String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+    let previous = clipboard.get_text().ok();
+    let _ = clipboard.clear();
+
+    send_copy_keystroke()?;
+
+    let started = std::time::Instant::now();
+    let selection = loop {
+        match clipboard.get_text() {
+            Ok(text) if !text.is_empty() => break Ok(text),
+            _ if started.elapsed() >= COPY_TIMEOUT => break Err("Nothing is selected".to_string()),
+            _ => std::thread::sleep(std::time::Duration::from_millis(20)),
+        }
+    };
+
+    // Put back what the user had copied before the hotkey
+    match previous {
+        Some(previous) => {
+            let _ = clipboard.set_text(previous);
+        }
+        None => {
+            let _ = clipboard.clear();
+        }
+    }
+    selection
+}
+
+/// macOS: System Events sends Cmd+C to the frontmost app (needs Accessibility access)
+#[cfg(all(feature = "global-shortcut", target_os = "macos"))]
+fn send_copy_keystroke() -> Result<(), String> {
+    use crate::commands::permissions::{check_permission, OsPermission, PermissionState};
+
+    if check_permission(OsPermission::Accessibility) == PermissionState::Denied {
+        return Err("Leaxer needs Accessibility access to capture selected text".to_string());
+    }
+    let status = std::process::Command::new("osascript")
+        .args(["-e", "tell application \"System Events\" to keystroke \"c\" using command down"])
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err("Failed to copy the selection".to_string())
+    }
+}
+
+/// Windows: synthesize Ctrl+C, releasing the hotkey's modifiers first so they don't
+/// turn it into a different shortcut
+#[cfg(all(feature = "global-shortcut", target_os = "windows"))]
+fn send_copy_keystroke() -> Result<(), String> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP, VIRTUAL_KEY,
+        VK_CONTROL, VK_LWIN, VK_MENU, VK_SHIFT,
+    };
+
+    let key = |vk: VIRTUAL_KEY, up: bool| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: 0,
+                dwFlags: if up { KEYEVENTF_KEYUP } else { KEYBD_EVENT_FLAGS(0) },
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+    let c = VIRTUAL_KEY(b'C' as u16);
+    let inputs = [
+        key(VK_SHIFT, true),
+        key(VK_MENU, true),
+        key(VK_LWIN, true),
+        key(VK_CONTROL, false),
+        key(c, false),
+        key(c, true),
+        key(VK_CONTROL, true),
+    ];
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize == inputs.len() {
+        Ok(())
+    } else {
+        Err("Failed to copy the selection".to_string())
+    }
+}
+
+/// Show the capture popup, creating it on first use
+#[cfg(feature = "global-shortcut")]
+fn show_capture_window(app: &tauri::AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(CAPTURE_WINDOW) {
+        window.show()?;
+        window.set_focus()?;
+        return Ok(());
+    }
+    // Stays hidden until the page has loaded, like the main window
+    tauri::WebviewWindowBuilder::new(app, CAPTURE_WINDOW, tauri::WebviewUrl::App("index.html".into()))
+        .title("Leaxer")
+        .inner_size(480.0, 360.0)
+        .always_on_top(true)
+        .center()
+        .visible(false)
+        .devtools(crate::commands::window::devtools_enabled(app))
+        .build()?;
+    Ok(())
+}
+
+/// Grab the selection and hand it to the popup
+#[cfg(feature = "global-shortcut")]
+fn capture_selection(app: &tauri::AppHandle) {
+    let action = configured_action(&app.state::<ConfigStore>().load());
+    let text = match read_selection() {
+        Ok(text) if !text.trim().is_empty() => text,
+        Ok(_) => return,
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Capture failed: {}", e));
+            return;
+        }
+    };
+    let (text, truncated) = crate::commands::clipboard::truncate_utf8(&text, CAPTURE_MAX_TEXT_BYTES);
+    log_to_file(&format!("[Leaxer] Captured {} bytes of selected text for {}", text.len(), action));
+
+    *app.state::<CaptureState>().pending.lock().unwrap() = Some(Capture {
+        text: text.to_string(),
+        action,
+        truncated,
+    });
+
+    let handle = app.clone();
+    let _ = app.run_on_main_thread(move || {
+        if let Err(e) = show_capture_window(&handle) {
+            log_to_file(&format!("[Leaxer] Failed to open capture window: {}", e));
+            return;
+        }
+        // A popup that is already open picks the capture up on this event; a new one on load
+        let _ = handle.emit_to(CAPTURE_WINDOW, "capture-ready", ());
+    });
+}
+
+/// Global shortcut handler, installed with the plugin
+#[cfg(feature = "global-shortcut")]
+pub fn on_shortcut(app: &tauri::AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let is_capture_hotkey = app.state::<CaptureState>().hotkey.lock().unwrap().as_ref() == Some(shortcut);
+    if is_capture_hotkey {
+        // Simulating the copy blocks for a moment; keep it off the event loop
+        let app = app.clone();
+        std::thread::spawn(move || capture_selection(&app));
+    }
+}
+
+/// Swap the registered hotkey for `hotkey` (`None` disables capture)
+#[cfg(feature = "global-shortcut")]
+fn register_hotkey(app: &tauri::AppHandle, hotkey: Option<&str>) -> Result<(), String> {
+    let shortcut = hotkey
+        .map(|hotkey| hotkey.parse::<Shortcut>().map_err(|e| format!("Invalid hotkey {}: {}", hotkey, e)))
+        .transpose()?;
+
+    let state = app.state::<CaptureState>();
+    let mut current = state.hotkey.lock().unwrap();
+    if let Some(old) = current.take() {
+        let _ = app.global_shortcut().unregister(old);
+    }
+    if let Some(shortcut) = shortcut {
+        app.global_shortcut()
+            .register(shortcut)
+            .map_err(|e| format!("Hotkey {} is not available: {}", hotkey.unwrap_or_default(), e))?;
+        *current = Some(shortcut);
+    }
+    Ok(())
+}
+
+/// Register the configured hotkey at startup
+#[cfg(feature = "global-shortcut")]
+pub fn init(app: &tauri::AppHandle) {
+    app.manage(CaptureState::default());
+    let hotkey = configured_hotkey(&app.state::<ConfigStore>().load());
+    match register_hotkey(app, hotkey.as_deref()) {
+        Ok(()) => {
+            if let Some(hotkey) = hotkey {
+                log_to_file(&format!("[Leaxer] Capture hotkey registered: {}", hotkey));
+            }
+        }
+        Err(e) => log_to_file(&format!("[Leaxer] {}", e)),
+    }
+}
+
+/// Change the capture hotkey and action; a `None` hotkey disables capture
+#[cfg(feature = "global-shortcut")]
+#[tauri::command]
+pub fn set_capture_hotkey(
+    app: tauri::AppHandle,
+    config: tauri::State<'_, ConfigStore>,
+    hotkey: Option<String>,
+    action: String,
+) -> Result<(), String> {
+    validate_action(&action)?;
+    register_hotkey(&app, hotkey.as_deref())?;
+    config.set("capture_hotkey", serde_json::json!(hotkey));
+    config.set("capture_action", serde_json::json!(action));
+    Ok(())
+}
+
+/// Hand the latest capture to the popup window (once)
+#[cfg(feature = "global-shortcut")]
+#[tauri::command]
+pub fn take_capture(state: tauri::State<'_, CaptureState>) -> Option<Capture> {
+    state.pending.lock().unwrap().take()
+}
+
+/// Capture needs the `global-shortcut` feature, which this build was compiled without
+#[cfg(not(feature = "global-shortcut"))]
+#[tauri::command]
+pub fn set_capture_hotkey(hotkey: Option<String>, action: String) -> Result<(), String> {
+    let _ = hotkey;
+    validate_action(&action)?;
+    Err(crate::features::unavailable("Capture hotkeys", &["global-shortcut"]))
+}
+
+#[cfg(not(feature = "global-shortcut"))]
+#[tauri::command]
+pub fn take_capture() -> Option<Capture> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_known_actions_are_accepted() {
+        assert!(validate_action("summarize").is_ok());
+        assert!(validate_action("translate").is_ok());
+        assert!(validate_action("rm -rf").is_err());
+    }
+
+    #[cfg(feature = "global-shortcut")]
+    #[test]
+    fn hotkey_defaults_and_can_be_disabled() {
+        assert_eq!(configured_hotkey(&serde_json::json!({})).as_deref(), Some(DEFAULT_CAPTURE_HOTKEY));
+        assert_eq!(configured_hotkey(&serde_json::json!({ "capture_hotkey": null })), None);
+        assert_eq!(configured_action(&serde_json::json!({ "capture_action": "bogus" })), "summarize");
+        assert!(DEFAULT_CAPTURE_HOTKEY.parse::<Shortcut>().is_ok());
+    }
+}
@@ -0,0 +1,248 @@
+//! Opt-in clipboard monitor with size limits and secret redaction. It samples on the
+//! `MonitorSignal`, so it rests while the app is hidden in the tray and catches up when shown.
+//! Images are told apart by their size and an even sample of their pixels, so a large
+//! image on the clipboard isn't hashed in full on every poll.
+
+use tauri::{Emitter, Manager};
+
+use crate::logging::log_to_file;
+use crate::monitor::MonitorSignal;
+
+/// How often the clipboard is sampled while the watcher is enabled and the window visible
+const CLIPBOARD_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Copied text longer than this is truncated before being sent to the frontend
+const CLIPBOARD_MAX_TEXT_BYTES: usize = 64 * 1024;
+
+/// Token prefixes of well-known API keys that are masked in clipboard events
+const CLIPBOARD_SECRET_PREFIXES: &[&str] = &["sk-", "ghp_", "gho_", "github_pat_", "xoxb-", "xoxp-", "hf_", "AKIA"];
+
+/// Bytes of an image the fingerprint looks at, spread evenly over it
+const IMAGE_FINGERPRINT_SAMPLES: usize = 4096;
+
+/// Opt-in clipboard monitor state. Every change bumps `generation` and a watcher thread
+/// exits once it no longer matches the one it started with, so turning the watcher off
+/// and on again quickly never leaves two running.
+#[derive(Default)]
+pub struct ClipboardWatcher {
+    enabled: std::sync::Mutex<bool>,
+    generation: std::sync::atomic::AtomicU64,
+}
+
+impl ClipboardWatcher {
+    fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(std::sync::atomic::Ordering::SeqCst) == generation
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum ClipboardEvent {
+    Text {
+        text: String,
+        truncated: bool,
+        redacted: bool,
+    },
+    Image {
+        width: usize,
+        height: usize,
+    },
+}
+
+/// Check whether a whitespace-separated word looks like a credential
+fn looks_like_secret(word: &str) -> bool {
+    if CLIPBOARD_SECRET_PREFIXES.iter().any(|p| word.starts_with(p) && word.len() > p.len() + 8) {
+        return true;
+    }
+
+    // Long mixed alphanumeric strings are almost always tokens or hashes, not prose
+    word.len() >= 32
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && word.chars().any(|c| c.is_ascii_digit())
+        && word.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+/// Mask credential-like words; returns the cleaned text and whether anything was replaced
+fn redact_clipboard_text(text: &str) -> (String, bool) {
+    let mut redacted = false;
+    let cleaned = text
+        .split_inclusive(char::is_whitespace)
+        .map(|chunk| {
+            let word = chunk.trim_end();
+            if looks_like_secret(word) {
+                redacted = true;
+                chunk.replacen(word, "[REDACTED]", 1)
+            } else {
+                chunk.to_string()
+            }
+        })
+        .collect();
+    (cleaned, redacted)
+}
+
+/// Truncate text to at most `max` bytes without splitting a character
+pub fn truncate_utf8(text: &str, max: usize) -> (&str, bool) {
+    if text.len() <= max {
+        return (text, false);
+    }
+    let mut end = max;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    (&text[..end], true)
+}
+
+/// Image size plus an even sample of its bytes
+fn image_fingerprint(width: usize, height: usize, bytes: &[u8], hasher: &mut impl std::hash::Hasher) {
+    use std::hash::Hash;
+
+    (width, height, bytes.len()).hash(hasher);
+    let step = (bytes.len() / IMAGE_FINGERPRINT_SAMPLES).max(1);
+    bytes.iter().step_by(step).copied().collect::<Vec<u8>>().hash(hasher);
+}
+
+/// Poll the clipboard and emit `clipboard-changed` for new content until disabled or
+/// started again as a later `generation`
+fn run_clipboard_watcher(app: tauri::AppHandle, generation: u64) {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let current = || app.state::<ClipboardWatcher>().is_current(generation);
+    let mut clipboard = match arboard::Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Clipboard watcher unavailable: {}", e));
+            let watcher = app.state::<ClipboardWatcher>();
+            let mut enabled = watcher.enabled.lock().unwrap();
+            if watcher.is_current(generation) {
+                *enabled = false;
+            }
+            return;
+        }
+    };
+
+    // Start from whatever is already on the clipboard so enabling doesn't fire immediately
+    let fingerprint = |clipboard: &mut arboard::Clipboard| -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        if let Ok(text) = clipboard.get_text() {
+            text.hash(&mut hasher);
+        } else if let Ok(image) = clipboard.get_image() {
+            image_fingerprint(image.width, image.height, &image.bytes, &mut hasher);
+        } else {
+            return None;
+        }
+        Some(hasher.finish())
+    };
+    let mut last = fingerprint(&mut clipboard);
+
+    let mut seen = 0;
+    while current() {
+        app.state::<MonitorSignal>().wait(&mut seen, CLIPBOARD_POLL_INTERVAL);
+        if !current() {
+            break;
+        }
+
+        let current = fingerprint(&mut clipboard);
+        if current.is_none() || current == last {
+            continue;
+        }
+        last = current;
+
+        let event = if let Ok(text) = clipboard.get_text() {
+            let (text, truncated) = truncate_utf8(&text, CLIPBOARD_MAX_TEXT_BYTES);
+            let (text, redacted) = redact_clipboard_text(text);
+            ClipboardEvent::Text {
+                text,
+                truncated,
+                redacted,
+            }
+        } else if let Ok(image) = clipboard.get_image() {
+            ClipboardEvent::Image {
+                width: image.width,
+                height: image.height,
+            }
+        } else {
+            continue;
+        };
+
+        let _ = app.emit("clipboard-changed", event);
+    }
+
+    log_to_file("[Leaxer] Clipboard watcher stopped");
+}
+
+/// Enable or disable the clipboard watcher (off by default)
+#[tauri::command]
+pub fn set_clipboard_watcher(app: tauri::AppHandle, enabled: bool) {
+    let watcher = app.state::<ClipboardWatcher>();
+    let mut state = watcher.enabled.lock().unwrap();
+    if *state == enabled {
+        return;
+    }
+    *state = enabled;
+    let generation = watcher.generation.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+
+    if enabled {
+        log_to_file("[Leaxer] Clipboard watcher started");
+        let app = app.clone();
+        std::thread::spawn(move || run_clipboard_watcher(app, generation));
+    }
+}
+
+/// Whether the clipboard watcher is currently running
+#[tauri::command]
+pub fn is_clipboard_watcher_enabled(app: tauri::AppHandle) -> bool {
+    *app.state::<ClipboardWatcher>().enabled.lock().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_api_keys_and_tokens() {
+        let (text, redacted) = redact_clipboard_text("key: sk-abcdefghijklmnop1234 rest");
+        assert!(redacted);
+        assert_eq!(text, "key: [REDACTED] rest");
+
+        let (text, redacted) = redact_clipboard_text("token 0123456789abcdef0123456789abcdef\nnext line");
+        assert!(redacted);
+        assert_eq!(text, "token [REDACTED]\nnext line");
+    }
+
+    #[test]
+    fn leaves_prose_alone() {
+        let input = "Summarize the meeting notes from yesterday, please.";
+        assert_eq!(redact_clipboard_text(input), (input.to_string(), false));
+
+        // A known prefix on its own isn't a secret
+        assert!(!redact_clipboard_text("sk-").1);
+    }
+
+    #[test]
+    fn image_fingerprints_sample_large_images() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::Hasher;
+
+        let fingerprint = |width: usize, height: usize, bytes: &[u8]| {
+            let mut hasher = DefaultHasher::new();
+            image_fingerprint(width, height, bytes, &mut hasher);
+            hasher.finish()
+        };
+        let image = vec![7u8; 2000 * 1000 * 4];
+        assert_eq!(fingerprint(2000, 1000, &image), fingerprint(2000, 1000, &image.to_vec()));
+        assert_ne!(fingerprint(2000, 1000, &image), fingerprint(1000, 2000, &image));
+
+        let mut changed = image.clone();
+        changed[0] = 8;
+        assert_ne!(fingerprint(2000, 1000, &image), fingerprint(2000, 1000, &changed));
+    }
+
+    #[test]
+    fn truncates_on_char_boundaries() {
+        assert_eq!(truncate_utf8("hello", 10), ("hello", false));
+        assert_eq!(truncate_utf8("hello", 3), ("hel", true));
+        // "é" is two bytes; cutting in the middle must back off
+        assert_eq!(truncate_utf8("aé", 2), ("a", true));
+    }
+}
@@ -0,0 +1,152 @@
+//! Diagnostics bundle for bug reports: logs, config.json without secrets and system info,
+//! zipped to a location the user picks.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::logging::{is_secret_key, log_to_file, REDACTED};
+
+/// Rotated copies of a log included besides the current file
+const ROTATED_LOGS: usize = 2;
+
+/// Copy of a config value with every secret-looking entry blanked out
+pub fn sanitize_config(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if is_secret_key(key) && !value.is_null() {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        sanitize_config(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(sanitize_config).collect()),
+        other => other.clone(),
+    }
+}
+
+/// Log files to bundle, with their names inside the archive
+fn log_files() -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    for path in [crate::logging::startup_log_path(), crate::logging::backend_log_path()].into_iter().flatten() {
+        let Some(name) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+            continue;
+        };
+        for index in 0..=ROTATED_LOGS {
+            let (name, path) = match index {
+                0 => (name.clone(), path.clone()),
+                _ => (format!("{}.{}", name, index), PathBuf::from(format!("{}.{}", path.display(), index))),
+            };
+            if path.is_file() {
+                files.push((format!("logs/{}", name), path));
+            }
+        }
+    }
+    files
+}
+
+/// Write the bundle to `dest`
+fn write_bundle(dest: &Path, config: &Value, system: &Value) -> Result<(), String> {
+    let file = std::fs::File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+    let mut add = |name: &str, bytes: &[u8]| -> zip::result::ZipResult<()> {
+        zip.start_file(name, options)?;
+        zip.write_all(bytes)?;
+        Ok(())
+    };
+
+    for (name, path) in log_files() {
+        if let Ok(bytes) = std::fs::read(&path) {
+            add(&name, &bytes).map_err(|e| e.to_string())?;
+        }
+    }
+    let config = serde_json::to_vec_pretty(&sanitize_config(config)).unwrap_or_default();
+    add("config.json", &config).map_err(|e| e.to_string())?;
+    add("system.json", &serde_json::to_vec_pretty(system).unwrap_or_default()).map_err(|e| e.to_string())?;
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Ask where to save a diagnostics zip and write it there. Returns the saved path, or
+/// `None` if the user cancelled the dialog.
+#[cfg(feature = "dialog")]
+#[tauri::command]
+pub async fn export_diagnostics(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    use tauri::Manager;
+    use tauri_plugin_dialog::DialogExt;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter("Zip archive", &["zip"])
+        .set_file_name("leaxer-diagnostics.zip")
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+    let dest = match rx.await.map_err(|e| e.to_string())? {
+        Some(path) => path.into_path().map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+
+    crate::logging::flush();
+    let config = app.state::<crate::config::ConfigStore>().load();
+    let system = super::system::get_system_info().await?;
+    let system = serde_json::to_value(system).map_err(|e| e.to_string())?;
+    let path = dest.clone();
+    tauri::async_runtime::spawn_blocking(move || write_bundle(&path, &config, &system))
+        .await
+        .map_err(|e| e.to_string())??;
+
+    log_to_file(&format!("[Leaxer] Diagnostics written to {:?}", dest));
+    Ok(Some(dest.to_string_lossy().to_string()))
+}
+
+#[cfg(not(feature = "dialog"))]
+#[tauri::command]
+pub async fn export_diagnostics() -> Result<Option<String>, String> {
+    Err(crate::features::unavailable("Diagnostics exports", &["dialog"]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_blanked_out() {
+        let config = serde_json::json!({
+            "developer_mode": true,
+            "webhook": { "token": "abc123", "port": 4010 },
+            "api_keys": [{ "name": "hf", "HF_TOKEN": "hf_x" }],
+            "openai_api_key": null
+        });
+        assert_eq!(
+            sanitize_config(&config),
+            serde_json::json!({
+                "developer_mode": true,
+                "webhook": { "token": REDACTED, "port": 4010 },
+                "api_keys": REDACTED,
+                "openai_api_key": null
+            })
+        );
+    }
+
+    #[test]
+    fn bundles_config_and_system_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("diagnostics.zip");
+        write_bundle(&dest, &serde_json::json!({ "secret_key_base": "s" }), &serde_json::json!({ "os": "test" })).unwrap();
+
+        let mut zip = zip::ZipArchive::new(std::fs::File::open(&dest).unwrap()).unwrap();
+        let mut config = String::new();
+        std::io::Read::read_to_string(&mut zip.by_name("config.json").unwrap(), &mut config).unwrap();
+        assert!(config.contains(REDACTED) && !config.contains("\"s\""));
+        assert!(zip.by_name("system.json").is_ok());
+    }
+}
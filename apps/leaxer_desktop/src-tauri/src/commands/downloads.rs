@@ -0,0 +1,322 @@
+//! Exporting backend resources straight to disk, bypassing JS blobs.
+//!
+//! Needs the `http` and `dialog` features; without them the command reports that
+//! downloads are unavailable in this build.
+
+#[cfg(all(feature = "http", feature = "dialog"))]
+use {
+    crate::checksum::{chunk_range, verify, ChunkManifest},
+    crate::commands::tasks::{register_task, TaskHandle},
+    crate::journal::{part_path, Journal, Operation},
+    crate::logging::log_to_file,
+    crate::net::resolve_backend_resource,
+    crate::paths::allow_path,
+    std::path::{Path, PathBuf},
+    tauri::Manager,
+    tauri_plugin_dialog::DialogExt,
+    tauri_plugin_http::reqwest,
+    tokio::io::{AsyncSeekExt, AsyncWriteExt},
+};
+
+/// Minimum number of bytes between two `task:progress` events for a download
+#[cfg(all(feature = "http", feature = "dialog"))]
+const DOWNLOAD_PROGRESS_STEP: u64 = 1024 * 1024;
+
+/// Constructor for the `Lazy` download client: its connection pool and TLS setup are
+/// only paid for once something is actually downloaded
+#[cfg(all(feature = "http", feature = "dialog"))]
+pub fn create_client(_app: &tauri::AppHandle) -> reqwest::Client {
+    reqwest::Client::new()
+}
+
+/// Where the validator of the response a part file holds is kept
+#[cfg(all(feature = "http", feature = "dialog"))]
+fn validator_path(part_path: &Path) -> PathBuf {
+    let mut path = part_path.as_os_str().to_owned();
+    path.push(".validator");
+    PathBuf::from(path)
+}
+
+/// A strong ETag, else Last-Modified: what `If-Range` accepts
+#[cfg(all(feature = "http", feature = "dialog"))]
+fn response_validator(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    let header = |name: reqwest::header::HeaderName| headers.get(name).and_then(|value| value.to_str().ok());
+    header(reqwest::header::ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+        .map(str::to_string)
+}
+
+/// Stream `url` into `part_path`, reporting progress on `task`. Returns the number of bytes written.
+///
+/// An existing part file is continued with a Range request, so an interrupted or cancelled
+/// export resumes the next time it is saved to the same path. The request carries the
+/// saved validator in `If-Range`, so a resource that changed meanwhile comes back whole
+/// instead of being appended to the old bytes; a part without one starts over.
+#[cfg(all(feature = "http", feature = "dialog"))]
+async fn stream_to_file(
+    client: &reqwest::Client,
+    task: &TaskHandle,
+    url: reqwest::Url,
+    part_path: &Path,
+) -> Result<u64, String> {
+    let validator_path = validator_path(part_path);
+    let saved_validator = tokio::fs::read_to_string(&validator_path).await.ok();
+    let resume_from = match saved_validator {
+        Some(_) => tokio::fs::metadata(&part_path).await.map(|m| m.len()).unwrap_or(0),
+        None => 0,
+    };
+
+    let mut request = client.get(url.clone());
+    if let (true, Some(validator)) = (resume_from > 0, saved_validator.as_deref()) {
+        request = request
+            .header(reqwest::header::RANGE, format!("bytes={}-", resume_from))
+            .header(reqwest::header::IF_RANGE, validator.trim());
+    }
+
+    let mut response = request.send().await.map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Download failed with status {}", response.status()));
+    }
+    match response_validator(response.headers()) {
+        Some(validator) => tokio::fs::write(&validator_path, validator).await.map_err(|e| e.to_string())?,
+        None => {
+            let _ = tokio::fs::remove_file(&validator_path).await;
+        }
+    }
+
+    // The backend may ignore the Range header, in which case we start over
+    let resumed = resume_from > 0 && response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut received = if resumed { resume_from } else { 0 };
+    let total = response.content_length().map(|len| len + received);
+
+    log_to_file(&format!(
+        "[Leaxer] Downloading {} to {:?} (resume from {})",
+        url, part_path, received
+    ));
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part_path)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut last_emitted = received;
+    task.progress(received, total);
+
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        if task.is_cancelled() {
+            let _ = file.flush().await;
+            return Err("Download cancelled".to_string());
+        }
+
+        file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+        received += chunk.len() as u64;
+
+        if received - last_emitted >= DOWNLOAD_PROGRESS_STEP {
+            last_emitted = received;
+            task.progress(received, total);
+        }
+    }
+
+    file.flush().await.map_err(|e| e.to_string())?;
+
+    task.progress(received, total);
+    Ok(received)
+}
+
+/// Hash the downloaded file on all cores and compare it with `manifest`. Corrupt chunks
+/// are fetched again with Range requests and patched in place, once; a file that still
+/// doesn't match is deleted so the next attempt starts clean.
+#[cfg(all(feature = "http", feature = "dialog"))]
+async fn verify_download(
+    client: &reqwest::Client,
+    url: &reqwest::Url,
+    part_path: &Path,
+    manifest: &ChunkManifest,
+) -> Result<(), String> {
+    let check = |path: PathBuf, manifest: ChunkManifest| async move {
+        tauri::async_runtime::spawn_blocking(move || verify(&path, &manifest))
+            .await
+            .map_err(|e| e.to_string())?
+            .map_err(|e| format!("Checksum verification failed: {}", e))
+    };
+
+    let started = std::time::Instant::now();
+    let bad = check(part_path.to_path_buf(), manifest.clone()).await;
+    log_to_file(&format!(
+        "[Leaxer] Verified {:?} in {} ms",
+        part_path,
+        started.elapsed().as_millis()
+    ));
+
+    let bad = match bad {
+        Ok(bad) if bad.is_empty() => return Ok(()),
+        Ok(bad) => bad,
+        Err(e) => {
+            let _ = tokio::fs::remove_file(part_path).await;
+            return Err(e);
+        }
+    };
+
+    log_to_file(&format!("[Leaxer] Re-downloading {} corrupt chunks", bad.len()));
+    let validator = tokio::fs::read_to_string(validator_path(part_path)).await.ok();
+    let mut file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(part_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    for &index in &bad {
+        let (start, end) = chunk_range(manifest, index);
+        let mut request = client.get(url.clone()).header(reqwest::header::RANGE, format!("bytes={}-{}", start, end));
+        if let Some(validator) = validator.as_deref() {
+            request = request.header(reqwest::header::IF_RANGE, validator.trim());
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(format!("Server cannot resend chunk {} ({})", index, response.status()));
+        }
+        let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+        file.seek(std::io::SeekFrom::Start(start)).await.map_err(|e| e.to_string())?;
+        file.write_all(&bytes).await.map_err(|e| e.to_string())?;
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    drop(file);
+
+    match check(part_path.to_path_buf(), manifest.clone()).await {
+        Ok(bad) if bad.is_empty() => Ok(()),
+        result => {
+            let _ = tokio::fs::remove_file(part_path).await;
+            Err(match result {
+                Ok(bad) => format!("Checksum mismatch in {} chunks after retry", bad.len()),
+                Err(e) => e,
+            })
+        }
+    }
+}
+
+/// Show a save dialog and stream a backend resource to the chosen file.
+/// Progress is reported through the task registry (`task:progress` / `task:done`).
+/// Returns the saved path, or `None` if the user cancelled the dialog.
+/// With a `manifest` (for model weights), the file is verified in parallel before it is
+/// moved into place.
+#[cfg(all(feature = "http", feature = "dialog"))]
+#[tauri::command]
+pub async fn download_to_disk(
+    app: tauri::AppHandle,
+    url_or_id: String,
+    suggested_name: String,
+    manifest: Option<ChunkManifest>,
+) -> Result<Option<String>, String> {
+    let url = resolve_backend_resource(&url_or_id)?;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .set_file_name(&suggested_name)
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+    let dest = match rx.await.map_err(|e| e.to_string())? {
+        Some(path) => path.into_path().map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+
+    fetch_to(&app, url, dest, manifest).await.map(Some)
+}
+
+/// Download `url` into `dest` as a journaled task, resuming a `.part` file left from before
+#[cfg(all(feature = "http", feature = "dialog"))]
+async fn fetch_to(
+    app: &tauri::AppHandle,
+    url: reqwest::Url,
+    dest: PathBuf,
+    manifest: Option<ChunkManifest>,
+) -> Result<String, String> {
+    let label = dest
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let task = register_task(app, "download", &label);
+    let journal = app.state::<Journal>();
+    let entry = journal.begin(Operation::Download {
+        url: url.to_string(),
+        dest: dest.clone(),
+        manifest: manifest.clone(),
+    });
+
+    let client = crate::lazy::get::<reqwest::Client>(app);
+    let part_path = part_path(&dest);
+    let mut result = stream_to_file(client, &task, url.clone(), &part_path).await;
+    if let (Ok(_), Some(manifest)) = (&result, &manifest) {
+        if let Err(e) = verify_download(client, &url, &part_path, manifest).await {
+            result = Err(e);
+        }
+    }
+    if result.is_ok() {
+        if let Err(e) = tokio::fs::rename(&part_path, &dest).await {
+            result = Err(e.to_string());
+        }
+        let _ = tokio::fs::remove_file(validator_path(&part_path)).await;
+    }
+    // Failed or cancelled downloads keep their part file; only a crash leaves the entry open
+    journal.end(entry);
+    task.finish(&result.as_ref().map(|_| ()).map_err(|e| e.clone()));
+
+    let received = result?;
+    log_to_file(&format!("[Leaxer] Download complete: {:?} ({} bytes)", dest, received));
+    let dest_str = dest.to_string_lossy().to_string();
+    allow_path(app, dest);
+
+    Ok(dest_str)
+}
+
+/// Continue a download the journal found interrupted, to the destination chosen back then
+#[cfg(all(feature = "http", feature = "dialog"))]
+pub async fn resume_download(
+    app: &tauri::AppHandle,
+    url: &str,
+    dest: PathBuf,
+    manifest: Option<ChunkManifest>,
+) -> Result<String, String> {
+    let url = resolve_backend_resource(url)?;
+    fetch_to(app, url, dest, manifest).await
+}
+
+/// Downloads need the `http` and `dialog` features, which this build was compiled without
+#[cfg(not(all(feature = "http", feature = "dialog")))]
+#[tauri::command]
+pub async fn download_to_disk() -> Result<Option<String>, String> {
+    Err(crate::features::unavailable("Downloads", &["http", "dialog"]))
+}
+
+#[cfg(not(all(feature = "http", feature = "dialog")))]
+pub async fn resume_download(
+    _app: &tauri::AppHandle,
+    _url: &str,
+    _dest: std::path::PathBuf,
+    _manifest: Option<crate::checksum::ChunkManifest>,
+) -> Result<String, String> {
+    Err(crate::features::unavailable("Downloads", &["http", "dialog"]))
+}
+
+#[cfg(all(test, feature = "http", feature = "dialog"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_only_against_strong_validators() {
+        use reqwest::header::{HeaderMap, HeaderValue, ETAG, LAST_MODIFIED};
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(response_validator(&headers), None);
+        headers.insert(LAST_MODIFIED, HeaderValue::from_static("Wed, 14 Oct 2026 10:00:00 GMT"));
+        headers.insert(ETAG, HeaderValue::from_static("W/\"weak\""));
+        assert_eq!(response_validator(&headers).as_deref(), Some("Wed, 14 Oct 2026 10:00:00 GMT"));
+        headers.insert(ETAG, HeaderValue::from_static("\"strong\""));
+        assert_eq!(response_validator(&headers).as_deref(), Some("\"strong\""));
+    }
+}
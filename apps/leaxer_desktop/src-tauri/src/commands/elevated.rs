@@ -0,0 +1,669 @@
+//! Broker for a fixed allowlist of privileged operations.
+//!
+//! The allowlist holds firewall rules for network exposure, and the backend as a system
+//! service that starts at boot. Every operation is a fixed list of steps built from
+//! validated numbers and paths the shell found itself, run behind a single prompt.
+//!
+//! The service runs the bundled release as the current user on its own port, so it never
+//! competes with a backend the shell spawns. Its settings (port, origins) are system config
+//! in a fixed file; the keys stay in `service.env` in the user's Leaxer dir, readable only
+//! by the user. The backend loads both through `LEAXER_ENV_FILES`. Once the service is
+//! installed the shell uses it as its external backend from the next launch on.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use tauri::Manager;
+
+#[cfg(target_os = "windows")]
+use crate::process::CREATE_NO_WINDOW;
+use crate::logging::log_to_file;
+
+/// Name used for firewall rules created by Leaxer
+const FIREWALL_RULE_NAME: &str = "Leaxer";
+
+/// Port the service backend listens on unless configured otherwise, below the profiles' ports
+const SERVICE_PORT: u16 = 4100;
+
+/// The service's keys and user dir, in the user's Leaxer dir
+const SERVICE_ENV_FILE: &str = "service.env";
+
+/// Lists the files the backend loads its environment from, separated like PATH
+const ENV_FILES_ENV: &str = "LEAXER_ENV_FILES";
+
+#[cfg(target_os = "linux")]
+const SYSTEMD_UNIT: &str = "leaxer-core.service";
+
+#[cfg(target_os = "macos")]
+const LAUNCHD_LABEL: &str = "ai.leaxer.core";
+
+#[cfg(target_os = "windows")]
+const WINDOWS_SERVICE: &str = "LeaxerCore";
+
+/// The fixed set of privileged actions the shell is willing to perform.
+/// Anything not listed here cannot be run elevated, regardless of what the frontend sends.
+#[derive(Clone, Copy, Debug, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ElevatedOperation {
+    /// Allow inbound TCP connections on a port (for network exposure mode)
+    AddFirewallRule { port: u16 },
+    /// Remove the rule created by `AddFirewallRule`
+    RemoveFirewallRule { port: u16 },
+    /// Run the bundled backend as a system service that starts at boot
+    InstallService,
+    /// Write the service's system config and restart it
+    ConfigureService { port: u16 },
+    /// Stop the service and remove it along with its system config
+    UninstallService,
+}
+
+/// One step of an operation
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    Run(String, Vec<String>),
+    /// Create or replace a file, and its directory
+    Write(PathBuf, String),
+    /// Delete a file if it's there
+    Remove(PathBuf),
+}
+
+fn run_step(program: &str, args: &[&str]) -> Step {
+    Step::Run(program.to_string(), args.iter().map(|arg| arg.to_string()).collect())
+}
+
+/// What the service runs, found before asking for elevation
+#[derive(Clone, Debug)]
+struct Service {
+    /// The release's launcher, `bin/leaxer_core`
+    launcher: PathBuf,
+    /// The release root
+    root: PathBuf,
+    /// The user the service runs as
+    user: String,
+    /// The system config and the user's `service.env`, joined like PATH
+    env_files: String,
+}
+
+/// The backend VM, which the macOS application firewall lets through per executable
+#[cfg(target_os = "macos")]
+fn backend_vm(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let launcher = crate::process::locate_backend(app).ok_or("No bundled backend")?;
+    let root = launcher.parent().and_then(Path::parent).ok_or("No backend release")?;
+    let (erts_vsn, _) = crate::process::release_versions(root).ok_or("The backend release has no ERTS")?;
+    let vm = root.join(format!("erts-{}", erts_vsn)).join("bin").join("beam.smp");
+    if !vm.exists() {
+        return Err(format!("Backend VM not found at {}", vm.display()));
+    }
+    Ok(vm)
+}
+
+/// The service's system config file
+fn system_config_path() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let program_data = std::env::var_os("ProgramData").unwrap_or_else(|| "C:\\ProgramData".into());
+        PathBuf::from(program_data).join("Leaxer").join("leaxer-core.env")
+    }
+    #[cfg(target_os = "macos")]
+    {
+        PathBuf::from("/Library/Application Support/Leaxer/leaxer-core.env")
+    }
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        PathBuf::from("/etc/leaxer/leaxer-core.env")
+    }
+}
+
+/// The system config: only validated, non-secret settings
+fn service_config(port: u16) -> String {
+    format!(
+        "PHX_SERVER=true\nPHX_HOST=localhost\nPORT={}\nCORS_ORIGINS={}\n",
+        port,
+        crate::net::cors_origins(port, &[])
+    )
+}
+
+/// Address the shell reaches the service at
+fn service_url(port: u16) -> String {
+    format!("http://127.0.0.1:{}", port)
+}
+
+fn locate_service(app: &tauri::AppHandle) -> Result<Service, String> {
+    let launcher = crate::process::locate_backend(app).ok_or("No bundled backend")?;
+    let root = launcher.parent().and_then(Path::parent).ok_or("No backend release")?.to_path_buf();
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .map_err(|_| "Could not tell which user to run the service as")?;
+    let dir = crate::paths::base_leaxer_dir().ok_or("No data directory")?;
+    let env_files = std::env::join_paths([system_config_path(), dir.join(SERVICE_ENV_FILE)])
+        .map_err(|e| e.to_string())?
+        .to_string_lossy()
+        .to_string();
+    Ok(Service { launcher, root, user, env_files })
+}
+
+/// Write the keys and user dir the service backend needs, for the user's eyes only
+fn write_service_env(app: &tauri::AppHandle) -> Result<(), String> {
+    let secrets = app.state::<crate::secrets::SecretStore>();
+    let keys = secrets.backend_keys();
+    let mut env = vec![
+        ("SECRET_KEY_BASE".to_string(), keys.secret_key_base),
+        ("SIGNING_SALT".to_string(), keys.signing_salt),
+        (crate::logging::LOG_LEVEL_ENV.to_string(), crate::logging::backend_log_level().to_string()),
+    ];
+    if let Some(dir) = crate::paths::get_leaxer_user_dir() {
+        env.push(("LEAXER_USER_DIR".to_string(), dir.to_string_lossy().to_string()));
+    }
+    env.extend(secrets.backend_env());
+    let contents: String = env
+        .into_iter()
+        .filter(|(_, value)| !value.contains(['\r', '\n']))
+        .map(|(name, value)| format!("{}={}\n", name, value))
+        .collect();
+    let dir = crate::paths::base_leaxer_dir().ok_or("No data directory")?;
+    crate::secrets::write_private(&dir.join(SERVICE_ENV_FILE), contents.as_bytes()).map_err(|e| e.to_string())
+}
+
+fn refuse_privileged_port(port: u16) -> Result<(), String> {
+    if port < 1024 {
+        return Err(format!("Refusing to use privileged port {}", port));
+    }
+    Ok(())
+}
+
+/// A word quoted for `sh`
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn sh_quote(word: &str) -> String {
+    format!("'{}'", word.replace('\'', "'\\''"))
+}
+
+/// The steps as one `sh` command line, stopping at the first that fails
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn shell_script(steps: &[Step]) -> String {
+    steps
+        .iter()
+        .map(|step| match step {
+            Step::Run(program, args) => std::iter::once(program)
+                .chain(args)
+                .map(|word| sh_quote(word))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Step::Write(path, contents) => format!(
+                "mkdir -p {} && printf '%s' {} > {}",
+                sh_quote(&path.parent().unwrap_or(Path::new("/")).to_string_lossy()),
+                sh_quote(contents),
+                sh_quote(&path.to_string_lossy())
+            ),
+            Step::Remove(path) => format!("rm -f {}", sh_quote(&path.to_string_lossy())),
+        })
+        .collect::<Vec<_>>()
+        .join(" && ")
+}
+
+/// A string literal for AppleScript
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// An argument quoted the way `CommandLineToArgvW` and the C runtime split command lines
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn windows_quote(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        let escapes = if c == '"' { backslashes * 2 + 1 } else { backslashes };
+        quoted.push_str(&"\\".repeat(escapes));
+        quoted.push(c);
+        backslashes = 0;
+    }
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+/// A string literal for PowerShell
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn powershell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// The steps as a PowerShell script, stopping at the first that fails. Programs get their
+/// command line through Start-Process, which passes it on as is.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn powershell_script(steps: &[Step]) -> String {
+    let mut script = String::from("$ErrorActionPreference = 'Stop'\n");
+    for step in steps {
+        match step {
+            Step::Run(program, args) => script.push_str(&format!(
+                "$p = Start-Process -FilePath {} -ArgumentList {} -NoNewWindow -Wait -PassThru\n\
+                 if ($p.ExitCode) {{ exit $p.ExitCode }}\n",
+                powershell_quote(program),
+                powershell_quote(&args.iter().map(|arg| windows_quote(arg)).collect::<Vec<_>>().join(" "))
+            )),
+            Step::Write(path, contents) => script.push_str(&format!(
+                "New-Item -ItemType Directory -Force -Path {} | Out-Null\nSet-Content -NoNewline -Path {} -Value {}\n",
+                powershell_quote(&path.parent().unwrap_or(Path::new("\\")).to_string_lossy()),
+                powershell_quote(&path.to_string_lossy()),
+                powershell_quote(contents)
+            )),
+            Step::Remove(path) => script.push_str(&format!(
+                "Remove-Item -Force -ErrorAction SilentlyContinue -Path {}\n",
+                powershell_quote(&path.to_string_lossy())
+            )),
+        }
+    }
+    script
+}
+
+/// Firewall steps for ufw, or firewalld, whose permanent rules only apply after a reload,
+/// so the running firewall gets the same change
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn linux_firewall_steps(add: bool, port: u16, ufw: bool) -> Vec<Step> {
+    let port = format!("{}/tcp", port);
+    match (add, ufw) {
+        (true, true) => vec![run_step("ufw", &["allow", &port, "comment", FIREWALL_RULE_NAME])],
+        (false, true) => vec![run_step("ufw", &["delete", "allow", &port])],
+        (true, false) => {
+            let arg = format!("--add-port={}", port);
+            vec![run_step("firewall-cmd", &["--permanent", &arg]), run_step("firewall-cmd", &[&arg])]
+        }
+        (false, false) => {
+            let arg = format!("--remove-port={}", port);
+            vec![run_step("firewall-cmd", &["--permanent", &arg]), run_step("firewall-cmd", &[&arg])]
+        }
+    }
+}
+
+/// `%` starts a specifier in systemd unit files
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn systemd_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('%', "%%"))
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn systemd_unit(service: &Service) -> String {
+    format!(
+        "[Unit]\nDescription=Leaxer backend\nAfter=network.target\n\n\
+         [Service]\nUser={}\nWorkingDirectory={}\nEnvironment=RELEASE_DISTRIBUTION=none\n\
+         Environment={}\nExecStart={} start\nRestart=on-failure\n\n\
+         [Install]\nWantedBy=multi-user.target\n",
+        service.user,
+        systemd_quote(&service.root.to_string_lossy()),
+        systemd_quote(&format!("{}={}", ENV_FILES_ENV, service.env_files)),
+        systemd_quote(&service.launcher.to_string_lossy())
+    )
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launchd_plist(label: &str, service: &Service) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n<dict>\n\
+         \t<key>Label</key><string>{}</string>\n\
+         \t<key>UserName</key><string>{}</string>\n\
+         \t<key>WorkingDirectory</key><string>{}</string>\n\
+         \t<key>ProgramArguments</key><array><string>{}</string><string>start</string></array>\n\
+         \t<key>EnvironmentVariables</key><dict>\n\
+         \t\t<key>RELEASE_DISTRIBUTION</key><string>none</string>\n\
+         \t\t<key>{}</key><string>{}</string>\n\
+         \t</dict>\n\
+         \t<key>RunAtLoad</key><true/>\n\
+         \t<key>KeepAlive</key><true/>\n\
+         </dict>\n</plist>\n",
+        label,
+        xml_escape(&service.user),
+        xml_escape(&service.root.to_string_lossy()),
+        xml_escape(&service.launcher.to_string_lossy()),
+        ENV_FILES_ENV,
+        xml_escape(&service.env_files)
+    )
+}
+
+/// erlsrv from the release's ERTS, and the arguments that register the release with it
+#[cfg(target_os = "windows")]
+fn erlsrv_add(service: &Service) -> Result<(String, Vec<String>), String> {
+    let (erts_vsn, _) =
+        crate::process::release_versions(&service.root).ok_or("The backend release has no ERTS")?;
+    let erlsrv = service.root.join(format!("erts-{}", erts_vsn)).join("bin").join("erlsrv.exe");
+    let erl = crate::process::release_start_command(&service.root).ok_or("The backend release has no ERTS")?;
+    let mut args = vec![
+        "add".to_string(),
+        WINDOWS_SERVICE.to_string(),
+        "-onfail".to_string(),
+        "restart".to_string(),
+        "-workdir".to_string(),
+        service.root.to_string_lossy().to_string(),
+        "-env".to_string(),
+        format!("{}={}", ENV_FILES_ENV, service.env_files),
+    ];
+    for (name, value) in erl.get_envs().filter_map(|(name, value)| Some((name, value?))) {
+        args.push("-env".to_string());
+        args.push(format!("{}={}", name.to_string_lossy(), value.to_string_lossy()));
+    }
+    let erl_args: Vec<String> = erl.get_args().map(|arg| windows_quote(&arg.to_string_lossy())).collect();
+    args.push("-args".to_string());
+    args.push(erl_args.join(" "));
+    Ok((erlsrv.to_string_lossy().to_string(), args))
+}
+
+/// Build the steps of an operation; the values are fixed apart from validated numbers and
+/// the paths of the bundled backend
+#[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
+fn elevated_steps(
+    operation: ElevatedOperation,
+    backend_vm: Option<&Path>,
+    service: Option<&Service>,
+) -> Result<Vec<Step>, String> {
+    match operation {
+        ElevatedOperation::AddFirewallRule { port } | ElevatedOperation::RemoveFirewallRule { port } => {
+            refuse_privileged_port(port)?
+        }
+        ElevatedOperation::ConfigureService { port } => refuse_privileged_port(port)?,
+        ElevatedOperation::InstallService | ElevatedOperation::UninstallService => {}
+    }
+    let config = system_config_path();
+
+    #[cfg(target_os = "windows")]
+    {
+        let erlsrv = || -> Result<String, String> {
+            let service = service.ok_or("No bundled backend")?;
+            Ok(erlsrv_add(service)?.0)
+        };
+        Ok(match operation {
+            ElevatedOperation::AddFirewallRule { port } => vec![Step::Run(
+                "netsh".to_string(),
+                format!(
+                    "advfirewall firewall add rule name={} dir=in action=allow protocol=TCP localport={}",
+                    FIREWALL_RULE_NAME, port
+                )
+                .split(' ')
+                .map(String::from)
+                .collect(),
+            )],
+            ElevatedOperation::RemoveFirewallRule { port } => vec![Step::Run(
+                "netsh".to_string(),
+                format!("advfirewall firewall delete rule name={} protocol=TCP localport={}", FIREWALL_RULE_NAME, port)
+                    .split(' ')
+                    .map(String::from)
+                    .collect(),
+            )],
+            ElevatedOperation::InstallService => {
+                let (erlsrv, add) = erlsrv_add(service.ok_or("No bundled backend")?)?;
+                vec![
+                    Step::Write(config, service_config(SERVICE_PORT)),
+                    Step::Run(erlsrv.clone(), add),
+                    run_step(&erlsrv, &["start", WINDOWS_SERVICE]),
+                ]
+            }
+            ElevatedOperation::ConfigureService { port } => {
+                let erlsrv = erlsrv()?;
+                vec![
+                    Step::Write(config, service_config(port)),
+                    run_step(&erlsrv, &["stop", WINDOWS_SERVICE]),
+                    run_step(&erlsrv, &["start", WINDOWS_SERVICE]),
+                ]
+            }
+            ElevatedOperation::UninstallService => {
+                vec![run_step(&erlsrv()?, &["remove", WINDOWS_SERVICE]), Step::Remove(config)]
+            }
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let unit = Path::new("/etc/systemd/system").join(SYSTEMD_UNIT);
+        Ok(match operation {
+            ElevatedOperation::AddFirewallRule { port } => {
+                linux_firewall_steps(true, port, Path::new("/usr/sbin/ufw").exists())
+            }
+            ElevatedOperation::RemoveFirewallRule { port } => {
+                linux_firewall_steps(false, port, Path::new("/usr/sbin/ufw").exists())
+            }
+            ElevatedOperation::InstallService => vec![
+                Step::Write(config, service_config(SERVICE_PORT)),
+                Step::Write(unit, systemd_unit(service.ok_or("No bundled backend")?)),
+                run_step("systemctl", &["daemon-reload"]),
+                run_step("systemctl", &["enable", "--now", SYSTEMD_UNIT]),
+            ],
+            ElevatedOperation::ConfigureService { port } => vec![
+                Step::Write(config, service_config(port)),
+                run_step("systemctl", &["restart", SYSTEMD_UNIT]),
+            ],
+            ElevatedOperation::UninstallService => vec![
+                run_step("systemctl", &["disable", "--now", SYSTEMD_UNIT]),
+                Step::Remove(unit),
+                Step::Remove(config),
+                run_step("systemctl", &["daemon-reload"]),
+            ],
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let plist = Path::new("/Library/LaunchDaemons").join(format!("{}.plist", LAUNCHD_LABEL));
+        let target = format!("system/{}", LAUNCHD_LABEL);
+        let plist_arg = plist.to_string_lossy().to_string();
+        Ok(match operation {
+            // The application firewall allows executables, not ports
+            ElevatedOperation::AddFirewallRule { .. } | ElevatedOperation::RemoveFirewallRule { .. } => {
+                let _ = FIREWALL_RULE_NAME;
+                let vm = backend_vm.ok_or("No backend VM to allow")?.to_string_lossy().to_string();
+                let args = match operation {
+                    ElevatedOperation::AddFirewallRule { .. } => {
+                        vec!["--add".into(), vm.clone(), "--unblockapp".into(), vm]
+                    }
+                    _ => vec!["--remove".into(), vm],
+                };
+                vec![Step::Run("/usr/libexec/ApplicationFirewall/socketfilterfw".to_string(), args)]
+            }
+            ElevatedOperation::InstallService => vec![
+                Step::Write(config, service_config(SERVICE_PORT)),
+                Step::Write(plist, launchd_plist(LAUNCHD_LABEL, service.ok_or("No bundled backend")?)),
+                run_step("launchctl", &["bootstrap", "system", &plist_arg]),
+            ],
+            ElevatedOperation::ConfigureService { port } => vec![
+                Step::Write(config, service_config(port)),
+                run_step("launchctl", &["kickstart", "-k", &target]),
+            ],
+            ElevatedOperation::UninstallService => vec![
+                run_step("launchctl", &["bootout", &target]),
+                Step::Remove(plist),
+                Step::Remove(config),
+            ],
+        })
+    }
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(unused_variables))]
+async fn run(app: &tauri::AppHandle, operation: ElevatedOperation) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    let backend_vm = match operation {
+        ElevatedOperation::AddFirewallRule { .. } | ElevatedOperation::RemoveFirewallRule { .. } => {
+            Some(backend_vm(app)?)
+        }
+        _ => None,
+    };
+    #[cfg(not(target_os = "macos"))]
+    let backend_vm: Option<std::path::PathBuf> = None;
+    let service = match operation {
+        ElevatedOperation::InstallService => {
+            write_service_env(app)?;
+            Some(locate_service(app)?)
+        }
+        ElevatedOperation::ConfigureService { .. } => {
+            write_service_env(app)?;
+            locate_service(app).ok()
+        }
+        ElevatedOperation::UninstallService => locate_service(app).ok(),
+        _ => None,
+    };
+    let steps = elevated_steps(operation, backend_vm.as_deref(), service.as_ref())?;
+    log_to_file(&format!("[Leaxer] Requesting elevation for: {:?}", operation));
+
+    let status = tauri::async_runtime::spawn_blocking(move || {
+        #[cfg(target_os = "windows")]
+        {
+            use base64::Engine;
+
+            // Start-Process -Verb RunAs shows one UAC prompt for the whole script; -PassThru
+            // lets us forward the exit code
+            let utf16: Vec<u8> = powershell_script(&steps).encode_utf16().flat_map(u16::to_le_bytes).collect();
+            let script = format!(
+                "$p = Start-Process -FilePath powershell -ArgumentList '-NoProfile -NonInteractive -EncodedCommand {}' \
+                 -Verb RunAs -WindowStyle Hidden -Wait -PassThru; exit $p.ExitCode",
+                base64::engine::general_purpose::STANDARD.encode(utf16)
+            );
+            Command::new("powershell")
+                .args(["-NoProfile", "-Command", &script])
+                .creation_flags(CREATE_NO_WINDOW)
+                .status()
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let script = format!(
+                "do shell script {} with administrator privileges",
+                applescript_string(&shell_script(&steps))
+            );
+            Command::new("osascript").args(["-e", &script]).status()
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            Command::new("pkexec").args(["/bin/sh", "-c", &shell_script(&steps)]).status()
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    if !status.success() {
+        log_to_file(&format!("[Leaxer] Elevated operation failed: {}", status));
+        return Err(format!("Operation failed or was cancelled ({})", status));
+    }
+    log_to_file("[Leaxer] Elevated operation completed");
+
+    // The shell switches to or away from the service when it next starts
+    let config = app.state::<crate::config::ConfigStore>();
+    match operation {
+        ElevatedOperation::InstallService => config.set("backend_url", serde_json::json!(service_url(SERVICE_PORT))),
+        ElevatedOperation::ConfigureService { port } => config.set("backend_url", serde_json::json!(service_url(port))),
+        ElevatedOperation::UninstallService => {
+            let url = config.load().get("backend_url").and_then(serde_json::Value::as_str).map(str::to_string);
+            if url.is_some_and(|url| crate::external::parse(&url).is_ok_and(|(host, _)| host == "127.0.0.1")) {
+                config.remove("backend_url");
+            }
+            if let Some(dir) = crate::paths::base_leaxer_dir() {
+                let _ = std::fs::remove_file(dir.join(SERVICE_ENV_FILE));
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Run an allowlisted privileged operation through UAC (Windows), polkit (Linux) or
+/// an administrator prompt (macOS)
+#[tauri::command]
+pub async fn run_elevated(app: tauri::AppHandle, operation: ElevatedOperation) -> Result<(), String> {
+    let result = run(&app, operation).await;
+    crate::audit::record("elevated", Some(&format!("{:?}", operation)), &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service() -> Service {
+        Service {
+            launcher: PathBuf::from("/opt/Lea xer/bin/leaxer_core"),
+            root: PathBuf::from("/opt/Lea xer"),
+            user: "sam".to_string(),
+            env_files: "/etc/leaxer/leaxer-core.env:/home/sam/.leaxer/service.env".to_string(),
+        }
+    }
+
+    #[test]
+    fn scripts_quote_every_word() {
+        let steps = vec![
+            run_step("/usr/bin/tool", &["--add", "/Apps/Lea xer's \"VM\""]),
+            Step::Write(PathBuf::from("/etc/leaxer/a b.env"), "PORT=1\n".to_string()),
+        ];
+        let script = shell_script(&steps);
+        assert_eq!(
+            script,
+            r#"'/usr/bin/tool' '--add' '/Apps/Lea xer'\''s "VM"' && mkdir -p '/etc/leaxer' && printf '%s' 'PORT=1
+' > '/etc/leaxer/a b.env'"#
+        );
+        assert_eq!(applescript_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+
+    #[test]
+    fn windows_arguments_survive_the_command_line() {
+        assert_eq!(windows_quote("plain"), "plain");
+        assert_eq!(windows_quote("C:\\Program Files\\Leaxer\\"), "\"C:\\Program Files\\Leaxer\\\\\"");
+        assert_eq!(windows_quote("-config \"C:\\a b\""), "\"-config \\\"C:\\a b\\\"\"");
+        assert_eq!(windows_quote(""), "\"\"");
+        assert_eq!(powershell_quote("it's"), "'it''s'");
+    }
+
+    #[test]
+    fn firewalld_rules_apply_now_and_after_reboot() {
+        let steps = linux_firewall_steps(true, 4000, false);
+        assert_eq!(
+            steps,
+            vec![
+                run_step("firewall-cmd", &["--permanent", "--add-port=4000/tcp"]),
+                run_step("firewall-cmd", &["--add-port=4000/tcp"]),
+            ]
+        );
+        assert_eq!(linux_firewall_steps(false, 4000, true), vec![run_step("ufw", &["delete", "allow", "4000/tcp"])]);
+    }
+
+    #[test]
+    fn privileged_ports_are_refused() {
+        assert!(elevated_steps(ElevatedOperation::AddFirewallRule { port: 80 }, None, None).is_err());
+        assert!(elevated_steps(ElevatedOperation::ConfigureService { port: 443 }, None, None).is_err());
+    }
+
+    #[test]
+    fn the_service_gets_no_secrets() {
+        let config = service_config(4100);
+        assert!(config.contains("PORT=4100\n"));
+        assert!(config.contains("http://127.0.0.1:4100"));
+        assert!(!config.contains("SECRET"));
+
+        let unit = systemd_unit(&service());
+        assert!(unit.contains("User=sam\n"));
+        assert!(unit.contains("ExecStart=\"/opt/Lea xer/bin/leaxer_core\" start\n"));
+        assert!(unit.contains(&format!("Environment=\"LEAXER_ENV_FILES={}\"", service().env_files)));
+        assert!(launchd_plist("ai.leaxer.core", &service()).contains("<string>/opt/Lea xer/bin/leaxer_core</string>"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn the_service_is_installed_behind_one_prompt() {
+        let steps = elevated_steps(ElevatedOperation::InstallService, None, Some(&service())).unwrap();
+        assert_eq!(steps[0], Step::Write(system_config_path(), service_config(SERVICE_PORT)));
+        assert_eq!(steps.last(), Some(&run_step("systemctl", &["enable", "--now", SYSTEMD_UNIT])));
+        assert!(elevated_steps(ElevatedOperation::InstallService, None, None).is_err());
+    }
+}
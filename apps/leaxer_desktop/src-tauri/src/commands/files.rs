@@ -0,0 +1,328 @@
+//! Handing files and links to the OS: file manager, drag and drop, share UI and browser.
+
+use std::path::PathBuf;
+use std::process::Command;
+use tauri::{Emitter, Manager};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+#[cfg(target_os = "windows")]
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use crate::process::CREATE_NO_WINDOW;
+use crate::logging::log_to_file;
+use crate::net::validate_external_url;
+use crate::paths::is_path_allowed;
+
+/// Open the platform file manager with the given file or folder selected
+#[tauri::command]
+pub fn reveal_path(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let path = PathBuf::from(&path);
+    if !path.exists() {
+        return Err(format!("Path does not exist: {:?}", path));
+    }
+    if !is_path_allowed(&app, &path) {
+        log_to_file(&format!("[Leaxer] Refused to reveal path outside allowed roots: {:?}", path));
+        return Err("Path is outside the allowed locations".to_string());
+    }
+
+    log_to_file(&format!("[Leaxer] Revealing {:?}", path));
+
+    #[cfg(target_os = "windows")]
+    {
+        // explorer.exe does its own argument parsing, so the quoting has to be passed through verbatim
+        Command::new("explorer")
+            .raw_arg(format!("/select,\"{}\"", path.display()))
+            .spawn()
+            .map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Command::new("open")
+            .arg("-R")
+            .arg(&path)
+            .spawn()
+            .map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Most file managers implement the FileManager1 D-Bus interface, which can select the item.
+        // Fall back to opening the containing folder if it isn't available.
+        let uri = tauri::Url::from_file_path(&path)
+            .map_err(|_| format!("Invalid path: {:?}", path))?;
+        let selected = Command::new("dbus-send")
+            .args([
+                "--session",
+                "--print-reply",
+                "--dest=org.freedesktop.FileManager1",
+                "--type=method_call",
+                "/org/freedesktop/FileManager1",
+                "org.freedesktop.FileManager1.ShowItems",
+            ])
+            .arg(format!("array:string:{}", uri))
+            .arg("string:")
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+
+        if !selected {
+            let folder = if path.is_dir() {
+                path.clone()
+            } else {
+                path.parent().map(|p| p.to_path_buf()).unwrap_or(path.clone())
+            };
+            Command::new("xdg-open")
+                .arg(folder)
+                .spawn()
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Image extensions that can be used as their own drag preview
+const DRAG_PREVIEW_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+/// Start a native OS drag of one or more files from the given window, so they can be
+/// dropped into Finder/Explorer or another application.
+/// Emits `drag-finished` with `true` if the files were dropped, `false` if the drag was cancelled.
+#[tauri::command]
+pub fn start_file_drag(app: tauri::AppHandle, window: tauri::Window, paths: Vec<String>) -> Result<(), String> {
+    if paths.is_empty() {
+        return Err("No files to drag".to_string());
+    }
+
+    let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+    for path in &paths {
+        if !path.is_file() || !is_path_allowed(&app, path) {
+            log_to_file(&format!("[Leaxer] Refused to drag path: {:?}", path));
+            return Err(format!("Path cannot be dragged: {:?}", path));
+        }
+    }
+
+    // Use the file itself as preview when it is an image, otherwise fall back to the app icon
+    let first = &paths[0];
+    let is_image = first
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| DRAG_PREVIEW_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        .unwrap_or(false);
+    let preview = if is_image {
+        drag::Image::File(first.clone())
+    } else {
+        drag::Image::Raw(include_bytes!("../../icons/icon.png").to_vec())
+    };
+
+    let webview_window = app
+        .get_webview_window(window.label())
+        .ok_or_else(|| "Window not found".to_string())?;
+
+    log_to_file(&format!("[Leaxer] Starting drag of {} file(s)", paths.len()));
+
+    let handle = app.clone();
+    app.run_on_main_thread(move || {
+        #[cfg(target_os = "linux")]
+        let target = match webview_window.gtk_window() {
+            Ok(target) => target,
+            Err(e) => {
+                log_to_file(&format!("[Leaxer] Drag failed: {}", e));
+                return;
+            }
+        };
+        #[cfg(not(target_os = "linux"))]
+        let target = webview_window;
+
+        let result = drag::start_drag(
+            &target,
+            drag::DragItem::Files(paths),
+            preview,
+            move |result, _cursor| {
+                let dropped = matches!(result, drag::DragResult::Dropped);
+                let _ = handle.emit("drag-finished", dropped);
+            },
+            drag::Options::default(),
+        );
+
+        if let Err(e) = result {
+            log_to_file(&format!("[Leaxer] Drag failed: {}", e));
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Content handed to the OS share UI: either a list of files or a piece of text
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+pub enum ShareContent {
+    Paths(Vec<String>),
+    Text(String),
+}
+
+/// Show the macOS share sheet anchored to the window's content view
+#[cfg(target_os = "macos")]
+fn show_share_ui(window: &tauri::WebviewWindow, files: Vec<PathBuf>, text: Option<String>) -> Result<(), String> {
+    use objc2::rc::Id;
+    use objc2::runtime::AnyObject;
+    use objc2::ClassType;
+    use objc2_app_kit::{NSSharingServicePicker, NSView};
+    use objc2_foundation::{NSArray, NSRectEdge, NSString, NSURL};
+
+    let ns_view = window.ns_view().map_err(|e| e.to_string())?;
+
+    let mut items: Vec<Id<AnyObject>> = Vec::new();
+    for file in &files {
+        let path = NSString::from_str(&file.to_string_lossy());
+        let url = unsafe { NSURL::fileURLWithPath(&path) };
+        items.push(Id::into_super(Id::into_super(url)));
+    }
+    if let Some(text) = text {
+        items.push(Id::into_super(Id::into_super(NSString::from_str(&text))));
+    }
+    let items = NSArray::from_vec(items);
+
+    unsafe {
+        let view: &NSView = &*(ns_view as *const NSView);
+        let picker = NSSharingServicePicker::initWithItems(NSSharingServicePicker::alloc(), &items);
+        picker.showRelativeToRect_ofView_preferredEdge(view.bounds(), view, NSRectEdge::NSRectEdgeMinY);
+    }
+
+    Ok(())
+}
+
+/// Show the Windows Share UI for the window via the DataTransferManager interop interface
+#[cfg(target_os = "windows")]
+fn show_share_ui(window: &tauri::WebviewWindow, files: Vec<PathBuf>, text: Option<String>) -> Result<(), String> {
+    use windows::core::{Interface, HSTRING};
+    use windows::ApplicationModel::DataTransfer::{DataRequestedEventArgs, DataTransferManager};
+    use windows::Foundation::TypedEventHandler;
+    use windows::Storage::{IStorageItem, StorageFile};
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::Shell::IDataTransferManagerInterop;
+
+    // Only one DataRequested handler should be registered at a time
+    static HANDLER_TOKEN: Mutex<Option<i64>> = Mutex::new(None);
+
+    let hwnd = HWND(window.hwnd().map_err(|e| e.to_string())?.0 as _);
+
+    let interop = windows::core::factory::<DataTransferManager, IDataTransferManagerInterop>()
+        .map_err(|e| e.to_string())?;
+    let manager: DataTransferManager = unsafe { interop.GetForWindow(hwnd) }.map_err(|e| e.to_string())?;
+
+    let mut token = HANDLER_TOKEN.lock().unwrap();
+    if let Some(previous) = token.take() {
+        let _ = manager.RemoveDataRequested(previous);
+    }
+
+    let handler = TypedEventHandler::new(move |_, args: &Option<DataRequestedEventArgs>| {
+        let Some(args) = args else { return Ok(()) };
+        let data = args.Request()?.Data()?;
+        data.Properties()?.SetTitle(&HSTRING::from("Leaxer"))?;
+
+        if let Some(ref text) = text {
+            data.SetText(&HSTRING::from(text.as_str()))?;
+        }
+
+        if !files.is_empty() {
+            let mut items: Vec<Option<IStorageItem>> = Vec::new();
+            for file in &files {
+                let file = StorageFile::GetFileFromPathAsync(&HSTRING::from(file.as_path()))?.get()?;
+                items.push(Some(file.cast()?));
+            }
+            data.SetStorageItemsReadOnly(&windows::Foundation::Collections::IIterable::try_from(items)?)?;
+        }
+
+        Ok(())
+    });
+
+    *token = Some(manager.DataRequested(&handler).map_err(|e| e.to_string())?);
+    unsafe { interop.ShowShareUIForWindow(hwnd) }.map_err(|e| e.to_string())
+}
+
+/// Linux has no system share sheet; hand the content to the default mail client instead
+#[cfg(target_os = "linux")]
+fn show_share_ui(_window: &tauri::WebviewWindow, files: Vec<PathBuf>, text: Option<String>) -> Result<(), String> {
+    let mut cmd = Command::new("xdg-email");
+    for file in &files {
+        cmd.arg("--attach").arg(file);
+    }
+    if let Some(text) = text {
+        cmd.arg("--body").arg(text);
+    }
+    cmd.spawn().map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Send files or text to other apps (Mail, Messages, ...) through the OS share UI
+#[tauri::command]
+pub fn share(app: tauri::AppHandle, window: tauri::Window, paths_or_text: ShareContent) -> Result<(), String> {
+    let (files, text) = match paths_or_text {
+        ShareContent::Paths(paths) => {
+            let files: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+            if files.is_empty() {
+                return Err("Nothing to share".to_string());
+            }
+            for file in &files {
+                if !file.is_file() || !is_path_allowed(&app, file) {
+                    log_to_file(&format!("[Leaxer] Refused to share path: {:?}", file));
+                    return Err(format!("Path cannot be shared: {:?}", file));
+                }
+            }
+            (files, None)
+        }
+        ShareContent::Text(text) => (Vec::new(), Some(text)),
+    };
+
+    let webview_window = app
+        .get_webview_window(window.label())
+        .ok_or_else(|| "Window not found".to_string())?;
+
+    log_to_file(&format!("[Leaxer] Opening share UI ({} file(s))", files.len()));
+
+    app.run_on_main_thread(move || {
+        if let Err(e) = show_share_ui(&webview_window, files, text) {
+            log_to_file(&format!("[Leaxer] Share failed: {}", e));
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Open a web or mail link in the user's default application
+#[tauri::command]
+pub fn open_external(url: String) -> Result<(), String> {
+    let parsed = match validate_external_url(&url) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Refused to open external URL {:?}: {}", url, e));
+            return Err(e);
+        }
+    };
+
+    log_to_file(&format!("[Leaxer] Opening external URL: {}", parsed));
+
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("rundll32");
+        cmd.args(["url.dll,FileProtocolHandler", parsed.as_str()]);
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd
+    };
+
+    #[cfg(target_os = "macos")]
+    let mut cmd = {
+        let mut cmd = Command::new("open");
+        cmd.arg(parsed.as_str());
+        cmd
+    };
+
+    #[cfg(target_os = "linux")]
+    let mut cmd = {
+        let mut cmd = Command::new("xdg-open");
+        cmd.arg(parsed.as_str());
+        cmd
+    };
+
+    cmd.spawn().map(|_| ()).map_err(|e| e.to_string())
+}
@@ -0,0 +1,411 @@
+//! Importing models and chats from other local AI apps.
+//!
+//! Scans the default data folders of Ollama, LM Studio, Jan and GPT4All. GGUF models
+//! are hard-linked (or copied, across volumes) into `models/llm`, where the backend
+//! picks them up; chats are converted and saved through the backend's chat API.
+
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use tauri::Manager;
+
+use crate::commands::tasks::register_task;
+use crate::journal::{Journal, Operation};
+use crate::logging::log_to_file;
+
+/// How deep model folders are searched for .gguf files
+const MAX_SCAN_DEPTH: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportSource {
+    Ollama,
+    LmStudio,
+    Jan,
+    Gpt4All,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportKind {
+    Model,
+    Chat,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ImportItem {
+    /// Stable key to select the item for `import_items`
+    id: String,
+    source: ImportSource,
+    kind: ImportKind,
+    name: String,
+    path: PathBuf,
+    size_bytes: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct ImportResult {
+    id: String,
+    error: Option<String>,
+}
+
+fn item(source: ImportSource, kind: ImportKind, name: String, path: PathBuf) -> ImportItem {
+    let size_bytes = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    ImportItem {
+        id: format!("{:?}:{}", source, path.display()).to_lowercase(),
+        source,
+        kind,
+        name,
+        path,
+        size_bytes,
+    }
+}
+
+/// Every .gguf file below `root`
+fn gguf_files(root: &Path, depth: usize, found: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() && depth < MAX_SCAN_DEPTH {
+            gguf_files(&path, depth + 1, found);
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("gguf")) {
+            found.push(path);
+        }
+    }
+}
+
+fn scan_gguf(source: ImportSource, root: &Path) -> Vec<ImportItem> {
+    let mut found = Vec::new();
+    gguf_files(root, 0, &mut found);
+    found
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            Some(item(source, ImportKind::Model, name, path))
+        })
+        .collect()
+}
+
+/// Ollama keeps GGUF weights as content-addressed blobs named by its manifests
+fn scan_ollama(root: &Path) -> Vec<ImportItem> {
+    let manifests = root.join("manifests");
+    let mut files = Vec::new();
+    // manifests/<registry>/<namespace>/<model>/<tag>
+    let walk = |dir: &Path| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .collect()
+    };
+    for registry in walk(&manifests) {
+        for namespace in walk(&registry) {
+            for model in walk(&namespace) {
+                files.extend(walk(&model).into_iter().filter(|p| p.is_file()));
+            }
+        }
+    }
+
+    files
+        .into_iter()
+        .filter_map(|manifest| {
+            let content: Value = serde_json::from_str(&std::fs::read_to_string(&manifest).ok()?).ok()?;
+            let digest = content
+                .get("layers")?
+                .as_array()?
+                .iter()
+                .find(|layer| layer.get("mediaType").and_then(Value::as_str) == Some("application/vnd.ollama.image.model"))?
+                .get("digest")?
+                .as_str()?
+                .replace(':', "-");
+
+            let tag = manifest.file_name()?.to_string_lossy().to_string();
+            let model_dir = manifest.parent()?;
+            let model = model_dir.file_name()?.to_string_lossy().to_string();
+            let namespace = model_dir.parent()?.file_name()?.to_string_lossy().to_string();
+            let name = if namespace == "library" {
+                format!("{}-{}", model, tag)
+            } else {
+                format!("{}-{}-{}", namespace, model, tag)
+            };
+            let blob = root.join("blobs").join(digest);
+            blob.is_file().then(|| item(ImportSource::Ollama, ImportKind::Model, name, blob))
+        })
+        .collect()
+}
+
+/// Text of an LM Studio message: the selected version's text parts
+fn lmstudio_message(message: &Value) -> Option<(String, String)> {
+    let versions = message.get("versions")?.as_array()?;
+    let selected = message.get("currentlySelected").and_then(Value::as_u64).unwrap_or(0) as usize;
+    let version = versions.get(selected).or(versions.first())?;
+    let role = version.get("role")?.as_str()?.to_string();
+    let text = match version.get("content")? {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+    Some((role, text))
+}
+
+fn read_lmstudio_chat(path: &Path) -> Option<(String, Vec<(String, String)>)> {
+    let chat: Value = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    let name = chat.get("name").and_then(Value::as_str).unwrap_or("LM Studio chat").to_string();
+    let messages = chat.get("messages")?.as_array()?.iter().filter_map(lmstudio_message).collect();
+    Some((name, messages))
+}
+
+fn scan_lmstudio_chats(dir: &Path) -> Vec<ImportItem> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.to_string_lossy().ends_with(".conversation.json"))
+        .filter_map(|path| {
+            let (name, _) = read_lmstudio_chat(&path)?;
+            Some(item(ImportSource::LmStudio, ImportKind::Chat, name, path))
+        })
+        .collect()
+}
+
+/// Jan keeps one folder per thread: thread.json plus messages.jsonl
+fn read_jan_thread(dir: &Path) -> Option<(String, Vec<(String, String)>)> {
+    let thread: Value = serde_json::from_str(&std::fs::read_to_string(dir.join("thread.json")).ok()?).ok()?;
+    let name = thread.get("title").and_then(Value::as_str).unwrap_or("Jan thread").to_string();
+    let messages = std::fs::read_to_string(dir.join("messages.jsonl"))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter_map(|message| {
+            let role = message.get("role")?.as_str()?.to_string();
+            let text = message
+                .get("content")?
+                .as_array()?
+                .iter()
+                .filter_map(|part| part.pointer("/text/value").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some((role, text))
+        })
+        .collect();
+    Some((name, messages))
+}
+
+fn scan_jan_threads(dir: &Path) -> Vec<ImportItem> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let (name, _) = read_jan_thread(&path)?;
+            Some(item(ImportSource::Jan, ImportKind::Chat, name, path))
+        })
+        .collect()
+}
+
+/// Where each app keeps its data by default; missing folders are skipped
+fn scan_all() -> Vec<ImportItem> {
+    let home = dirs::home_dir().unwrap_or_default();
+    let data_dirs: Vec<PathBuf> = [dirs::data_dir(), dirs::data_local_dir()].into_iter().flatten().collect();
+    let mut items = Vec::new();
+
+    let ollama = std::env::var_os("OLLAMA_MODELS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".ollama").join("models"));
+    items.extend(scan_ollama(&ollama));
+
+    for root in [home.join(".lmstudio"), home.join(".cache").join("lm-studio")] {
+        items.extend(scan_gguf(ImportSource::LmStudio, &root.join("models")));
+        items.extend(scan_lmstudio_chats(&root.join("conversations")));
+    }
+
+    let jan_roots = std::iter::once(home.join("jan")).chain(data_dirs.iter().map(|dir| dir.join("Jan").join("data")));
+    for root in jan_roots {
+        items.extend(scan_gguf(ImportSource::Jan, &root.join("models")));
+        items.extend(scan_jan_threads(&root.join("threads")));
+    }
+
+    for dir in &data_dirs {
+        items.extend(scan_gguf(ImportSource::Gpt4All, &dir.join("nomic.ai").join("GPT4All")));
+    }
+
+    // data_dir and data_local_dir are the same folder on some platforms
+    let mut seen = std::collections::HashSet::new();
+    items.retain(|item| seen.insert(item.id.clone()));
+    items
+}
+
+/// Backend chat session for imported messages; roles the UI doesn't know are dropped
+fn chat_from_messages(name: &str, messages: &[(String, String)], now: u128) -> Value {
+    let messages: Vec<Value> = messages
+        .iter()
+        .filter(|(role, text)| matches!(role.as_str(), "user" | "assistant" | "system") && !text.is_empty())
+        .map(|(role, text)| {
+            json!({
+                "id": crate::native_messaging::new_id("msg", now),
+                "role": role,
+                "content": text,
+                "timestamp": now,
+            })
+        })
+        .collect();
+    crate::native_messaging::new_session(name, messages, now)
+}
+
+async fn import_model(journal: &Journal, item: &ImportItem) -> Result<(), String> {
+    let dir = crate::paths::get_leaxer_user_dir()
+        .ok_or("No Leaxer directory")?
+        .join("models")
+        .join("llm");
+    let name: String = item
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || "-_.".contains(c) { c } else { '_' })
+        .collect();
+    let dest = dir.join(format!("{}.gguf", name));
+    if dest.exists() {
+        return Err(format!("{} is already in Leaxer", name));
+    }
+    let source = item.path.clone();
+    // A crash mid-copy would leave a truncated model the backend takes for a real one
+    let entry = journal.begin(Operation::Import { dest: dest.clone() });
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        std::fs::create_dir_all(&dir)?;
+        // A hard link shares the weights instead of duplicating gigabytes
+        let result = std::fs::hard_link(&source, &dest).or_else(|_| std::fs::copy(&source, &dest).map(|_| ()));
+        if result.is_err() {
+            let _ = std::fs::remove_file(&dest);
+        }
+        result
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result.map_err(|e| e.to_string()));
+    journal.end(entry);
+    result
+}
+
+async fn import_chat(item: &ImportItem) -> Result<(), String> {
+    let (name, messages) = match item.source {
+        ImportSource::LmStudio => read_lmstudio_chat(&item.path),
+        ImportSource::Jan => read_jan_thread(&item.path),
+        _ => None,
+    }
+    .ok_or("Chat could not be read")?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    crate::native_messaging::save_chat(&chat_from_messages(&name, &messages, now)).await
+}
+
+/// List models and chats found in other local AI apps
+#[tauri::command]
+pub async fn scan_imports() -> Result<Vec<ImportItem>, String> {
+    tauri::async_runtime::spawn_blocking(scan_all)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Import the selected items (ids from `scan_imports`); progress is reported as a task
+#[tauri::command]
+pub async fn import_items(app: tauri::AppHandle, ids: Vec<String>) -> Result<Vec<ImportResult>, String> {
+    // Re-scan rather than trusting paths from the webview
+    let available = scan_imports().await?;
+    let selected: Vec<&ImportItem> = available.iter().filter(|item| ids.contains(&item.id)).collect();
+
+    let task = register_task(&app, "import", "Importing from other apps");
+    let mut results = Vec::new();
+    for (done, item) in selected.iter().enumerate() {
+        if task.is_cancelled() {
+            break;
+        }
+        task.progress(done as u64, Some(selected.len() as u64));
+        let result = match item.kind {
+            ImportKind::Model => import_model(&app.state::<Journal>(), item).await,
+            ImportKind::Chat => import_chat(item).await,
+        };
+        log_to_file(&format!("[Leaxer] Import {} -> {:?}", item.id, result));
+        results.push(ImportResult {
+            id: item.id.clone(),
+            error: result.err(),
+        });
+    }
+    task.progress(selected.len() as u64, Some(selected.len() as u64));
+    task.finish(&Ok(()));
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_ollama_models_through_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_dir = dir.path().join("manifests/registry.ollama.ai/library/llama3");
+        std::fs::create_dir_all(&manifest_dir).unwrap();
+        std::fs::create_dir_all(dir.path().join("blobs")).unwrap();
+        std::fs::write(
+            manifest_dir.join("8b"),
+            r#"{"layers":[{"mediaType":"application/vnd.ollama.image.template","digest":"sha256:aa"},
+                          {"mediaType":"application/vnd.ollama.image.model","digest":"sha256:bb"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("blobs/sha256-bb"), b"GGUF").unwrap();
+
+        let items = scan_ollama(dir.path());
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].name, "llama3-8b");
+        assert_eq!(items[0].size_bytes, 4);
+        assert!(items[0].path.ends_with("blobs/sha256-bb"));
+    }
+
+    #[test]
+    fn reads_lmstudio_conversations() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("1.conversation.json"),
+            r#"{"name":"Recipes","messages":[
+                {"versions":[{"role":"user","content":[{"type":"text","text":"Soup?"}]}],"currentlySelected":0},
+                {"versions":[{"role":"assistant","content":[{"type":"text","text":"no"}]},
+                             {"role":"assistant","content":[{"type":"text","text":"Yes!"}]}],"currentlySelected":1}]}"#,
+        )
+        .unwrap();
+
+        let items = scan_lmstudio_chats(dir.path());
+        assert_eq!(items[0].name, "Recipes");
+        let (_, messages) = read_lmstudio_chat(&items[0].path).unwrap();
+        assert_eq!(
+            messages,
+            vec![("user".to_string(), "Soup?".to_string()), ("assistant".to_string(), "Yes!".to_string())]
+        );
+    }
+
+    #[test]
+    fn reads_jan_threads() {
+        let dir = tempfile::tempdir().unwrap();
+        let thread = dir.path().join("jan_1");
+        std::fs::create_dir_all(&thread).unwrap();
+        std::fs::write(thread.join("thread.json"), r#"{"title":"Trip"}"#).unwrap();
+        std::fs::write(
+            thread.join("messages.jsonl"),
+            "{\"role\":\"user\",\"content\":[{\"type\":\"text\",\"text\":{\"value\":\"Where to?\"}}]}\n",
+        )
+        .unwrap();
+
+        let items = scan_jan_threads(dir.path());
+        assert_eq!(items[0].name, "Trip");
+        let session = chat_from_messages("Trip", &read_jan_thread(&thread).unwrap().1, 5);
+        assert_eq!(session["name"], "Trip");
+        assert_eq!(session["messages"][0]["content"], "Where to?");
+    }
+}
@@ -0,0 +1,102 @@
+//! Provider API keys (OpenAI, Hugging Face, ...) in the platform credential store: the
+//! macOS Keychain, Windows Credential Manager or the Secret Service on Linux.
+//!
+//! Each key is its own entry under the Leaxer service, named `api-key/<name>`, so the UI
+//! never keeps them in web storage or config.json. Unlike the encrypted secrets there is no
+//! file fallback; without a credential store these commands fail.
+
+use crate::logging::{log_to_file, register_secret};
+use crate::secrets::KEYRING_SERVICE;
+
+/// Entries of the shell's own, such as the secrets key, live outside this prefix
+const ENTRY_PREFIX: &str = "api-key/";
+
+const MAX_NAME_LEN: usize = 64;
+
+/// The credential store entry for the key called `name`
+fn entry_name(name: &str) -> Result<String, String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        return Err(format!(
+            "Key names are 1 to {} letters, digits, '_', '-' or '.': {:?}",
+            MAX_NAME_LEN, name
+        ));
+    }
+    Ok(format!("{}{}", ENTRY_PREFIX, name))
+}
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, &entry_name(name)?).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+/// The store can block on an unlock prompt, so it is kept off the async runtime's threads
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, String> + Send + 'static) -> Result<T, String> {
+    tauri::async_runtime::spawn_blocking(f).await.map_err(|e| e.to_string())?
+}
+
+/// Save an API key under `name`, replacing any earlier one
+#[tauri::command]
+pub async fn keychain_set(name: String, value: String) -> Result<(), String> {
+    if value.is_empty() {
+        return keychain_delete(name).await;
+    }
+    let target = name.clone();
+    let result = blocking(move || {
+        register_secret(&value);
+        entry(&name)?.set_password(&value).map_err(|e| format!("Failed to save {}: {}", name, e))?;
+        log_to_file(&format!("[Leaxer] Saved API key {} in the keychain", name));
+        Ok(())
+    })
+    .await;
+    crate::audit::record("keychain_set", Some(&target), &result);
+    result
+}
+
+/// The API key saved under `name`, `None` if there is none
+#[tauri::command]
+pub async fn keychain_get(name: String) -> Result<Option<String>, String> {
+    let target = name.clone();
+    let result = blocking(move || match entry(&name)?.get_password() {
+        Ok(value) => {
+            register_secret(&value);
+            Ok(Some(value))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {}: {}", name, e)),
+    })
+    .await;
+    crate::audit::record("keychain_get", Some(&target), &result);
+    result
+}
+
+/// Remove the API key saved under `name`; removing a missing key succeeds
+#[tauri::command]
+pub async fn keychain_delete(name: String) -> Result<(), String> {
+    let target = name.clone();
+    let result = blocking(move || match entry(&name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {
+            log_to_file(&format!("[Leaxer] Removed API key {} from the keychain", name));
+            Ok(())
+        }
+        Err(e) => Err(format!("Failed to remove {}: {}", name, e)),
+    })
+    .await;
+    crate::audit::record("keychain_delete", Some(&target), &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_stay_under_the_prefix() {
+        assert_eq!(entry_name("openai").unwrap(), "api-key/openai");
+        assert_eq!(entry_name("hf_token.v2").unwrap(), "api-key/hf_token.v2");
+        assert!(entry_name("").is_err());
+        assert!(entry_name("../secrets-key").is_err());
+        assert!(entry_name(&"a".repeat(MAX_NAME_LEN + 1)).is_err());
+    }
+}
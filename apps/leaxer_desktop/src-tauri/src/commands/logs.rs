@@ -0,0 +1,157 @@
+//! Live log viewer: the end of the shell and backend logs, then new lines as `log-lines`
+//! events while the viewer is open. The frontend never reads log files itself. New lines
+//! are picked up on the `MonitorSignal`, so nothing is read while the app is in the tray.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+
+use crate::logging::{backend_log_path, read_tail, startup_log_path};
+use crate::monitor::MonitorSignal;
+
+/// Lines returned when the viewer doesn't ask for a number
+const DEFAULT_LINES: usize = 200;
+
+const MAX_LINES: usize = 5000;
+
+/// How often the logs are checked for new lines while the window is visible
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Bumped by every `tail_logs` and `stop_tailing_logs`; a follower stops once it's stale
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogSource {
+    Shell,
+    Backend,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct LogLines {
+    pub source: LogSource,
+    pub lines: Vec<String>,
+}
+
+/// Reads what is appended to a log file
+struct Follower {
+    path: PathBuf,
+    offset: u64,
+}
+
+impl Follower {
+    /// Follow from the current end of the file
+    fn new(path: PathBuf) -> Self {
+        let offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Follower { path, offset }
+    }
+
+    /// Complete lines written since the last call. A file shorter than before was
+    /// rotated, so it's read from the start.
+    fn read_new(&mut self) -> Vec<String> {
+        let len = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if len < self.offset {
+            self.offset = 0;
+        }
+        if len == self.offset {
+            return Vec::new();
+        }
+        let mut bytes = Vec::new();
+        let read = std::fs::File::open(&self.path).and_then(|mut file| {
+            file.seek(SeekFrom::Start(self.offset))?;
+            file.take(len - self.offset).read_to_end(&mut bytes)
+        });
+        if read.is_err() {
+            return Vec::new();
+        }
+        // A partly written line is picked up on the next call
+        let Some(end) = bytes.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+        self.offset += end as u64 + 1;
+        String::from_utf8_lossy(&bytes[..end]).lines().map(str::to_string).collect()
+    }
+}
+
+fn sources() -> Vec<(LogSource, PathBuf)> {
+    [(LogSource::Shell, startup_log_path()), (LogSource::Backend, backend_log_path())]
+        .into_iter()
+        .filter_map(|(source, path)| path.map(|path| (source, path)))
+        .collect()
+}
+
+/// The last `lines` lines (default 200) of the shell and backend logs. Lines written
+/// afterwards arrive as `log-lines` events until `stop_tailing_logs` or the next call.
+#[tauri::command]
+pub fn tail_logs(app: tauri::AppHandle, lines: Option<usize>) -> Vec<LogLines> {
+    let lines = lines.unwrap_or(DEFAULT_LINES).min(MAX_LINES);
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    // Shell lines still in the buffer would otherwise show up late
+    crate::logging::flush();
+
+    let mut followers = Vec::new();
+    let mut tails = Vec::new();
+    for (source, path) in sources() {
+        followers.push((source, Follower::new(path.clone())));
+        let tail = read_tail(&path, lines).unwrap_or_default();
+        tails.push(LogLines {
+            source,
+            lines: tail.lines().map(str::to_string).collect(),
+        });
+    }
+
+    std::thread::spawn(move || {
+        let mut seen = 0;
+        while GENERATION.load(Ordering::SeqCst) == generation {
+            app.state::<MonitorSignal>().wait(&mut seen, POLL_INTERVAL);
+            for (source, follower) in followers.iter_mut() {
+                let lines = follower.read_new();
+                if !lines.is_empty() {
+                    let _ = app.emit("log-lines", LogLines { source: *source, lines });
+                }
+            }
+        }
+    });
+    tails
+}
+
+/// The last `lines` lines (default 200) the shell logged, from memory. Works when the log
+/// file couldn't be written, e.g. because the Leaxer folder is read-only.
+#[tauri::command]
+pub fn get_recent_logs(lines: Option<usize>) -> Vec<String> {
+    crate::logging::recent_lines(lines.unwrap_or(DEFAULT_LINES).min(MAX_LINES))
+}
+
+/// Stop the `log-lines` events started by `tail_logs`
+#[tauri::command]
+pub fn stop_tailing_logs() {
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn follows_complete_lines_across_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backend.log");
+        std::fs::write(&path, "old\n").unwrap();
+
+        let mut follower = Follower::new(path.clone());
+        assert!(follower.read_new().is_empty());
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"one\ntwo\npart").unwrap();
+        assert_eq!(follower.read_new(), vec!["one", "two"]);
+        file.write_all(b"ial\n").unwrap();
+        assert_eq!(follower.read_new(), vec!["partial"]);
+
+        std::fs::write(&path, "fresh\n").unwrap();
+        assert_eq!(follower.read_new(), vec!["fresh"]);
+    }
+}
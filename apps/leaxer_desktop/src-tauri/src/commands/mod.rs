@@ -0,0 +1,17 @@
+//! Tauri commands invoked from the frontend, grouped by subsystem.
+
+pub mod associations;
+pub mod capture;
+pub mod clipboard;
+pub mod diagnostics;
+pub mod downloads;
+pub mod elevated;
+pub mod files;
+pub mod imports;
+pub mod keychain;
+pub mod logs;
+pub mod permissions;
+pub mod settings;
+pub mod system;
+pub mod tasks;
+pub mod window;
@@ -0,0 +1,147 @@
+//! macOS privacy (TCC) permission checks and guided grant prompts.
+
+#[cfg(feature = "dialog")]
+use tauri_plugin_dialog::DialogExt;
+
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+use crate::logging::log_to_file;
+
+#[cfg(target_os = "macos")]
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGPreflightScreenCaptureAccess() -> bool;
+    fn CGRequestScreenCaptureAccess() -> bool;
+}
+
+#[cfg(target_os = "macos")]
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrusted() -> bool;
+}
+
+/// macOS privacy (TCC) permissions used by screen capture and global hotkeys
+#[derive(Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OsPermission {
+    ScreenRecording,
+    Accessibility,
+}
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    /// The platform doesn't gate this capability behind a user permission
+    NotRequired,
+}
+
+#[derive(serde::Serialize)]
+pub struct PermissionStatus {
+    screen_recording: PermissionState,
+    accessibility: PermissionState,
+}
+
+/// Query the current grant state of a permission without prompting
+pub fn check_permission(permission: OsPermission) -> PermissionState {
+    #[cfg(target_os = "macos")]
+    {
+        let granted = unsafe {
+            match permission {
+                OsPermission::ScreenRecording => CGPreflightScreenCaptureAccess(),
+                OsPermission::Accessibility => AXIsProcessTrusted(),
+            }
+        };
+        if granted {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = permission;
+        PermissionState::NotRequired
+    }
+}
+
+/// Report which OS permissions are granted so the UI can explain what's missing
+#[tauri::command]
+pub fn get_permission_status() -> PermissionStatus {
+    PermissionStatus {
+        screen_recording: check_permission(OsPermission::ScreenRecording),
+        accessibility: check_permission(OsPermission::Accessibility),
+    }
+}
+
+/// Walk the user through granting a permission: explain why it is needed, trigger the
+/// system prompt and open the matching System Settings pane.
+/// Returns the state at the time of the call; macOS only applies grants after a relaunch.
+#[tauri::command]
+pub fn request_permission(app: tauri::AppHandle, permission: OsPermission) -> PermissionState {
+    let state = check_permission(permission);
+    if state != PermissionState::Denied {
+        return state;
+    }
+
+    let (name, reason, pane) = match permission {
+        OsPermission::ScreenRecording => (
+            "Screen Recording",
+            "Leaxer needs Screen Recording access to capture your screen.",
+            "Privacy_ScreenCapture",
+        ),
+        OsPermission::Accessibility => (
+            "Accessibility",
+            "Leaxer needs Accessibility access for global hotkeys and capturing selected text.",
+            "Privacy_Accessibility",
+        ),
+    };
+
+    log_to_file(&format!("[Leaxer] Requesting {} permission", name));
+
+    #[cfg(feature = "dialog")]
+    app.dialog()
+        .message(format!(
+            "{}\n\nClick OK to open System Settings, enable Leaxer under {}, then restart Leaxer.",
+            reason, name
+        ))
+        .title(format!("{} permission required", name))
+        .buttons(tauri_plugin_dialog::MessageDialogButtons::OkCancel)
+        .show(move |confirmed| {
+            if confirmed {
+                open_permission_settings(permission, pane);
+            }
+        });
+
+    // Without the dialog plugin there is nothing to explain the request with; go straight to Settings
+    #[cfg(not(feature = "dialog"))]
+    {
+        let _ = (app, reason);
+        open_permission_settings(permission, pane);
+    }
+
+    state
+}
+
+/// Trigger the system prompt where one exists and open the permission's System Settings pane
+fn open_permission_settings(permission: OsPermission, pane: &str) {
+    #[cfg(target_os = "macos")]
+    {
+        if let OsPermission::ScreenRecording = permission {
+            // Registers Leaxer in the Screen Recording list so the user can tick it
+            unsafe {
+                CGRequestScreenCaptureAccess();
+            }
+        }
+        let _ = Command::new("open")
+            .arg(format!(
+                "x-apple.systempreferences:com.apple.preference.security?{}",
+                pane
+            ))
+            .spawn();
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (permission, pane);
+}
@@ -0,0 +1,168 @@
+//! Moving the shell's settings between machines: config.json without its secrets, saved
+//! to and loaded from a file the user picks. An import replaces the settings but keeps
+//! this machine's secrets.
+
+use serde_json::{json, Value};
+
+use crate::logging::{is_secret_key, log_to_file};
+
+/// Marks a file as exported settings, and its format version
+const FORMAT_KEY: &str = "leaxer_settings";
+const FORMAT_VERSION: u64 = 1;
+
+/// Copy of a config value with every secret-looking entry left out
+fn without_secrets(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(key, _)| !is_secret_key(key))
+                .map(|(key, value)| (key.clone(), without_secrets(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(without_secrets).collect()),
+        other => other.clone(),
+    }
+}
+
+/// `settings` with the secrets of `current` put back where they were
+fn with_secrets_from(mut settings: Value, current: &Value) -> Value {
+    if let (Some(settings_map), Some(current_map)) = (settings.as_object_mut(), current.as_object()) {
+        for (key, value) in current_map {
+            if is_secret_key(key) {
+                settings_map.insert(key.clone(), value.clone());
+            } else if let Some(child) = settings_map.get_mut(key) {
+                *child = with_secrets_from(child.take(), value);
+            }
+        }
+    }
+    settings
+}
+
+/// Contents of an export file for `config`
+fn export_document(config: &Value) -> Value {
+    json!({ FORMAT_KEY: FORMAT_VERSION, "settings": without_secrets(config) })
+}
+
+/// The config an export file turns `current` into
+fn imported_config(document: &[u8], current: &Value) -> Result<Value, String> {
+    let document: Value = serde_json::from_slice(document).map_err(|e| format!("Not a settings file: {}", e))?;
+    match document.get(FORMAT_KEY).and_then(Value::as_u64) {
+        Some(version) if version <= FORMAT_VERSION => {}
+        Some(version) => return Err(format!("Settings format {} needs a newer Leaxer", version)),
+        None => return Err("Not a Leaxer settings file".to_string()),
+    }
+    let settings = document
+        .get("settings")
+        .filter(|settings| settings.is_object())
+        .ok_or("The settings file has no settings")?;
+    // Secrets never come from a file, even one edited by hand
+    Ok(with_secrets_from(without_secrets(settings), current))
+}
+
+/// Ask where to save the settings and write them there. Returns the saved path, or `None`
+/// if the user cancelled the dialog.
+#[cfg(feature = "dialog")]
+#[tauri::command]
+pub async fn export_settings(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    use tauri::Manager;
+    use tauri_plugin_dialog::DialogExt;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter("Leaxer settings", &["json"])
+        .set_file_name("leaxer-settings.json")
+        .save_file(move |path| {
+            let _ = tx.send(path);
+        });
+    let dest = match rx.await.map_err(|e| e.to_string())? {
+        Some(path) => path.into_path().map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+
+    let config = app.state::<crate::config::ConfigStore>().load_file();
+    let content = serde_json::to_vec_pretty(&export_document(&config)).map_err(|e| e.to_string())?;
+    std::fs::write(&dest, content).map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+    log_to_file(&format!("[Leaxer] Settings exported to {:?}", dest));
+    Ok(Some(dest.to_string_lossy().to_string()))
+}
+
+#[cfg(not(feature = "dialog"))]
+#[tauri::command]
+pub async fn export_settings() -> Result<Option<String>, String> {
+    Err(crate::features::unavailable("Settings exports", &["dialog"]))
+}
+
+/// Ask for a settings file and apply it. Returns the imported path, or `None` if the user
+/// cancelled the dialog; the changes are picked up like any edit of config.json.
+#[cfg(feature = "dialog")]
+#[tauri::command]
+pub async fn import_settings(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    use tauri::Manager;
+    use tauri_plugin_dialog::DialogExt;
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .file()
+        .add_filter("Leaxer settings", &["json"])
+        .pick_file(move |path| {
+            let _ = tx.send(path);
+        });
+    let source = match rx.await.map_err(|e| e.to_string())? {
+        Some(path) => path.into_path().map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+
+    let document = std::fs::read(&source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    let config = app.state::<crate::config::ConfigStore>();
+    let imported = imported_config(&document, &config.load_file())?;
+    config
+        .replace(imported)
+        .map_err(|e| format!("Failed to write config.json: {}", e))?;
+    log_to_file(&format!("[Leaxer] Settings imported from {:?}", source));
+    Ok(Some(source.to_string_lossy().to_string()))
+}
+
+#[cfg(not(feature = "dialog"))]
+#[tauri::command]
+pub async fn import_settings() -> Result<Option<String>, String> {
+    Err(crate::features::unavailable("Settings imports", &["dialog"]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_without_secrets_and_imports_keeping_local_ones() {
+        let source = json!({
+            "developer_mode": true,
+            "webhook": { "token": "abc123", "port": 4010 },
+            "openai_api_key": "sk-source"
+        });
+        let document = serde_json::to_vec(&export_document(&source)).unwrap();
+        assert!(!String::from_utf8_lossy(&document).contains("abc123"));
+
+        let current = json!({
+            "developer_mode": false,
+            "webhook": { "token": "local", "port": 4000 },
+            "openai_api_key": "sk-local",
+            "capture_hotkey": "Ctrl+Shift+L"
+        });
+        assert_eq!(
+            imported_config(&document, &current).unwrap(),
+            json!({
+                "developer_mode": true,
+                "webhook": { "token": "local", "port": 4010 },
+                "openai_api_key": "sk-local"
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(imported_config(b"{ nope", &json!({})).is_err());
+        assert!(imported_config(br#"{"developer_mode": true}"#, &json!({})).is_err());
+        assert!(imported_config(br#"{"leaxer_settings": 99, "settings": {}}"#, &json!({})).is_err());
+    }
+}
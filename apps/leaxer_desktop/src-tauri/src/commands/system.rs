@@ -0,0 +1,658 @@
+//! Hardware, power, input-locale and regional-format information about the host machine.
+
+use std::process::Command;
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+#[cfg(target_os = "windows")]
+use crate::process::CREATE_NO_WINDOW;
+use crate::logging::log_to_file;
+use crate::monitor::MonitorSignal;
+use crate::paths::get_leaxer_user_dir;
+
+#[derive(serde::Serialize)]
+pub struct DiskInfo {
+    mount_point: String,
+    total_bytes: u64,
+    available_bytes: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct SystemInfo {
+    os_name: String,
+    os_version: String,
+    arch: String,
+    cpu_model: String,
+    cpu_physical_cores: Option<usize>,
+    cpu_logical_cores: usize,
+    total_memory_bytes: u64,
+    gpus: Vec<String>,
+    disks: Vec<DiskInfo>,
+    /// Free space on the disk holding the Leaxer user dir
+    data_dir_available_bytes: Option<u64>,
+    app_version: String,
+}
+
+/// List GPU adapter names using the platform's own tooling
+fn detect_gpus() -> Vec<String> {
+    #[cfg(target_os = "windows")]
+    let output = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            "Get-CimInstance Win32_VideoController | Select-Object -ExpandProperty Name",
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output();
+
+    #[cfg(target_os = "macos")]
+    let output = Command::new("system_profiler")
+        .arg("SPDisplaysDataType")
+        .output();
+
+    #[cfg(target_os = "linux")]
+    let output = Command::new("lspci").output();
+
+    let stdout = match output {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).to_string(),
+        _ => return Vec::new(),
+    };
+
+    #[cfg(target_os = "windows")]
+    let gpus = stdout
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    #[cfg(target_os = "macos")]
+    let gpus = stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Chipset Model:"))
+        .map(|name| name.trim().to_string())
+        .collect();
+
+    #[cfg(target_os = "linux")]
+    let gpus = stdout
+        .lines()
+        .filter(|line| line.contains("VGA compatible controller") || line.contains("3D controller"))
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .map(|name| name.trim().to_string())
+        .collect();
+
+    gpus
+}
+
+/// GPU names are detected once per run; the platform tools used for it take a second or more
+static GPUS: std::sync::OnceLock<Vec<String>> = std::sync::OnceLock::new();
+
+pub fn cached_gpus() -> Vec<String> {
+    GPUS.get_or_init(detect_gpus).clone()
+}
+
+/// Probe the slow hardware details in the background during startup, so they are
+/// ready by the time the About screen or hardware-aware defaults ask for them
+pub fn prefetch_hardware_info() {
+    tauri::async_runtime::spawn_blocking(|| {
+        let gpus = cached_gpus();
+        log_to_file(&format!("[Leaxer] Detected GPUs: {:?}", gpus));
+    });
+}
+
+fn list_disks() -> Vec<DiskInfo> {
+    sysinfo::Disks::new_with_refreshed_list()
+        .list()
+        .iter()
+        .map(|disk| DiskInfo {
+            mount_point: disk.mount_point().to_string_lossy().to_string(),
+            total_bytes: disk.total_space(),
+            available_bytes: disk.available_space(),
+        })
+        .collect()
+}
+
+/// Free space on the disk holding `path`: the disk with the longest mount point prefixing it
+fn available_space_on(disks: &[DiskInfo], path: &std::path::Path) -> Option<u64> {
+    disks
+        .iter()
+        .filter(|disk| path.starts_with(&disk.mount_point))
+        .max_by_key(|disk| disk.mount_point.len())
+        .map(|disk| disk.available_bytes)
+}
+
+/// Free space on the disk holding `path`
+pub fn available_space_at(path: &std::path::Path) -> Option<u64> {
+    available_space_on(&list_disks(), path)
+}
+
+/// Collect OS, CPU, memory, GPU and disk information
+fn collect_system_info() -> SystemInfo {
+    use sysinfo::{CpuRefreshKind, MemoryRefreshKind, RefreshKind, System};
+
+    let sys = System::new_with_specifics(
+        RefreshKind::new()
+            .with_cpu(CpuRefreshKind::everything())
+            .with_memory(MemoryRefreshKind::everything()),
+    );
+
+    let disks = list_disks();
+    let data_dir_available_bytes = get_leaxer_user_dir().and_then(|dir| available_space_on(&disks, &dir));
+
+    SystemInfo {
+        os_name: System::name().unwrap_or_else(|| std::env::consts::OS.to_string()),
+        os_version: System::long_os_version()
+            .or_else(System::os_version)
+            .unwrap_or_default(),
+        arch: std::env::consts::ARCH.to_string(),
+        cpu_model: sys
+            .cpus()
+            .first()
+            .map(|cpu| cpu.brand().trim().to_string())
+            .unwrap_or_default(),
+        cpu_physical_cores: sys.physical_core_count(),
+        cpu_logical_cores: sys.cpus().len(),
+        total_memory_bytes: sys.total_memory(),
+        gpus: cached_gpus(),
+        disks,
+        data_dir_available_bytes,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+/// Report hardware and OS details for the About screen and hardware-aware defaults
+#[tauri::command]
+pub async fn get_system_info() -> Result<SystemInfo, String> {
+    tauri::async_runtime::spawn_blocking(collect_system_info)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// How often the power source is checked. This runs on its own timer rather than the
+/// `MonitorSignal`, which pauses in the tray: schedulers in the shell need to see the
+/// laptop being unplugged while the window is hidden too.
+const POWER_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+#[derive(Clone, Default, PartialEq, serde::Serialize)]
+pub struct PowerStatus {
+    /// True when running from battery (no AC adapter connected)
+    on_battery: bool,
+    has_battery: bool,
+    /// Combined charge of all batteries, 0-100
+    charge_percent: Option<f32>,
+    charging: bool,
+}
+
+/// Read the current battery and power-source state.
+/// Machines without a battery (or where it cannot be queried) report AC power.
+fn read_power_status() -> PowerStatus {
+    use starship_battery::State;
+
+    let batteries: Vec<_> = starship_battery::Manager::new()
+        .and_then(|manager| manager.batteries().map(|iter| iter.flatten().collect()))
+        .unwrap_or_default();
+
+    if batteries.is_empty() {
+        return PowerStatus {
+            on_battery: false,
+            has_battery: false,
+            charge_percent: None,
+            charging: false,
+        };
+    }
+
+    let charge = batteries
+        .iter()
+        .map(|b| b.state_of_charge().value)
+        .sum::<f32>()
+        / batteries.len() as f32;
+
+    PowerStatus {
+        on_battery: batteries.iter().all(|b| b.state() == State::Discharging),
+        has_battery: true,
+        charge_percent: Some(charge * 100.0),
+        charging: batteries.iter().any(|b| b.state() == State::Charging),
+    }
+}
+
+/// Watch the power source and emit `power-source-changed` when switching between AC and battery.
+/// The latest status is kept in managed state so shell-side schedulers can check it too.
+pub fn start_power_monitor(app: tauri::AppHandle) {
+    std::thread::spawn(move || loop {
+        check_power_source(&app);
+        std::thread::sleep(POWER_POLL_INTERVAL);
+    });
+}
+
+/// Re-read the power source and emit `power-source-changed` if it switched
+fn check_power_source(app: &tauri::AppHandle) {
+    let status = read_power_status();
+    let changed = {
+        let state = app.state::<Mutex<PowerStatus>>();
+        let mut guard = state.lock().unwrap();
+        let changed = guard.on_battery != status.on_battery;
+        *guard = status.clone();
+        changed
+    };
+
+    if changed {
+        log_to_file(&format!(
+            "[Leaxer] Power source changed: {}",
+            if status.on_battery { "battery" } else { "AC" }
+        ));
+        let _ = app.emit("power-source-changed", status);
+    }
+}
+
+/// Report battery state so the frontend can defer heavy jobs while unplugged
+#[tauri::command]
+pub fn get_power_status(app: tauri::AppHandle) -> PowerStatus {
+    app.state::<Mutex<PowerStatus>>().lock().unwrap().clone()
+}
+
+/// Longest keyboard layout and locale go unchecked while the window is visible. Layouts are
+/// usually switched in other apps, so regaining focus is what normally triggers a check.
+const INPUT_LOCALE_FALLBACK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(120);
+
+#[derive(Clone, PartialEq, serde::Serialize)]
+pub struct InputLocale {
+    /// Platform identifier of the active layout, e.g. "us", "de", "00000407" or "com.apple.keylayout.German"
+    keyboard_layout: Option<String>,
+    /// Coarse letter arrangement: "qwerty", "qwertz", "azerty", "dvorak", "colemak" or "other"
+    layout_family: String,
+    /// System locale as a BCP 47 tag, e.g. "en-US"
+    locale: Option<String>,
+    /// User's preferred languages in priority order
+    preferred_languages: Vec<String>,
+}
+
+/// Read the identifier of the active keyboard layout
+fn detect_keyboard_layout() -> Option<String> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::UI::Input::KeyboardAndMouse::GetKeyboardLayoutNameW;
+
+        let mut name = [0u16; 9];
+        unsafe { GetKeyboardLayoutNameW(&mut name) }.ok()?;
+        let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+        Some(String::from_utf16_lossy(&name[..len]))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let output = Command::new("defaults")
+            .args(["read", "com.apple.HIToolbox", "AppleCurrentKeyboardLayoutInputSourceID"])
+            .output()
+            .ok()?;
+        let id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if id.is_empty() { None } else { Some(id) }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // X11 reports the layout directly; localectl covers Wayland sessions using the system keymap
+        let from_setxkbmap = Command::new("setxkbmap").arg("-query").output().ok().and_then(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .find_map(|line| line.strip_prefix("layout:"))
+                .map(|layout| layout.trim().split(',').next().unwrap_or("").to_string())
+        });
+        from_setxkbmap.filter(|l| !l.is_empty()).or_else(|| {
+            let output = Command::new("localectl").arg("status").output().ok()?;
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("X11 Layout:"))
+                .map(|layout| layout.trim().split(',').next().unwrap_or("").to_string())
+                .filter(|l| !l.is_empty())
+        })
+    }
+}
+
+/// Map a platform layout identifier to its letter arrangement, for shortcut hints
+fn classify_keyboard_layout(layout: &str) -> &'static str {
+    let id = layout.to_lowercase();
+    let name = id.rsplit(['.', ':']).next().unwrap_or(&id);
+
+    if id.contains("dvorak") || id == "00010409" {
+        "dvorak"
+    } else if id.contains("colemak") {
+        "colemak"
+    } else if ["00000407", "00000807", "00000c07", "00000405", "0000041b", "0000100c"].contains(&name)
+        || ["de", "ch", "at", "cz", "sk", "hu", "german", "swiss-german", "czech", "slovak", "hungarian"].contains(&name)
+    {
+        "qwertz"
+    } else if ["0000040c", "0000080c"].contains(&name)
+        || ["fr", "be", "french", "belgian"].contains(&name)
+    {
+        "azerty"
+    } else {
+        "qwerty"
+    }
+}
+
+/// Collect the keyboard layout, system locale and preferred languages
+pub fn read_input_locale() -> InputLocale {
+    let keyboard_layout = detect_keyboard_layout();
+    InputLocale {
+        layout_family: keyboard_layout
+            .as_deref()
+            .map(classify_keyboard_layout)
+            .unwrap_or("other")
+            .to_string(),
+        keyboard_layout,
+        locale: sys_locale::get_locale(),
+        preferred_languages: sys_locale::get_locales().collect(),
+    }
+}
+
+/// Emit `input-locale-changed` whenever the keyboard layout or locale changes
+pub fn start_input_locale_monitor(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut last = read_input_locale();
+        let mut seen = 0;
+        loop {
+            app.state::<MonitorSignal>().wait(&mut seen, INPUT_LOCALE_FALLBACK_INTERVAL);
+            let current = read_input_locale();
+            if current != last {
+                log_to_file(&format!(
+                    "[Leaxer] Input locale changed: layout {:?}, locale {:?}",
+                    current.keyboard_layout, current.locale
+                ));
+                let _ = app.emit("input-locale-changed", &current);
+                last = current;
+            }
+        }
+    });
+}
+
+/// Report keyboard layout and locale so shortcut hints and hotkeys can adapt
+#[tauri::command]
+pub async fn get_input_locale() -> Result<InputLocale, String> {
+    tauri::async_runtime::spawn_blocking(read_input_locale)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct RegionalFormat {
+    /// Locale whose conventions the OS applies, as a BCP 47 tag. Can differ from the UI
+    /// language (English UI with German formats); pass it to `Intl` for anything not below.
+    locale: Option<String>,
+    decimal_separator: Option<String>,
+    grouping_separator: Option<String>,
+    /// ISO 4217 code, e.g. "EUR"
+    currency_code: Option<String>,
+    currency_symbol: Option<String>,
+    /// 0 = Sunday ... 6 = Saturday
+    first_day_of_week: Option<u8>,
+    /// Short date pattern in Unicode (LDML) notation, e.g. "dd.MM.yyyy"
+    short_date_pattern: Option<String>,
+    /// "h12" or "h23", as in `Intl.DateTimeFormat`'s `hourCycle`
+    hour_cycle: Option<String>,
+    /// "metric" or "us"
+    measurement_system: Option<String>,
+}
+
+/// "de_DE.UTF-8@euro" to "de-DE"; None for the POSIX locale
+fn posix_to_bcp47(name: &str) -> Option<String> {
+    let name = name.split(['.', '@']).next().unwrap_or_default();
+    if name.is_empty() || name == "C" || name == "POSIX" {
+        return None;
+    }
+    Some(name.replace('_', "-"))
+}
+
+/// Convert a strftime date format to LDML, quoting literal letters
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn strftime_to_ldml(format: &str) -> String {
+    let mut pattern = String::new();
+    // Runs of literal letters are quoted together: 'de', not 'd''e' (an escaped quote)
+    let mut literal = String::new();
+    let mut chars = format.chars();
+    while let Some(c) = chars.next() {
+        if c.is_ascii_alphabetic() {
+            literal.push(c);
+            continue;
+        }
+        if !literal.is_empty() {
+            pattern.push_str(&format!("'{}'", literal));
+            literal.clear();
+        }
+        match c {
+            '%' => pattern.push_str(match chars.next() {
+                Some('d') => "dd",
+                Some('e') => "d",
+                Some('m') => "MM",
+                Some('y') => "yy",
+                Some('Y') => "yyyy",
+                Some('b') | Some('h') => "MMM",
+                Some('B') => "MMMM",
+                Some('a') => "EEE",
+                Some('A') => "EEEE",
+                Some('D') => "MM/dd/yy",
+                Some('F') => "yyyy-MM-dd",
+                Some('%') => "%",
+                _ => "",
+            }),
+            '\'' => pattern.push_str("''"),
+            c => pattern.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        pattern.push_str(&format!("'{}'", literal));
+    }
+    pattern
+}
+
+/// Value of `key` in `locale -k` output (`key="value"` or `key=number`)
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn locale_keyword<'a>(output: &'a str, key: &str) -> Option<&'a str> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+        .map(|value| value.trim_matches('"'))
+        .filter(|value| !value.is_empty())
+}
+
+/// Value of `key` in a dictionary printed by `defaults read` (`    key = "value";`)
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn defaults_dict_entry<'a>(output: &'a str, key: &str) -> Option<&'a str> {
+    output.lines().find_map(|line| {
+        let (name, value) = line.trim().split_once(" = ")?;
+        (name.trim_matches('"') == key).then(|| value.trim_end_matches(';').trim_matches('"'))
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn read_regional_format() -> RegionalFormat {
+    use windows::core::PCWSTR;
+    use windows::Win32::Globalization::{
+        GetLocaleInfoEx, GetUserDefaultLocaleName, LOCALE_IFIRSTDAYOFWEEK, LOCALE_IMEASURE, LOCALE_SCURRENCY,
+        LOCALE_SDECIMAL, LOCALE_SINTLSYMBOL, LOCALE_SSHORTDATE, LOCALE_STHOUSAND, LOCALE_STIMEFORMAT,
+    };
+
+    // LOCALE_NAME_MAX_LENGTH; the info strings asked for here are shorter still
+    let mut name = [0u16; 85];
+    let len = unsafe { GetUserDefaultLocaleName(&mut name) };
+    let locale = (len > 1).then(|| String::from_utf16_lossy(&name[..len as usize - 1]));
+
+    // A null locale name means the user default, including their customizations
+    let info = |lctype: u32| {
+        let mut value = [0u16; 85];
+        let len = unsafe { GetLocaleInfoEx(PCWSTR::null(), lctype, Some(&mut value)) };
+        (len > 1).then(|| String::from_utf16_lossy(&value[..len as usize - 1]))
+    };
+
+    RegionalFormat {
+        locale,
+        decimal_separator: info(LOCALE_SDECIMAL),
+        grouping_separator: info(LOCALE_STHOUSAND),
+        currency_code: info(LOCALE_SINTLSYMBOL),
+        currency_symbol: info(LOCALE_SCURRENCY),
+        // Windows counts from Monday
+        first_day_of_week: info(LOCALE_IFIRSTDAYOFWEEK)
+            .and_then(|day| day.parse::<u8>().ok())
+            .map(|day| (day + 1) % 7),
+        short_date_pattern: info(LOCALE_SSHORTDATE),
+        hour_cycle: info(LOCALE_STIMEFORMAT).map(|format| {
+            if format.contains('H') { "h23" } else { "h12" }.to_string()
+        }),
+        measurement_system: info(LOCALE_IMEASURE).map(|m| if m == "1" { "us" } else { "metric" }.to_string()),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn read_regional_format() -> RegionalFormat {
+    // Settings the user never changed from their region's defaults are absent
+    let defaults = |key: &str| {
+        let output = Command::new("defaults").args(["read", "-g", key]).output().ok()?;
+        let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !value.is_empty() { Some(value) } else { None }
+    };
+
+    // e.g. "de_DE" or "en_US@currency=EUR"
+    let apple_locale = defaults("AppleLocale");
+    let currency_code = apple_locale.as_deref().and_then(|locale| {
+        locale
+            .split_once('@')?
+            .1
+            .split(';')
+            .find_map(|keyword| keyword.strip_prefix("currency="))
+            .map(str::to_string)
+    });
+    let symbols = defaults("AppleICUNumberSymbols").unwrap_or_default();
+    let date_formats = defaults("AppleICUDateFormatStrings").unwrap_or_default();
+    let hour_cycle = if defaults("AppleICUForce24HourTime").as_deref() == Some("1") {
+        Some("h23".to_string())
+    } else if defaults("AppleICUForce12HourTime").as_deref() == Some("1") {
+        Some("h12".to_string())
+    } else {
+        None
+    };
+
+    RegionalFormat {
+        locale: apple_locale.as_deref().and_then(posix_to_bcp47),
+        // ICU number symbol indices: 0 decimal, 1 grouping, 8 currency
+        decimal_separator: defaults_dict_entry(&symbols, "0").map(str::to_string),
+        grouping_separator: defaults_dict_entry(&symbols, "1").map(str::to_string),
+        currency_code,
+        currency_symbol: defaults_dict_entry(&symbols, "8").map(str::to_string),
+        // macOS counts from 1 = Sunday
+        first_day_of_week: defaults("AppleFirstWeekday")
+            .and_then(|days| defaults_dict_entry(&days, "gregorian")?.parse::<u8>().ok())
+            .filter(|day| (1..=7).contains(day))
+            .map(|day| day - 1),
+        // Key 1 is the short style
+        short_date_pattern: defaults_dict_entry(&date_formats, "1").map(str::to_string),
+        hour_cycle,
+        measurement_system: defaults("AppleMeasurementUnits")
+            .map(|units| if units == "Inches" { "us" } else { "metric" }.to_string()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_regional_format() -> RegionalFormat {
+    let output = Command::new("locale")
+        .args(["-k", "LC_NUMERIC", "LC_MONETARY", "LC_TIME", "LC_MEASUREMENT"])
+        .output()
+        .map(|out| String::from_utf8_lossy(&out.stdout).to_string())
+        .unwrap_or_default();
+    let keyword = |key: &str| locale_keyword(&output, key).map(str::to_string);
+
+    // first_weekday counts from 1 at week-1stday: 19971130 is a Sunday, 19971201 a Monday
+    let week_start = if keyword("week-1stday").as_deref() == Some("19971201") { 1 } else { 0 };
+    let first_day_of_week = keyword("first_weekday")
+        .and_then(|day| day.parse::<u8>().ok())
+        .filter(|day| (1..=7).contains(day))
+        .map(|day| (week_start + day - 1) % 7);
+    let hour_cycle = keyword("t_fmt").map(|format| {
+        if ["%H", "%k", "%R", "%T"].iter().any(|h| format.contains(h)) { "h23" } else { "h12" }.to_string()
+    });
+    let locale = ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| posix_to_bcp47(&value));
+
+    RegionalFormat {
+        locale: locale.or_else(sys_locale::get_locale),
+        decimal_separator: keyword("decimal_point"),
+        grouping_separator: keyword("thousands_sep"),
+        // int_curr_symbol carries a trailing separator: "EUR "
+        currency_code: keyword("int_curr_symbol").map(|code| code.trim().to_string()),
+        currency_symbol: keyword("currency_symbol"),
+        first_day_of_week,
+        short_date_pattern: keyword("d_fmt").map(|format| strftime_to_ldml(&format)),
+        hour_cycle,
+        measurement_system: keyword("measurement").map(|m| if m == "2" { "us" } else { "metric" }.to_string()),
+    }
+}
+
+/// Emit `regional-format-changed` whenever the user's number, date or currency settings change
+pub fn start_regional_format_monitor(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut last = read_regional_format();
+        let mut seen = 0;
+        loop {
+            app.state::<MonitorSignal>().wait(&mut seen, INPUT_LOCALE_FALLBACK_INTERVAL);
+            let current = read_regional_format();
+            if current != last {
+                log_to_file(&format!("[Leaxer] Regional format changed: {:?}", current));
+                let _ = app.emit("regional-format-changed", &current);
+                last = current;
+            }
+        }
+    });
+}
+
+/// Report the OS's number, date and currency conventions so values are formatted per
+/// the user's regional settings. Unset fields mean "whatever `locale` implies".
+#[tauri::command]
+pub async fn get_regional_format() -> Result<RegionalFormat, String> {
+    tauri::async_runtime::spawn_blocking(read_regional_format)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_common_layouts() {
+        assert_eq!(classify_keyboard_layout("us"), "qwerty");
+        assert_eq!(classify_keyboard_layout("00000409"), "qwerty");
+        assert_eq!(classify_keyboard_layout("de"), "qwertz");
+        assert_eq!(classify_keyboard_layout("00000407"), "qwertz");
+        assert_eq!(classify_keyboard_layout("com.apple.keylayout.German"), "qwertz");
+        assert_eq!(classify_keyboard_layout("fr"), "azerty");
+        assert_eq!(classify_keyboard_layout("0000040C"), "azerty");
+        assert_eq!(classify_keyboard_layout("com.apple.keylayout.Dvorak"), "dvorak");
+        assert_eq!(classify_keyboard_layout("00010409"), "dvorak");
+        assert_eq!(classify_keyboard_layout("us:colemak"), "colemak");
+    }
+
+    #[test]
+    fn converts_platform_locale_formats() {
+        assert_eq!(posix_to_bcp47("de_DE.UTF-8@euro").as_deref(), Some("de-DE"));
+        assert_eq!(posix_to_bcp47("en_US@currency=EUR").as_deref(), Some("en-US"));
+        assert_eq!(posix_to_bcp47("C.UTF-8"), None);
+
+        assert_eq!(strftime_to_ldml("%d.%m.%Y"), "dd.MM.yyyy");
+        assert_eq!(strftime_to_ldml("%m/%d/%y"), "MM/dd/yy");
+        assert_eq!(strftime_to_ldml("%Y年%m月%d日"), "yyyy年MM月dd日");
+        assert_eq!(strftime_to_ldml("%e de %B"), "d 'de' MMMM");
+
+        let locale_output = "decimal_point=\",\"\nthousands_sep=\".\"\nfirst_weekday=2\nint_curr_symbol=\"EUR \"\ncurrency_symbol=\"\"";
+        assert_eq!(locale_keyword(locale_output, "decimal_point"), Some(","));
+        assert_eq!(locale_keyword(locale_output, "first_weekday"), Some("2"));
+        assert_eq!(locale_keyword(locale_output, "currency_symbol"), None);
+
+        let defaults_output = "{\n    0 = \",\";\n    1 = \".\";\n    gregorian = 2;\n}";
+        assert_eq!(defaults_dict_entry(defaults_output, "0"), Some(","));
+        assert_eq!(defaults_dict_entry(defaults_output, "gregorian"), Some("2"));
+        assert_eq!(defaults_dict_entry(defaults_output, "8"), None);
+    }
+}
@@ -0,0 +1,145 @@
+//! Registry of long-running native operations (downloads, backups, conversions).
+//!
+//! Every task gets an ID and reports through standardized `task:progress` / `task:done`
+//! events; the frontend can list and cancel running tasks.
+
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use crate::logging::log_to_file;
+
+#[derive(Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Public view of a long-running shell task, sent with every `task:*` event
+#[derive(Clone, serde::Serialize)]
+pub struct TaskInfo {
+    pub id: u64,
+    /// Category such as "download", "backup" or "conversion"
+    pub kind: String,
+    pub label: String,
+    pub progress: u64,
+    pub total: Option<u64>,
+    pub status: TaskStatus,
+    pub error: Option<String>,
+}
+
+pub struct TaskEntry {
+    info: TaskInfo,
+    cancel: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+/// Registry of running native operations, listed and cancelled from the frontend
+pub struct TaskRegistry {
+    pub next_id: u64,
+    pub tasks: std::collections::HashMap<u64, TaskEntry>,
+}
+
+/// Handle held by the code performing a task; reports progress and completion
+pub struct TaskHandle {
+    id: u64,
+    app: tauri::AppHandle,
+    cancel: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+/// Register a new task and emit its initial `task:progress` event
+pub fn register_task(app: &tauri::AppHandle, kind: &str, label: &str) -> TaskHandle {
+    let cancel = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let info = {
+        let state = app.state::<Mutex<TaskRegistry>>();
+        let mut registry = state.lock().unwrap();
+        registry.next_id += 1;
+        let info = TaskInfo {
+            id: registry.next_id,
+            kind: kind.to_string(),
+            label: label.to_string(),
+            progress: 0,
+            total: None,
+            status: TaskStatus::Running,
+            error: None,
+        };
+        registry.tasks.insert(info.id, TaskEntry {
+            info: info.clone(),
+            cancel: cancel.clone(),
+        });
+        info
+    };
+
+    let _ = app.emit("task:progress", &info);
+    TaskHandle {
+        id: info.id,
+        app: app.clone(),
+        cancel,
+    }
+}
+
+impl TaskHandle {
+    /// Whether the frontend asked for this task to be cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// Update progress and emit `task:progress`
+    pub fn progress(&self, progress: u64, total: Option<u64>) {
+        let state = self.app.state::<Mutex<TaskRegistry>>();
+        let info = {
+            let mut registry = state.lock().unwrap();
+            match registry.tasks.get_mut(&self.id) {
+                Some(entry) => {
+                    entry.info.progress = progress;
+                    entry.info.total = total;
+                    entry.info.clone()
+                }
+                None => return,
+            }
+        };
+        let _ = self.app.emit("task:progress", info);
+    }
+
+    /// Remove the task from the registry and emit `task:done` with its final status
+    pub fn finish(self, result: &Result<(), String>) {
+        let state = self.app.state::<Mutex<TaskRegistry>>();
+        let entry = state.lock().unwrap().tasks.remove(&self.id);
+        if let Some(entry) = entry {
+            let mut info = entry.info;
+            info.status = match result {
+                Ok(()) => TaskStatus::Completed,
+                Err(_) if self.is_cancelled() => TaskStatus::Cancelled,
+                Err(_) => TaskStatus::Failed,
+            };
+            info.error = result.as_ref().err().cloned();
+            let _ = self.app.emit("task:done", info);
+        }
+    }
+}
+
+/// List all running shell tasks
+#[tauri::command]
+pub fn list_tasks(app: tauri::AppHandle) -> Vec<TaskInfo> {
+    let state = app.state::<Mutex<TaskRegistry>>();
+    let registry = state.lock().unwrap();
+    let mut tasks: Vec<TaskInfo> = registry.tasks.values().map(|entry| entry.info.clone()).collect();
+    tasks.sort_by_key(|task| task.id);
+    tasks
+}
+
+/// Request cancellation of a running task; the task stops at its next checkpoint
+#[tauri::command]
+pub fn cancel_task(app: tauri::AppHandle, id: u64) -> Result<(), String> {
+    let state = app.state::<Mutex<TaskRegistry>>();
+    let registry = state.lock().unwrap();
+    match registry.tasks.get(&id) {
+        Some(entry) => {
+            log_to_file(&format!("[Leaxer] Cancelling task {} ({})", id, entry.info.label));
+            entry.cancel.store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+        None => Err(format!("No running task with id {}", id)),
+    }
+}
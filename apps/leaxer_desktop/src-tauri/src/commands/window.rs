@@ -0,0 +1,257 @@
+//! Window chrome: title, unread badge, tray icon and devtools.
+
+use std::sync::Mutex;
+use tauri::Manager;
+
+use crate::config::ConfigStore;
+use crate::process::BackendHandle;
+use crate::logging::log_to_file;
+
+/// ID of the system tray icon created in `setup`
+const TRAY_ID: &str = "main";
+
+/// Title and unread count mirrored to the title bar, tray tooltip and dock/taskbar badge
+pub struct AttentionState {
+    pub title: String,
+    pub unread: u32,
+}
+
+/// Whether new webviews get the inspector (F12, context menu Inspect): only with
+/// `developer_mode` set in config.json, so release builds don't expose devtools to everyone
+pub fn devtools_enabled(app: &tauri::AppHandle) -> bool {
+    app.state::<ConfigStore>().get().developer_mode
+}
+
+/// Create the main window from its entry in tauri.conf.json (`create` is off there so
+/// devtools can be decided here)
+pub fn build_main_window(app: &tauri::AppHandle) -> Result<tauri::WebviewWindow, String> {
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|window| window.label == "main")
+        .ok_or("no main window in the config")?;
+    tauri::WebviewWindowBuilder::from_config(app, config)
+        .and_then(|builder| builder.devtools(devtools_enabled(app)).build())
+        .map_err(|e| e.to_string())
+}
+
+/// Open the webview inspector. Only allowed when `developer_mode` is set in config.json;
+/// windows created before it was set have no inspector until they are recreated.
+#[cfg(any(debug_assertions, feature = "devtools"))]
+#[tauri::command]
+pub fn open_devtools(window: tauri::WebviewWindow) -> Result<(), String> {
+    if !devtools_enabled(window.app_handle()) {
+        return Err("Developer mode is disabled".to_string());
+    }
+
+    log_to_file(&format!("[Leaxer] Opening devtools for window '{}'", window.label()));
+    window.open_devtools();
+    Ok(())
+}
+
+#[cfg(not(any(debug_assertions, feature = "devtools")))]
+#[tauri::command]
+pub fn open_devtools(_window: tauri::WebviewWindow) -> Result<(), String> {
+    Err(crate::features::unavailable("Devtools", &["devtools"]))
+}
+
+/// Bring the main window to the front, waking the backend if it was left in standby
+pub fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(backend) = app.try_state::<BackendHandle>() {
+        backend.resume();
+    }
+    if let Some(signal) = app.try_state::<crate::monitor::MonitorSignal>() {
+        signal.set_paused(false);
+    }
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Prefix of the tray menu item ids that switch profiles
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+
+/// Tray menu: show, a profile switcher (once there is more than one profile) and quit
+fn tray_menu(app: &tauri::AppHandle) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{CheckMenuItem, Menu, MenuItem, Submenu};
+
+    let show = MenuItem::with_id(app, "show", "Show Leaxer", true, None::<&str>)?;
+    // In service mode closing the window leaves the backend running, so say what quit does
+    let quit_label = if app.state::<ConfigStore>().get().service_mode {
+        "Quit and stop server"
+    } else {
+        "Quit Leaxer"
+    };
+    let quit = MenuItem::with_id(app, "quit", quit_label, true, None::<&str>)?;
+    let menu = Menu::with_items(app, &[&show])?;
+
+    let profiles = app.state::<crate::profiles::ProfileStore>().list();
+    if profiles.profiles.len() > 1 {
+        let submenu = Submenu::with_id(app, "profiles", "Profile", true)?;
+        for profile in &profiles.profiles {
+            let item = CheckMenuItem::with_id(
+                app,
+                format!("{}{}", PROFILE_ITEM_PREFIX, profile.id),
+                &profile.name,
+                true,
+                profile.id == profiles.active,
+                None::<&str>,
+            )?;
+            submenu.append(&item)?;
+        }
+        menu.append(&submenu)?;
+    }
+    menu.append(&quit)?;
+    Ok(menu)
+}
+
+/// Rebuild the tray menu after the profile list or active profile changed
+pub fn refresh_tray_menu(app: &tauri::AppHandle) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        match tray_menu(app) {
+            Ok(menu) => {
+                let _ = tray.set_menu(Some(menu));
+            }
+            Err(e) => log_to_file(&format!("[Leaxer] Failed to rebuild tray menu: {}", e)),
+        }
+    }
+}
+
+/// Create the tray icon; clicking it brings the main window to the front and its menu
+/// offers a real quit (needed once closing the window only hides it)
+pub fn create_tray(app: &tauri::AppHandle) -> tauri::Result<()> {
+    use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+
+    let menu = tray_menu(app)?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Leaxer")
+        .menu(&menu)
+        .show_menu_on_left_click(false);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+
+    builder
+        .on_menu_event(|app, event| match event.id().as_ref() {
+            "show" => show_main_window(app),
+            "quit" => crate::shutdown(app),
+            id => {
+                if let Some(profile) = id.strip_prefix(PROFILE_ITEM_PREFIX) {
+                    crate::profiles::switch_in_background(app, profile.to_string());
+                }
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                show_main_window(tray.app_handle());
+            }
+        })
+        .build(app)?;
+
+    Ok(())
+}
+
+/// Lazily created tray icon; holds whether creating it succeeded
+pub struct TrayInstalled(pub bool);
+
+/// Constructor for `Lazy<TrayInstalled>`
+pub fn install_tray(app: &tauri::AppHandle) -> TrayInstalled {
+    match create_tray(app) {
+        Ok(()) => {
+            refresh_tray_tooltip(app);
+            TrayInstalled(true)
+        }
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Failed to create tray icon: {}", e));
+            TrayInstalled(false)
+        }
+    }
+}
+
+/// Quit the app and stop the backend, the explicit exit when closing the window only
+/// hides it (service mode, warm standby)
+#[tauri::command]
+pub fn quit_and_stop_server(app: tauri::AppHandle) {
+    log_to_file("[Leaxer] Quit and stop server requested");
+    crate::shutdown(&app);
+}
+
+/// Create the tray icon if it doesn't exist yet. It only matters once the window can be
+/// hidden (service mode, warm standby), so it is not built at startup. Returns whether
+/// the tray exists.
+pub fn ensure_tray(app: &tauri::AppHandle) -> bool {
+    crate::lazy::get::<TrayInstalled>(app).0
+}
+
+/// Push the current title and unread count to the tray tooltip
+fn refresh_tray_tooltip(app: &tauri::AppHandle) {
+    let tooltip = {
+        let state = app.state::<Mutex<AttentionState>>();
+        let guard = state.lock().unwrap();
+        match guard.unread {
+            0 => guard.title.clone(),
+            n => format!("{} ({} unread)", guard.title, n),
+        }
+    };
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+/// Small red dot used as the Windows taskbar overlay, which can't display a number
+#[cfg(target_os = "windows")]
+fn unread_overlay_icon() -> tauri::image::Image<'static> {
+    const SIZE: u32 = 16;
+    let mut rgba = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    let center = (SIZE as f32 - 1.0) / 2.0;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let dx = x as f32 - center;
+            let dy = y as f32 - center;
+            let inside = dx * dx + dy * dy <= center * center;
+            rgba.extend_from_slice(if inside { &[220, 38, 38, 255] } else { &[0, 0, 0, 0] });
+        }
+    }
+    tauri::image::Image::new_owned(rgba, SIZE, SIZE)
+}
+
+/// Set the native window title (also shown in the taskbar and tray tooltip)
+#[tauri::command]
+pub fn set_window_title(app: tauri::AppHandle, window: tauri::WebviewWindow, text: String) -> Result<(), String> {
+    window.set_title(&text).map_err(|e| e.to_string())?;
+    app.state::<Mutex<AttentionState>>().lock().unwrap().title = text;
+    refresh_tray_tooltip(&app);
+    Ok(())
+}
+
+/// Show the number of unseen background completions on the dock/taskbar badge and tray tooltip.
+/// Passing 0 clears the badge.
+#[tauri::command]
+pub fn set_unread_count(app: tauri::AppHandle, window: tauri::WebviewWindow, n: u32) -> Result<(), String> {
+    app.state::<Mutex<AttentionState>>().lock().unwrap().unread = n;
+    refresh_tray_tooltip(&app);
+
+    #[cfg(target_os = "windows")]
+    {
+        let overlay = if n > 0 { Some(unread_overlay_icon()) } else { None };
+        window.set_overlay_icon(overlay).map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let count = if n > 0 { Some(n as i64) } else { None };
+        window.set_badge_count(count).map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+//! Version handshake between the shell and the bundled backend.
+//!
+//! The shell and leaxer_core ship together but can drift apart, e.g. when one of them is
+//! replaced by an update and the other isn't. Before the backend is spawned, the release
+//! version in its `start_erl.data` is checked against the range this shell was built
+//! for, and startup stops with an explanation instead of failing in odd ways later.
+
+use crate::logging::log_to_file;
+
+/// `major.minor.patch`
+type Version = (u64, u64, u64);
+
+/// Backend releases this shell works with: at least the first, below the second
+const COMPATIBLE_BACKEND: (Version, Version) = ((0, 1, 0), (0, 2, 0));
+
+/// Parse `1.2.3`, `v1.2` or `1.2.3-rc.1`; pre-release and build suffixes are ignored
+pub(crate) fn parse_version(version: &str) -> Option<Version> {
+    let core = version.trim().trim_start_matches('v').split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+fn format_version((major, minor, patch): Version) -> String {
+    format!("{}.{}.{}", major, minor, patch)
+}
+
+fn is_compatible(version: Version) -> bool {
+    let (min, max) = COMPATIBLE_BACKEND;
+    version >= min && version < max
+}
+
+/// Whether this shell can run the backend release `version`
+pub(crate) fn is_compatible_version(version: &str) -> bool {
+    parse_version(version).is_some_and(is_compatible)
+}
+
+/// Check the bundled backend's version. Dev mode and releases without a readable version
+/// pass, since there's nothing reliable to compare.
+pub fn check(app: &tauri::AppHandle) -> Result<(), String> {
+    let Some(root) = crate::process::locate_backend(app)
+        .and_then(|exe| exe.parent().and_then(|bin| bin.parent()).map(|root| root.to_path_buf()))
+    else {
+        return Ok(());
+    };
+    let Some((_, release)) = crate::process::release_versions(&root) else {
+        log_to_file("[Leaxer] Backend version unknown, skipping the compatibility check");
+        return Ok(());
+    };
+    let shell = app.package_info().version.to_string();
+    match parse_version(&release) {
+        Some(version) if is_compatible(version) => {
+            log_to_file(&format!("[Leaxer] Backend {} is compatible with shell {}", release, shell));
+            Ok(())
+        }
+        _ => {
+            let (min, max) = COMPATIBLE_BACKEND;
+            Err(format!(
+                "This version of Leaxer ({}) can't run the installed backend {}, it needs {} up to (not including) {}. \
+                 Reinstall Leaxer to get matching versions.",
+                shell,
+                release,
+                format_version(min),
+                format_version(max)
+            ))
+        }
+    }
+}
+
+/// Explain the mismatch and quit once the user has read it
+#[cfg(feature = "dialog")]
+pub fn report(app: &tauri::AppHandle, error: &str) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let handle = app.clone();
+    app.dialog()
+        .message(error)
+        .title("Leaxer needs to be reinstalled")
+        .kind(MessageDialogKind::Error)
+        .buttons(MessageDialogButtons::OkCustom("Quit".to_string()))
+        .show(move |_| handle.exit(1));
+}
+
+/// Without a dialog the splash and the main window show the error
+#[cfg(not(feature = "dialog"))]
+pub fn report(_app: &tauri::AppHandle, _error: &str) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_release_versions() {
+        assert_eq!(parse_version("0.1.0"), Some((0, 1, 0)));
+        assert_eq!(parse_version("v1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_version("0.1.3-rc.1+build.5\n"), Some((0, 1, 3)));
+        assert_eq!(parse_version("latest"), None);
+    }
+
+    #[test]
+    fn accepts_only_the_compatible_range() {
+        let (min, max) = COMPATIBLE_BACKEND;
+        assert!(is_compatible(min));
+        assert!(!is_compatible(max));
+        assert!(!is_compatible((0, 0, 9)));
+    }
+}
@@ -0,0 +1,759 @@
+//! The user's config.json, cached in memory.
+//!
+//! Reads are served from a parsed copy that a file watcher invalidates when the file
+//! changes on disk. Writes update the cache immediately and are flushed to disk after a
+//! short quiet period, so rapid settings changes don't thrash the disk. The write goes to
+//! a temporary file that replaces config.json, so a crash mid-write can't truncate it.
+//!
+//! The shell's own settings are read as a typed `Config` (`ConfigStore::get`). Values of
+//! the wrong type or outside the allowed set are logged when the file is read and fall
+//! back to their defaults. Sections owned by one subsystem (`extensions`, `kiosk`, ...)
+//! are parsed by that subsystem from `load()`.
+//!
+//! Every `Config` key can be overridden from the environment as `LEAXER_` plus the key in
+//! upper case (`LEAXER_DEVELOPER_MODE=1`, `LEAXER_NETWORK_EXPOSURE_ENABLED=true`, or the
+//! shorter `LEAXER_NETWORK_EXPOSURE`). Overrides win over config.json on every read but
+//! are never written to it. `LEAXER_PORT` and `LEAXER_DATA_DIR` are handled by `net` and
+//! `paths`.
+//!
+//! `--config <path>` reads and writes another file instead, for running several setups
+//! side by side.
+//!
+//! Edits made while the app runs are picked up by `start_hot_reload`: settings the shell
+//! reads as it goes (log level, tray behaviour) apply at once, and for those only read
+//! when the backend or the app starts the user is offered a restart.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::logging::log_to_file;
+use crate::paths::get_leaxer_user_dir;
+
+/// Command-line flag naming the config file to use instead of the user dir's
+pub const CONFIG_FLAG: &str = "--config";
+
+/// Quiet period after the last `set` before the config is written to disk
+const CONFIG_WRITE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Quiet period after a change on disk before it is applied; editors write in bursts
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Environment overrides are this plus the key in upper case
+const ENV_PREFIX: &str = "LEAXER_";
+
+/// Override names accepted besides the generated ones
+const ENV_ALIASES: &[(&str, &str)] = &[("LEAXER_NETWORK_EXPOSURE", "network_exposure_enabled")];
+
+/// Settings the backend only reads when it starts
+const BACKEND_RESTART_KEYS: &[&str] = &["network_exposure_enabled", "network_tls", "network_pairing"];
+
+/// Settings whose changes go to the audit log
+const AUDITED_KEYS: &[&str] = &["network_exposure_enabled", "network_tls", "network_pairing"];
+
+/// Settings the shell only reads at launch
+const RELAUNCH_KEYS: &[&str] = &["backend_url", "backend_transport", "log_format"];
+
+/// Settings the webview may write with `set_config_value`. The rest are either managed by
+/// the shell behind a native confirmation (`fs_allowed_folders`, `extensions`), have their
+/// own audited command (`network_exposure_enabled`), or choose what the app connects to,
+/// weaken network security or unlock devtools (`developer_mode`), and are only changed by
+/// editing config.json.
+const UI_SETTABLE_KEYS: &[&str] = &[
+    "service_mode",
+    "warm_standby",
+    "backend_watchdog_restart",
+    "search_indexing",
+    "backend_startup_timeout_secs",
+    "log_level",
+    "log_format",
+    "log_retained_files",
+];
+
+/// Config file named with `--config <path>` or `--config=<path>`
+pub fn config_file_arg(args: &[String]) -> Option<PathBuf> {
+    let path = args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == CONFIG_FLAG {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(CONFIG_FLAG).and_then(|rest| rest.strip_prefix('=')).map(str::to_string)
+        }
+    })?;
+    let path = PathBuf::from(path);
+    Some(std::path::absolute(&path).unwrap_or(path))
+}
+
+/// Location of config.json: the `--config` file, or the one in the Leaxer user dir
+pub fn config_path() -> Option<PathBuf> {
+    config_file_arg(&std::env::args().collect::<Vec<_>>())
+        .or_else(|| get_leaxer_user_dir().map(|dir| dir.join("config.json")))
+}
+
+/// The shell's settings in config.json. Missing or invalid keys take their defaults.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Bind the backend to all interfaces instead of loopback
+    pub network_exposure_enabled: bool,
+    /// Serve other devices over HTTPS with a self-signed certificate when exposed
+    pub network_tls: bool,
+    /// Only answer other devices paired with a PIN when exposed
+    pub network_pairing: bool,
+    /// Advertise the exposed backend over mDNS (`_leaxer._tcp`)
+    pub network_mdns: bool,
+    pub developer_mode: bool,
+    /// Closing the window leaves the backend running behind the tray
+    pub service_mode: bool,
+    /// Closing the window pauses the backend behind the tray
+    pub warm_standby: bool,
+    pub backend_watchdog_restart: bool,
+    pub search_indexing: bool,
+    pub backend_url: Option<String>,
+    pub backend_transport: Option<String>,
+    pub backend_update_url: Option<String>,
+    pub backend_startup_timeout_secs: Option<u64>,
+    pub log_level: Option<String>,
+    pub log_format: Option<String>,
+    pub log_retained_files: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            network_exposure_enabled: false,
+            network_tls: true,
+            network_pairing: true,
+            network_mdns: true,
+            developer_mode: false,
+            service_mode: false,
+            warm_standby: false,
+            backend_watchdog_restart: false,
+            search_indexing: true,
+            backend_url: None,
+            backend_transport: None,
+            backend_update_url: None,
+            backend_startup_timeout_secs: None,
+            log_level: None,
+            log_format: None,
+            log_retained_files: None,
+        }
+    }
+}
+
+/// What a `Config` key must hold. Integers read from the file are clamped by their
+/// readers; the range is enforced on values written from the UI.
+enum Kind {
+    Bool,
+    Integer(std::ops::RangeInclusive<u64>),
+    Text,
+    OneOf(&'static [&'static str]),
+}
+
+const SCHEMA: &[(&str, Kind)] = &[
+    ("network_exposure_enabled", Kind::Bool),
+    ("network_tls", Kind::Bool),
+    ("network_pairing", Kind::Bool),
+    ("network_mdns", Kind::Bool),
+    ("developer_mode", Kind::Bool),
+    ("service_mode", Kind::Bool),
+    ("warm_standby", Kind::Bool),
+    ("backend_watchdog_restart", Kind::Bool),
+    ("search_indexing", Kind::Bool),
+    ("backend_url", Kind::Text),
+    ("backend_transport", Kind::OneOf(&["tcp", "unix"])),
+    ("backend_update_url", Kind::Text),
+    ("backend_startup_timeout_secs", Kind::Integer(crate::readiness::TIMEOUT_RANGE)),
+    ("log_level", Kind::OneOf(&["error", "warn", "warning", "info", "debug", "trace"])),
+    ("log_format", Kind::OneOf(&["text", "json"])),
+    ("log_retained_files", Kind::Integer(0..=crate::logging::MAX_RETAINED_FILES as u64)),
+];
+
+impl Kind {
+    /// The value an environment variable stands for. Anything that doesn't parse is kept
+    /// as text, so validation reports it.
+    fn parse_env(&self, raw: &str) -> Value {
+        let raw = raw.trim();
+        match self {
+            Kind::Bool => match raw.to_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => Value::Bool(true),
+                "0" | "false" | "no" | "off" => Value::Bool(false),
+                _ => Value::String(raw.to_string()),
+            },
+            Kind::Integer(_) => raw.parse::<u64>().map(Value::from).unwrap_or_else(|_| Value::String(raw.to_string())),
+            Kind::Text | Kind::OneOf(_) => Value::String(raw.to_string()),
+        }
+    }
+
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            Kind::Bool => value.is_boolean(),
+            Kind::Integer(_) => value.is_u64(),
+            Kind::Text => value.is_string(),
+            Kind::OneOf(allowed) => value.as_str().is_some_and(|value| allowed.contains(&value)),
+        }
+    }
+
+    /// `accepts`, with integers also held to their range; the error says what is expected
+    fn check(&self, value: &Value) -> Result<(), String> {
+        let in_range = match (self, value.as_u64()) {
+            (Kind::Integer(range), Some(number)) => range.contains(&number),
+            _ => true,
+        };
+        if self.accepts(value) && in_range {
+            return Ok(());
+        }
+        Err(match self {
+            Kind::Bool => "expected true or false".to_string(),
+            Kind::Integer(range) => format!("expected a whole number from {} to {}", range.start(), range.end()),
+            Kind::Text => "expected text".to_string(),
+            Kind::OneOf(allowed) => format!("expected one of {}", allowed.join(", ")),
+        })
+    }
+}
+
+impl Config {
+    /// Typed settings from a config object, and a message for every key that was ignored
+    pub fn from_value(config: &Value) -> (Config, Vec<String>) {
+        let mut valid = serde_json::Map::new();
+        let mut problems = Vec::new();
+        for (key, kind) in SCHEMA {
+            match config.get(*key) {
+                None | Some(Value::Null) => {}
+                Some(value) if kind.accepts(value) => {
+                    valid.insert(key.to_string(), value.clone());
+                }
+                Some(value) => problems.push(format!("Ignoring invalid {} in config.json: {}", key, value)),
+            }
+        }
+        let config = serde_json::from_value(Value::Object(valid)).unwrap_or_default();
+        (config, problems)
+    }
+}
+
+/// `config` with the environment overrides found through `lookup` applied
+fn with_env_overrides(mut config: Value, lookup: impl Fn(&str) -> Option<String>) -> Value {
+    let Some(object) = config.as_object_mut() else {
+        return config;
+    };
+    for (key, kind) in SCHEMA {
+        let names = std::iter::once(format!("{}{}", ENV_PREFIX, key.to_uppercase()))
+            .chain(ENV_ALIASES.iter().filter(|(_, target)| target == key).map(|(alias, _)| alias.to_string()));
+        if let Some(raw) = names.filter_map(|name| lookup(&name)).find(|raw| !raw.trim().is_empty()) {
+            object.insert(key.to_string(), kind.parse_env(&raw));
+        }
+    }
+    config
+}
+
+fn env_lookup(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+struct ConfigInner {
+    /// Changes when another profile becomes active
+    path: Mutex<Option<PathBuf>>,
+    cache: Mutex<Option<serde_json::Value>>,
+    /// Bumped on every `set`; a scheduled write only runs if no newer one replaced it
+    write_generation: AtomicU64,
+    dirty: AtomicBool,
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    /// Bumped whenever the file changes on disk
+    changed: tokio::sync::watch::Sender<u64>,
+}
+
+/// Managed handle to the cached config; cheap to clone
+#[derive(Clone)]
+pub struct ConfigStore {
+    inner: Arc<ConfigInner>,
+}
+
+impl ConfigStore {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        ConfigStore {
+            inner: Arc::new(ConfigInner {
+                path: Mutex::new(path),
+                cache: Mutex::new(None),
+                write_generation: AtomicU64::new(0),
+                dirty: AtomicBool::new(false),
+                watcher: Mutex::new(None),
+                changed: tokio::sync::watch::Sender::new(0),
+            }),
+        }
+    }
+
+    /// Read config.json from disk; a missing or malformed file yields an empty object
+    fn read_from_disk(&self) -> serde_json::Value {
+        let config = self
+            .path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .filter(|config| config.is_object())
+            .unwrap_or_else(|| serde_json::json!({}));
+        for problem in Config::from_value(&with_env_overrides(config.clone(), env_lookup)).1 {
+            log_to_file(&format!("[Leaxer] {}", problem));
+        }
+        config
+    }
+
+    /// The parsed config with environment overrides applied
+    pub fn load(&self) -> serde_json::Value {
+        with_env_overrides(self.load_file(), env_lookup)
+    }
+
+    /// config.json as stored, loading it on first use or after invalidation
+    pub fn load_file(&self) -> serde_json::Value {
+        let mut cache = self.inner.cache.lock().unwrap();
+        if cache.is_none() {
+            *cache = Some(self.read_from_disk());
+        }
+        cache.clone().unwrap_or_default()
+    }
+
+    /// The shell's settings, typed
+    pub fn get(&self) -> Config {
+        Config::from_value(&self.load()).0
+    }
+
+    /// Read a boolean flag, defaulting to false if missing or not a boolean
+    pub fn get_bool(&self, key: &str) -> bool {
+        self.load()
+            .get(key)
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Check if network exposure is enabled in config.json
+    pub fn network_exposure_enabled(&self) -> bool {
+        self.get().network_exposure_enabled
+    }
+
+    /// Update a key in memory and schedule a debounced write to disk
+    pub fn set(&self, key: &str, value: serde_json::Value) {
+        self.update(|object| {
+            object.insert(key.to_string(), value);
+        });
+    }
+
+    /// Drop a key in memory and schedule a debounced write to disk
+    pub fn remove(&self, key: &str) {
+        self.update(|object| {
+            object.remove(key);
+        });
+    }
+
+    fn update(&self, change: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>)) {
+        {
+            let mut config = self.load_file();
+            if let Some(object) = config.as_object_mut() {
+                change(object);
+            }
+            *self.inner.cache.lock().unwrap() = Some(config);
+        }
+        self.inner.dirty.store(true, Ordering::SeqCst);
+
+        let generation = self.inner.write_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let store = self.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(CONFIG_WRITE_DEBOUNCE).await;
+            if store.inner.write_generation.load(Ordering::SeqCst) == generation {
+                if let Err(e) = store.flush() {
+                    log_to_file(&format!("[Leaxer] Failed to write config.json: {}", e));
+                }
+            }
+        });
+    }
+
+    /// Write pending changes to disk immediately, replacing the file in one step
+    pub fn flush(&self) -> std::io::Result<()> {
+        if !self.inner.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let path = match self.path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let content = serde_json::to_string_pretty(&self.load_file()).map_err(std::io::Error::other)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        fs::write(&temp, content)?;
+        fs::rename(&temp, &path).inspect_err(|_| {
+            let _ = fs::remove_file(&temp);
+        })
+    }
+
+    /// Replace the whole config and write it to disk right away
+    pub fn replace(&self, config: serde_json::Value) -> std::io::Result<()> {
+        *self.inner.cache.lock().unwrap() = Some(config);
+        self.inner.dirty.store(true, Ordering::SeqCst);
+        // A write still scheduled by `set` would be redundant
+        self.inner.write_generation.fetch_add(1, Ordering::SeqCst);
+        self.flush()
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        self.inner.path.lock().unwrap().clone()
+    }
+
+    /// Write pending changes, then serve (and watch) the config at `path` instead
+    pub fn switch_to(&self, path: Option<PathBuf>) -> std::io::Result<()> {
+        self.flush()?;
+        *self.inner.path.lock().unwrap() = path;
+        *self.inner.cache.lock().unwrap() = None;
+        self.watch().map_err(std::io::Error::other)
+    }
+
+    /// Drop the cached copy so the next read goes back to disk.
+    /// Pending (unflushed) changes are kept, they will overwrite the file anyway.
+    pub fn invalidate(&self) {
+        if !self.inner.dirty.load(Ordering::SeqCst) {
+            *self.inner.cache.lock().unwrap() = None;
+        }
+    }
+
+    /// Invalidate the cache whenever config.json changes on disk
+    pub fn watch(&self) -> notify::Result<()> {
+        use notify::{RecursiveMode, Watcher};
+
+        let path = match self.path() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        // Watch the directory: editors often replace the file instead of writing in place
+        let dir = match path.parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => return Ok(()),
+        };
+        fs::create_dir_all(&dir).map_err(notify::Error::io)?;
+
+        let store = self.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if event.paths.iter().any(|p| p.file_name() == path.file_name()) {
+                    store.invalidate();
+                    store.inner.changed.send_modify(|count| *count += 1);
+                }
+            }
+        })?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+        *self.inner.watcher.lock().unwrap() = Some(watcher);
+        Ok(())
+    }
+}
+
+/// Keys whose value differs between two configs
+fn changed_keys(old: &Value, new: &Value) -> Vec<&'static str> {
+    SCHEMA
+        .iter()
+        .map(|(key, _)| *key)
+        .filter(|key| old.get(*key) != new.get(*key))
+        .collect()
+}
+
+/// What an edit of config.json needs to take full effect, sent as `config-reloaded`
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct ConfigReload {
+    /// Changed settings that apply once the backend restarts
+    pub restart_backend: Vec<&'static str>,
+    /// Changed settings that apply once the app restarts
+    pub relaunch: Vec<&'static str>,
+}
+
+fn classify(changed: &[&'static str]) -> ConfigReload {
+    ConfigReload {
+        restart_backend: changed.iter().copied().filter(|key| BACKEND_RESTART_KEYS.contains(key)).collect(),
+        relaunch: changed.iter().copied().filter(|key| RELAUNCH_KEYS.contains(key)).collect(),
+    }
+}
+
+#[cfg(feature = "dialog")]
+fn offer_restart(app: &tauri::AppHandle, reload: &ConfigReload) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    let (keys, action) = if reload.relaunch.is_empty() {
+        (&reload.restart_backend, "Restart server")
+    } else {
+        (&reload.relaunch, "Restart Leaxer")
+    };
+    let handle = app.clone();
+    let relaunch = !reload.relaunch.is_empty();
+    app.dialog()
+        .message(format!("{} changed in config.json and applies after a restart.", keys.join(", ")))
+        .title("Restart to apply settings")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(action.to_string(), "Later".to_string()))
+        .show(move |restart| {
+            if !restart {
+                return;
+            }
+            if relaunch {
+                handle.restart();
+            }
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::process::restart_backend(handle).await {
+                    log_to_file(&format!("[Leaxer] Restart after config change failed: {}", e));
+                }
+            });
+        });
+}
+
+#[cfg(not(feature = "dialog"))]
+fn offer_restart(_app: &tauri::AppHandle, _reload: &ConfigReload) {}
+
+/// Apply config.json edits as they happen
+pub fn start_hot_reload(app: &tauri::AppHandle) {
+    use tauri::{Emitter, Manager};
+
+    let store = app.state::<ConfigStore>().inner().clone();
+    let mut changes = store.inner.changed.subscribe();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut path = store.path();
+        let mut current = store.load();
+        while changes.changed().await.is_ok() {
+            tokio::time::sleep(RELOAD_DEBOUNCE).await;
+            changes.borrow_and_update();
+            let next = store.load();
+            // A profile switch brings a whole other config and restarts the backend itself
+            if store.path() != path {
+                path = store.path();
+                current = next;
+                continue;
+            }
+            let changed = changed_keys(&current, &next);
+            current = next;
+            if changed.is_empty() {
+                continue;
+            }
+            log_to_file(&format!("[Leaxer] config.json changed: {}", changed.join(", ")));
+            for key in changed.iter().filter(|key| AUDITED_KEYS.contains(key)) {
+                let target = format!("{}={}", key, current.get(*key).unwrap_or(&Value::Null));
+                crate::audit::record::<()>("setting_change", Some(&target), &Ok(()));
+            }
+
+            crate::logging::set_level(&current);
+            if changed.iter().any(|key| matches!(*key, "service_mode" | "warm_standby")) {
+                crate::commands::window::refresh_tray_menu(&app);
+            }
+            if changed.contains(&"network_mdns") {
+                let app = app.clone();
+                tauri::async_runtime::spawn_blocking(move || crate::mdns::refresh(&app));
+            }
+            let mut reload = classify(&changed);
+            if crate::process::restarting() {
+                // The restart under way, e.g. from `set_network_exposure`, starts with these
+                reload.restart_backend.clear();
+            }
+            let _ = app.emit("config-reloaded", &reload);
+            if !reload.restart_backend.is_empty() || !reload.relaunch.is_empty() {
+                offer_restart(&app, &reload);
+            }
+        }
+    });
+}
+
+fn validate_setting(key: &str, value: &Value) -> Result<(), String> {
+    let (_, kind) = SCHEMA.iter().find(|(known, _)| *known == key).ok_or_else(|| format!("Unknown setting {}", key))?;
+    kind.check(value).map_err(|expected| format!("Invalid {}: {}", key, expected))
+}
+
+/// Set a single config key from the settings UI; the write to disk is debounced.
+/// Only `UI_SETTABLE_KEYS` are accepted, with values their `SCHEMA` kind allows.
+#[tauri::command]
+pub fn set_config_value(
+    config: tauri::State<'_, ConfigStore>,
+    key: String,
+    value: serde_json::Value,
+) -> Result<(), String> {
+    if !UI_SETTABLE_KEYS.contains(&key.as_str()) {
+        log_to_file(&format!("[Leaxer] Refused to set {} from the webview", key));
+        return Err(format!("{} can't be changed from the settings UI", key));
+    }
+    validate_setting(&key, &value)?;
+    config.set(&key, value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(content: &str) -> (tempfile::TempDir, ConfigStore) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, content).unwrap();
+        (dir, ConfigStore::new(Some(path)))
+    }
+
+    #[test]
+    fn reads_boolean_flags() {
+        let (_dir, config) = write_config(r#"{"network_exposure_enabled": true, "developer_mode": false}"#);
+        assert!(config.network_exposure_enabled());
+        assert!(!config.get_bool("developer_mode"));
+    }
+
+    #[test]
+    fn missing_or_non_boolean_keys_are_false() {
+        let (_dir, config) = write_config(r#"{"developer_mode": "yes"}"#);
+        assert!(!config.get_bool("developer_mode"));
+        assert!(!config.network_exposure_enabled());
+    }
+
+    #[test]
+    fn unreadable_config_is_empty() {
+        let (dir, config) = write_config("{ not json");
+        assert!(!config.get_bool("developer_mode"));
+        assert_eq!(config.load(), serde_json::json!({}));
+
+        let missing = ConfigStore::new(Some(dir.path().join("missing.json")));
+        assert!(!missing.get_bool("developer_mode"));
+    }
+
+    #[test]
+    fn serves_cached_value_until_invalidated() {
+        let (dir, config) = write_config(r#"{"developer_mode": false}"#);
+        assert!(!config.get_bool("developer_mode"));
+
+        fs::write(dir.path().join("config.json"), r#"{"developer_mode": true}"#).unwrap();
+        assert!(!config.get_bool("developer_mode"));
+
+        config.invalidate();
+        assert!(config.get_bool("developer_mode"));
+    }
+
+    #[test]
+    fn flush_writes_pending_changes() {
+        let (dir, config) = write_config(r#"{"developer_mode": false, "other": 1}"#);
+        config.set("developer_mode", serde_json::json!(true));
+        assert!(config.get_bool("developer_mode"));
+
+        // Pending changes survive an invalidation triggered by an external edit
+        config.invalidate();
+        assert!(config.get_bool("developer_mode"));
+
+        config.flush().unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("config.json")).unwrap()).unwrap();
+        assert_eq!(written, serde_json::json!({"developer_mode": true, "other": 1}));
+    }
+
+    #[test]
+    fn typed_config_ignores_invalid_values() {
+        let (_dir, config) = write_config(
+            r#"{"developer_mode": "yes", "service_mode": true, "log_level": "loud",
+                "backend_startup_timeout_secs": 30}"#,
+        );
+        let typed = config.get();
+        assert!(!typed.developer_mode);
+        assert!(typed.service_mode);
+        assert!(typed.search_indexing);
+        assert_eq!(typed.log_level, None);
+        assert_eq!(typed.backend_startup_timeout_secs, Some(30));
+
+        let (_, problems) = Config::from_value(&config.load());
+        assert_eq!(problems.len(), 2);
+    }
+
+    #[test]
+    fn flush_replaces_the_file_without_leaving_a_temp_file() {
+        let (dir, config) = write_config(r#"{"developer_mode": false}"#);
+        config.set("developer_mode", serde_json::json!(true));
+        config.flush().unwrap();
+
+        let names: Vec<_> = fs::read_dir(dir.path()).unwrap().flatten().map(|entry| entry.file_name()).collect();
+        assert_eq!(names, vec![std::ffi::OsString::from("config.json")]);
+    }
+
+    #[test]
+    fn sorts_changes_by_what_they_need() {
+        let old = serde_json::json!({ "log_level": "info", "network_exposure_enabled": false, "other": 1 });
+        let new = serde_json::json!({
+            "log_level": "debug",
+            "network_exposure_enabled": true,
+            "backend_url": "http://nas:4000",
+            "other": 2
+        });
+        let changed = changed_keys(&old, &new);
+        assert_eq!(changed, vec!["network_exposure_enabled", "backend_url", "log_level"]);
+        assert_eq!(
+            classify(&changed),
+            ConfigReload {
+                restart_backend: vec!["network_exposure_enabled"],
+                relaunch: vec!["backend_url"],
+            }
+        );
+    }
+
+    #[test]
+    fn environment_overrides_win_over_the_file() {
+        let env = |name: &str| match name {
+            "LEAXER_NETWORK_EXPOSURE" => Some("1".to_string()),
+            "LEAXER_BACKEND_STARTUP_TIMEOUT_SECS" => Some("45".to_string()),
+            "LEAXER_DEVELOPER_MODE" => Some("maybe".to_string()),
+            _ => None,
+        };
+        let config = with_env_overrides(serde_json::json!({ "developer_mode": true, "other": 1 }), env);
+        let (typed, problems) = Config::from_value(&config);
+        assert!(typed.network_exposure_enabled);
+        assert_eq!(typed.backend_startup_timeout_secs, Some(45));
+        assert!(!typed.developer_mode);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(config["other"], 1);
+    }
+
+    #[test]
+    fn shell_managed_keys_are_not_ui_settable() {
+        assert!(UI_SETTABLE_KEYS.iter().all(|key| SCHEMA.iter().any(|(known, _)| known == key)));
+        for key in ["fs_allowed_folders", "extensions", "network_exposure_enabled", "backend_url", "developer_mode"] {
+            assert!(!UI_SETTABLE_KEYS.contains(&key));
+        }
+    }
+
+    #[test]
+    fn ui_values_must_match_the_schema() {
+        assert!(validate_setting("log_level", &serde_json::json!("debug")).is_ok());
+        assert!(validate_setting("log_level", &serde_json::json!(5)).is_err());
+        assert!(validate_setting("backend_startup_timeout_secs", &serde_json::json!("abc")).is_err());
+        assert!(validate_setting("backend_startup_timeout_secs", &serde_json::json!(60)).is_ok());
+        assert!(validate_setting("backend_startup_timeout_secs", &serde_json::json!(1)).is_err());
+        assert!(validate_setting("service_mode", &serde_json::json!("yes")).is_err());
+    }
+
+    #[test]
+    fn config_flag_names_the_file() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(config_file_arg(&args(&["leaxer"])), None);
+        let path = config_file_arg(&args(&["leaxer", "--config", "tester.json"])).unwrap();
+        assert!(path.is_absolute() && path.ends_with("tester.json"));
+        assert_eq!(
+            config_file_arg(&args(&["leaxer", "--config=/tmp/a.json"])),
+            Some(PathBuf::from("/tmp/a.json"))
+        );
+    }
+
+    #[test]
+    fn switching_flushes_and_reads_the_new_file() {
+        let (dir, config) = write_config(r#"{"developer_mode": false}"#);
+        config.set("developer_mode", serde_json::json!(true));
+
+        let other = dir.path().join("profiles").join("work").join("config.json");
+        fs::create_dir_all(other.parent().unwrap()).unwrap();
+        fs::write(&other, r#"{"warm_standby": true}"#).unwrap();
+        config.switch_to(Some(other)).unwrap();
+
+        assert!(config.get_bool("warm_standby"));
+        assert!(!config.get_bool("developer_mode"));
+        let first: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("config.json")).unwrap()).unwrap();
+        assert_eq!(first, serde_json::json!({"developer_mode": true}));
+    }
+}
@@ -0,0 +1,224 @@
+//! Backups of the shell's settings, to recover from a change that breaks things.
+//!
+//! Each backup is one JSON file in `<Leaxer dir>/config-backups/` holding config.json and,
+//! if the UI passed it, its own state (window layout, panels). `backup_config` makes one on
+//! request; a launch whose backend comes up saves the config as last known good. When a
+//! bad setting keeps the app from starting, `--restore-config` (or
+//! `--restore-config=<id>`) puts the newest backup back before anything reads config.json.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::ConfigStore;
+use crate::logging::log_to_file;
+
+pub const RESTORE_FLAG: &str = "--restore-config";
+
+/// Automatic backups kept; the ones made on request are kept until deleted by hand
+const MAX_AUTOMATIC: usize = 10;
+
+/// A saved backup as listed to the UI
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ConfigBackup {
+    pub id: String,
+    /// Unix time in milliseconds
+    pub created: u64,
+    pub label: Option<String>,
+    /// Saved by a successful launch rather than by the user
+    pub automatic: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupFile {
+    created: u64,
+    label: Option<String>,
+    automatic: bool,
+    config: Value,
+    ui_state: Option<Value>,
+}
+
+/// `<Leaxer dir>/config-backups`
+fn backups_dir() -> Option<PathBuf> {
+    crate::paths::get_leaxer_user_dir().map(|dir| dir.join("config-backups"))
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn read_backup(path: &Path) -> Option<BackupFile> {
+    serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+}
+
+/// Backups in `dir`, newest first. Names sort by time.
+fn backups_in(dir: &Path) -> Vec<(ConfigBackup, PathBuf)> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths
+        .into_iter()
+        .rev()
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.strip_prefix("backup-")?.to_string();
+            let file = read_backup(&path)?;
+            let backup = ConfigBackup {
+                id,
+                created: file.created,
+                label: file.label,
+                automatic: file.automatic,
+            };
+            Some((backup, path))
+        })
+        .collect()
+}
+
+/// Save `file` to `dir` and delete automatic backups beyond `MAX_AUTOMATIC`
+fn write_backup_to(dir: &Path, file: &BackupFile) -> std::io::Result<ConfigBackup> {
+    std::fs::create_dir_all(dir)?;
+    // Zero-padded so names sort by time
+    let id = format!("{:015}", file.created);
+    let json = serde_json::to_vec_pretty(file).map_err(std::io::Error::other)?;
+    std::fs::write(dir.join(format!("backup-{}.json", id)), json)?;
+
+    let automatic = backups_in(dir).into_iter().filter(|(backup, _)| backup.automatic);
+    for (_, old) in automatic.skip(MAX_AUTOMATIC) {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(ConfigBackup {
+        id,
+        created: file.created,
+        label: file.label.clone(),
+        automatic: file.automatic,
+    })
+}
+
+/// The backup with `id` in `dir`, or the newest one
+fn find_backup(dir: &Path, id: Option<&str>) -> Option<BackupFile> {
+    let (_, path) = backups_in(dir)
+        .into_iter()
+        .find(|(backup, _)| id.is_none_or(|id| backup.id == id))?;
+    read_backup(&path)
+}
+
+/// Save the config as last known good, unless it is unchanged since the newest backup.
+/// Called once the backend has come up with it.
+pub fn save_last_good(config: &ConfigStore) {
+    let Some(dir) = backups_dir() else {
+        return;
+    };
+    let current = config.load_file();
+    if find_backup(&dir, None).is_some_and(|newest| newest.config == current) {
+        return;
+    }
+    let file = BackupFile {
+        created: now_millis(),
+        label: None,
+        automatic: true,
+        config: current,
+        ui_state: None,
+    };
+    if let Err(e) = write_backup_to(&dir, &file) {
+        log_to_file(&format!("[Leaxer] Failed to back up config.json: {}", e));
+    }
+}
+
+/// Handle `--restore-config` before the config is first read
+pub fn restore_from_args(args: &[String]) {
+    let Some(id) = args.iter().find_map(|arg| match arg.as_str() {
+        RESTORE_FLAG => Some(None),
+        arg => arg.strip_prefix(RESTORE_FLAG).and_then(|rest| rest.strip_prefix('=')).map(Some),
+    }) else {
+        return;
+    };
+    let Some(backup) = backups_dir().and_then(|dir| find_backup(&dir, id)) else {
+        log_to_file("[Leaxer] No config backup to restore");
+        return;
+    };
+    match ConfigStore::new(crate::config::config_path()).replace(backup.config) {
+        Ok(()) => log_to_file(&format!("[Leaxer] Restored config.json from the backup of {}", backup.created)),
+        Err(e) => log_to_file(&format!("[Leaxer] Failed to restore config.json: {}", e)),
+    }
+}
+
+/// Back up config.json now, with the UI's own state if it passes it
+#[tauri::command]
+pub fn backup_config(
+    config: tauri::State<'_, ConfigStore>,
+    label: Option<String>,
+    ui_state: Option<Value>,
+) -> Result<ConfigBackup, String> {
+    let dir = backups_dir().ok_or("No data directory")?;
+    let file = BackupFile {
+        created: now_millis(),
+        label: label.filter(|label| !label.trim().is_empty()),
+        automatic: false,
+        config: config.load_file(),
+        ui_state,
+    };
+    write_backup_to(&dir, &file).map_err(|e| format!("Failed to save the backup: {}", e))
+}
+
+/// Saved backups, newest first
+#[tauri::command]
+pub fn list_config_backups() -> Vec<ConfigBackup> {
+    backups_dir()
+        .map(|dir| backups_in(&dir).into_iter().map(|(backup, _)| backup).collect())
+        .unwrap_or_default()
+}
+
+/// Put a backup's config.json back. Returns the UI state saved with it, for the UI to
+/// apply; the config change itself is picked up like any edit of config.json.
+#[tauri::command]
+pub fn restore_config_backup(config: tauri::State<'_, ConfigStore>, id: String) -> Result<Option<Value>, String> {
+    let dir = backups_dir().ok_or("No data directory")?;
+    let backup = find_backup(&dir, Some(&id)).ok_or_else(|| format!("No backup {}", id))?;
+    config
+        .replace(backup.config)
+        .map_err(|e| format!("Failed to restore config.json: {}", e))?;
+    log_to_file(&format!("[Leaxer] Restored config.json from backup {}", id));
+    Ok(backup.ui_state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backup(created: u64, automatic: bool) -> BackupFile {
+        BackupFile {
+            created,
+            label: None,
+            automatic,
+            config: serde_json::json!({ "n": created }),
+            ui_state: None,
+        }
+    }
+
+    #[test]
+    fn prunes_only_automatic_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        write_backup_to(dir.path(), &backup(0, false)).unwrap();
+        for created in 1..=MAX_AUTOMATIC as u64 + 2 {
+            write_backup_to(dir.path(), &backup(created, true)).unwrap();
+        }
+        let backups = backups_in(dir.path());
+        assert_eq!(backups.len(), MAX_AUTOMATIC + 1);
+        assert_eq!(backups[0].0.created, MAX_AUTOMATIC as u64 + 2);
+        assert!(!backups.last().unwrap().0.automatic);
+    }
+
+    #[test]
+    fn finds_a_backup_by_id_or_the_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = write_backup_to(dir.path(), &backup(1, false)).unwrap();
+        write_backup_to(dir.path(), &backup(2, true)).unwrap();
+        assert_eq!(find_backup(dir.path(), None).unwrap().created, 2);
+        assert_eq!(find_backup(dir.path(), Some(&first.id)).unwrap().config, serde_json::json!({ "n": 1 }));
+        assert!(find_backup(dir.path(), Some("../config")).is_none());
+    }
+}
@@ -0,0 +1,183 @@
+//! JSON line control channel to the backend over its stdin and stdout.
+//!
+//! The backend is spawned with `LEAXER_CONTROL=stdio` and a piped stdin. Requests are
+//! written as `{"id": 1, "cmd": "status"}` lines; the backend answers, and sends
+//! notifications such as `ready` and `progress`, as stdout lines starting with `PREFIX`.
+//! The log capture hands those lines to `receive` instead of writing them to backend.log.
+//! Notifications are forwarded to the UI as `backend-event`.
+//!
+//! The backend asks the shell too, with `{"type": "request", "id": 1, "cmd": "pair"}` lines
+//! (see `handle_request`), and gets `{"type": "reply", ...}` lines back on stdin.
+//!
+//! Closing stdin tells the backend the shell is gone, so it stops instead of becoming an
+//! orphan when the shell dies.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::Value;
+use tauri::Emitter;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::logging::log_to_file;
+
+/// Tells the backend to serve the control channel on stdin/stdout
+pub const CONTROL_ENV: &str = "LEAXER_CONTROL";
+
+/// Marks control messages among the backend's stdout lines
+pub const PREFIX: &str = "LEAXER_CONTROL ";
+
+/// Commands the UI may send through `backend_control`
+const UI_COMMANDS: &[&str] = &["status", "reload_config"];
+
+struct Connection {
+    tx: mpsc::UnboundedSender<String>,
+    pending: HashMap<u64, oneshot::Sender<Result<Value, String>>>,
+    app: tauri::AppHandle,
+}
+
+/// Channel to the current backend process
+static CONNECTION: Mutex<Option<Connection>> = Mutex::new(None);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Talk to a freshly spawned backend through its stdin, replacing any earlier channel
+pub fn attach(app: &tauri::AppHandle, mut stdin: tokio::process::ChildStdin) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+    tauri::async_runtime::spawn(async move {
+        while let Some(line) = rx.recv().await {
+            if stdin.write_all(line.as_bytes()).await.is_err() || stdin.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+    *CONNECTION.lock().unwrap() = Some(Connection {
+        tx,
+        pending: HashMap::new(),
+        app: app.clone(),
+    });
+}
+
+/// Send `command` and wait up to `timeout` for the backend's result
+pub async fn request(command: &str, timeout: Duration) -> Result<Value, String> {
+    request_with(command, serde_json::json!({}), timeout).await
+}
+
+/// Send `command` with `args` and wait up to `timeout` for the backend's result
+pub async fn request_with(command: &str, args: Value, timeout: Duration) -> Result<Value, String> {
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let (reply, rx) = oneshot::channel();
+    {
+        let mut connection = CONNECTION.lock().unwrap();
+        let connection = connection.as_mut().ok_or("No control channel to the backend")?;
+        let line = format!("{}\n", serde_json::json!({ "id": id, "cmd": command, "args": args }));
+        connection.tx.send(line).map_err(|_| "The control channel is closed")?;
+        connection.pending.insert(id, reply);
+    }
+
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err("The control channel is closed".to_string()),
+        Err(_) => {
+            if let Some(connection) = CONNECTION.lock().unwrap().as_mut() {
+                connection.pending.remove(&id);
+            }
+            Err(format!("The backend did not answer '{}' in time", command))
+        }
+    }
+}
+
+/// Result carried by a reply message
+fn reply_result(message: &Value) -> Result<Value, String> {
+    if message["ok"].as_bool() == Some(true) {
+        Ok(message["result"].clone())
+    } else {
+        Err(message["error"].as_str().unwrap_or("The backend reported an error").to_string())
+    }
+}
+
+/// Answer a request the backend sent with `id`
+fn reply(id: &Value, result: Result<Value, String>) {
+    let message = match result {
+        Ok(result) => serde_json::json!({ "type": "reply", "id": id, "ok": true, "result": result }),
+        Err(error) => serde_json::json!({ "type": "reply", "id": id, "ok": false, "error": error }),
+    };
+    if let Some(connection) = CONNECTION.lock().unwrap().as_ref() {
+        let _ = connection.tx.send(format!("{}\n", message));
+    }
+}
+
+/// Handle a request from the backend
+async fn handle_request(app: &tauri::AppHandle, command: &str, args: &Value) -> Result<Value, String> {
+    match command {
+        "pair" => crate::pairing::pair_request(app, args).await,
+        _ => Err(format!("Unknown command: {}", command)),
+    }
+}
+
+/// Handle one control line (without `PREFIX`) from the backend's stdout
+pub fn receive(line: &str) {
+    let Ok(message) = serde_json::from_str::<Value>(line) else {
+        log_to_file(&format!("[Leaxer] Malformed control message from the backend: {}", line));
+        return;
+    };
+    let app = {
+        let mut connection = CONNECTION.lock().unwrap();
+        let Some(connection) = connection.as_mut() else {
+            return;
+        };
+        if message["type"] == "reply" {
+            if let Some(reply) = message["id"].as_u64().and_then(|id| connection.pending.remove(&id)) {
+                let _ = reply.send(reply_result(&message));
+            }
+            return;
+        }
+        connection.app.clone()
+    };
+
+    if message["type"] == "request" {
+        tauri::async_runtime::spawn(async move {
+            let command = message["cmd"].as_str().unwrap_or_default();
+            let result = handle_request(&app, command, &message["args"]).await;
+            reply(&message["id"], result);
+        });
+        return;
+    }
+
+    match message["event"].as_str() {
+        Some("progress") => {
+            if let Some(status) = message["message"].as_str() {
+                crate::splash::set_status(&app, status);
+            }
+        }
+        Some("ready") => log_to_file("[Leaxer] Backend reported ready"),
+        _ => {}
+    }
+    let _ = app.emit("backend-event", message);
+}
+
+/// Send a control command from the UI (`status` or `reload_config`)
+#[tauri::command]
+pub async fn backend_control(command: String) -> Result<Value, String> {
+    if !UI_COMMANDS.contains(&command.as_str()) {
+        return Err(format!("Unknown backend command: {}", command));
+    }
+    request(&command, Duration::from_secs(5)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replies_carry_results_or_errors() {
+        let ok = serde_json::json!({ "type": "reply", "id": 1, "ok": true, "result": { "processes": 5 } });
+        assert_eq!(reply_result(&ok), Ok(serde_json::json!({ "processes": 5 })));
+
+        let failed = serde_json::json!({ "type": "reply", "id": 2, "ok": false, "error": "Unknown command: x" });
+        assert_eq!(reply_result(&failed), Err("Unknown command: x".to_string()));
+    }
+}
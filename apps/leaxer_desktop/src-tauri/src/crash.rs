@@ -0,0 +1,223 @@
+//! Crash reports: a panic, or a backend that can't be spawned at all, leaves a JSON report
+//! in `<Leaxer dir>/crashes/` with a backtrace, the OS and the last log lines. The next
+//! launch tells the user about reports they haven't seen and offers to show the newest.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::logging::log_to_file;
+
+/// Log lines included in a report
+const REPORT_LOG_LINES: usize = 200;
+
+/// Reports kept; older ones are deleted when a new one is written
+const MAX_REPORTS: usize = 20;
+
+/// Name of the newest report the user was told about
+const SEEN_FILE: &str = "last_seen";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    Panic,
+    SpawnFailure,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub kind: CrashKind,
+    /// Unix time in milliseconds
+    pub time: u64,
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+    pub message: String,
+    /// Source location of a panic
+    pub location: Option<String>,
+    pub backtrace: Option<String>,
+    pub recent_logs: Vec<String>,
+}
+
+/// `<Leaxer dir>/crashes`
+pub fn crashes_dir() -> Option<PathBuf> {
+    crate::paths::get_leaxer_user_dir().map(|dir| dir.join("crashes"))
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn os_description() -> String {
+    sysinfo::System::long_os_version()
+        .or_else(sysinfo::System::os_version)
+        .unwrap_or_else(|| std::env::consts::OS.to_string())
+}
+
+impl CrashReport {
+    fn new(kind: CrashKind, message: String, location: Option<String>, backtrace: Option<String>) -> Self {
+        CrashReport {
+            kind,
+            time: now_millis(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: os_description(),
+            arch: std::env::consts::ARCH.to_string(),
+            message,
+            location,
+            backtrace,
+            recent_logs: crate::logging::try_recent_lines(REPORT_LOG_LINES).unwrap_or_default(),
+        }
+    }
+}
+
+/// Report files in `dir`, oldest first. Names sort by time.
+fn reports_in(dir: &Path) -> Vec<PathBuf> {
+    let mut reports: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+                    name.starts_with("crash-") && name.ends_with(".json")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort();
+    reports
+}
+
+/// Save a report to `dir` and delete the oldest beyond `MAX_REPORTS`
+fn write_report_to(dir: &Path, report: &CrashReport) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    // Zero-padded so names sort by time
+    let path = dir.join(format!("crash-{:015}.json", report.time));
+    let json = serde_json::to_vec_pretty(report).map_err(std::io::Error::other)?;
+    std::fs::write(&path, json)?;
+
+    let reports = reports_in(dir);
+    for old in &reports[..reports.len().saturating_sub(MAX_REPORTS)] {
+        let _ = std::fs::remove_file(old);
+    }
+    Ok(path)
+}
+
+fn write_report(report: &CrashReport) {
+    let Some(dir) = crashes_dir() else {
+        return;
+    };
+    match write_report_to(&dir, report) {
+        Ok(path) => log_to_file(&format!("[Leaxer] Crash report written to {:?}", path)),
+        Err(e) => log_to_file(&format!("[Leaxer] Failed to write crash report: {}", e)),
+    }
+}
+
+/// Write a report for a panic. Called from the panic hook, so it takes no lock it could
+/// wait on.
+pub fn record_panic(info: &std::panic::PanicHookInfo) {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "Box<dyn Any>".to_string());
+    let location = info.location().map(|location| location.to_string());
+    let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+    write_report(&CrashReport::new(CrashKind::Panic, message, location, Some(backtrace)));
+}
+
+/// Write a report for a backend that could not be started
+pub fn record_spawn_failure(error: &std::io::Error) {
+    let message = format!("Failed to start the backend: {}", error);
+    write_report(&CrashReport::new(CrashKind::SpawnFailure, message, None, None));
+}
+
+/// Newest report in `dir` written after the one recorded as seen, which it then becomes
+fn take_unseen(dir: &Path) -> Option<PathBuf> {
+    let newest = reports_in(dir).pop()?;
+    let name = newest.file_name()?.to_string_lossy().to_string();
+    let seen = std::fs::read_to_string(dir.join(SEEN_FILE)).unwrap_or_default();
+    if seen.trim() >= name.as_str() {
+        return None;
+    }
+    let _ = std::fs::write(dir.join(SEEN_FILE), &name);
+    Some(newest)
+}
+
+#[cfg(feature = "dialog")]
+fn ask_to_open(app: &tauri::AppHandle, report: PathBuf) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+    const OPEN: &str = "Show report";
+    let app_handle = app.clone();
+    app.dialog()
+        .message(
+            "Leaxer quit unexpectedly last time. A crash report was saved; \
+             including it in a bug report helps fix the problem.",
+        )
+        .title("A previous crash was detected")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(OPEN.to_string(), "Close".to_string()))
+        .show(move |open| {
+            if open {
+                let path = report.to_string_lossy().to_string();
+                if let Err(e) = crate::commands::files::reveal_path(app_handle, path) {
+                    log_to_file(&format!("[Leaxer] Failed to show crash report: {}", e));
+                }
+            }
+        });
+}
+
+#[cfg(not(feature = "dialog"))]
+fn ask_to_open(_app: &tauri::AppHandle, _report: PathBuf) {}
+
+/// Tell the user about a crash report from an earlier run, once
+pub fn notify_previous(app: &tauri::AppHandle) {
+    let Some(report) = crashes_dir().and_then(|dir| take_unseen(&dir)) else {
+        return;
+    };
+    log_to_file(&format!("[Leaxer] Previous crash report found: {:?}", report));
+    ask_to_open(app, report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(time: u64) -> CrashReport {
+        CrashReport {
+            time,
+            ..CrashReport::new(CrashKind::Panic, "boom".to_string(), None, None)
+        }
+    }
+
+    #[test]
+    fn keeps_the_newest_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        for time in 0..MAX_REPORTS as u64 + 3 {
+            write_report_to(dir.path(), &report(time)).unwrap();
+        }
+        let reports = reports_in(dir.path());
+        assert_eq!(reports.len(), MAX_REPORTS);
+        assert!(reports[0].ends_with(format!("crash-{:015}.json", 3)));
+
+        let saved: CrashReport = serde_json::from_slice(&std::fs::read(&reports[0]).unwrap()).unwrap();
+        assert_eq!(saved.kind, CrashKind::Panic);
+        assert_eq!(saved.message, "boom");
+    }
+
+    #[test]
+    fn each_report_is_announced_once() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(take_unseen(dir.path()).is_none());
+        let first = write_report_to(dir.path(), &report(1)).unwrap();
+        assert_eq!(take_unseen(dir.path()), Some(first));
+        assert!(take_unseen(dir.path()).is_none());
+        let second = write_report_to(dir.path(), &report(2)).unwrap();
+        assert_eq!(take_unseen(dir.path()), Some(second));
+    }
+}
@@ -0,0 +1,254 @@
+//! Moving the Leaxer folder elsewhere, e.g. to a bigger drive for models and outputs.
+//!
+//! The home Leaxer dir (see `paths`) keeps a `data_dir.json` with the new location as
+//! `data_dir`, which `paths::base_leaxer_dir` follows; the backend gets the resolved path
+//! as `LEAXER_USER_DIR` as before. `move_data_dir` asks for the destination, stops the
+//! backend, copies everything, checks the copy, then points the app at it, removes the
+//! original and restarts.
+
+use std::path::Path;
+
+use crate::logging::log_to_file;
+use crate::paths::LOCATION_FILE;
+
+/// Number of files and their total size under `dir`
+fn dir_stats(dir: &Path) -> std::io::Result<(u64, u64)> {
+    let mut stats = (0, 0);
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            let (files, bytes) = dir_stats(&entry.path())?;
+            stats = (stats.0 + files, stats.1 + bytes);
+        } else {
+            stats = (stats.0 + 1, stats.1 + entry.metadata()?.len());
+        }
+    }
+    Ok(stats)
+}
+
+/// Copy `from` into `to`, calling `copied` with the bytes copied after every file. Stops
+/// with `Interrupted` once `copied` returns false.
+fn copy_tree(from: &Path, to: &Path, copied: &mut dyn FnMut(u64) -> bool) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &target, copied)?;
+        } else {
+            let bytes = std::fs::copy(entry.path(), target)?;
+            if !copied(bytes) {
+                return Err(std::io::ErrorKind::Interrupted.into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check that `target` can take the Leaxer dir at `current`
+fn check_target(current: &Path, target: &Path) -> Result<(), String> {
+    if target.starts_with(current) || current.starts_with(target) {
+        return Err("The new location can't be inside the current Leaxer folder or contain it".to_string());
+    }
+    let empty = match std::fs::read_dir(target) {
+        Ok(mut entries) => entries.next().is_none(),
+        Err(_) => !target.exists(),
+    };
+    if !empty {
+        return Err(format!("{} already exists and is not empty", target.display()));
+    }
+    Ok(())
+}
+
+/// Point the home dir at `dir`, replacing the file in one step
+fn write_location(home: &Path, dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(home)?;
+    let content = serde_json::to_vec_pretty(&serde_json::json!({ "data_dir": dir })).map_err(std::io::Error::other)?;
+    let temp = home.join(format!("{}.tmp", LOCATION_FILE));
+    std::fs::write(&temp, content)?;
+    std::fs::rename(&temp, home.join(LOCATION_FILE))
+}
+
+/// Delete the original after a successful move. The home dir itself stays, holding only
+/// the location file.
+fn remove_original(current: &Path, home: &Path) -> std::io::Result<()> {
+    if current != home {
+        return std::fs::remove_dir_all(current);
+    }
+    for entry in std::fs::read_dir(current)? {
+        let entry = entry?;
+        if entry.file_name() == LOCATION_FILE {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy `current` to `target` through a staging dir and check the copy is complete
+fn copy_checked(current: &Path, target: &Path, copied: &mut dyn FnMut(u64) -> bool) -> Result<(), String> {
+    let expected = dir_stats(current).map_err(|e| format!("Failed to read {}: {}", current.display(), e))?;
+    let staging = target.with_extension("moving");
+    let _ = std::fs::remove_dir_all(&staging);
+    let result = copy_tree(current, &staging, copied)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::Interrupted => "Cancelled".to_string(),
+            _ => format!("Failed to copy the Leaxer folder: {}", e),
+        })
+        // The shell keeps logging into the original, so it may have grown meanwhile
+        .and_then(|_| match dir_stats(&staging) {
+            Ok((files, bytes)) if files >= expected.0 && bytes >= expected.1 => Ok(()),
+            Ok((files, bytes)) => Err(format!(
+                "The copy is incomplete ({} of {} files, {} of {} bytes)",
+                files, expected.0, bytes, expected.1
+            )),
+            Err(e) => Err(format!("Failed to check the copy: {}", e)),
+        })
+        .and_then(|_| {
+            // A target dir the user picked is empty; it is replaced by the staging dir
+            let _ = std::fs::remove_dir(target);
+            std::fs::rename(&staging, target).map_err(|e| format!("Failed to move the copy into place: {}", e))
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+/// Where the Leaxer folder is now
+#[tauri::command]
+pub fn get_data_dir() -> Option<String> {
+    crate::paths::base_leaxer_dir().map(|dir| dir.to_string_lossy().to_string())
+}
+
+/// Ask for a new location and move the Leaxer folder there, then restart the app. Returns
+/// `None` if the user cancelled the dialog; the copy runs as a cancellable `data-dir` task.
+#[cfg(feature = "dialog")]
+#[tauri::command]
+pub async fn move_data_dir(app: tauri::AppHandle) -> Result<Option<String>, String> {
+    use tauri::Manager;
+    use tauri_plugin_dialog::DialogExt;
+
+    if std::env::var_os(crate::paths::DATA_DIR_ENV).is_some() {
+        return Err(format!("The Leaxer folder is set by {}", crate::paths::DATA_DIR_ENV));
+    }
+    if crate::paths::portable_dir().is_some() {
+        return Err("The Leaxer folder can't be moved in portable mode".to_string());
+    }
+    let (Some(home), Some(current)) = (crate::paths::home_leaxer_dir(), crate::paths::base_leaxer_dir()) else {
+        return Err("No data directory".to_string());
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog().file().set_title("Move the Leaxer folder to").pick_folder(move |folder| {
+        let _ = tx.send(folder);
+    });
+    let chosen = match rx.await.map_err(|e| e.to_string())? {
+        Some(folder) => folder.into_path().map_err(|e| e.to_string())?,
+        None => return Ok(None),
+    };
+    // An empty folder is used as is; anything else gets a Leaxer folder inside
+    let chosen_is_empty = std::fs::read_dir(&chosen).is_ok_and(|mut entries| entries.next().is_none());
+    let target = if chosen_is_empty { chosen } else { chosen.join("Leaxer") };
+    check_target(&current, &target)?;
+
+    let (_, bytes) = dir_stats(&current).map_err(|e| e.to_string())?;
+    let free = target.ancestors().find(|dir| dir.exists()).and_then(crate::commands::system::available_space_at);
+    if free.is_some_and(|free| free < bytes) {
+        return Err(format!("Not enough free space at {} ({} bytes needed)", target.display(), bytes));
+    }
+
+    log_to_file(&format!("[Leaxer] Moving the Leaxer folder from {:?} to {:?}", current, target));
+    let task = crate::commands::tasks::register_task(&app, "data-dir", "Moving the Leaxer folder");
+    // Nothing may write to the folder while it is copied
+    let backend = app.state::<crate::process::BackendHandle>().inner().clone();
+    backend.stop(crate::BACKEND_STOP_TIMEOUT).await;
+    let _ = app.state::<crate::config::ConfigStore>().flush();
+    crate::logging::flush();
+
+    let task = std::sync::Arc::new(task);
+    let progress = task.clone();
+    let (from, to) = (current.clone(), target.clone());
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let mut done = 0;
+        copy_checked(&from, &to, &mut |copied| {
+            done += copied;
+            progress.progress(done, Some(bytes));
+            !progress.is_cancelled()
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|result| result)
+    .and_then(|_| write_location(&home, &target).map_err(|e| format!("Failed to save the new location: {}", e)));
+    if let Some(task) = std::sync::Arc::into_inner(task) {
+        task.finish(&result);
+    }
+    if let Err(error) = result {
+        log_to_file(&format!("[Leaxer] Moving the Leaxer folder failed: {}", error));
+        backend.start();
+        return Err(error);
+    }
+
+    if let Err(e) = remove_original(&current, &home) {
+        log_to_file(&format!("[Leaxer] Failed to remove the old Leaxer folder: {}", e));
+    }
+    log_to_file(&format!("[Leaxer] Leaxer folder moved to {:?}, restarting", target));
+    // Every path the shell holds points at the old location
+    app.restart();
+}
+
+#[cfg(not(feature = "dialog"))]
+#[tauri::command]
+pub async fn move_data_dir() -> Result<Option<String>, String> {
+    Err(crate::features::unavailable("Moving the Leaxer folder", &["dialog"]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_checks_and_relinks() {
+        let root = tempfile::tempdir().unwrap();
+        let home = root.path().join("home");
+        std::fs::create_dir_all(home.join("models")).unwrap();
+        std::fs::write(home.join("config.json"), "{}").unwrap();
+        std::fs::write(home.join("models").join("a.gguf"), "weights").unwrap();
+
+        let target = root.path().join("big-drive").join("Leaxer");
+        assert!(check_target(&home, &home.join("inside")).is_err());
+        check_target(&home, &target).unwrap();
+
+        let mut total = 0;
+        copy_checked(&home, &target, &mut |copied| {
+            total += copied;
+            true
+        })
+        .unwrap();
+        assert_eq!(total, 9);
+        assert_eq!(dir_stats(&target).unwrap(), (2, 9));
+
+        write_location(&home, &target).unwrap();
+        remove_original(&home, &home).unwrap();
+        assert_eq!(crate::paths::configured_data_dir(&home), Some(target));
+        assert_eq!(std::fs::read_dir(&home).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn cancelling_leaves_no_partial_copy() {
+        let root = tempfile::tempdir().unwrap();
+        let home = root.path().join("home");
+        std::fs::create_dir_all(&home).unwrap();
+        std::fs::write(home.join("config.json"), "{}").unwrap();
+
+        let target = root.path().join("Leaxer");
+        assert_eq!(copy_checked(&home, &target, &mut |_| false), Err("Cancelled".to_string()));
+        assert!(!target.exists() && !target.with_extension("moving").exists());
+    }
+}
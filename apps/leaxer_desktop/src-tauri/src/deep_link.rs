@@ -0,0 +1,123 @@
+//! `leaxer://open/<kind>/<id>` links that open a workflow or chat.
+//!
+//! They arrive as a command-line argument (first launch, or forwarded by the
+//! single-instance plugin) or from a Spotlight activation on macOS. Links are queued
+//! until the UI takes them, so one that arrives before the page has loaded isn't lost.
+
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+
+use crate::logging::log_to_file;
+
+const OPEN_PREFIX: &str = "leaxer://open/";
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemKind {
+    Workflow,
+    Chat,
+}
+
+impl ItemKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ItemKind::Workflow => "workflow",
+            ItemKind::Chat => "chat",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct DeepLink {
+    pub kind: ItemKind,
+    pub id: String,
+}
+
+/// Links waiting for the UI
+#[derive(Default)]
+pub struct PendingDeepLinks(Mutex<Vec<DeepLink>>);
+
+impl PendingDeepLinks {
+    pub fn push(&self, link: DeepLink) {
+        self.0.lock().unwrap().push(link);
+    }
+}
+
+/// The link that opens an item
+pub fn link_for(kind: ItemKind, id: &str) -> String {
+    format!(
+        "{}{}/{}",
+        OPEN_PREFIX,
+        kind.as_str(),
+        percent_encoding::utf8_percent_encode(id, percent_encoding::NON_ALPHANUMERIC)
+    )
+}
+
+pub fn parse(url: &str) -> Option<DeepLink> {
+    let rest = url.strip_prefix(OPEN_PREFIX)?.trim_end_matches('/');
+    let (kind, id) = rest.split_once('/')?;
+    let kind = match kind {
+        "workflow" => ItemKind::Workflow,
+        "chat" => ItemKind::Chat,
+        _ => return None,
+    };
+    let id = percent_encoding::percent_decode_str(id).decode_utf8().ok()?;
+    // Ids become file names on the backend; never let a link walk out of its folder
+    if id.is_empty() || id.contains(['/', '\\']) || id == ".." {
+        return None;
+    }
+    Some(DeepLink {
+        kind,
+        id: id.into_owned(),
+    })
+}
+
+/// Queue a link and bring the main window forward to show it
+pub fn open(app: &tauri::AppHandle, url: &str) {
+    let Some(link) = parse(url) else {
+        return;
+    };
+    log_to_file(&format!("[Leaxer] Opening {:?} from deep link", link));
+    app.state::<PendingDeepLinks>().push(link);
+    crate::commands::window::show_main_window(app);
+    let _ = app.emit_to("main", "deep-link", ());
+}
+
+/// Open any links among command-line arguments
+pub fn open_from_args(app: &tauri::AppHandle, args: &[String]) {
+    for arg in args.iter().filter(|arg| arg.starts_with(OPEN_PREFIX)) {
+        open(app, arg);
+    }
+}
+
+/// Hand queued links to the UI (once)
+#[tauri::command]
+pub fn take_deep_links(pending: tauri::State<'_, PendingDeepLinks>) -> Vec<DeepLink> {
+    std::mem::take(&mut *pending.0.lock().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_round_trip() {
+        let url = link_for(ItemKind::Workflow, "My flow #2");
+        assert_eq!(url, "leaxer://open/workflow/My%20flow%20%232");
+        assert_eq!(
+            parse(&url),
+            Some(DeepLink {
+                kind: ItemKind::Workflow,
+                id: "My flow #2".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_or_escaping_links() {
+        assert_eq!(parse("leaxer://open/model/x"), None);
+        assert_eq!(parse("leaxer://open/chat/..%2F..%2Fconfig"), None);
+        assert_eq!(parse("leaxer://open/chat/"), None);
+        assert_eq!(parse("https://open/chat/x"), None);
+    }
+}
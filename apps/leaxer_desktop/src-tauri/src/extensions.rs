@@ -0,0 +1,567 @@
+//! Sidecar extensions declared in config.json.
+//!
+//! Each entry of the `extensions` array names an executable that serves HTTP on a local
+//! port, e.g.
+//! `{"name": "ocr", "command": "ocr-server", "args": ["--fast"], "port": 4101, "health": "/health", "env": {"MODE": "cpu"}}`.
+//! The shell starts every extension, restarts it with backoff when it exits or stops
+//! answering its health endpoint, and tells the backend where to reach the healthy ones:
+//! `LEAXER_EXTENSIONS` (a JSON object of name to base URL) when it starts, and
+//! `set_extensions` over the control channel whenever that set changes.
+//!
+//! Relative commands are resolved against `<Leaxer dir>/extensions/<name>`, which is
+//! also the working directory when it exists.
+//!
+//! An entry only runs once the user allowed it in a native dialog. What was allowed (name,
+//! command, arguments and environment) is remembered with the encrypted secrets, so editing
+//! any of those asks again.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tauri::Manager;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::config::ConfigStore;
+use crate::logging::log_to_file;
+use crate::process_handle::ProcessHandle;
+use crate::secrets::SecretStore;
+
+const EXTENSIONS_KEY: &str = "extensions";
+
+/// Secret holding the fingerprints of the entries the user allowed to run, as JSON
+const APPROVED_SECRET: &str = "leaxer.approved_extensions";
+
+/// Environment variable carrying the extension URLs to the backend
+pub const BACKEND_ENV: &str = "LEAXER_EXTENSIONS";
+
+const PUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+const HEALTH_INTERVAL: Duration = Duration::from_secs(10);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How long a freshly started extension has to pass its first health check
+const STARTUP_GRACE: Duration = Duration::from_secs(30);
+
+/// Consecutive failed health checks after which a running extension is restarted
+const MAX_HEALTH_FAILURES: u32 = 3;
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize)]
+pub struct ExtensionSpec {
+    pub name: String,
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub port: u16,
+    /// Path answering 2xx while the extension is healthy; without one, running is enough
+    #[serde(default)]
+    pub health: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+}
+
+impl ExtensionSpec {
+    pub fn url(&self) -> String {
+        format!("http://127.0.0.1:{}", self.port)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExtensionState {
+    Starting,
+    Healthy,
+    Unhealthy,
+    Exited,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ExtensionStatus {
+    name: String,
+    url: String,
+    state: ExtensionState,
+    pid: Option<u32>,
+    restarts: u32,
+    last_error: Option<String>,
+}
+
+struct Running {
+    spec: ExtensionSpec,
+    status: Arc<Mutex<ExtensionStatus>>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+/// Supervised extensions, keyed by name
+#[derive(Default)]
+pub struct Extensions {
+    running: Mutex<Vec<Running>>,
+    /// Bumped by `stop_all`, so a `start` still waiting on the user doesn't launch afterwards
+    generation: AtomicU64,
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// Valid extension entries from config; malformed or duplicate ones are logged and skipped
+pub fn specs(config: &Value) -> Vec<ExtensionSpec> {
+    let Some(entries) = config.get(EXTENSIONS_KEY).and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    let mut specs: Vec<ExtensionSpec> = Vec::new();
+    for entry in entries {
+        let spec = match serde_json::from_value::<ExtensionSpec>(entry.clone()) {
+            Ok(spec) => spec,
+            Err(e) => {
+                log_to_file(&format!("[Leaxer] Ignoring malformed extension entry: {}", e));
+                continue;
+            }
+        };
+        let problem = if !valid_name(&spec.name) {
+            Some("names may only use a-z, 0-9, - and _")
+        } else if spec.command.trim().is_empty() {
+            Some("command is empty")
+        } else if spec.port == 0 || spec.port == crate::net::backend_port() {
+            Some("port is not usable")
+        } else if spec
+            .health
+            .as_deref()
+            .is_some_and(|path| !path.starts_with('/') || path.contains(char::is_whitespace))
+        {
+            Some("health must be a path such as /health")
+        } else if specs.iter().any(|s| s.name == spec.name) {
+            Some("name is already taken")
+        } else if specs.iter().any(|s| s.port == spec.port) {
+            Some("port is already taken")
+        } else {
+            None
+        };
+        match problem {
+            Some(problem) => log_to_file(&format!("[Leaxer] Ignoring extension {:?}: {}", spec.name, problem)),
+            None => specs.push(spec),
+        }
+    }
+    specs
+}
+
+/// Name to base URL of each extension
+fn backend_urls(specs: &[ExtensionSpec]) -> Value {
+    let urls: serde_json::Map<String, Value> = specs
+        .iter()
+        .map(|spec| (spec.name.clone(), Value::String(spec.url())))
+        .collect();
+    Value::Object(urls)
+}
+
+/// Value of `LEAXER_EXTENSIONS` for the backend
+pub fn backend_env(specs: &[ExtensionSpec]) -> String {
+    backend_urls(specs).to_string()
+}
+
+impl Extensions {
+    /// Extensions that are running and answer their health check; only these are
+    /// announced to the backend
+    pub fn available(&self) -> Vec<ExtensionSpec> {
+        self.running
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|running| running.status.lock().unwrap().state == ExtensionState::Healthy)
+            .map(|running| running.spec.clone())
+            .collect()
+    }
+}
+
+/// Tell the running backend which extensions it can reach now
+fn push_urls(app: &tauri::AppHandle) {
+    let urls = backend_urls(&app.state::<Extensions>().available());
+    tauri::async_runtime::spawn(async move {
+        let args = serde_json::json!({ "urls": urls });
+        if let Err(e) = crate::control::request_with("set_extensions", args, PUSH_TIMEOUT).await {
+            // It gets them from the environment when it next starts
+            log_to_file(&format!("[Leaxer] Failed to update the backend's extensions: {}", e));
+        }
+    });
+}
+
+/// Record an extension's state, telling the backend when it became reachable or stopped being so
+fn set_state(app: &tauri::AppHandle, status: &Mutex<ExtensionStatus>, state: ExtensionState) {
+    let was = std::mem::replace(&mut status.lock().unwrap().state, state);
+    if (was == ExtensionState::Healthy) != (state == ExtensionState::Healthy) {
+        push_urls(app);
+    }
+}
+
+/// What running an entry amounts to; the port and health check don't change that
+fn fingerprint(spec: &ExtensionSpec) -> String {
+    let env: BTreeMap<_, _> = spec.env.iter().collect();
+    let launch = serde_json::json!([spec.name, spec.command, spec.args, env]);
+    Sha256::digest(launch.to_string().as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn approved(secrets: &SecretStore) -> Result<Vec<String>, String> {
+    match secrets.get(APPROVED_SECRET)? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("Unreadable approved extensions: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn approve(secrets: &SecretStore, fingerprint: String) -> Result<(), String> {
+    let mut fingerprints = approved(secrets)?;
+    fingerprints.push(fingerprint);
+    let json = serde_json::to_string(&fingerprints).map_err(|e| e.to_string())?;
+    secrets.set(APPROVED_SECRET, Some(&json))
+}
+
+#[cfg(feature = "dialog")]
+async fn confirm(app: &tauri::AppHandle, spec: &ExtensionSpec) -> bool {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+
+    const RUN: &str = "Run";
+    let command = std::iter::once(&spec.command).chain(&spec.args).cloned().collect::<Vec<_>>().join(" ");
+    let message = format!(
+        "config.json asks Leaxer to run this program as the extension \"{}\":\n\n{}\n\n\
+         It runs with your permissions. Only allow it if you added it yourself.",
+        spec.name, command
+    );
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(message)
+        .title("Run extension?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(RUN.to_string(), "Don't run".to_string()))
+        .show_with_result(move |result| {
+            let _ = tx.send(result);
+        });
+    match rx.await {
+        Ok(MessageDialogResult::Custom(label)) => label == RUN,
+        Ok(MessageDialogResult::Ok) => true,
+        _ => false,
+    }
+}
+
+#[cfg(not(feature = "dialog"))]
+async fn confirm(_app: &tauri::AppHandle, spec: &ExtensionSpec) -> bool {
+    log_to_file(&format!("[Leaxer] Can't ask whether to run extension {} without the dialog feature", spec.name));
+    false
+}
+
+/// Whether the user allowed `spec` to run, asking if it is new or changed
+async fn allowed_to_run(app: &tauri::AppHandle, spec: &ExtensionSpec) -> bool {
+    let secrets = app.state::<SecretStore>();
+    let fingerprint = fingerprint(spec);
+    match approved(&secrets) {
+        Ok(fingerprints) if fingerprints.contains(&fingerprint) => return true,
+        Ok(_) => {}
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Not starting extension {}: {}", spec.name, e));
+            return false;
+        }
+    }
+    if !confirm(app, spec).await {
+        log_to_file(&format!("[Leaxer] Extension {} not allowed to run", spec.name));
+        crate::audit::record::<()>("extension_approve", Some(&spec.name), &Err("Declined by the user".to_string()));
+        return false;
+    }
+    let result = approve(&secrets, fingerprint);
+    crate::audit::record("extension_approve", Some(&spec.name), &result);
+    if let Err(e) = &result {
+        log_to_file(&format!("[Leaxer] Failed to remember extension {}: {}", spec.name, e));
+    }
+    result.is_ok()
+}
+
+fn extension_dir(name: &str) -> Option<PathBuf> {
+    crate::paths::get_leaxer_user_dir().map(|dir| dir.join("extensions").join(name))
+}
+
+fn extension_command(spec: &ExtensionSpec, dir: Option<&Path>) -> Command {
+    let program = Path::new(&spec.command);
+    let program = match dir {
+        // A bare name is looked up on PATH; anything with a separator is relative to the dir
+        Some(dir) if program.is_relative() && program.components().count() > 1 => dir.join(program),
+        _ => program.to_path_buf(),
+    };
+
+    let mut cmd = Command::new(program);
+    cmd.args(&spec.args);
+    if let Some(dir) = dir.filter(|dir| dir.is_dir()) {
+        cmd.current_dir(dir);
+    }
+    cmd.envs(&spec.env);
+    cmd.env("LEAXER_EXTENSION_PORT", spec.port.to_string());
+    if let Ok(url) = crate::net::backend_http_url() {
+        cmd.env("LEAXER_BACKEND_URL", url);
+    }
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(crate::process::CREATE_NO_WINDOW);
+
+    cmd
+}
+
+/// GET the health path and report whether it answered 2xx in time
+async fn probe(port: u16, path: &str) -> bool {
+    crate::net::probe_http(port, path, HEALTH_TIMEOUT).await
+}
+
+/// Why a run of the extension ended
+async fn run_once(app: &tauri::AppHandle, spec: &ExtensionSpec, status: &Mutex<ExtensionStatus>) -> String {
+    let mut process = match ProcessHandle::spawn(extension_command(spec, extension_dir(&spec.name).as_deref())) {
+        Ok(process) => process,
+        Err(e) => return format!("failed to start: {}", e),
+    };
+    status.lock().unwrap().pid = process.id();
+    set_state(app, status, ExtensionState::Starting);
+    log_to_file(&format!("[Leaxer] Extension {} started with PID: {:?}", spec.name, process.id()));
+
+    let started = Instant::now();
+    let mut healthy_once = false;
+    let mut failures = 0;
+    let mut interval = tokio::time::interval(HEALTH_INTERVAL);
+    loop {
+        tokio::select! {
+            exit = process.wait() => {
+                return match exit {
+                    Ok(code) => format!("exited ({})", code),
+                    Err(e) => format!("lost track of process: {}", e),
+                };
+            }
+            _ = interval.tick() => {
+                let ok = match &spec.health {
+                    Some(path) => probe(spec.port, path).await,
+                    None => true,
+                };
+                let state = if ok {
+                    healthy_once = true;
+                    failures = 0;
+                    ExtensionState::Healthy
+                } else if healthy_once {
+                    failures += 1;
+                    ExtensionState::Unhealthy
+                } else {
+                    ExtensionState::Starting
+                };
+                set_state(app, status, state);
+
+                if failures >= MAX_HEALTH_FAILURES {
+                    process.shutdown().await;
+                    return "stopped answering its health check".to_string();
+                }
+                if !healthy_once && started.elapsed() > STARTUP_GRACE {
+                    process.shutdown().await;
+                    return "never became healthy".to_string();
+                }
+            }
+        }
+    }
+}
+
+/// Keep one extension running until the task is aborted (which kills the process tree)
+async fn supervise(app: tauri::AppHandle, spec: ExtensionSpec, status: Arc<Mutex<ExtensionStatus>>) {
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        let started = Instant::now();
+        let reason = run_once(&app, &spec, &status).await;
+        log_to_file(&format!("[Leaxer] Extension {} {}", spec.name, reason));
+        {
+            let mut status = status.lock().unwrap();
+            status.pid = None;
+            status.last_error = Some(reason);
+        }
+        set_state(&app, &status, ExtensionState::Exited);
+
+        // A run that lasted a while was healthy; start over with a short delay
+        if started.elapsed() > MAX_BACKOFF {
+            backoff = MIN_BACKOFF;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+        status.lock().unwrap().restarts += 1;
+    }
+}
+
+fn launch(app: &tauri::AppHandle, spec: ExtensionSpec, restarts: u32) -> Running {
+    let status = Arc::new(Mutex::new(ExtensionStatus {
+        name: spec.name.clone(),
+        url: spec.url(),
+        state: ExtensionState::Starting,
+        pid: None,
+        restarts,
+        last_error: None,
+    }));
+    let task = tauri::async_runtime::spawn(supervise(app.clone(), spec.clone(), status.clone()));
+    Running { spec, status, task }
+}
+
+/// Start every extension declared in config that the user allows to run
+pub fn start(app: &tauri::AppHandle) {
+    let specs = specs(&app.state::<ConfigStore>().load());
+    if specs.is_empty() {
+        return;
+    }
+    let generation = app.state::<Extensions>().generation.load(Ordering::SeqCst);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut allowed = Vec::new();
+        for spec in specs {
+            if allowed_to_run(&app, &spec).await {
+                allowed.push(spec);
+            }
+        }
+        let extensions = app.state::<Extensions>();
+        let mut running = extensions.running.lock().unwrap();
+        if allowed.is_empty() || extensions.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        log_to_file(&format!("[Leaxer] Starting {} extension(s)", allowed.len()));
+        running.extend(allowed.into_iter().map(|spec| launch(&app, spec, 0)));
+    });
+}
+
+/// Stop every extension; aborting a supervisor drops its process handle, killing the tree
+pub fn stop_all(app: &tauri::AppHandle) {
+    let extensions = app.state::<Extensions>();
+    let mut running = extensions.running.lock().unwrap();
+    extensions.generation.fetch_add(1, Ordering::SeqCst);
+    if running.is_empty() {
+        return;
+    }
+    for running in running.drain(..) {
+        running.task.abort();
+    }
+    drop(running);
+    push_urls(app);
+}
+
+#[tauri::command]
+pub fn list_extensions(extensions: tauri::State<'_, Extensions>) -> Vec<ExtensionStatus> {
+    extensions
+        .running
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|running| running.status.lock().unwrap().clone())
+        .collect()
+}
+
+/// Kill and start an extension again right away, without waiting out its backoff
+#[tauri::command]
+pub fn restart_extension(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    let extensions = app.state::<Extensions>();
+    let mut running = extensions.running.lock().unwrap();
+    let index = running
+        .iter()
+        .position(|r| r.spec.name == name)
+        .ok_or_else(|| format!("No extension named {}", name))?;
+    let old = running.remove(index);
+    old.task.abort();
+    let restarts = old.status.lock().unwrap().restarts + 1;
+    log_to_file(&format!("[Leaxer] Restarting extension {}", name));
+    let was_healthy = old.status.lock().unwrap().state == ExtensionState::Healthy;
+    running.insert(index, launch(&app, old.spec, restarts));
+    drop(running);
+    if was_healthy {
+        push_urls(&app);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn keeps_only_valid_unique_entries() {
+        let config = json!({ "extensions": [
+            { "name": "ocr", "command": "ocr-server", "port": 4101, "health": "/health" },
+            { "name": "ocr", "command": "other", "port": 4102 },
+            { "name": "tts", "command": "tts", "port": 4101 },
+            { "name": "Bad Name", "command": "x", "port": 4103 },
+            { "name": "backend", "command": "x", "port": 4000 },
+            { "name": "noport", "command": "x" },
+            { "name": "badhealth", "command": "x", "port": 4105, "health": "health\r\nX: y" },
+            { "name": "asr", "command": "./bin/asr", "args": ["--cpu"], "port": 4104, "env": { "A": "1" } },
+        ]});
+        let specs = specs(&config);
+        assert_eq!(specs.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["ocr", "asr"]);
+        assert_eq!(specs[1].args, vec!["--cpu"]);
+        assert!(super::specs(&json!({})).is_empty());
+    }
+
+    #[test]
+    fn backend_learns_extension_urls() {
+        let specs = specs(&json!({ "extensions": [{ "name": "ocr", "command": "ocr", "port": 4101 }] }));
+        let env: Value = serde_json::from_str(&backend_env(&specs)).unwrap();
+        assert_eq!(env, json!({ "ocr": "http://127.0.0.1:4101" }));
+    }
+
+    #[test]
+    fn relative_commands_resolve_against_the_extension_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let spec = ExtensionSpec {
+            name: "asr".to_string(),
+            command: "bin/asr".to_string(),
+            args: vec![],
+            port: 4104,
+            health: None,
+            env: HashMap::from([("MODE".to_string(), "cpu".to_string())]),
+        };
+        let cmd = extension_command(&spec, Some(dir.path()));
+        assert_eq!(Path::new(cmd.get_program()), dir.path().join("bin/asr"));
+        assert_eq!(cmd.get_current_dir(), Some(dir.path()));
+        assert!(cmd.get_envs().any(|(key, value)| key == "LEAXER_EXTENSION_PORT" && value == Some("4104".as_ref())));
+
+        let on_path = ExtensionSpec { command: "asr".to_string(), ..spec };
+        assert_eq!(extension_command(&on_path, Some(dir.path())).get_program(), "asr");
+    }
+
+    #[test]
+    fn fingerprints_cover_what_runs() {
+        let spec = specs(&json!({ "extensions": [
+            { "name": "ocr", "command": "ocr", "args": ["--fast"], "port": 4101, "env": { "A": "1", "B": "2" } }
+        ]}))
+        .remove(0);
+        let moved = ExtensionSpec { port: 4102, health: Some("/health".to_string()), ..spec.clone() };
+        assert_eq!(fingerprint(&spec), fingerprint(&moved));
+
+        let changed_command = ExtensionSpec { command: "sh".to_string(), ..spec.clone() };
+        let mut changed_env = spec.clone();
+        changed_env.env.insert("LD_PRELOAD".to_string(), "/tmp/x.so".to_string());
+        assert_ne!(fingerprint(&spec), fingerprint(&changed_command));
+        assert_ne!(fingerprint(&spec), fingerprint(&changed_env));
+    }
+
+    #[test]
+    fn probe_accepts_only_success_statuses() {
+        tauri::async_runtime::block_on(async {
+            let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            tauri::async_runtime::spawn(async move {
+                for response in ["HTTP/1.1 200 OK\r\n\r\n", "HTTP/1.1 503 Service Unavailable\r\n\r\n"] {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let _ = stream.read(&mut [0u8; 256]).await;
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                }
+            });
+            assert!(probe(port, "/health").await);
+            assert!(!probe(port, "/health").await);
+        });
+    }
+}
@@ -0,0 +1,108 @@
+//! Optional plugins selected with Cargo features, and what this build includes.
+//!
+//! Capabilities for optional plugins live in `plugin-capabilities/` rather than
+//! `capabilities/`, because tauri-build rejects permissions of plugins that are not
+//! compiled in; they are added at runtime for the plugins that are.
+
+use tauri::Manager;
+
+use crate::logging::log_to_file;
+
+/// Every optional feature and whether this binary was built with it
+const OPTIONAL_FEATURES: &[(&str, bool)] = &[
+    ("dialog", cfg!(feature = "dialog")),
+    ("http", cfg!(feature = "http")),
+    ("fs", cfg!(feature = "fs")),
+    ("shell", cfg!(feature = "shell")),
+    ("global-shortcut", cfg!(feature = "global-shortcut")),
+    ("notification", cfg!(feature = "notification")),
+    ("mcp", cfg!(feature = "mcp")),
+    ("devtools", cfg!(feature = "devtools")),
+];
+
+#[derive(serde::Serialize)]
+pub struct BuildFeatures {
+    enabled: Vec<&'static str>,
+    disabled: Vec<&'static str>,
+}
+
+/// Error message for commands whose subsystem was compiled out
+pub fn unavailable(subsystem: &str, features: &[&str]) -> String {
+    format!(
+        "{} are not available in this build (requires feature {})",
+        subsystem,
+        features.join(" + ")
+    )
+}
+
+/// Register the optional plugins compiled into this build
+pub fn register_plugins(builder: tauri::Builder<tauri::Wry>) -> tauri::Builder<tauri::Wry> {
+    #[cfg(feature = "shell")]
+    let builder = builder.plugin(tauri_plugin_shell::init());
+    #[cfg(feature = "dialog")]
+    let builder = builder.plugin(tauri_plugin_dialog::init());
+    #[cfg(feature = "fs")]
+    let builder = builder.plugin(tauri_plugin_fs::init());
+    #[cfg(feature = "http")]
+    let builder = builder.plugin(tauri_plugin_http::init());
+    #[cfg(feature = "global-shortcut")]
+    let builder = builder.plugin(
+        tauri_plugin_global_shortcut::Builder::new()
+            .with_handler(crate::commands::capture::on_shortcut)
+            .build(),
+    );
+    #[cfg(feature = "notification")]
+    let builder = builder.plugin(tauri_plugin_notification::init());
+    builder
+}
+
+/// Grant the webview access to the optional plugins compiled into this build
+pub fn add_plugin_capabilities(app: &tauri::App) {
+    let capabilities: &[&str] = &[
+        #[cfg(feature = "shell")]
+        include_str!("../plugin-capabilities/shell.json"),
+        #[cfg(feature = "dialog")]
+        include_str!("../plugin-capabilities/dialog.json"),
+        #[cfg(feature = "fs")]
+        include_str!("../plugin-capabilities/fs.json"),
+        #[cfg(feature = "http")]
+        include_str!("../plugin-capabilities/http.json"),
+    ];
+    for capability in capabilities {
+        if let Err(e) = app.add_capability(*capability) {
+            log_to_file(&format!("[Leaxer] Failed to add plugin capability: {}", e));
+        }
+    }
+}
+
+/// Report which optional subsystems this build includes, so the UI can hide what's missing
+#[tauri::command]
+pub fn get_build_features() -> BuildFeatures {
+    BuildFeatures {
+        enabled: OPTIONAL_FEATURES.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect(),
+        disabled: OPTIONAL_FEATURES.iter().filter(|(_, on)| !*on).map(|(name, _)| *name).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_every_optional_feature_once() {
+        let features = get_build_features();
+        assert_eq!(features.enabled.len() + features.disabled.len(), OPTIONAL_FEATURES.len());
+        #[cfg(feature = "dialog")]
+        assert!(features.enabled.contains(&"dialog"));
+        #[cfg(not(feature = "dialog"))]
+        assert!(features.disabled.contains(&"dialog"));
+    }
+
+    #[test]
+    fn unavailable_names_the_missing_features() {
+        assert_eq!(
+            unavailable("Downloads", &["http", "dialog"]),
+            "Downloads are not available in this build (requires feature http + dialog)"
+        );
+    }
+}
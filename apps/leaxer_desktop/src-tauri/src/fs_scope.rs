@@ -0,0 +1,147 @@
+//! What the webview may reach through the fs plugin.
+//!
+//! The plugin's capability (`plugin-capabilities/fs.json`) allows its commands without any
+//! paths; the scope is managed here instead. At startup it holds the Leaxer user dir and
+//! the folders the user approved before (`fs_allowed_folders` in config.json), and
+//! `grant_fs_access` adds more, but only after the user agrees in a native dialog.
+#![cfg_attr(not(feature = "fs"), allow(dead_code, unused_imports))]
+
+use std::path::{Path, PathBuf};
+
+use crate::logging::log_to_file;
+
+/// Folders the user approved, kept in config.json
+const FOLDERS_KEY: &str = "fs_allowed_folders";
+
+fn approved_folders(config: &serde_json::Value) -> Vec<PathBuf> {
+    config
+        .get(FOLDERS_KEY)
+        .and_then(serde_json::Value::as_array)
+        .map(|folders| folders.iter().filter_map(|folder| folder.as_str()).map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+/// A path that can be granted: existing, absolute after canonicalizing, and not a
+/// filesystem root
+fn grantable(path: &Path) -> Result<PathBuf, String> {
+    let path = path.canonicalize().map_err(|e| format!("Can't open {}: {}", path.display(), e))?;
+    if path.parent().is_none() {
+        return Err(format!("Access to all of {} can't be granted", path.display()));
+    }
+    Ok(path)
+}
+
+#[cfg(feature = "fs")]
+fn allow(app: &tauri::AppHandle, path: &Path) -> Result<(), String> {
+    use tauri_plugin_fs::FsExt;
+
+    let scope = app.fs_scope();
+    let allowed = if path.is_dir() {
+        scope.allow_directory(path, true)
+    } else {
+        scope.allow_file(path)
+    };
+    allowed.map_err(|e| format!("Failed to allow {}: {}", path.display(), e))?;
+    crate::paths::allow_path(app, path.to_path_buf());
+    Ok(())
+}
+
+/// Set up the scope: the Leaxer user dir and the folders approved earlier
+#[cfg(feature = "fs")]
+pub fn init(app: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    let config = app.state::<crate::config::ConfigStore>().load();
+    let roots = crate::paths::get_leaxer_user_dir().into_iter().chain(approved_folders(&config));
+    for root in roots {
+        // Approved folders that are gone or moved are skipped, not dropped from the list
+        if let Err(e) = grantable(&root).and_then(|root| allow(app, &root)) {
+            log_to_file(&format!("[Leaxer] Not adding {:?} to the file access scope: {}", root, e));
+        }
+    }
+}
+
+#[cfg(all(feature = "fs", feature = "dialog"))]
+async fn confirm(app: &tauri::AppHandle, path: &Path) -> bool {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+
+    const ALLOW: &str = "Allow";
+    let message = format!(
+        "Leaxer is asking to read and write {}{}.\n\nOnly allow this for a location you chose.",
+        path.display(),
+        if path.is_dir() { " and everything in it" } else { "" }
+    );
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(message)
+        .title("Allow file access?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(ALLOW.to_string(), "Cancel".to_string()))
+        .show_with_result(move |result| {
+            let _ = tx.send(result);
+        });
+    match rx.await {
+        Ok(MessageDialogResult::Custom(label)) => label == ALLOW,
+        Ok(MessageDialogResult::Ok) => true,
+        _ => false,
+    }
+}
+
+/// Let the webview reach `path` (and everything in it, for a folder) once the user agrees.
+/// Returns whether access is granted; paths already in scope are granted without asking.
+#[cfg(all(feature = "fs", feature = "dialog"))]
+#[tauri::command]
+pub async fn grant_fs_access(app: tauri::AppHandle, path: String) -> Result<bool, String> {
+    use tauri::Manager;
+
+    let path = grantable(Path::new(&path))?;
+    if crate::paths::is_path_allowed(&app, &path) {
+        return Ok(true);
+    }
+    let target = path.to_string_lossy().to_string();
+    if !confirm(&app, &path).await {
+        log_to_file(&format!("[Leaxer] File access to {:?} declined", path));
+        crate::audit::record::<()>("fs_grant", Some(&target), &Err("Declined by the user".to_string()));
+        return Ok(false);
+    }
+
+    let allowed = allow(&app, &path);
+    crate::audit::record("fs_grant", Some(&target), &allowed);
+    allowed?;
+    let config = app.state::<crate::config::ConfigStore>();
+    let mut folders = approved_folders(&config.load_file());
+    folders.push(path.clone());
+    let folders: Vec<_> = folders.iter().map(|folder| folder.to_string_lossy().to_string()).collect();
+    config.set(FOLDERS_KEY, serde_json::json!(folders));
+    log_to_file(&format!("[Leaxer] File access granted to {:?}", path));
+    Ok(true)
+}
+
+#[cfg(not(all(feature = "fs", feature = "dialog")))]
+#[tauri::command]
+pub async fn grant_fs_access(_path: String) -> Result<bool, String> {
+    Err(crate::features::unavailable("File access grants", &["fs", "dialog"]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_approved_folders() {
+        let config = serde_json::json!({ FOLDERS_KEY: ["/data/renders", 3, "/mnt/models"] });
+        assert_eq!(
+            approved_folders(&config),
+            vec![PathBuf::from("/data/renders"), PathBuf::from("/mnt/models")]
+        );
+        assert!(approved_folders(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn roots_and_missing_paths_are_not_grantable() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(grantable(dir.path()).is_ok());
+        assert!(grantable(&dir.path().join("missing")).is_err());
+        assert!(grantable(Path::new("/")).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Temporary second backends for side-by-side comparison.
+//!
+//! Each one gets a free port, a throwaway data dir under the system temp dir (with the
+//! main models folder linked in, so the same models can be compared) and its own window.
+//! A different launcher can be given to try a beta backend against the installed one.
+//! Closing the window stops the backend and deletes its data dir.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use tauri::Manager;
+
+use crate::logging::log_to_file;
+use crate::process_handle::ProcessHandle;
+
+/// Labels of comparison windows start with this
+pub const WINDOW_PREFIX: &str = "compare-";
+
+struct TempInstance {
+    process: ProcessHandle,
+    dir: PathBuf,
+}
+
+/// Running comparison backends, keyed by window label
+#[derive(Default)]
+pub struct TempInstances(Mutex<HashMap<String, TempInstance>>);
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct TempInstanceInfo {
+    window: String,
+    port: u16,
+    url: String,
+    data_dir: PathBuf,
+}
+
+/// Only launchers laid out like the bundled one may be started
+fn check_launcher(path: &Path) -> Result<(), String> {
+    let expected = crate::process::backend_relative_path();
+    if !path.is_file() || path.file_name() != expected.file_name() {
+        return Err(format!("{} is not a Leaxer backend launcher", path.display()));
+    }
+    Ok(())
+}
+
+/// Share the main models folder with the sandbox instead of copying gigabytes
+fn link_models(sandbox: &Path) {
+    let Some(models) = crate::paths::get_leaxer_user_dir().map(|dir| dir.join("models")) else {
+        return;
+    };
+    if !models.is_dir() {
+        return;
+    }
+    let link = sandbox.join("models");
+    #[cfg(unix)]
+    let linked = std::os::unix::fs::symlink(&models, &link);
+    #[cfg(target_os = "windows")]
+    let linked = std::os::windows::fs::symlink_dir(&models, &link);
+    if let Err(e) = linked {
+        log_to_file(&format!("[Leaxer] Comparison instance starts without models: {}", e));
+    }
+}
+
+fn backend_command(
+    launcher: &Path,
+    port: u16,
+    dir: &Path,
+    keys: &crate::secrets::BackendKeys,
+) -> std::process::Command {
+    let mut cmd = crate::process::backend_command(launcher, false, keys);
+    cmd.env("PORT", port.to_string());
+    cmd.env("CORS_ORIGINS", crate::net::cors_origins(port, &[]));
+    cmd.env("LEAXER_USER_DIR", dir);
+    cmd
+}
+
+fn open_window(app: &tauri::AppHandle, label: &str, port: u16) -> tauri::Result<()> {
+    // The window's UI talks to its own backend without touching the saved settings
+    let script = format!("window.__LEAXER_BACKEND_URL__ = 'ws://localhost:{}/socket';", port);
+    tauri::WebviewWindowBuilder::new(app, label, tauri::WebviewUrl::App("index.html".into()))
+        .title(format!("Leaxer (comparison, port {})", port))
+        .inner_size(1280.0, 800.0)
+        .initialization_script(&script)
+        .devtools(crate::commands::window::devtools_enabled(app))
+        .build()?;
+    Ok(())
+}
+
+/// Stop the backend behind a comparison window and delete its data
+pub fn stop(app: &tauri::AppHandle, label: &str) {
+    let Some(instance) = app.state::<TempInstances>().0.lock().unwrap().remove(label) else {
+        return;
+    };
+    log_to_file(&format!("[Leaxer] Stopping comparison instance {}", label));
+    tauri::async_runtime::spawn(async move {
+        instance.process.shutdown().await;
+        if let Err(e) = std::fs::remove_dir_all(&instance.dir) {
+            log_to_file(&format!("[Leaxer] Failed to remove {}: {}", instance.dir.display(), e));
+        }
+    });
+}
+
+/// Kill every comparison backend (when the app quits)
+pub fn stop_all(app: &tauri::AppHandle) {
+    for (_, instance) in app.state::<TempInstances>().0.lock().unwrap().drain() {
+        // Dropping the handle kills the tree
+        drop(instance.process);
+        let _ = std::fs::remove_dir_all(&instance.dir);
+    }
+}
+
+/// Start a temporary backend in a fresh sandbox and open a window on it.
+/// `launcher` picks another backend build; by default the installed one is used.
+#[tauri::command]
+pub fn start_comparison_instance(app: tauri::AppHandle, launcher: Option<PathBuf>) -> Result<TempInstanceInfo, String> {
+    let launcher = match launcher {
+        Some(path) => {
+            check_launcher(&path)?;
+            path
+        }
+        None => crate::process::locate_backend(&app).ok_or("No bundled backend to start")?,
+    };
+
+    let port = crate::net::free_port().map_err(|e| format!("No free port: {}", e))?;
+    let label = format!("{}{}", WINDOW_PREFIX, port);
+    let dir = std::env::temp_dir().join(format!("leaxer-{}-{}", std::process::id(), label));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    link_models(&dir);
+
+    let keys = app.state::<crate::secrets::SecretStore>().backend_keys();
+    let process = match ProcessHandle::spawn(backend_command(&launcher, port, &dir, &keys)) {
+        Ok(process) => process,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&dir);
+            return Err(format!("Failed to start {}: {}", launcher.display(), e));
+        }
+    };
+    log_to_file(&format!(
+        "[Leaxer] Comparison instance {:?} on port {} with PID: {:?}",
+        launcher,
+        port,
+        process.id()
+    ));
+    app.state::<TempInstances>().0.lock().unwrap().insert(
+        label.clone(),
+        TempInstance {
+            process,
+            dir: dir.clone(),
+        },
+    );
+
+    if let Err(e) = open_window(&app, &label, port) {
+        stop(&app, &label);
+        return Err(format!("Failed to open window: {}", e));
+    }
+
+    Ok(TempInstanceInfo {
+        window: label,
+        port,
+        url: format!("http://localhost:{}", port),
+        data_dir: dir,
+    })
+}
+
+#[tauri::command]
+pub fn stop_comparison_instance(app: tauri::AppHandle, window: String) {
+    if let Some(window) = app.get_webview_window(&window) {
+        // Closing the window stops the instance
+        let _ = window.close();
+    } else {
+        stop(&app, &window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn only_backend_launchers_are_accepted() {
+        let dir = tempfile::tempdir().unwrap();
+        let launcher = dir.path().join(crate::process::backend_relative_path());
+        std::fs::create_dir_all(launcher.parent().unwrap()).unwrap();
+        std::fs::write(&launcher, "").unwrap();
+        let other = dir.path().join("evil.sh");
+        std::fs::write(&other, "").unwrap();
+
+        assert!(check_launcher(&launcher).is_ok());
+        assert!(check_launcher(&other).is_err());
+        assert!(check_launcher(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn sandbox_gets_its_own_port_and_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let launcher = dir.path().join(crate::process::backend_relative_path());
+        let sandbox = dir.path().join("sandbox");
+
+        let keys = crate::secrets::BackendKeys {
+            secret_key_base: "k".repeat(86),
+            signing_salt: "s".repeat(32),
+        };
+        let cmd = backend_command(&launcher, 4321, &sandbox, &keys);
+        let envs: HashMap<_, _> = cmd.get_envs().collect();
+        assert_eq!(envs[OsStr::new("PORT")], Some(OsStr::new("4321")));
+        assert_eq!(envs[OsStr::new("LEAXER_USER_DIR")], Some(sandbox.as_os_str()));
+        assert!(envs[OsStr::new("CORS_ORIGINS")]
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("http://localhost:4321,"));
+    }
+}
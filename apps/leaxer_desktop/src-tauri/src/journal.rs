@@ -0,0 +1,300 @@
+//! Write-ahead journal of in-flight shell operations.
+//!
+//! Operations that leave files behind while they run (downloads into `.part` files, model
+//! imports copying gigabytes) record a `begin` line in `journal.jsonl` in the Leaxer dir
+//! before they touch the disk and an `end` line when they are done, each synced before
+//! continuing. Whatever has a `begin` without an `end` on the next start was cut off by
+//! a crash or power loss: half-copied imports are rolled back right away, interrupted
+//! downloads are kept for the UI to resume or discard.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::checksum::ChunkManifest;
+use crate::logging::log_to_file;
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Operation {
+    /// A download to `dest`, written to `dest.part` until it is complete
+    Download {
+        url: String,
+        dest: PathBuf,
+        #[serde(default)]
+        manifest: Option<ChunkManifest>,
+    },
+    /// A model import creating `dest`
+    Import { dest: PathBuf },
+}
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "record", rename_all = "lowercase")]
+enum Record {
+    Begin { id: u64, op: Operation },
+    End { id: u64 },
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct Interrupted {
+    pub id: u64,
+    #[serde(flatten)]
+    pub op: Operation,
+}
+
+struct JournalState {
+    next_id: u64,
+    /// Operations running now
+    open: BTreeMap<u64, Operation>,
+    /// Operations a previous run never finished
+    interrupted: BTreeMap<u64, Operation>,
+}
+
+/// Managed journal
+pub struct Journal {
+    path: Option<PathBuf>,
+    state: Mutex<JournalState>,
+}
+
+/// `begin` records without an `end`, plus the highest id seen. A torn last line (crash
+/// mid-write) is ignored.
+fn unfinished(content: &str) -> (BTreeMap<u64, Operation>, u64) {
+    let mut open = BTreeMap::new();
+    let mut max_id = 0;
+    for record in content.lines().filter_map(|line| serde_json::from_str::<Record>(line).ok()) {
+        match record {
+            Record::Begin { id, op } => {
+                max_id = max_id.max(id);
+                open.insert(id, op);
+            }
+            Record::End { id } => {
+                open.remove(&id);
+            }
+        }
+    }
+    (open, max_id)
+}
+
+/// Partial download file for `dest`
+pub fn part_path(dest: &Path) -> PathBuf {
+    let mut part_path = dest.to_path_buf().into_os_string();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
+impl Journal {
+    /// Open the journal, noting what the previous run left unfinished
+    pub fn open(path: Option<PathBuf>) -> Self {
+        let content = path
+            .as_deref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+        let (interrupted, max_id) = unfinished(&content);
+        let journal = Journal {
+            path,
+            state: Mutex::new(JournalState {
+                next_id: max_id + 1,
+                open: BTreeMap::new(),
+                interrupted,
+            }),
+        };
+        // Start over with only the records that still matter
+        if let Err(e) = journal.compact(&journal.state.lock().unwrap()) {
+            log_to_file(&format!("[Leaxer] Failed to compact journal: {}", e));
+        }
+        journal
+    }
+
+    fn append(&self, record: &Record) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        file.sync_data()
+    }
+
+    /// Rewrite the file with just the unfinished operations
+    fn compact(&self, state: &JournalState) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut content = Vec::new();
+        for (&id, op) in state.interrupted.iter().chain(&state.open) {
+            serde_json::to_writer(&mut content, &Record::Begin { id, op: op.clone() })?;
+            content.push(b'\n');
+        }
+        let tmp = path.with_extension("jsonl.tmp");
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(&content)?;
+        file.sync_data()?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Record that an operation is starting; must be called before it touches the disk
+    pub fn begin(&self, op: Operation) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        if let Err(e) = self.append(&Record::Begin { id, op: op.clone() }) {
+            log_to_file(&format!("[Leaxer] Failed to write journal: {}", e));
+        }
+        state.open.insert(id, op);
+        id
+    }
+
+    /// Record that an operation finished (successfully or not, but cleanly)
+    pub fn end(&self, id: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.open.remove(&id);
+        state.interrupted.remove(&id);
+        let result = if state.open.is_empty() && state.interrupted.is_empty() {
+            // Nothing in flight: keep the file from growing forever
+            self.compact(&state)
+        } else {
+            self.append(&Record::End { id })
+        };
+        if let Err(e) = result {
+            log_to_file(&format!("[Leaxer] Failed to write journal: {}", e));
+        }
+    }
+
+    pub fn interrupted(&self) -> Vec<Interrupted> {
+        self.state
+            .lock()
+            .unwrap()
+            .interrupted
+            .iter()
+            .map(|(&id, op)| Interrupted { id, op: op.clone() })
+            .collect()
+    }
+
+    fn take_interrupted(&self, id: u64) -> Option<Operation> {
+        let op = self.state.lock().unwrap().interrupted.get(&id).cloned()?;
+        self.end(id);
+        Some(op)
+    }
+
+    /// Undo what can't be resumed. Interrupted imports leave a partial model that would
+    /// look complete to the backend, so they are removed without asking.
+    pub fn roll_back_imports(&self) {
+        for entry in self.interrupted() {
+            if let Operation::Import { dest } = &entry.op {
+                log_to_file(&format!("[Leaxer] Rolling back interrupted import of {:?}", dest));
+                match std::fs::remove_file(dest) {
+                    Ok(()) => self.end(entry.id),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => self.end(entry.id),
+                    Err(e) => log_to_file(&format!("[Leaxer] Failed to remove {:?}: {}", dest, e)),
+                }
+            }
+        }
+    }
+}
+
+/// Operations the previous run left unfinished (interrupted downloads)
+#[tauri::command]
+pub fn list_interrupted(journal: tauri::State<'_, Journal>) -> Vec<Interrupted> {
+    journal.interrupted()
+}
+
+/// Drop an interrupted operation and delete what it left behind
+#[tauri::command]
+pub fn discard_interrupted(journal: tauri::State<'_, Journal>, id: u64) -> Result<(), String> {
+    let op = journal.take_interrupted(id).ok_or_else(|| format!("No interrupted operation {}", id))?;
+    let leftover = match op {
+        Operation::Download { dest, .. } => part_path(&dest),
+        Operation::Import { dest } => dest,
+    };
+    match std::fs::remove_file(&leftover) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Continue an interrupted download where it stopped; resolves with the saved path
+#[tauri::command]
+pub async fn resume_interrupted(app: tauri::AppHandle, id: u64) -> Result<String, String> {
+    use tauri::Manager;
+
+    let op = app
+        .state::<Journal>()
+        .take_interrupted(id)
+        .ok_or_else(|| format!("No interrupted operation {}", id))?;
+    match op {
+        Operation::Download { url, dest, manifest } => {
+            crate::commands::downloads::resume_download(&app, &url, dest, manifest).await
+        }
+        Operation::Import { .. } => Err("Imports can't be resumed, run them again".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn download(name: &str) -> Operation {
+        Operation::Download {
+            url: format!("http://localhost:4000/api/outputs/{}", name),
+            dest: PathBuf::from(name),
+            manifest: None,
+        }
+    }
+
+    #[test]
+    fn unfinished_operations_survive_a_crash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+
+        let journal = Journal::open(Some(path.clone()));
+        let done = journal.begin(download("done.png"));
+        let cut_off = journal.begin(download("big.safetensors"));
+        journal.end(done);
+        drop(journal);
+        // A crash in the middle of writing a record
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"record":"end","i"#).unwrap();
+
+        let reopened = Journal::open(Some(path));
+        assert_eq!(
+            reopened.interrupted(),
+            vec![Interrupted {
+                id: cut_off,
+                op: download("big.safetensors")
+            }]
+        );
+        assert!(reopened.begin(download("next.png")) > cut_off);
+    }
+
+    #[test]
+    fn file_is_emptied_once_nothing_is_in_flight() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+        let journal = Journal::open(Some(path.clone()));
+        let first = journal.begin(download("a.png"));
+        let second = journal.begin(download("b.png"));
+        journal.end(first);
+        assert!(!std::fs::read_to_string(&path).unwrap().is_empty());
+        journal.end(second);
+        assert!(std::fs::read_to_string(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn interrupted_imports_are_rolled_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal.jsonl");
+        let partial = dir.path().join("model.gguf");
+        std::fs::write(&partial, "half a model").unwrap();
+
+        Journal::open(Some(path.clone())).begin(Operation::Import { dest: partial.clone() });
+        let journal = Journal::open(Some(path));
+        journal.roll_back_imports();
+        assert!(!partial.exists());
+        assert!(journal.interrupted().is_empty());
+    }
+}
@@ -0,0 +1,259 @@
+//! Kiosk/demo mode: reset to a clean state after a period of inactivity.
+//!
+//! Enabled with `--kiosk` (optionally `--kiosk-idle=<minutes>`) or a `kiosk` object in
+//! config.json: `{"enabled": true, "idle_minutes": 10, "snapshot": "/path/to/snapshot"}`.
+//! Without a configured snapshot, the data dir as it is on the first kiosk start is
+//! saved to `<Leaxer dir>/.kiosk-snapshot` and that becomes the clean state.
+//!
+//! The UI reports input through `report_activity`. Once nobody has touched the app for
+//! the idle period, the backend is stopped, the data dir restored from the snapshot,
+//! the backend started again and the UI reloaded with its local storage cleared.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tauri::Manager;
+
+use crate::logging::log_to_file;
+
+pub const KIOSK_FLAG: &str = "--kiosk";
+const KIOSK_IDLE_FLAG: &str = "--kiosk-idle=";
+const KIOSK_KEY: &str = "kiosk";
+
+const DEFAULT_IDLE_MINUTES: u64 = 5;
+const SNAPSHOT_DIR: &str = ".kiosk-snapshot";
+
+/// How often the idle timer is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Entries of the data dir a reset never touches: settings, models, logs, other profiles,
+/// the shell's own state (audit log, installed backend, TLS certificate, secrets)
+const KEEP: &[&str] = &[
+    "config.json",
+    "profiles.json",
+    "profiles",
+    "models",
+    "startup.log",
+    "logs",
+    "audit.log",
+    "backend",
+    "tls",
+    "secrets.enc",
+    "secrets.key",
+    SNAPSHOT_DIR,
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KioskSettings {
+    pub idle: Duration,
+    pub snapshot: Option<PathBuf>,
+}
+
+/// Kiosk settings from the command line and config; `None` when kiosk mode is off
+pub fn settings(args: &[String], config: &Value) -> Option<KioskSettings> {
+    let section = config.get(KIOSK_KEY);
+    let from_flag = args.iter().any(|arg| arg == KIOSK_FLAG || arg.starts_with(KIOSK_IDLE_FLAG));
+    let from_config = section
+        .and_then(|s| s.get("enabled"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    if !from_flag && !from_config {
+        return None;
+    }
+
+    let minutes = args
+        .iter()
+        .find_map(|arg| arg.strip_prefix(KIOSK_IDLE_FLAG))
+        .and_then(|minutes| minutes.parse().ok())
+        .or_else(|| section.and_then(|s| s.get("idle_minutes")).and_then(Value::as_u64))
+        .filter(|&minutes| minutes > 0)
+        .unwrap_or(DEFAULT_IDLE_MINUTES);
+    let snapshot = section
+        .and_then(|s| s.get("snapshot"))
+        .and_then(Value::as_str)
+        .map(PathBuf::from);
+    Some(KioskSettings {
+        idle: Duration::from_secs(minutes * 60),
+        snapshot,
+    })
+}
+
+/// Activity tracking; managed in every mode so `report_activity` always resolves
+#[derive(Default)]
+pub struct Kiosk {
+    /// Last input, and whether anything happened since the last reset
+    activity: Mutex<Option<Instant>>,
+}
+
+fn copy_dir(from: &Path, to: &Path, skip: &[&str]) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if skip.iter().any(|s| name == *s) {
+            continue;
+        }
+        let target = to.join(&name);
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target, &[])?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+fn take_snapshot(data_dir: &Path, snapshot: &Path) -> std::io::Result<()> {
+    let tmp = snapshot.with_extension("tmp");
+    let _ = std::fs::remove_dir_all(&tmp);
+    // The temporary copy may live inside the data dir; don't copy it into itself
+    let tmp_name = tmp.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    let skip: Vec<&str> = KEEP.iter().copied().chain([tmp_name]).collect();
+    copy_dir(data_dir, &tmp, &skip)?;
+    std::fs::rename(&tmp, snapshot)
+}
+
+/// Replace everything in the data dir (except `KEEP`) with the snapshot's contents
+fn restore_snapshot(data_dir: &Path, snapshot: &Path) -> std::io::Result<()> {
+    if !snapshot.is_dir() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("snapshot {} is missing", snapshot.display()),
+        ));
+    }
+    for entry in std::fs::read_dir(data_dir)? {
+        let entry = entry?;
+        if KEEP.iter().any(|keep| entry.file_name() == *keep) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    copy_dir(snapshot, data_dir, KEEP)
+}
+
+async fn reset(app: &tauri::AppHandle, data_dir: &Path, snapshot: &Path) {
+    log_to_file("[Leaxer] Kiosk idle, resetting to the snapshot");
+    let backend = app.state::<crate::process::BackendHandle>().inner().clone();
+    backend.stop(crate::BACKEND_STOP_TIMEOUT).await;
+
+    let (data_dir, snapshot) = (data_dir.to_path_buf(), snapshot.to_path_buf());
+    match tauri::async_runtime::spawn_blocking(move || restore_snapshot(&data_dir, &snapshot)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log_to_file(&format!("[Leaxer] Kiosk reset failed to restore data: {}", e)),
+        Err(e) => log_to_file(&format!("[Leaxer] Kiosk reset failed: {}", e)),
+    }
+
+    backend.start();
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.eval("localStorage.clear(); sessionStorage.clear(); window.location.reload();");
+    }
+}
+
+/// Take the snapshot if needed and reset whenever the app has been idle long enough
+pub fn start(app: &tauri::AppHandle) {
+    let args: Vec<String> = std::env::args().collect();
+    let Some(settings) = settings(&args, &app.state::<crate::config::ConfigStore>().load()) else {
+        return;
+    };
+    let Some(data_dir) = crate::paths::get_leaxer_user_dir() else {
+        return;
+    };
+    let snapshot = settings.snapshot.clone().unwrap_or_else(|| data_dir.join(SNAPSHOT_DIR));
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if !snapshot.exists() {
+            let (from, to) = (data_dir.clone(), snapshot.clone());
+            match tauri::async_runtime::spawn_blocking(move || take_snapshot(&from, &to)).await {
+                Ok(Ok(())) => log_to_file(&format!("[Leaxer] Kiosk snapshot saved to {}", snapshot.display())),
+                result => {
+                    log_to_file(&format!("[Leaxer] Kiosk mode disabled, no snapshot: {:?}", result));
+                    return;
+                }
+            }
+        }
+        log_to_file(&format!("[Leaxer] Kiosk mode, resetting after {:?} idle", settings.idle));
+
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+            let idle = {
+                let kiosk = app.state::<Kiosk>();
+                let mut activity = kiosk.activity.lock().unwrap();
+                // Nothing to reset until someone has used the app
+                let idle = activity.is_some_and(|last| last.elapsed() >= settings.idle);
+                if idle {
+                    *activity = None;
+                }
+                idle
+            };
+            if idle {
+                reset(&app, &data_dir, &snapshot).await;
+            }
+        }
+    });
+}
+
+/// Called by the UI (throttled) on user input
+#[tauri::command]
+pub fn report_activity(kiosk: tauri::State<'_, Kiosk>) {
+    *kiosk.activity.lock().unwrap() = Some(Instant::now());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::fs;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn enabled_by_flag_or_config() {
+        assert_eq!(settings(&args(&["leaxer"]), &json!({})), None);
+        assert_eq!(
+            settings(&args(&["leaxer", "--kiosk"]), &json!({})).unwrap().idle,
+            Duration::from_secs(DEFAULT_IDLE_MINUTES * 60)
+        );
+
+        let config = json!({ "kiosk": { "enabled": true, "idle_minutes": 10, "snapshot": "/demo" } });
+        let from_config = settings(&args(&["leaxer"]), &config).unwrap();
+        assert_eq!(from_config.idle, Duration::from_secs(600));
+        assert_eq!(from_config.snapshot, Some(PathBuf::from("/demo")));
+
+        // The command line wins over config
+        let overridden = settings(&args(&["leaxer", "--kiosk-idle=2"]), &config).unwrap();
+        assert_eq!(overridden.idle, Duration::from_secs(120));
+    }
+
+    #[test]
+    fn restore_brings_back_the_snapshot_and_keeps_models() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path();
+        fs::create_dir_all(data.join("workflows")).unwrap();
+        fs::write(data.join("workflows").join("demo.lxr"), "demo").unwrap();
+        fs::create_dir_all(data.join("models")).unwrap();
+        fs::write(data.join("models").join("big.gguf"), "weights").unwrap();
+        let snapshot = data.join(SNAPSHOT_DIR);
+        take_snapshot(data, &snapshot).unwrap();
+        assert!(!snapshot.join("models").exists());
+
+        // A visitor's session
+        fs::write(data.join("workflows").join("mess.lxr"), "mess").unwrap();
+        fs::write(data.join("workflows").join("demo.lxr"), "changed").unwrap();
+        fs::create_dir_all(data.join("chats")).unwrap();
+
+        restore_snapshot(data, &snapshot).unwrap();
+        assert_eq!(fs::read_to_string(data.join("workflows").join("demo.lxr")).unwrap(), "demo");
+        assert!(!data.join("workflows").join("mess.lxr").exists());
+        assert!(!data.join("chats").exists());
+        assert!(data.join("models").join("big.gguf").exists());
+    }
+}
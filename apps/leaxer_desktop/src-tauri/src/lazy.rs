@@ -0,0 +1,49 @@
+//! Subsystems created on first use instead of during startup.
+//!
+//! Each one is managed as a `Lazy<T>` holding its constructor; the first `get` builds it
+//! (exactly once, even with concurrent callers) and later calls return the same value.
+
+use std::sync::OnceLock;
+use tauri::Manager;
+
+use crate::logging::log_to_file;
+
+pub struct Lazy<T> {
+    name: &'static str,
+    init: fn(&tauri::AppHandle) -> T,
+    cell: OnceLock<T>,
+}
+
+impl<T: Send + Sync + 'static> Lazy<T> {
+    pub const fn new(name: &'static str, init: fn(&tauri::AppHandle) -> T) -> Self {
+        Lazy {
+            name,
+            init,
+            cell: OnceLock::new(),
+        }
+    }
+
+    /// The subsystem, initializing it on first use
+    pub fn get(&self, app: &tauri::AppHandle) -> &T {
+        self.cell.get_or_init(|| {
+            let started = std::time::Instant::now();
+            let value = (self.init)(app);
+            log_to_file(&format!(
+                "[Leaxer] Initialized {} on first use ({} ms)",
+                self.name,
+                started.elapsed().as_millis()
+            ));
+            value
+        })
+    }
+
+    /// Whether the subsystem has been created yet
+    pub fn is_initialized(&self) -> bool {
+        self.cell.get().is_some()
+    }
+}
+
+/// Fetch a lazily initialized subsystem from managed state
+pub fn get<T: Send + Sync + 'static>(app: &tauri::AppHandle) -> &T {
+    app.state::<Lazy<T>>().inner().get(app)
+}
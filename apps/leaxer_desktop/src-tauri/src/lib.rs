@@ -0,0 +1,512 @@
+//! Leaxer desktop shell: spawns the Elixir backend and hosts the web UI.
+
+pub mod audit;
+pub mod backend_update;
+pub mod checksum;
+pub mod commands;
+pub mod compat;
+pub mod config;
+pub mod config_backup;
+pub mod control;
+pub mod crash;
+pub mod data_dir;
+pub mod deep_link;
+pub mod extensions;
+pub mod external;
+pub mod features;
+pub mod fs_scope;
+pub mod instances;
+pub mod journal;
+pub mod kiosk;
+pub mod lazy;
+pub mod logging;
+#[cfg(feature = "mcp")]
+pub mod mcp;
+pub mod mdns;
+pub mod metrics;
+pub mod mock_backend;
+pub mod monitor;
+pub mod native_messaging;
+pub mod net;
+pub mod orphans;
+pub mod pairing;
+pub mod paths;
+pub mod ports;
+pub mod preflight;
+pub mod process;
+pub mod process_handle;
+pub mod profiles;
+pub mod profiling;
+pub mod readiness;
+pub mod recovery;
+pub mod reminders;
+pub mod search_index;
+pub mod secrets;
+pub mod splash;
+pub mod stream;
+pub mod tls;
+pub mod transport;
+pub mod watchdog;
+pub mod webhook;
+
+use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use tracing::Instrument;
+
+use commands::clipboard::ClipboardWatcher;
+use commands::tasks::TaskRegistry;
+use commands::window::AttentionState;
+use logging::log_to_file;
+use paths::AllowedPaths;
+
+/// How long closing the window waits for the backend to exit, covering its graceful shutdown
+pub(crate) const BACKEND_STOP_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Set once `shutdown` has begun; further close requests wait for it
+static SHUTTING_DOWN: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Flush config, stop the backend and exit the app. Runs off the UI thread.
+/// The window stays up (the UI shows that it is shutting down) until the backend has
+/// exited, so relaunching right away doesn't find the port still taken.
+pub fn shutdown(app: &tauri::AppHandle) {
+    if SHUTTING_DOWN.swap(true, std::sync::atomic::Ordering::SeqCst) {
+        return;
+    }
+    let span = tracing::info_span!("shutdown");
+    span.in_scope(|| log_to_file("[Leaxer] Shutting down"));
+    let _ = app.emit("app-shutting-down", ());
+    let app = app.clone();
+    let shutdown = async move {
+        if let Err(e) = app.state::<config::ConfigStore>().flush() {
+            log_to_file(&format!("[Leaxer] Failed to write config.json: {}", e));
+        }
+        extensions::stop_all(&app);
+        instances::stop_all(&app);
+        let _ = tauri::async_runtime::spawn_blocking(mdns::stop).await;
+        let backend = app.state::<process::BackendHandle>().inner().clone();
+        backend.stop(BACKEND_STOP_TIMEOUT).await;
+        logging::flush();
+        app.exit(0);
+    };
+    tauri::async_runtime::spawn(shutdown.instrument(span));
+}
+
+/// Shut down cleanly on SIGTERM/SIGHUP/SIGINT (service stop, logout, Ctrl+C in a terminal).
+/// Windows needs no equivalent: the backend's job object dies with this process.
+#[cfg(unix)]
+fn handle_termination_signals(app: &tauri::AppHandle) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (Ok(mut term), Ok(mut hup), Ok(mut int)) = (
+            signal(SignalKind::terminate()),
+            signal(SignalKind::hangup()),
+            signal(SignalKind::interrupt()),
+        ) else {
+            log_to_file("[Leaxer] Failed to install signal handlers");
+            return;
+        };
+        tokio::select! {
+            _ = term.recv() => {}
+            _ = hup.recv() => {}
+            _ = int.recv() => {}
+        }
+        log_to_file("[Leaxer] Received termination signal, shutting down");
+        shutdown(&app);
+    });
+}
+
+pub fn run() {
+    metrics::init();
+    // Before anything opens or logs to the Leaxer dir
+    let migration = paths::migrate_legacy_dir();
+    // WebView2 keeps its profile in AppData unless told otherwise
+    #[cfg(target_os = "windows")]
+    if let Some(dir) = paths::portable_dir() {
+        std::env::set_var("WEBVIEW2_USER_DATA_FOLDER", dir.join("webview"));
+    }
+    // Every mode works against the active profile's data dir and backend port
+    let profile_store = profiles::ProfileStore::open();
+    profile_store.apply_active(&std::env::args().collect::<Vec<_>>());
+
+    // MCP clients launch the app as a stdio server; that mode never opens a window
+    #[cfg(feature = "mcp")]
+    if std::env::args().any(|arg| arg == mcp::MCP_STDIO_FLAG) {
+        mcp::run_stdio();
+        return;
+    }
+
+    // Browsers launch the app as the extension's native messaging host
+    if let Some(caller) = native_messaging::detect_caller(&std::env::args().collect::<Vec<_>>()) {
+        native_messaging::run_host(caller);
+        return;
+    }
+
+    config_backup::restore_from_args(&std::env::args().collect::<Vec<_>>());
+    let config_store = config::ConfigStore::new(config::config_path());
+    logging::init(&config_store.load());
+    match migration {
+        Ok(Some((from, to))) => log_to_file(&format!("[Leaxer] Moved the Leaxer folder from {:?} to {:?}", from, to)),
+        Ok(None) => {}
+        Err(e) => log_to_file(&format!("[Leaxer] {}", e)),
+    }
+    if let Some(dir) = paths::portable_dir() {
+        log_to_file(&format!("[Leaxer] Portable mode, data in {:?}", dir));
+    }
+    if let Some(dir) = paths::home_leaxer_dir().and_then(|home| paths::configured_data_dir(&home)) {
+        if !dir.is_dir() {
+            log_to_file(&format!("[Leaxer] Leaxer folder {:?} is not available, using the default location", dir));
+        }
+    }
+    external::init(&std::env::args().collect::<Vec<_>>(), &config_store.load());
+    if net::external_backend().is_none() {
+        transport::init(&config_store.load());
+        if transport::socket_path().is_none() {
+            if let Some(busy) = net::allocate_backend_port() {
+                ports::record_conflict(busy);
+            }
+        }
+    }
+
+    profiling::init(std::env::args());
+    let first_page_load = Mutex::new(Some(profiling::span("first_page_load")));
+    let builder_span = profiling::span("builder");
+
+    // Release builds abort on panic, so drop handlers never run; reap the backend tree here
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        process_handle::kill_all();
+        logging::log_panic(info);
+        crash::record_panic(info);
+        default_hook(info);
+    }));
+
+    // Must be registered first: a second launch just surfaces the running instance,
+    // which is what makes reopening from warm standby instant. Launches with their own
+    // --config run side by side instead.
+    let builder = tauri::Builder::default();
+    let builder = if config::config_file_arg(&std::env::args().collect::<Vec<_>>()).is_some() {
+        builder
+    } else {
+        builder.plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
+            commands::window::show_main_window(app);
+            deep_link::open_from_args(app, &args);
+            if let Some(id) = profiles::launch_profile(&args) {
+                profiles::switch_in_background(app, id);
+            }
+        }))
+    };
+
+    let mut context = tauri::generate_context!();
+    external::apply(&mut context);
+    let builder = match net::backend_address_plugin() {
+        Some(plugin) => builder.plugin(plugin),
+        None => builder,
+    };
+
+    features::register_plugins(builder)
+        .manage(config_store)
+        .manage(profile_store)
+        .manage(secrets::SecretStore::open())
+        .manage(deep_link::PendingDeepLinks::default())
+        .manage(webhook::WebhookServer::default())
+        .manage(pairing::Pairing::default())
+        .manage(extensions::Extensions::default())
+        .manage(instances::TempInstances::default())
+        .manage(kiosk::Kiosk::default())
+        .manage(recovery::UiHeartbeat::default())
+        .manage(readiness::BackendReadiness::default())
+        .manage(splash::Splash::default())
+        .manage(journal::Journal::open(
+            paths::get_leaxer_user_dir().map(|dir| dir.join("journal.jsonl")),
+        ))
+        .manage(reminders::Reminders::new(
+            paths::get_leaxer_user_dir().map(|dir| dir.join("reminders.json")),
+        ))
+        .manage(Mutex::new(AllowedPaths { paths: Vec::new() }))
+        .manage(Mutex::new(AttentionState {
+            title: "Leaxer".to_string(),
+            unread: 0,
+        }))
+        .manage(Mutex::new(TaskRegistry {
+            next_id: 0,
+            tasks: std::collections::HashMap::new(),
+        }))
+        .manage(Mutex::new(commands::system::PowerStatus::default()))
+        .manage(Mutex::new(preflight::StartupReport::default()))
+        .manage(monitor::MonitorSignal::default())
+        // Rarely used subsystems are built on first use instead of at startup
+        .manage(lazy::Lazy::new("tray", commands::window::install_tray))
+        .manage(ClipboardWatcher::default())
+        .invoke_handler(tauri::generate_handler![
+            commands::downloads::download_to_disk,
+            commands::files::reveal_path,
+            commands::files::open_external,
+            commands::system::get_system_info,
+            commands::system::get_power_status,
+            commands::clipboard::set_clipboard_watcher,
+            commands::clipboard::is_clipboard_watcher_enabled,
+            commands::files::start_file_drag,
+            commands::files::share,
+            commands::permissions::get_permission_status,
+            commands::permissions::request_permission,
+            commands::window::open_devtools,
+            commands::window::quit_and_stop_server,
+            commands::tasks::list_tasks,
+            commands::tasks::cancel_task,
+            commands::logs::tail_logs,
+            commands::logs::stop_tailing_logs,
+            commands::logs::get_recent_logs,
+            commands::diagnostics::export_diagnostics,
+            commands::settings::export_settings,
+            commands::settings::import_settings,
+            commands::window::set_window_title,
+            commands::window::set_unread_count,
+            commands::elevated::run_elevated,
+            commands::system::get_input_locale,
+            commands::system::get_regional_format,
+            commands::associations::register_associations,
+            commands::associations::unregister_associations,
+            commands::associations::get_association_status,
+            commands::capture::set_capture_hotkey,
+            commands::capture::take_capture,
+            deep_link::take_deep_links,
+            recovery::ui_heartbeat,
+            readiness::wait_for_backend,
+            process::restart_backend,
+            process::cleanup_backend,
+            process::set_network_exposure,
+            process::get_backend_status,
+            net::get_backend_url,
+            tls::get_tls_info,
+            pairing::start_pairing,
+            pairing::cancel_pairing,
+            pairing::verify_pairing_pin,
+            pairing::pair_device,
+            pairing::list_paired_devices,
+            pairing::unpair_device,
+            control::backend_control,
+            backend_update::check_backend_update,
+            backend_update::install_backend_update,
+            splash::splash_state,
+            splash::close_splash,
+            splash::dismiss_splash,
+            splash::reveal_backend_log,
+            reminders::schedule_reminder,
+            reminders::cancel_reminder,
+            reminders::list_reminders,
+            webhook::get_webhook_info,
+            webhook::set_webhook_enabled,
+            webhook::rotate_webhook_token,
+            extensions::list_extensions,
+            extensions::restart_extension,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::rename_profile,
+            profiles::configure_profile,
+            profiles::delete_profile,
+            profiles::switch_profile,
+            instances::start_comparison_instance,
+            instances::stop_comparison_instance,
+            kiosk::report_activity,
+            journal::list_interrupted,
+            journal::discard_interrupted,
+            journal::resume_interrupted,
+            commands::imports::scan_imports,
+            commands::imports::import_items,
+            native_messaging::register_native_messaging_host,
+            native_messaging::unregister_native_messaging_host,
+            config::set_config_value,
+            secrets::secret_get,
+            secrets::secret_set,
+            commands::keychain::keychain_set,
+            commands::keychain::keychain_get,
+            commands::keychain::keychain_delete,
+            fs_scope::grant_fs_access,
+            audit::get_audit_log,
+            secrets::rotate_secrets,
+            config_backup::backup_config,
+            config_backup::list_config_backups,
+            config_backup::restore_config_backup,
+            stream::get_file_url,
+            preflight::get_startup_report,
+            metrics::get_startup_metrics,
+            data_dir::get_data_dir,
+            data_dir::move_data_dir,
+            features::get_build_features
+        ])
+        .register_asynchronous_uri_scheme_protocol(stream::STREAM_SCHEME, |ctx, request, responder| {
+            // File reads happen off the webview thread
+            let app = ctx.app_handle().clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                responder.respond(stream::handle_request(&app, &request));
+            });
+        })
+        .register_asynchronous_uri_scheme_protocol(transport::BACKEND_SCHEME, |_ctx, request, responder| {
+            tauri::async_runtime::spawn(async move {
+                responder.respond(transport::proxy(request).await);
+            });
+        })
+        .setup(move |app| {
+            drop(builder_span);
+            let _setup_span = profiling::span("setup");
+            let _setup = tracing::info_span!("setup").entered();
+
+            features::add_plugin_capabilities(app);
+            if let Err(e) = commands::window::build_main_window(app.handle()) {
+                log_to_file(&format!("[Leaxer] Failed to create the main window: {}", e));
+            }
+            #[cfg(feature = "fs")]
+            fs_scope::init(app.handle());
+            app.state::<journal::Journal>().roll_back_imports();
+            #[cfg(feature = "mcp")]
+            mcp::start_socket_server(app.handle());
+            #[cfg(feature = "global-shortcut")]
+            commands::capture::init(app.handle());
+            deep_link::open_from_args(app.handle(), &std::env::args().collect::<Vec<_>>());
+            search_index::start(app.handle());
+            reminders::start_scheduler(app.handle());
+            webhook::start(app.handle());
+            extensions::start(app.handle());
+            kiosk::start(app.handle());
+            recovery::start(app.handle());
+            crash::notify_previous(app.handle());
+            #[cfg(all(feature = "http", feature = "dialog"))]
+            app.manage(lazy::Lazy::new("download client", commands::downloads::create_client));
+
+            // Nothing here may block: the window created above is already loading.
+            // Preflight checks run concurrently and the backend is spawned as soon as they
+            // finish (they are time-bounded), so the BEAM boots while the webview loads.
+            splash::open(app.handle());
+            let backend = process::start_supervisor(app.handle().clone());
+            app.manage(backend.clone());
+            watchdog::start(app.handle());
+
+            #[cfg(unix)]
+            handle_termination_signals(app.handle());
+
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let external = net::external_backend().is_some();
+                // A dev server isn't ours to stop, only a bundled backend's leftovers are
+                if !external && process::locate_backend(&handle).is_some() {
+                    orphans::reap(net::backend_port()).await;
+                    if !ports::resolve_conflict(&handle).await {
+                        return;
+                    }
+                }
+                let report = {
+                    let _span = profiling::span("preflight");
+                    preflight::run_preflight(&handle).await
+                };
+                if !report.passed("port") && !external {
+                    log_to_file("[Leaxer] Backend port is busy, the backend may fail to start");
+                }
+                splash::set_status(&handle, "Starting backend...");
+                if external {
+                    splash::set_status(&handle, "Connecting to backend...");
+                } else if mock_backend::enabled(&std::env::args().collect::<Vec<_>>()) {
+                    mock_backend::start(net::backend_port());
+                } else if let Err(error) = compat::check(&handle) {
+                    log_to_file(&format!("[Leaxer] {}", error));
+                    compat::report(&handle, &error);
+                    readiness::fail(&handle, error);
+                    return;
+                } else {
+                    backend.start();
+                }
+                readiness::probe(&handle).await;
+            });
+
+            let watch_span = profiling::span("config_watch");
+            if let Err(e) = app.state::<config::ConfigStore>().watch() {
+                log_to_file(&format!("[Leaxer] Failed to watch config.json: {}", e));
+            }
+            config::start_hot_reload(app.handle());
+
+            drop(watch_span);
+
+            // Hardware and environment probing happens on background threads
+            let monitors_span = profiling::span("start_monitors");
+            commands::system::prefetch_hardware_info();
+            commands::system::start_power_monitor(app.handle().clone());
+            commands::system::start_input_locale_monitor(app.handle().clone());
+            commands::system::start_regional_format_monitor(app.handle().clone());
+            drop(monitors_span);
+
+            Ok(())
+        })
+        .on_page_load(move |_webview, payload| {
+            // The first finished load ends the startup profile
+            if payload.event() == tauri::webview::PageLoadEvent::Finished {
+                if let Some(span) = first_page_load.lock().unwrap().take() {
+                    drop(span);
+                    profiling::write_profile();
+                }
+            }
+        })
+        .on_window_event(|window, event| {
+            // Coming back to the app is when layout, locale etc. are worth re-checking
+            if let tauri::WindowEvent::Focused(true) = event {
+                window.state::<monitor::MonitorSignal>().notify();
+            }
+
+            if let tauri::WindowEvent::Destroyed = event {
+                if window.label().starts_with(instances::WINDOW_PREFIX) {
+                    instances::stop(window.app_handle(), window.label());
+                }
+                // The main window only goes away for good when the app is exiting
+                if window.label() == "main" && !window.state::<recovery::UiHeartbeat>().recreating() {
+                    process_handle::kill_all();
+                }
+            }
+
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // Popups such as the capture window just close
+                if window.label() != "main" {
+                    return;
+                }
+                api.prevent_close();
+
+                // Hiding needs the tray to get the window back; without one, quit instead
+                let config = window.state::<config::ConfigStore>();
+                if config.get().service_mode && commands::window::ensure_tray(window.app_handle()) {
+                    // Jobs keep running; only "Quit and stop server" tears the backend down
+                    log_to_file("[Leaxer] Window closed, backend keeps running in service mode");
+                    let _ = window.hide();
+                    window.state::<monitor::MonitorSignal>().set_paused(true);
+                } else if config.get().warm_standby && commands::window::ensure_tray(window.app_handle()) {
+                    // Keep the shell alive in the tray with the backend paused, so the
+                    // next launch only has to show the window again
+                    log_to_file("[Leaxer] Window closed, keeping backend in warm standby");
+                    let _ = window.hide();
+                    window.state::<process::BackendHandle>().standby();
+                    window.state::<monitor::MonitorSignal>().set_paused(true);
+                } else {
+                    // Stop the backend on the supervisor task and exit once it has exited,
+                    // so the UI thread never waits on the process
+                    shutdown(window.app_handle());
+                }
+            }
+        })
+        .build(context)
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            match event {
+                // Recreating a crashed main window briefly leaves no window open
+                tauri::RunEvent::ExitRequested { api, code: None, .. } => {
+                    if app.state::<recovery::UiHeartbeat>().recreating() {
+                        api.prevent_exit();
+                    }
+                }
+                // Anything still running missed the graceful shutdown; the process exits
+                // without running destructors, so reap the trees here
+                tauri::RunEvent::Exit => {
+                    mdns::stop();
+                    process_handle::kill_all();
+                }
+                _ => {}
+            }
+        });
+}
@@ -0,0 +1,882 @@
+//! File logging for the shell (the console is hidden in release builds).
+//!
+//! The shell logs through `tracing`: `init` installs a subscriber that formats events as
+//! text, or as JSON lines with `"log_format": "json"` in config.json, and spans such as
+//! `setup`, `backend_spawn` and `shutdown` give events their context. `log_to_file` is the
+//! shorthand used across the shell and becomes an info event. `"log_level"` in config.json,
+//! or `LEAXER_LOG_LEVEL` in the environment, sets the verbosity of both the shell and the
+//! backend.
+//!
+//! Every line is redacted before it reaches a file: values registered with
+//! `register_secret`, values of secret-looking keys (`SECRET_KEY_BASE=...`,
+//! `"token": "..."`) and well-known token formats are replaced with `[redacted]`.
+//!
+//! The most recent lines are also kept in memory (`recent_lines`), so the UI can show
+//! them when the log file can't be written.
+//!
+//! Lines go through one buffered writer that is flushed shortly after the first unflushed
+//! write, on shutdown and from the panic hook, so logging costs no syscall per line while
+//! the last lines before a crash still reach the disk.
+
+use std::fs;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, MutexGuard, Once, RwLock};
+use std::time::Duration;
+
+use crate::paths::get_leaxer_user_dir;
+
+/// How long a written line may sit in the buffer before it is flushed
+const LOG_FLUSH_DELAY: Duration = Duration::from_secs(1);
+
+/// Stands in for a secret in logs and diagnostics
+pub const REDACTED: &str = "[redacted]";
+
+/// Keys whose values are never logged, matched against whole words of the key ignoring
+/// case, so `HF_TOKEN` is one and `max_tokens` isn't
+const SECRET_KEY_PARTS: &[&str] = &[
+    "secret", "token", "password", "api_key", "apikey", "salt", "cookie", "credential", "credentials", "authorization",
+];
+
+/// Prefixes of API tokens that are redacted wherever they appear
+const TOKEN_PREFIXES: &[&str] = &["hf_", "sk-", "ghp_", "gho_", "ghs_", "github_pat_", "xoxb-", "xoxp-"];
+
+/// Shortest run of characters after a prefix that is taken for a token
+const MIN_TOKEN_LEN: usize = 16;
+
+/// Secret values handed to the backend, redacted verbatim
+static SECRETS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Lowercase words of a key, split at `_`, `-` and camelCase humps
+fn key_words(key: &str) -> Vec<String> {
+    let mut words = vec![String::new()];
+    let mut after_lowercase = false;
+    for c in key.chars() {
+        if c == '_' || c == '-' || (c.is_uppercase() && after_lowercase) {
+            words.push(String::new());
+        }
+        if c != '_' && c != '-' {
+            words.last_mut().unwrap().extend(c.to_lowercase());
+        }
+        after_lowercase = c.is_lowercase() || c.is_ascii_digit();
+    }
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+/// Whether a config key or environment variable holds a secret
+pub fn is_secret_key(key: &str) -> bool {
+    let words = key_words(key);
+    SECRET_KEY_PARTS.iter().any(|part| {
+        let part: Vec<&str> = part.split('_').collect();
+        words.windows(part.len()).any(|window| window.iter().zip(&part).all(|(word, part)| word == part))
+    })
+}
+
+/// Redact `value` wherever it shows up in a log line. Short values are ignored since they
+/// would mask unrelated text.
+pub fn register_secret(value: &str) {
+    let value = value.trim();
+    if value.len() < 8 {
+        return;
+    }
+    let mut secrets = SECRETS.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if !secrets.iter().any(|secret| secret == value) {
+        secrets.push(value.to_string());
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Bounds of the value starting at `start`: up to the closing quote for a quoted value
+/// (also escaped quotes, as in JSON log lines), else up to the first space, quote, comma,
+/// bracket or `&`
+fn value_end(line: &str, start: usize) -> (usize, usize) {
+    let rest = &line[start..];
+    if rest.starts_with("\\\"") {
+        let inner = start + 2;
+        let end = line[inner..].find("\\\"").map(|i| inner + i).unwrap_or(line.len());
+        return (inner, end);
+    }
+    if let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let inner = start + 1;
+        let end = line[inner..].find(quote).map(|i| inner + i).unwrap_or(line.len());
+        return (inner, end);
+    }
+    let end = rest
+        .find(|c: char| c.is_whitespace() || matches!(c, '"' | ',' | '}' | ']' | '&' | ';'))
+        .map(|i| start + i)
+        .unwrap_or(line.len());
+    (start, end)
+}
+
+/// Mask the values of secret-looking keys: `KEY=value`, `key: value`, `"key": "value"`
+fn redact_assignments(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut copied = 0;
+    let mut search = 0;
+    while let Some(offset) = line[search..].find(['=', ':']) {
+        let sep = search + offset;
+        search = sep + 1;
+        let key_end = line[..sep].trim_end_matches(['"', '\'']).len();
+        let key_start = line[..key_end].rfind(|c: char| !is_word_char(c)).map(|i| i + 1).unwrap_or(0);
+        if key_start == key_end || key_start < copied || !is_secret_key(&line[key_start..key_end]) {
+            continue;
+        }
+        let after = sep + 1 + (line[sep + 1..].len() - line[sep + 1..].trim_start().len());
+        let (mut start, mut end) = value_end(line, after);
+        // `Authorization: Bearer <token>` keeps the scheme
+        let scheme = &line[start..end];
+        if (scheme.eq_ignore_ascii_case("bearer") || scheme.eq_ignore_ascii_case("basic")) && line[end..].starts_with(' ') {
+            (start, end) = value_end(line, end + 1);
+        }
+        if start >= end {
+            continue;
+        }
+        out.push_str(&line[copied..start]);
+        out.push_str(REDACTED);
+        copied = end;
+        search = end.max(search);
+    }
+    out.push_str(&line[copied..]);
+    out
+}
+
+/// Mask tokens with a well-known prefix, and anything after `Bearer `
+fn redact_tokens(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while !rest.is_empty() {
+        let word_start = rest.find(is_word_char).unwrap_or(rest.len());
+        out.push_str(&rest[..word_start]);
+        rest = &rest[word_start..];
+        let word_end = rest.find(|c: char| !is_word_char(c)).unwrap_or(rest.len());
+        let word = &rest[..word_end];
+        let token = TOKEN_PREFIXES
+            .iter()
+            .find(|prefix| word.starts_with(*prefix))
+            .is_some_and(|prefix| word.len() - prefix.len() >= MIN_TOKEN_LEN);
+        if token {
+            out.push_str(REDACTED);
+        } else {
+            out.push_str(word);
+        }
+        rest = &rest[word_end..];
+        if word.eq_ignore_ascii_case("bearer") && rest.starts_with(' ') {
+            let token_end = rest[1..].find(char::is_whitespace).map(|i| i + 1).unwrap_or(rest.len());
+            if token_end > 1 {
+                out.push(' ');
+                out.push_str(REDACTED);
+                rest = &rest[token_end..];
+            }
+        }
+    }
+    out
+}
+
+/// A log line with secrets masked
+pub fn redact(line: &str) -> Cow<'_, str> {
+    let mut text = Cow::Borrowed(line);
+    for secret in SECRETS.read().unwrap_or_else(|poisoned| poisoned.into_inner()).iter() {
+        if text.contains(secret.as_str()) {
+            text = Cow::Owned(text.replace(secret.as_str(), REDACTED));
+        }
+    }
+    let redacted = redact_tokens(&redact_assignments(&text));
+    if redacted == text {
+        text
+    } else {
+        Cow::Owned(redacted)
+    }
+}
+
+/// Size-based rotation: `name.log` moves to `name.log.1`, `.1` to `.2` and so on
+#[derive(Clone, Copy)]
+struct Rotation {
+    max_bytes: u64,
+    /// Rotated files kept besides the current one
+    keep: usize,
+}
+
+/// Buffered, append-only writer for one log file. The file is opened on first write.
+pub struct LogWriter {
+    path: PathBuf,
+    file: Option<BufWriter<fs::File>>,
+    rotation: Option<Rotation>,
+    /// Size of the current file including buffered lines
+    size: u64,
+}
+
+impl LogWriter {
+    pub fn new(path: PathBuf) -> Self {
+        LogWriter {
+            path,
+            file: None,
+            rotation: None,
+            size: 0,
+        }
+    }
+
+    /// A writer that starts a new file once the current one reaches `max_bytes`,
+    /// keeping the `keep` most recent old files
+    pub fn rotating(path: PathBuf, max_bytes: u64, keep: usize) -> Self {
+        LogWriter {
+            rotation: Some(Rotation { max_bytes, keep }),
+            ..LogWriter::new(path)
+        }
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&mut self, keep: usize) -> std::io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+        let _ = fs::remove_file(self.rotated_path(keep));
+        for index in (1..keep).rev() {
+            let _ = fs::rename(self.rotated_path(index), self.rotated_path(index + 1));
+        }
+        if keep > 0 {
+            fs::rename(&self.path, self.rotated_path(1))
+        } else {
+            fs::remove_file(&self.path)
+        }
+    }
+
+    /// Buffer a timestamped line
+    pub fn write_line(&mut self, msg: &str) -> std::io::Result<()> {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.write_raw(format!("[{}] {}\n", timestamp, msg).as_bytes())
+    }
+
+    /// Buffer already formatted output, with secrets redacted
+    pub fn write_raw(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        let redacted = match std::str::from_utf8(bytes) {
+            Ok(text) => match redact(text) {
+                Cow::Owned(text) => Cow::Owned(text.into_bytes()),
+                Cow::Borrowed(_) => Cow::Borrowed(bytes),
+            },
+            Err(_) => Cow::Borrowed(bytes),
+        };
+        let bytes = redacted.as_ref();
+        if self.file.is_none() {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+            self.size = file.metadata().map(|m| m.len()).unwrap_or(0);
+            self.file = Some(BufWriter::new(file));
+        }
+        self.file.as_mut().unwrap().write_all(bytes)?;
+        self.size += bytes.len() as u64;
+
+        if let Some(rotation) = self.rotation {
+            if self.size >= rotation.max_bytes {
+                self.rotate(rotation.keep)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write buffered lines to the file
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+struct SharedLog {
+    writer: Option<LogWriter>,
+    /// Lines were written since the last flush
+    pending: bool,
+}
+
+static LOG: Mutex<SharedLog> = Mutex::new(SharedLog {
+    writer: None,
+    pending: false,
+});
+static LOG_PENDING: Condvar = Condvar::new();
+static FLUSHER: Once = Once::new();
+
+fn lock_log() -> MutexGuard<'static, SharedLog> {
+    // A panic while logging must not disable logging (or the panic hook's flush)
+    LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Background thread that sleeps until something is logged, then flushes after a short delay
+fn start_flusher() {
+    FLUSHER.call_once(|| {
+        let _ = std::thread::Builder::new()
+            .name("log-flusher".to_string())
+            .spawn(|| loop {
+                {
+                    let mut log = lock_log();
+                    while !log.pending {
+                        log = LOG_PENDING.wait(log).unwrap_or_else(|poisoned| poisoned.into_inner());
+                    }
+                }
+                std::thread::sleep(LOG_FLUSH_DELAY);
+                flush();
+            });
+    });
+}
+
+/// Largest startup.log before it is rotated
+const STARTUP_LOG_MAX_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Rotated files kept per log unless `log_retained_files` says otherwise
+const DEFAULT_RETAINED_FILES: usize = 4;
+
+/// Upper bound for `log_retained_files`
+pub(crate) const MAX_RETAINED_FILES: usize = 20;
+
+/// Rotated files to keep per log, from config.json (see `init`)
+static RETAINED_FILES: AtomicUsize = AtomicUsize::new(DEFAULT_RETAINED_FILES);
+
+/// `log_retained_files` from config.json, clamped
+fn retained_files(config: &serde_json::Value) -> usize {
+    config
+        .get("log_retained_files")
+        .and_then(serde_json::Value::as_u64)
+        .map(|keep| (keep as usize).min(MAX_RETAINED_FILES))
+        .unwrap_or(DEFAULT_RETAINED_FILES)
+}
+
+/// Delete rotated copies of `path` beyond the newest `keep`, e.g. after the setting was lowered
+fn prune_rotated(path: &Path, keep: usize) {
+    for index in keep + 1..=MAX_RETAINED_FILES + 1 {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(format!(".{}", index));
+        let _ = fs::remove_file(PathBuf::from(rotated));
+    }
+}
+
+/// Path of the shell's own log, `<Leaxer dir>/startup.log`
+pub fn startup_log_path() -> Option<PathBuf> {
+    get_leaxer_user_dir().map(|dir| dir.join("startup.log"))
+}
+
+/// Lines kept in memory by `remember`
+const RECENT_LINES: usize = 2000;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Keep redacted lines in the in-memory buffer, dropping the oldest beyond `RECENT_LINES`
+fn remember(text: &str) {
+    let recent = RECENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    remember_in(recent, text);
+}
+
+fn remember_in(mut recent: MutexGuard<'_, VecDeque<String>>, text: &str) {
+    let text = redact(text);
+    for line in text.lines().filter(|line| !line.is_empty()) {
+        if recent.len() == RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line.to_string());
+    }
+}
+
+/// The last `lines` shell log lines, oldest first, whether or not they reached the file
+pub fn recent_lines(lines: usize) -> Vec<String> {
+    let recent = RECENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    recent.iter().skip(recent.len().saturating_sub(lines)).cloned().collect()
+}
+
+/// `recent_lines` for the panic hook: None instead of waiting if the buffer is locked
+pub fn try_recent_lines(lines: usize) -> Option<Vec<String>> {
+    let recent = match RECENT.try_lock() {
+        Ok(recent) => recent,
+        Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(std::sync::TryLockError::WouldBlock) => return None,
+    };
+    Some(recent.iter().skip(recent.len().saturating_sub(lines)).cloned().collect())
+}
+
+fn write_shared(log: &mut SharedLog, write: impl FnOnce(&mut LogWriter) -> std::io::Result<()>) {
+    if log.writer.is_none() {
+        let keep = RETAINED_FILES.load(Ordering::SeqCst);
+        log.writer = startup_log_path().map(|path| LogWriter::rotating(path, STARTUP_LOG_MAX_BYTES, keep));
+    }
+    if let Some(writer) = log.writer.as_mut() {
+        if write(writer).is_ok() && !log.pending {
+            log.pending = true;
+            LOG_PENDING.notify_one();
+        }
+    }
+}
+
+/// `tracing` output, appended to the shared log as formatted
+struct SharedLogWriter;
+
+impl Write for SharedLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        remember(&String::from_utf8_lossy(buf));
+        write_shared(&mut lock_log(), |writer| writer.write_raw(buf));
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Overrides `log_level` from config.json, e.g. `LEAXER_LOG_LEVEL=debug`. Also how the
+/// level reaches the backend.
+pub const LOG_LEVEL_ENV: &str = "LEAXER_LOG_LEVEL";
+
+/// Verbosity chosen by `init`, as a `LogLevel` discriminant
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+enum LogLevel {
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 5] = [LogLevel::Error, LogLevel::Warning, LogLevel::Info, LogLevel::Debug, LogLevel::Trace];
+
+    fn parse(name: &str) -> Option<LogLevel> {
+        match name.trim().to_lowercase().as_str() {
+            "error" => Some(LogLevel::Error),
+            "warn" | "warning" => Some(LogLevel::Warning),
+            "info" => Some(LogLevel::Info),
+            "debug" => Some(LogLevel::Debug),
+            "trace" => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+
+    fn tracing(self) -> tracing::Level {
+        match self {
+            LogLevel::Error => tracing::Level::ERROR,
+            LogLevel::Warning => tracing::Level::WARN,
+            LogLevel::Info => tracing::Level::INFO,
+            LogLevel::Debug => tracing::Level::DEBUG,
+            LogLevel::Trace => tracing::Level::TRACE,
+        }
+    }
+
+    /// Name of the level in Elixir's Logger, which has nothing below debug
+    fn backend(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warning => "warning",
+            LogLevel::Info => "info",
+            LogLevel::Debug | LogLevel::Trace => "debug",
+        }
+    }
+}
+
+/// `LEAXER_LOG_LEVEL` if it names a level, else `log_level` from config.json, else info
+fn log_level(config: &serde_json::Value, env: Option<&str>) -> LogLevel {
+    env.and_then(LogLevel::parse)
+        .or_else(|| config.get("log_level").and_then(serde_json::Value::as_str).and_then(LogLevel::parse))
+        .unwrap_or(LogLevel::Info)
+}
+
+fn current_level() -> LogLevel {
+    let level = LEVEL.load(Ordering::SeqCst);
+    LogLevel::ALL.into_iter().find(|l| *l as u8 == level).unwrap_or(LogLevel::Info)
+}
+
+/// Logger level for the backend, passed to it as `LEAXER_LOG_LEVEL`
+pub fn backend_log_level() -> &'static str {
+    current_level().backend()
+}
+
+/// Install the `tracing` subscriber writing to the shell log. Lines logged before this
+/// are written as plain timestamped lines. Also applies `log_retained_files` and prunes
+/// rotated logs beyond it.
+pub fn init(config: &serde_json::Value) {
+    let keep = retained_files(config);
+    RETAINED_FILES.store(keep, Ordering::SeqCst);
+    {
+        // Reopened with the configured rotation on the next line
+        let mut log = lock_log();
+        if let Some(mut writer) = log.writer.take() {
+            let _ = writer.flush();
+        }
+    }
+    for path in [startup_log_path(), backend_log_path()].into_iter().flatten() {
+        prune_rotated(&path, keep);
+    }
+
+    let level = log_level(config, std::env::var(LOG_LEVEL_ENV).ok().as_deref());
+    LEVEL.store(level as u8, Ordering::SeqCst);
+
+    start_flusher();
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::Layer;
+
+    let json = config.get("log_format").and_then(serde_json::Value::as_str) == Some("json");
+    // Checked per event, so `set_level` takes effect without a new subscriber
+    let filter = tracing_subscriber::filter::filter_fn(|metadata| *metadata.level() <= current_level().tracing());
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(|| SharedLogWriter)
+        .with_ansi(false)
+        .with_target(false);
+    let result = if json {
+        tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer.json().with_filter(filter)))
+    } else {
+        tracing::subscriber::set_global_default(tracing_subscriber::registry().with(layer.with_filter(filter)))
+    };
+    if result.is_err() {
+        log_to_file("[Leaxer] A log subscriber was already installed");
+    } else if level != LogLevel::Info {
+        log_to_file(&format!("[Leaxer] Log level: {}", level.backend()));
+    }
+}
+
+/// Apply a changed `log_level` to the shell; `LEAXER_LOG_LEVEL` still wins. The backend
+/// picks the level up when it is next started.
+pub fn set_level(config: &serde_json::Value) {
+    let level = log_level(config, std::env::var(LOG_LEVEL_ENV).ok().as_deref());
+    if LEVEL.swap(level as u8, Ordering::SeqCst) != level as u8 {
+        // A warning, so it shows unless only errors are logged
+        tracing::warn!("[Leaxer] Log level changed to {}", level.backend());
+    }
+}
+
+/// Largest backend.log before it is rotated
+const BACKEND_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Path of the backend's output log, `<Leaxer dir>/logs/backend.log`
+pub fn backend_log_path() -> Option<PathBuf> {
+    get_leaxer_user_dir().map(|dir| dir.join("logs").join("backend.log"))
+}
+
+/// The last `lines` lines of a log file, reading at most its final 64 KiB
+pub fn read_tail(path: &Path, lines: usize) -> Option<String> {
+    const MAX_BYTES: u64 = 64 * 1024;
+
+    let mut file = fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(MAX_BYTES))).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    let text = String::from_utf8_lossy(&bytes);
+    let all: Vec<&str> = text.lines().collect();
+    Some(all[all.len().saturating_sub(lines)..].join("\n"))
+}
+
+/// A backend output line as sent to the UI in `backend-log` events
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct BackendLogLine {
+    /// `debug`, `info`, `warning` or `error`
+    pub level: &'static str,
+    pub message: String,
+}
+
+/// Split an Elixir Logger line (`12:00:00.123 [info] message`) into level and message.
+/// Lines without a level are info, or errors when they came from stderr.
+fn parse_backend_line(line: &str, stderr: bool) -> BackendLogLine {
+    let parsed = line.find('[').and_then(|start| {
+        let rest = &line[start + 1..];
+        let end = rest.find(']')?;
+        let level = match rest[..end].to_lowercase().as_str() {
+            "debug" => "debug",
+            "info" | "notice" => "info",
+            "warn" | "warning" => "warning",
+            "error" | "critical" | "alert" | "emergency" => "error",
+            _ => return None,
+        };
+        Some(BackendLogLine {
+            level,
+            message: rest[end + 1..].trim().to_string(),
+        })
+    });
+    parsed.unwrap_or_else(|| BackendLogLine {
+        level: if stderr { "error" } else { "info" },
+        message: line.to_string(),
+    })
+}
+
+/// Copy lines from one of the backend's output pipes into the shared backend log.
+/// On stdout (`control`), control channel messages are handed to the channel instead.
+/// With an `events` handle every line is also emitted as `backend-log`.
+async fn forward_lines<R>(
+    pipe: R,
+    prefix: &'static str,
+    control: bool,
+    log: std::sync::Arc<Mutex<LogWriter>>,
+    events: Option<tauri::AppHandle>,
+) where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    let mut reader = tokio::io::BufReader::new(pipe);
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let text = String::from_utf8_lossy(&line);
+        if control {
+            if let Some(message) = text.trim_end().strip_prefix(crate::control::PREFIX) {
+                crate::control::receive(message);
+                continue;
+            }
+        }
+        // The UI gets the same redacted lines the log file does
+        let text = redact(text.trim_end());
+        if let Some(app) = events.as_ref() {
+            use tauri::Emitter;
+            let _ = app.emit("backend-log", parse_backend_line(&text, !control));
+        }
+        let mut log = log.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = log.write_line(&format!("{}{}", prefix, text));
+        // Flush once a burst of output has been written, not per line
+        if reader.buffer().is_empty() {
+            let _ = log.flush();
+        }
+    }
+    let _ = log.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).flush();
+}
+
+/// Write the backend's stdout and stderr to the rotating backend log. The process must
+/// have been spawned with both piped. In debug builds and with `developer_mode` the lines
+/// are also sent to the UI as `backend-log` events, for the dev console.
+pub fn capture_backend_output(app: &tauri::AppHandle, process: &mut crate::process_handle::ProcessHandle) {
+    use tauri::Manager;
+
+    let Some(path) = backend_log_path() else {
+        return;
+    };
+    let events = (cfg!(debug_assertions) || app.state::<crate::config::ConfigStore>().get().developer_mode)
+        .then(|| app.clone());
+    let log = std::sync::Arc::new(Mutex::new(LogWriter::rotating(path, BACKEND_LOG_MAX_BYTES, RETAINED_FILES.load(Ordering::SeqCst))));
+    let (stdout, stderr) = process.take_output();
+    if let Some(stdout) = stdout {
+        tauri::async_runtime::spawn(forward_lines(stdout, "", true, log.clone(), events.clone()));
+    }
+    if let Some(stderr) = stderr {
+        tauri::async_runtime::spawn(forward_lines(stderr, "[stderr] ", false, log, events));
+    }
+}
+
+/// Log an info event (since the console is hidden in release)
+pub fn log_to_file(msg: &str) {
+    if tracing::dispatcher::has_been_set() {
+        tracing::info!("{}", msg);
+        return;
+    }
+    start_flusher();
+    remember(msg);
+    write_shared(&mut lock_log(), |writer| writer.write_line(msg));
+}
+
+/// Write everything logged so far to disk. Called on shutdown.
+pub fn flush() {
+    let mut log = lock_log();
+    if let Some(writer) = log.writer.as_mut() {
+        let _ = writer.flush();
+    }
+    log.pending = false;
+}
+
+/// Record a panic and flush the log. Never blocks: if the panicking thread was itself
+/// logging, the lock is held and the buffered lines are left to the flusher.
+pub fn log_panic(info: &dyn std::fmt::Display) {
+    let mut log = match LOG.try_lock() {
+        Ok(log) => log,
+        Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(std::sync::TryLockError::WouldBlock) => return,
+    };
+    let msg = format!("[Leaxer] Panic: {}", info);
+    if let Ok(recent) = RECENT.try_lock() {
+        remember_in(recent, &msg);
+    }
+    write_shared(&mut log, |writer| writer.write_line(&msg));
+    if let Some(writer) = log.writer.as_mut() {
+        let _ = writer.flush();
+    }
+    log.pending = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_timestamped_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("logs").join("startup.log");
+
+        let mut writer = LogWriter::new(log_path.clone());
+        writer.write_line("first").unwrap();
+        writer.write_line("second").unwrap();
+        writer.flush().unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with('[') && lines[0].ends_with("] first"));
+        assert!(lines[1].ends_with("] second"));
+    }
+
+    #[test]
+    fn lines_are_buffered_until_flushed() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("startup.log");
+        fs::write(&log_path, "[0] earlier run\n").unwrap();
+
+        let mut writer = LogWriter::new(log_path.clone());
+        writer.write_line("buffered").unwrap();
+        assert_eq!(fs::read_to_string(&log_path).unwrap(), "[0] earlier run\n");
+
+        writer.flush().unwrap();
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert!(content.starts_with("[0] earlier run\n"));
+        assert!(content.trim_end().ends_with("] buffered"));
+    }
+
+    #[test]
+    fn formatted_output_is_written_verbatim() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("startup.log");
+
+        let mut writer = LogWriter::new(log_path.clone());
+        writer.write_raw(b"{\"level\":\"INFO\",\"fields\":{\"message\":\"ready\"}}\n").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(fs::read_to_string(&log_path).unwrap(), "{\"level\":\"INFO\",\"fields\":{\"message\":\"ready\"}}\n");
+    }
+
+    #[test]
+    fn rotates_and_keeps_the_newest_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("backend.log");
+
+        // Every line is over the limit, so each one ends up in its own file
+        let mut writer = LogWriter::rotating(log_path.clone(), 10, 2);
+        for line in ["one", "two", "three", "four"] {
+            writer.write_line(&format!("line {}", line)).unwrap();
+        }
+        writer.flush().unwrap();
+
+        assert!(!log_path.exists());
+        let first = fs::read_to_string(dir.path().join("backend.log.1")).unwrap();
+        let second = fs::read_to_string(dir.path().join("backend.log.2")).unwrap();
+        assert!(first.trim_end().ends_with("line four"));
+        assert!(second.trim_end().ends_with("line three"));
+        assert!(!dir.path().join("backend.log.3").exists());
+    }
+
+    #[test]
+    fn prunes_rotated_files_beyond_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("startup.log");
+        for name in ["startup.log", "startup.log.1", "startup.log.2", "startup.log.3", "startup.log.7"] {
+            fs::write(dir.path().join(name), "x").unwrap();
+        }
+
+        prune_rotated(&log_path, 2);
+        assert!(log_path.exists() && dir.path().join("startup.log.2").exists());
+        assert!(!dir.path().join("startup.log.3").exists());
+        assert!(!dir.path().join("startup.log.7").exists());
+
+        assert_eq!(retained_files(&serde_json::json!({ "log_retained_files": 100 })), MAX_RETAINED_FILES);
+        assert_eq!(retained_files(&serde_json::json!({})), DEFAULT_RETAINED_FILES);
+    }
+
+    #[test]
+    fn environment_overrides_the_configured_level() {
+        let config = serde_json::json!({ "log_level": "debug" });
+        assert_eq!(log_level(&config, None), LogLevel::Debug);
+        assert_eq!(log_level(&config, Some("WARN")), LogLevel::Warning);
+        assert_eq!(log_level(&config, Some("loud")), LogLevel::Debug);
+        assert_eq!(log_level(&serde_json::json!({}), None), LogLevel::Info);
+        assert_eq!(LogLevel::Trace.backend(), "debug");
+    }
+
+    #[test]
+    fn secrets_are_redacted_before_writing() {
+        register_secret("0123456789abcdef-cookie");
+        assert_eq!(redact("SECRET_KEY_BASE=abc123 PORT=4000"), "SECRET_KEY_BASE=[redacted] PORT=4000");
+        assert_eq!(
+            redact(r#"{"hf_token": "x y", "port": 4000}"#),
+            r#"{"hf_token": "[redacted]", "port": 4000}"#
+        );
+        assert_eq!(redact("Authorization: Bearer abc.def"), "Authorization: Bearer [redacted]");
+        assert_eq!(redact("using hf_abcdefghijklmnopqrstu now"), "using [redacted] now");
+        assert_eq!(redact("cookie is 0123456789abcdef-cookie"), "cookie is [redacted]");
+        assert_eq!(
+            redact(r#"{"message":"SECRET_KEY_BASE=\"abc\"","level":"DEBUG"}"#),
+            r#"{"message":"SECRET_KEY_BASE=\"[redacted]\"","level":"DEBUG"}"#
+        );
+        let plain = "12:00:00.123 [info] Running on http://127.0.0.1:4000";
+        assert_eq!(redact(plain), plain);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.log");
+        let mut writer = LogWriter::new(path.clone());
+        writer.write_line("SIGNING_SALT=\"pepper\"").unwrap();
+        writer.flush().unwrap();
+        assert!(fs::read_to_string(&path).unwrap().ends_with("SIGNING_SALT=\"[redacted]\"\n"));
+    }
+
+    #[test]
+    fn secret_keys_match_whole_words() {
+        for key in ["SECRET_KEY_BASE", "HF_TOKEN", "x-leaxer-shell-token", "api_key", "openaiApiKey", "authToken"] {
+            assert!(is_secret_key(key), "{}", key);
+        }
+        for key in ["max_tokens", "tokens_per_second", "tokenizer", "tokenizer_path", "PORT", "saltwater"] {
+            assert!(!is_secret_key(key), "{}", key);
+        }
+        assert_eq!(redact("max_tokens=512 tokenizer=clip"), "max_tokens=512 tokenizer=clip");
+    }
+
+    #[test]
+    fn keeps_the_most_recent_lines() {
+        for index in 0..RECENT_LINES + 5 {
+            remember(&format!("recent line {}\n", index));
+        }
+        let lines = recent_lines(RECENT_LINES + 10);
+        assert_eq!(lines.len(), RECENT_LINES);
+        assert!(lines.iter().all(|line| line != "recent line 4"));
+        assert!(lines.contains(&format!("recent line {}", RECENT_LINES + 4)));
+    }
+
+    #[test]
+    fn parses_backend_log_levels() {
+        let line = parse_backend_line("12:00:00.123 [warning] Model cache is full", false);
+        assert_eq!((line.level, line.message.as_str()), ("warning", "Model cache is full"));
+        assert_eq!(parse_backend_line("[notice] Started", false).level, "info");
+
+        let plain = parse_backend_line("Protocol 'inet_tcp': register/listen error: [eaddrinuse]", true);
+        assert_eq!((plain.level, plain.message.as_str()), ("error", "Protocol 'inet_tcp': register/listen error: [eaddrinuse]"));
+    }
+
+    #[test]
+    fn reads_the_last_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("backend.log");
+        fs::write(&log_path, "one\ntwo\nthree\n").unwrap();
+
+        assert_eq!(read_tail(&log_path, 2).unwrap(), "two\nthree");
+        assert_eq!(read_tail(&log_path, 10).unwrap(), "one\ntwo\nthree");
+        assert!(read_tail(&dir.path().join("missing.log"), 2).is_none());
+    }
+}
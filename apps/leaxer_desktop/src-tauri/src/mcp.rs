@@ -0,0 +1,346 @@
+//! Opt-in Model Context Protocol server, so other AI tools can use Leaxer as a source.
+//!
+//! Speaks newline-delimited JSON-RPC 2.0 over two transports:
+//! - stdio, by launching the app with `--mcp-stdio` (what MCP clients spawn; no window opens)
+//! - a local socket (`mcp.sock` in the Leaxer dir, or the `leaxer-mcp` named pipe on
+//!   Windows) while the app runs with `mcp_server_enabled` set in config.json
+//!
+//! Tools are thin wrappers over the backend's REST API, so the backend must be running.
+
+use serde_json::{json, Value};
+use tauri::Manager;
+use tauri_plugin_http::reqwest;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::logging::log_to_file;
+use crate::net::backend_http_url;
+
+/// Command-line flag that runs the stdio transport instead of the app
+pub const MCP_STDIO_FLAG: &str = "--mcp-stdio";
+
+/// Config key that enables the socket transport
+pub const MCP_CONFIG_KEY: &str = "mcp_server_enabled";
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+#[cfg(target_os = "windows")]
+const PIPE_NAME: &str = r"\\.\pipe\leaxer-mcp";
+
+/// Tool definitions returned by `tools/list`
+fn tool_definitions() -> Value {
+    let no_args = json!({ "type": "object", "properties": {} });
+    let string_arg = |name: &str, description: &str| {
+        json!({
+            "type": "object",
+            "properties": { name: { "type": "string", "description": description } },
+            "required": [name],
+        })
+    };
+
+    json!([
+        {
+            "name": "list_workflows",
+            "description": "List saved Leaxer workflows with their modification times",
+            "inputSchema": no_args,
+        },
+        {
+            "name": "get_workflow",
+            "description": "Get the full node graph of a saved workflow",
+            "inputSchema": string_arg("name", "Workflow name as returned by list_workflows"),
+        },
+        {
+            "name": "list_chats",
+            "description": "List saved Leaxer chat sessions",
+            "inputSchema": no_args,
+        },
+        {
+            "name": "get_chat",
+            "description": "Get the messages of a chat session",
+            "inputSchema": string_arg("id", "Chat id as returned by list_chats"),
+        },
+        {
+            "name": "search",
+            "description": "Find workflows and chats whose name contains the query (case-insensitive)",
+            "inputSchema": string_arg("query", "Text to look for"),
+        },
+        {
+            "name": "list_models",
+            "description": "List the models installed in Leaxer",
+            "inputSchema": no_args,
+        },
+        {
+            "name": "validate_workflow",
+            "description": "Check a workflow graph for errors without running it",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "workflow": { "type": "object", "description": "Workflow JSON" } },
+                "required": ["workflow"],
+            },
+        },
+    ])
+}
+
+fn encode_segment(segment: &str) -> String {
+    percent_encoding::utf8_percent_encode(segment, percent_encoding::NON_ALPHANUMERIC).to_string()
+}
+
+fn string_arg<'a>(args: &'a Value, name: &str) -> Result<&'a str, String> {
+    args.get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("Missing string argument '{}'", name))
+}
+
+async fn backend_get(client: &reqwest::Client, path: &str) -> Result<Value, String> {
+    let response = client
+        .get(format!("{}/api/{}", backend_http_url()?, path))
+        .send()
+        .await
+        .map_err(|e| format!("Leaxer backend is not reachable: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Backend returned {}", response.status()));
+    }
+    response.json().await.map_err(|e| e.to_string())
+}
+
+/// Entries of `list` whose "name" contains `query`, tagged with `kind`
+fn matching_names(list: Option<&Value>, kind: &str, query: &str) -> Vec<Value> {
+    list.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|entry| {
+            entry
+                .get("name")
+                .and_then(Value::as_str)
+                .is_some_and(|name| name.to_lowercase().contains(query))
+        })
+        .map(|entry| {
+            let mut entry = entry.clone();
+            entry["kind"] = json!(kind);
+            entry
+        })
+        .collect()
+}
+
+/// Run a tool and return its result as JSON
+async fn call_tool(client: &reqwest::Client, name: &str, args: &Value) -> Result<Value, String> {
+    match name {
+        "list_workflows" => backend_get(client, "workflows").await,
+        "get_workflow" => backend_get(client, &format!("workflows/{}", encode_segment(string_arg(args, "name")?))).await,
+        "list_chats" => backend_get(client, "chats").await,
+        "get_chat" => backend_get(client, &format!("chats/{}", encode_segment(string_arg(args, "id")?))).await,
+        "list_models" => backend_get(client, "models").await,
+        "search" => {
+            let query = string_arg(args, "query")?.to_lowercase();
+            let (workflows, chats) = tokio::join!(backend_get(client, "workflows"), backend_get(client, "chats"));
+            let mut results = matching_names(workflows?.get("workflows"), "workflow", &query);
+            results.extend(matching_names(chats?.get("sessions"), "chat", &query));
+            Ok(json!({ "results": results }))
+        }
+        "validate_workflow" => {
+            let workflow = args.get("workflow").ok_or("Missing argument 'workflow'")?;
+            let response = client
+                .post(format!("{}/api/workflow/validate", backend_http_url()?))
+                .json(workflow)
+                .send()
+                .await
+                .map_err(|e| format!("Leaxer backend is not reachable: {}", e))?;
+            response.json().await.map_err(|e| e.to_string())
+        }
+        _ => Err(format!("Unknown tool: {}", name)),
+    }
+}
+
+fn rpc_result(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Handle one JSON-RPC message. Returns the response, or `None` for notifications.
+pub async fn handle_message(client: &reqwest::Client, message: &str) -> Option<Value> {
+    let request: Value = match serde_json::from_str(message) {
+        Ok(request) => request,
+        Err(_) => return Some(rpc_error(Value::Null, -32700, "Parse error")),
+    };
+    // Notifications (no id) never get a response
+    let id = request.get("id").cloned()?;
+    let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let response = match method {
+        "initialize" => rpc_result(
+            id,
+            json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": "leaxer", "version": env!("CARGO_PKG_VERSION") },
+            }),
+        ),
+        "ping" => rpc_result(id, json!({})),
+        "tools/list" => rpc_result(id, json!({ "tools": tool_definitions() })),
+        "tools/call" => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+            let args = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+            // Tool failures are results with isError, so the calling model can see them
+            let (text, is_error) = match call_tool(client, name, &args).await {
+                Ok(value) => (serde_json::to_string_pretty(&value).unwrap_or_default(), false),
+                Err(e) => (e, true),
+            };
+            rpc_result(
+                id,
+                json!({ "content": [{ "type": "text", "text": text }], "isError": is_error }),
+            )
+        }
+        _ => rpc_error(id, -32601, "Method not found"),
+    };
+    Some(response)
+}
+
+/// Serve one client until it disconnects
+async fn serve<R, W>(client: reqwest::Client, reader: R, mut writer: W) -> std::io::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_message(&client, &line).await {
+            let mut out = serde_json::to_vec(&response).unwrap_or_default();
+            out.push(b'\n');
+            writer.write_all(&out).await?;
+            writer.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Run the stdio transport until stdin closes. Used instead of starting the app.
+pub fn run_stdio() {
+    let result = tauri::async_runtime::block_on(serve(
+        reqwest::Client::new(),
+        tokio::io::BufReader::new(tokio::io::stdin()),
+        tokio::io::stdout(),
+    ));
+    if let Err(e) = result {
+        log_to_file(&format!("[Leaxer] MCP stdio transport failed: {}", e));
+    }
+    crate::logging::flush();
+}
+
+/// Accept local socket connections while the app runs (opt-in via `mcp_server_enabled`)
+pub fn start_socket_server(app: &tauri::AppHandle) {
+    if !app.state::<crate::config::ConfigStore>().get_bool(MCP_CONFIG_KEY) {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = accept_connections().await {
+            log_to_file(&format!("[Leaxer] MCP socket server stopped: {}", e));
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn accept_connections() -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = crate::paths::get_leaxer_user_dir().ok_or_else(|| std::io::Error::other("no Leaxer dir"))?;
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("mcp.sock");
+    // A socket left behind by a crashed instance would make bind fail
+    let _ = std::fs::remove_file(&path);
+
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    log_to_file(&format!("[Leaxer] MCP server listening on {:?}", path));
+
+    let client = reqwest::Client::new();
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let client = client.clone();
+        tauri::async_runtime::spawn(async move {
+            let (reader, writer) = stream.into_split();
+            let _ = serve(client, tokio::io::BufReader::new(reader), writer).await;
+        });
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn accept_connections() -> std::io::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let mut server = ServerOptions::new().first_pipe_instance(true).create(PIPE_NAME)?;
+    log_to_file(&format!("[Leaxer] MCP server listening on {}", PIPE_NAME));
+
+    let client = reqwest::Client::new();
+    loop {
+        server.connect().await?;
+        let connected = server;
+        // Create the next instance before serving, so new clients can connect meanwhile
+        server = ServerOptions::new().create(PIPE_NAME)?;
+
+        let client = client.clone();
+        tauri::async_runtime::spawn(async move {
+            let (reader, writer) = tokio::io::split(connected);
+            let _ = serve(client, tokio::io::BufReader::new(reader), writer).await;
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(message: &str) -> Option<Value> {
+        tauri::async_runtime::block_on(handle_message(&reqwest::Client::new(), message))
+    }
+
+    #[test]
+    fn initialize_advertises_tools() {
+        let response = handle(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert!(response["result"]["capabilities"]["tools"].is_object());
+    }
+
+    #[test]
+    fn lists_tools_with_schemas() {
+        let response = handle(r#"{"jsonrpc":"2.0","id":"a","method":"tools/list"}"#).unwrap();
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert!(tools.iter().any(|tool| tool["name"] == "search"));
+        assert!(tools.iter().all(|tool| tool["inputSchema"]["type"] == "object"));
+    }
+
+    #[test]
+    fn notifications_get_no_response() {
+        assert!(handle(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).is_none());
+    }
+
+    #[test]
+    fn errors_are_reported() {
+        let response = handle("not json").unwrap();
+        assert_eq!(response["error"]["code"], -32700);
+
+        let response = handle(r#"{"jsonrpc":"2.0","id":2,"method":"resources/list"}"#).unwrap();
+        assert_eq!(response["error"]["code"], -32601);
+
+        let response = handle(
+            r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"get_chat","arguments":{}}}"#,
+        )
+        .unwrap();
+        assert_eq!(response["result"]["isError"], true);
+    }
+
+    #[test]
+    fn search_matches_names_case_insensitively() {
+        let list = json!([{ "name": "Portrait Upscale" }, { "name": "landscape" }]);
+        let results = matching_names(Some(&list), "workflow", "upscale");
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["kind"], "workflow");
+    }
+}
@@ -0,0 +1,151 @@
+//! mDNS/Bonjour advertisement of a backend exposed on the network, so companion apps and
+//! other machines find it without typing an IP.
+//!
+//! While `network_exposure_enabled` and `network_mdns` (on by default) are set, the backend
+//! is announced as `_leaxer._tcp` under "Leaxer on <host>", on the port other devices use:
+//! the TLS listener's when there is one. TXT records carry `scheme`, `path` (the socket),
+//! `pairing` and the app `version`. The announcement is renewed whenever the backend is
+//! spawned and withdrawn with a goodbye packet on exit.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+
+use crate::logging::log_to_file;
+
+const SERVICE_TYPE: &str = "_leaxer._tcp.local.";
+
+/// How long to wait for the goodbye packet to go out
+const UNREGISTER_TIMEOUT: Duration = Duration::from_secs(1);
+
+struct Advertisement {
+    daemon: ServiceDaemon,
+    fullname: String,
+}
+
+/// The running announcement, if any
+static ADVERTISED: Mutex<Option<Advertisement>> = Mutex::new(None);
+
+/// This machine's name as an mDNS host label
+fn host_label(host: &str) -> String {
+    let label: String = host
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let label = label.trim_matches('-');
+    if label.is_empty() {
+        "leaxer".to_string()
+    } else {
+        label.to_string()
+    }
+}
+
+type TxtProperties = Vec<(&'static str, String)>;
+
+/// What other devices need to connect: the port and the TXT properties
+fn service_details(port: u16, tls: Option<&crate::tls::TlsInfo>, pairing: bool) -> (u16, TxtProperties) {
+    let (port, scheme) = match tls {
+        Some(tls) => (tls.port, "https"),
+        None => (port, "http"),
+    };
+    let properties = vec![
+        ("scheme", scheme.to_string()),
+        ("path", "/socket".to_string()),
+        ("pairing", if pairing { "required" } else { "none" }.to_string()),
+        ("version", env!("CARGO_PKG_VERSION").to_string()),
+    ];
+    (port, properties)
+}
+
+fn register(port: u16, pairing: bool) -> Result<Advertisement, String> {
+    let host = sysinfo::System::host_name().unwrap_or_default();
+    let label = host_label(&host);
+    let (port, properties) = service_details(port, crate::tls::active().as_ref(), pairing);
+    let ip = crate::net::lan_ip().map(|ip| ip.to_string()).unwrap_or_default();
+    let instance = format!("Leaxer on {}", if host.is_empty() { &label } else { &host });
+
+    let daemon = ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS: {}", e))?;
+    let info = ServiceInfo::new(
+        SERVICE_TYPE,
+        &instance,
+        &format!("{}.local.", label),
+        ip.as_str(),
+        port,
+        &properties.iter().map(|(key, value)| (*key, value.as_str())).collect::<Vec<_>>()[..],
+    )
+    .map_err(|e| format!("Invalid mDNS service: {}", e))?
+    .enable_addr_auto();
+    let fullname = info.get_fullname().to_string();
+    if let Err(e) = daemon.register(info) {
+        let _ = daemon.shutdown();
+        return Err(format!("Failed to advertise over mDNS: {}", e));
+    }
+    log_to_file(&format!("[Leaxer] Advertising {:?} over mDNS on port {}", instance, port));
+    Ok(Advertisement { daemon, fullname })
+}
+
+/// Withdraw the announcement, if any
+pub fn stop() {
+    let Some(advertised) = ADVERTISED.lock().unwrap().take() else {
+        return;
+    };
+    if let Ok(done) = advertised.daemon.unregister(&advertised.fullname) {
+        let _ = done.recv_timeout(UNREGISTER_TIMEOUT);
+    }
+    let _ = advertised.daemon.shutdown();
+    log_to_file("[Leaxer] Stopped mDNS advertisement");
+}
+
+/// Announce a backend on `port` when `enabled`, replacing any earlier announcement, or
+/// withdraw it otherwise
+fn update(port: u16, enabled: bool, pairing: bool) {
+    stop();
+    if !enabled {
+        return;
+    }
+    match register(port, pairing) {
+        Ok(advertised) => *ADVERTISED.lock().unwrap() = Some(advertised),
+        Err(e) => log_to_file(&format!("[Leaxer] {}", e)),
+    }
+}
+
+/// Bring the announcement in line with the settings and the spawned backend
+pub fn refresh(app: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    let config = app.state::<crate::config::ConfigStore>().get();
+    let exposed = crate::net::lan_exposed() && crate::net::external_backend().is_none();
+    update(crate::net::backend_port(), exposed && config.network_mdns, config.network_pairing);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_labels_are_dns_safe() {
+        assert_eq!(host_label("Studio-Mac.local"), "Studio-Mac");
+        assert_eq!(host_label("Kim's PC"), "Kim-s-PC");
+        assert_eq!(host_label(""), "leaxer");
+    }
+
+    #[test]
+    fn advertises_the_port_other_devices_use() {
+        let (port, properties) = service_details(4000, None, true);
+        assert_eq!(port, 4000);
+        assert!(properties.contains(&("scheme", "http".to_string())));
+        assert!(properties.contains(&("pairing", "required".to_string())));
+
+        let tls = crate::tls::TlsInfo {
+            port: 4443,
+            fingerprint: "AB:CD".to_string(),
+        };
+        let (port, properties) = service_details(4000, Some(&tls), false);
+        assert_eq!(port, 4443);
+        assert!(properties.contains(&("scheme", "https".to_string())));
+    }
+}
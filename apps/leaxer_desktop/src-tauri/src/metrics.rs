@@ -0,0 +1,114 @@
+//! Startup timings kept on every launch, unlike the `--profile-startup` trace: how long
+//! the backend took to locate and spawn, and when it first answered its health check and
+//! the main window was shown, counted from launch. Each is logged once and returned by
+//! `get_startup_metrics`; backend restarts don't overwrite them.
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::logging::log_to_file;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    /// Finding the bundled backend (duration)
+    ResolveBackend,
+    /// Building the command and spawning the process (duration)
+    Spawn,
+    /// First successful health check (since launch)
+    BackendReady,
+    /// Main window shown (since launch)
+    WindowShown,
+}
+
+impl Phase {
+    fn name(self) -> &'static str {
+        match self {
+            Phase::ResolveBackend => "resolve_backend",
+            Phase::Spawn => "spawn",
+            Phase::BackendReady => "backend_ready",
+            Phase::WindowShown => "window_shown",
+        }
+    }
+}
+
+/// Milliseconds per phase, None until the phase happened
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct StartupMetrics {
+    pub resolve_backend_ms: Option<u64>,
+    pub spawn_ms: Option<u64>,
+    pub backend_ready_ms: Option<u64>,
+    pub window_shown_ms: Option<u64>,
+}
+
+impl StartupMetrics {
+    fn slot(&mut self, phase: Phase) -> &mut Option<u64> {
+        match phase {
+            Phase::ResolveBackend => &mut self.resolve_backend_ms,
+            Phase::Spawn => &mut self.spawn_ms,
+            Phase::BackendReady => &mut self.backend_ready_ms,
+            Phase::WindowShown => &mut self.window_shown_ms,
+        }
+    }
+
+    /// Store the first value for `phase`; false if it already had one
+    fn record(&mut self, phase: Phase, elapsed: Duration) -> bool {
+        let slot = self.slot(phase);
+        if slot.is_some() {
+            return false;
+        }
+        *slot = Some(elapsed.as_millis() as u64);
+        true
+    }
+}
+
+static LAUNCHED: OnceLock<Instant> = OnceLock::new();
+static METRICS: Mutex<StartupMetrics> = Mutex::new(StartupMetrics {
+    resolve_backend_ms: None,
+    spawn_ms: None,
+    backend_ready_ms: None,
+    window_shown_ms: None,
+});
+
+/// Start the clock. Call first thing in `run`.
+pub fn init() {
+    let _ = LAUNCHED.set(Instant::now());
+}
+
+/// Time since `init`
+pub fn since_launch() -> Duration {
+    LAUNCHED.get().map(Instant::elapsed).unwrap_or_default()
+}
+
+/// Record how long `phase` took, or when it happened for the since-launch phases
+pub fn record(phase: Phase, elapsed: Duration) {
+    if METRICS.lock().unwrap().record(phase, elapsed) {
+        log_to_file(&format!("[Leaxer] Startup {}: {} ms", phase.name(), elapsed.as_millis()));
+    }
+}
+
+/// Record a since-launch phase as happening now
+pub fn mark(phase: Phase) {
+    record(phase, since_launch());
+}
+
+/// Timings of this launch's startup phases
+#[tauri::command]
+pub fn get_startup_metrics() -> StartupMetrics {
+    METRICS.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_first_value_per_phase() {
+        let mut metrics = StartupMetrics::default();
+        assert!(metrics.record(Phase::BackendReady, Duration::from_millis(2500)));
+        assert!(!metrics.record(Phase::BackendReady, Duration::from_millis(9000)));
+        assert_eq!(metrics.backend_ready_ms, Some(2500));
+        assert_eq!(metrics.window_shown_ms, None);
+    }
+}
@@ -0,0 +1,265 @@
+//! Built-in stand-in for the Elixir backend, for tests and CI.
+//!
+//! With `--mock-backend` the shell serves this stub on the backend port instead of
+//! spawning the release, so frontend e2e tests and shell integration tests run without
+//! building leaxer_core. It answers the health check, `/api/version` and the list
+//! endpoints with empty data, and speaks enough of the Phoenix channel protocol on
+//! `/socket/websocket` for the UI to connect: every join, push and heartbeat gets an
+//! empty `ok` reply.
+
+use base64::Engine;
+use serde_json::{json, Value};
+use sha1::{Digest, Sha1};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::logging::log_to_file;
+
+pub const MOCK_BACKEND_FLAG: &str = "--mock-backend";
+
+/// Reported by `/api/version`
+pub const MOCK_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "-mock");
+
+/// GUID every WebSocket server appends to the client's key (RFC 6455)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Larger request bodies and WebSocket frames are refused
+const MAX_PAYLOAD_BYTES: u64 = 1024 * 1024;
+
+const MAX_HEADERS: usize = 64;
+
+pub fn enabled(args: &[String]) -> bool {
+    args.iter().any(|arg| arg == MOCK_BACKEND_FLAG)
+}
+
+struct RequestHead {
+    method: String,
+    path: String,
+    content_length: u64,
+    websocket_key: Option<String>,
+}
+
+async fn read_head(reader: &mut BufReader<TcpStream>) -> Option<RequestHead> {
+    let mut line = String::new();
+    reader.read_line(&mut line).await.ok()?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.split('?').next()?.to_string();
+
+    let mut head = RequestHead {
+        method,
+        path,
+        content_length: 0,
+        websocket_key: None,
+    };
+    for _ in 0..MAX_HEADERS {
+        line.clear();
+        reader.read_line(&mut line).await.ok()?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            return Some(head);
+        }
+        let (name, value) = header.split_once(':')?;
+        if name.eq_ignore_ascii_case("content-length") {
+            head.content_length = value.trim().parse().ok()?;
+        } else if name.eq_ignore_ascii_case("sec-websocket-key") {
+            head.websocket_key = Some(value.trim().to_string());
+        }
+    }
+    None
+}
+
+/// Canned answer for an API request
+fn route(method: &str, path: &str) -> (u16, Value) {
+    if method != "GET" {
+        return (200, json!({ "success": true, "id": "mock" }));
+    }
+    match path {
+        "/api/health" => (200, json!({ "status": "healthy" })),
+        "/api/version" => (200, json!({ "version": MOCK_VERSION, "mock": true })),
+        "/api/nodes" => (200, json!({ "nodes": [], "stats": {} })),
+        "/api/workflows" => (200, json!({ "workflows": [] })),
+        "/api/chats" => (200, json!({ "sessions": [] })),
+        "/api/downloads" => (200, json!({ "downloads": [], "count": 0, "active_only": false })),
+        "/api/settings" | "/api/paths" => (200, json!({})),
+        path if path == "/api/models" || path.starts_with("/api/models/") => {
+            (200, json!({ "models": [], "count": 0 }))
+        }
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+/// Reply to a Phoenix channel message (`[join_ref, ref, topic, event, payload]`)
+fn channel_reply(message: &str) -> Option<String> {
+    let message: Value = serde_json::from_str(message).ok()?;
+    let [join_ref, msg_ref, topic, _event, _payload] = message.as_array()?.as_slice() else {
+        return None;
+    };
+    // Messages without a ref expect no reply
+    if msg_ref.is_null() {
+        return None;
+    }
+    Some(json!([join_ref, msg_ref, topic, "phx_reply", { "status": "ok", "response": {} }]).to_string())
+}
+
+fn accept_key(key: &str) -> String {
+    let digest = Sha1::digest(format!("{}{}", key, WEBSOCKET_GUID).as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(digest)
+}
+
+async fn write_frame(stream: &mut TcpStream, opcode: u8, payload: &[u8]) -> std::io::Result<()> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    stream.write_all(&frame).await
+}
+
+/// Read one client frame; returns its opcode and unmasked payload
+async fn read_frame(reader: &mut BufReader<TcpStream>) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header).await?;
+    let opcode = header[0] & 0x0f;
+    let len = match header[1] & 0x7f {
+        126 => reader.read_u16().await? as u64,
+        127 => reader.read_u64().await?,
+        len => len as u64,
+    };
+    if len > MAX_PAYLOAD_BYTES {
+        return Err(std::io::Error::other("frame too large"));
+    }
+    let mut mask = [0u8; 4];
+    if header[1] & 0x80 != 0 {
+        reader.read_exact(&mut mask).await?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).await?;
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+    Ok((opcode, payload))
+}
+
+async fn serve_websocket(mut reader: BufReader<TcpStream>, key: &str) -> std::io::Result<()> {
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    );
+    reader.get_mut().write_all(handshake.as_bytes()).await?;
+    loop {
+        let (opcode, payload) = read_frame(&mut reader).await?;
+        match opcode {
+            // Text
+            0x1 => {
+                if let Some(reply) = channel_reply(&String::from_utf8_lossy(&payload)) {
+                    write_frame(reader.get_mut(), 0x1, reply.as_bytes()).await?;
+                }
+            }
+            // Close
+            0x8 => {
+                write_frame(reader.get_mut(), 0x8, &payload).await?;
+                return Ok(());
+            }
+            // Ping
+            0x9 => write_frame(reader.get_mut(), 0xA, &payload).await?,
+            _ => {}
+        }
+    }
+}
+
+async fn handle_connection(stream: TcpStream) {
+    let mut reader = BufReader::new(stream);
+    let Some(head) = read_head(&mut reader).await else {
+        return;
+    };
+
+    if let (Some(key), true) = (&head.websocket_key, head.path.starts_with("/socket/websocket")) {
+        let _ = serve_websocket(reader, key).await;
+        return;
+    }
+
+    let (status, body) = if head.content_length > MAX_PAYLOAD_BYTES {
+        (413, json!({ "error": "Payload too large" }))
+    } else {
+        // The body doesn't matter to the stub, but has to be read before answering
+        let mut body = vec![0u8; head.content_length as usize];
+        if reader.read_exact(&mut body).await.is_err() {
+            return;
+        }
+        match head.method.as_str() {
+            "OPTIONS" => (204, Value::Null),
+            method => route(method, &head.path),
+        }
+    };
+    let body = if body.is_null() { String::new() } else { body.to_string() };
+    // Any origin: e2e tests load the UI from a dev server
+    let response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Headers: *\r\n\
+         Access-Control-Allow-Methods: GET, POST, PUT, DELETE, OPTIONS\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let stream = reader.get_mut();
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Answer connections on `listener` until the task is dropped
+pub async fn serve(listener: TcpListener) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tauri::async_runtime::spawn(handle_connection(stream));
+    }
+}
+
+/// Serve the stub on the backend port
+pub fn start(port: u16) {
+    tauri::async_runtime::spawn(async move {
+        match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => {
+                log_to_file(&format!("[Leaxer] Mock backend listening on 127.0.0.1:{}", port));
+                serve(listener).await;
+            }
+            Err(e) => log_to_file(&format!("[Leaxer] Mock backend could not bind port {}: {}", port, e)),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_the_rfc_example() {
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn joins_and_heartbeats_get_ok_replies() {
+        let reply = channel_reply(r#"["1","1","graph:main","phx_join",{}]"#).unwrap();
+        let reply: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(reply, json!(["1", "1", "graph:main", "phx_reply", { "status": "ok", "response": {} }]));
+
+        assert!(channel_reply(r#"[null,"2","phoenix","heartbeat",{}]"#).is_some());
+        assert!(channel_reply(r#"[null,null,"graph:main","event",{}]"#).is_none());
+        assert!(channel_reply("not json").is_none());
+    }
+
+    #[test]
+    fn routes_answer_like_the_backend() {
+        assert_eq!(route("GET", "/api/health"), (200, json!({ "status": "healthy" })));
+        assert_eq!(route("GET", "/api/models/llms").1["count"], 0);
+        assert_eq!(route("GET", "/api/nothing").0, 404);
+        assert_eq!(route("POST", "/api/chats").0, 200);
+    }
+}
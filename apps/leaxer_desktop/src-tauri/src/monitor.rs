@@ -0,0 +1,147 @@
+//! Wake-up source for background monitors.
+//!
+//! Monitors block on this signal instead of sleeping on a fixed interval: they wake when
+//! the app reports something that may have changed (window focused, shown from the tray),
+//! or after a long fallback timeout while the window is visible. While the app is hidden in
+//! the tray the signal is paused and monitors do not wake at all until it is shown again.
+//! That suits monitors that only feed the visible UI (input locale, clipboard); anything
+//! the shell itself acts on, like the power source, keeps its own timer.
+
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct SignalState {
+    generation: u64,
+    paused: bool,
+}
+
+#[derive(Default)]
+pub struct MonitorSignal {
+    state: Mutex<SignalState>,
+    cond: Condvar,
+}
+
+/// Why a wait returned
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Wake {
+    /// `notify` was called since the caller last woke
+    Event,
+    /// The fallback timeout elapsed while the app was active
+    Timeout,
+}
+
+impl MonitorSignal {
+    /// Wake every waiting monitor so it re-checks now
+    pub fn notify(&self) {
+        self.state.lock().unwrap().generation += 1;
+        self.cond.notify_all();
+    }
+
+    /// Pause (app hidden in the tray) or resume monitoring; resuming also wakes monitors
+    pub fn set_paused(&self, paused: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.paused = paused;
+        if !paused {
+            state.generation += 1;
+        }
+        drop(state);
+        self.cond.notify_all();
+    }
+
+    /// Block until notified or, while not paused, until `fallback` has elapsed.
+    /// `seen` tracks the last generation the caller handled.
+    pub fn wait(&self, seen: &mut u64, fallback: Duration) -> Wake {
+        let deadline = Instant::now() + fallback;
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.generation != *seen {
+                *seen = state.generation;
+                return Wake::Event;
+            }
+            if state.paused {
+                state = self.cond.wait(state).unwrap();
+                continue;
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Wake::Timeout;
+            }
+            state = self.cond.wait_timeout(state, remaining).unwrap().0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Run a monitor loop with a very short fallback and count how often it wakes
+    fn count_wakeups(signal: Arc<MonitorSignal>, run_for: Duration) -> usize {
+        let wakeups = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let thread = {
+            let (signal, wakeups, stop) = (signal.clone(), wakeups.clone(), stop.clone());
+            std::thread::spawn(move || {
+                let mut seen = 0;
+                while !stop.load(Ordering::SeqCst) {
+                    signal.wait(&mut seen, Duration::from_millis(5));
+                    wakeups.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+        };
+
+        std::thread::sleep(run_for);
+        let count = wakeups.load(Ordering::SeqCst);
+        stop.store(true, Ordering::SeqCst);
+        signal.set_paused(false);
+        thread.join().unwrap();
+        count
+    }
+
+    #[test]
+    fn idle_in_tray_means_no_wakeups() {
+        let signal = Arc::new(MonitorSignal::default());
+        signal.set_paused(true);
+        assert_eq!(count_wakeups(signal.clone(), Duration::from_millis(300)), 0);
+
+        // The same loop wakes on its fallback timeout once the app is visible again
+        assert!(count_wakeups(signal, Duration::from_millis(100)) > 1);
+    }
+
+    #[test]
+    fn notify_wakes_immediately() {
+        let signal = Arc::new(MonitorSignal::default());
+        let waiter = {
+            let signal = signal.clone();
+            std::thread::spawn(move || {
+                let mut seen = 0;
+                let started = Instant::now();
+                (signal.wait(&mut seen, Duration::from_secs(60)), started.elapsed())
+            })
+        };
+        std::thread::sleep(Duration::from_millis(20));
+        signal.notify();
+
+        let (wake, elapsed) = waiter.join().unwrap();
+        assert_eq!(wake, Wake::Event);
+        assert!(elapsed < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn paused_monitors_wake_on_resume() {
+        let signal = Arc::new(MonitorSignal::default());
+        signal.set_paused(true);
+        let waiter = {
+            let signal = signal.clone();
+            std::thread::spawn(move || signal.wait(&mut 0, Duration::from_millis(1)))
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+
+        signal.set_paused(false);
+        assert_eq!(waiter.join().unwrap(), Wake::Event);
+    }
+}
@@ -0,0 +1,466 @@
+//! Native messaging host for the Leaxer browser extension.
+//!
+//! Browsers launch the app as a host process and exchange length-prefixed JSON over
+//! stdio: Chrome passes the calling `chrome-extension://<id>/` origin, Firefox the host
+//! manifest path and the extension id. Only extensions listed under
+//! `browser_extension_ids` in config.json are served; anything else is refused before a
+//! single message is read. Page content and selections are saved as new chats on the
+//! backend, where they show up in the chat list; that part needs the `http` feature.
+
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{json, Value};
+
+use crate::config::{config_path, ConfigStore};
+use crate::logging::log_to_file;
+
+/// Name of the host, as referenced by the extension's `connectNative` calls
+pub const HOST_NAME: &str = "com.leaxer.native";
+
+/// Config key listing the extension ids allowed to talk to the host
+pub const EXTENSION_IDS_CONFIG_KEY: &str = "browser_extension_ids";
+
+/// Browsers refuse host messages larger than 1 MB
+const MAX_OUTGOING_BYTES: usize = 1024 * 1024;
+
+/// Upper bound for a message from the extension; page text beyond this is refused
+const MAX_INCOMING_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default sampling settings of a new chat, matching the UI's DEFAULT_CHAT_SETTINGS
+const DEFAULT_CHAT_SETTINGS: (f64, u32, f64, u32) = (0.7, 2048, 0.9, 40);
+
+/// The extension that launched the host
+#[derive(Debug, PartialEq)]
+pub enum Caller {
+    Chrome(String),
+    Firefox(String),
+}
+
+impl Caller {
+    fn id(&self) -> &str {
+        match self {
+            Caller::Chrome(id) | Caller::Firefox(id) => id,
+        }
+    }
+}
+
+/// Recognize a native messaging launch from the command line, returning the caller
+pub fn detect_caller(args: &[String]) -> Option<Caller> {
+    if let Some(id) = args
+        .iter()
+        .find_map(|arg| arg.strip_prefix("chrome-extension://"))
+    {
+        return Some(Caller::Chrome(id.trim_end_matches('/').to_string()));
+    }
+    match args {
+        [_, manifest, id, ..] if manifest.ends_with(&format!("{}.json", HOST_NAME)) => {
+            Some(Caller::Firefox(id.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// Extension ids allowed in config.json
+fn allowed_extension_ids(config: &Value) -> Vec<String> {
+    config
+        .get(EXTENSION_IDS_CONFIG_KEY)
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_string)
+        .collect()
+}
+
+fn is_allowed(caller: &Caller, allowed: &[String]) -> bool {
+    !caller.id().is_empty() && allowed.iter().any(|id| id == caller.id())
+}
+
+/// Read one message. Returns `None` once the browser closes the pipe.
+pub fn read_message<R: Read>(reader: &mut R) -> io::Result<Option<Value>> {
+    let mut len = [0u8; 4];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_ne_bytes(len) as usize;
+    if len > MAX_INCOMING_BYTES {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "message too large"));
+    }
+    let mut body = vec![0u8; len];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write one message in the browser's framing
+pub fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_vec(message)?;
+    if body.len() > MAX_OUTGOING_BYTES {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "reply too large"));
+    }
+    writer.write_all(&(body.len() as u32).to_ne_bytes())?;
+    writer.write_all(&body)?;
+    writer.flush()
+}
+
+/// `<prefix>_<millis>_<suffix>`, the id format the UI uses for chats and messages
+pub fn new_id(prefix: &str, now: u128) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0)
+        ^ std::process::id();
+    format!("{}_{}_{:05x}", prefix, now, nanos & 0xfffff)
+}
+
+/// Chat session saved for a page or selection sent by the extension (or a webhook)
+pub fn chat_session(kind: &str, message: &Value, now: u128) -> Result<Value, String> {
+    let text = message
+        .get("text")
+        .and_then(Value::as_str)
+        .filter(|text| !text.trim().is_empty())
+        .ok_or("Missing text")?;
+    let url = message.get("url").and_then(Value::as_str).unwrap_or("");
+    let title = message
+        .get("title")
+        .and_then(Value::as_str)
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or(if url.is_empty() { "Browser capture" } else { url });
+
+    let content = match (kind, url.is_empty()) {
+        ("send_selection", false) => format!("> {}\n\nSource: [{}]({})", text.replace('\n', "\n> "), title, url),
+        ("send_selection", true) => format!("> {}", text.replace('\n', "\n> ")),
+        (_, false) => format!("# [{}]({})\n\n{}", title, url, text),
+        (_, true) => format!("# {}\n\n{}", title, text),
+    };
+
+    let message = json!({
+        "id": new_id("msg", now),
+        "role": "user",
+        "content": content,
+        "timestamp": now,
+    });
+    Ok(new_session(title, vec![message], now))
+}
+
+/// A chat session in the shape the backend stores, with the UI's default settings
+pub fn new_session(name: &str, messages: Vec<Value>, now: u128) -> Value {
+    let (temperature, max_tokens, top_p, top_k) = DEFAULT_CHAT_SETTINGS;
+    json!({
+        "id": new_id("chat", now),
+        "name": name,
+        "messages": messages,
+        "created_at": now,
+        "updated_at": now,
+        "model": null,
+        "settings": {
+            "temperature": temperature,
+            "max_tokens": max_tokens,
+            "top_p": top_p,
+            "top_k": top_k,
+        },
+    })
+}
+
+#[cfg(feature = "http")]
+pub async fn save_chat(session: &Value) -> Result<(), String> {
+    let response = tauri_plugin_http::reqwest::Client::new()
+        .post(format!("{}/api/chats", crate::net::backend_http_url()?))
+        .json(session)
+        .send()
+        .await
+        .map_err(|_| "Leaxer is not running".to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Backend returned {}", response.status()));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+pub async fn save_chat(_session: &Value) -> Result<(), String> {
+    Err(crate::features::unavailable("Browser captures", &["http"]))
+}
+
+/// Handle one message from the extension and build the reply
+async fn handle_message(message: &Value) -> Value {
+    let kind = message.get("type").and_then(Value::as_str).unwrap_or("");
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+
+    let result = match kind {
+        "ping" => Ok(json!({ "version": env!("CARGO_PKG_VERSION") })),
+        "send_page" | "send_selection" => match chat_session(kind, message, now) {
+            Ok(session) => save_chat(&session)
+                .await
+                .map(|_| json!({ "chat_id": session["id"] })),
+            Err(e) => Err(e),
+        },
+        _ => Err(format!("Unknown message type: {}", kind)),
+    };
+
+    match result {
+        Ok(mut reply) => {
+            reply["ok"] = json!(true);
+            reply
+        }
+        Err(e) => json!({ "ok": false, "error": e }),
+    }
+}
+
+/// Serve the calling extension until the browser closes stdin. Used instead of starting the app.
+pub fn run_host(caller: Caller) {
+    let allowed = allowed_extension_ids(&ConfigStore::new(config_path()).load());
+    if !is_allowed(&caller, &allowed) {
+        log_to_file(&format!("[Leaxer] Refused native messaging connection from {:?}", caller));
+        let _ = write_message(
+            &mut io::stdout(),
+            &json!({ "ok": false, "error": "This extension is not allowed to connect to Leaxer" }),
+        );
+        crate::logging::flush();
+        return;
+    }
+
+    log_to_file(&format!("[Leaxer] Native messaging host started for {:?}", caller));
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    loop {
+        let message = match read_message(&mut stdin) {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                log_to_file(&format!("[Leaxer] Native messaging read failed: {}", e));
+                break;
+            }
+        };
+        let reply = tauri::async_runtime::block_on(handle_message(&message));
+        if let Err(e) = write_message(&mut stdout, &reply) {
+            log_to_file(&format!("[Leaxer] Native messaging write failed: {}", e));
+            break;
+        }
+    }
+    crate::logging::flush();
+}
+
+/// Chrome extension ids are 32 letters a-p; Firefox ids are emails or GUIDs
+fn is_chrome_id(id: &str) -> bool {
+    id.len() == 32 && id.bytes().all(|b| (b'a'..=b'p').contains(&b))
+}
+
+fn chrome_manifest(exe: &std::path::Path, ids: &[String]) -> Value {
+    let origins: Vec<String> = ids
+        .iter()
+        .filter(|id| is_chrome_id(id))
+        .map(|id| format!("chrome-extension://{}/", id))
+        .collect();
+    json!({
+        "name": HOST_NAME,
+        "description": "Leaxer",
+        "path": exe,
+        "type": "stdio",
+        "allowed_origins": origins,
+    })
+}
+
+fn firefox_manifest(exe: &std::path::Path, ids: &[String]) -> Value {
+    let extensions: Vec<&String> = ids.iter().filter(|id| !is_chrome_id(id)).collect();
+    json!({
+        "name": HOST_NAME,
+        "description": "Leaxer",
+        "path": exe,
+        "type": "stdio",
+        "allowed_extensions": extensions,
+    })
+}
+
+/// Directories browsers search for host manifests: (chrome, firefox)
+#[cfg(target_os = "linux")]
+fn manifest_dirs() -> Option<(PathBuf, PathBuf)> {
+    let home = dirs::home_dir()?;
+    Some((
+        dirs::config_dir()?.join("google-chrome").join("NativeMessagingHosts"),
+        home.join(".mozilla").join("native-messaging-hosts"),
+    ))
+}
+
+#[cfg(target_os = "macos")]
+fn manifest_dirs() -> Option<(PathBuf, PathBuf)> {
+    let support = dirs::home_dir()?.join("Library").join("Application Support");
+    Some((
+        support.join("Google").join("Chrome").join("NativeMessagingHosts"),
+        support.join("Mozilla").join("NativeMessagingHosts"),
+    ))
+}
+
+/// Windows browsers find manifests through the registry, so they live in the Leaxer dir
+#[cfg(target_os = "windows")]
+fn manifest_dirs() -> Option<(PathBuf, PathBuf)> {
+    let dir = crate::paths::get_leaxer_user_dir()?.join("native-messaging");
+    Some((dir.join("chrome"), dir.join("firefox")))
+}
+
+#[cfg(target_os = "windows")]
+fn set_registry(chrome: &std::path::Path, firefox: &std::path::Path, register: bool) -> Result<(), String> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    for (browser, manifest) in [("Google\\Chrome", chrome), ("Mozilla", firefox)] {
+        let key = format!("HKCU\\Software\\{}\\NativeMessagingHosts\\{}", browser, HOST_NAME);
+        let mut cmd = Command::new("reg");
+        if register {
+            cmd.args(["add", &key, "/f", "/ve", "/d"]).arg(manifest);
+        } else {
+            cmd.args(["delete", &key, "/f"]);
+        }
+        let ok = cmd
+            .creation_flags(crate::process::CREATE_NO_WINDOW)
+            .output()
+            .map(|out| out.status.success())
+            .unwrap_or(false);
+        if register && !ok {
+            return Err(format!("Failed to write registry key {}", key));
+        }
+    }
+    Ok(())
+}
+
+/// Write (or remove) the host manifests for Chrome and Firefox
+fn set_host_manifests(ids: &[String], register: bool) -> Result<(), String> {
+    let (chrome_dir, firefox_dir) = manifest_dirs().ok_or("No browser profile directory")?;
+    let file_name = format!("{}.json", HOST_NAME);
+    let chrome_path = chrome_dir.join(&file_name);
+    let firefox_path = firefox_dir.join(&file_name);
+
+    if register {
+        let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+        for (path, manifest) in [
+            (&chrome_path, chrome_manifest(&exe, ids)),
+            (&firefox_path, firefox_manifest(&exe, ids)),
+        ] {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let content = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+            std::fs::write(path, content).map_err(|e| e.to_string())?;
+        }
+    } else {
+        let _ = std::fs::remove_file(&chrome_path);
+        let _ = std::fs::remove_file(&firefox_path);
+    }
+
+    #[cfg(target_os = "windows")]
+    set_registry(&chrome_path, &firefox_path, register)?;
+    Ok(())
+}
+
+/// Allow the given extensions and register the host with Chrome and Firefox for the current user
+#[tauri::command]
+pub async fn register_native_messaging_host(
+    config: tauri::State<'_, ConfigStore>,
+    extension_ids: Vec<String>,
+) -> Result<(), String> {
+    let ids: Vec<String> = extension_ids
+        .into_iter()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    if ids.is_empty() {
+        return Err("At least one extension id is required".to_string());
+    }
+    config.set(EXTENSION_IDS_CONFIG_KEY, json!(ids));
+
+    log_to_file(&format!("[Leaxer] Registering native messaging host for {:?}", ids));
+    tauri::async_runtime::spawn_blocking(move || set_host_manifests(&ids, true))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Remove the host manifests and forget the allowed extensions
+#[tauri::command]
+pub async fn unregister_native_messaging_host(config: tauri::State<'_, ConfigStore>) -> Result<(), String> {
+    config.set(EXTENSION_IDS_CONFIG_KEY, json!([]));
+    log_to_file("[Leaxer] Removing native messaging host");
+    tauri::async_runtime::spawn_blocking(|| set_host_manifests(&[], false))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn detects_browser_launches() {
+        let chrome_id = "abcdefghijklmnopabcdefghijklmnop";
+        assert_eq!(
+            detect_caller(&args(&["leaxer", &format!("chrome-extension://{}/", chrome_id), "--parent-window=0"])),
+            Some(Caller::Chrome(chrome_id.to_string()))
+        );
+        assert_eq!(
+            detect_caller(&args(&["leaxer", "/home/u/.mozilla/native-messaging-hosts/com.leaxer.native.json", "ext@leaxer.ai"])),
+            Some(Caller::Firefox("ext@leaxer.ai".to_string()))
+        );
+        assert_eq!(detect_caller(&args(&["leaxer", "workflow.leaxer"])), None);
+    }
+
+    #[test]
+    fn only_configured_extensions_are_allowed() {
+        let config = json!({ EXTENSION_IDS_CONFIG_KEY: ["ext@leaxer.ai"] });
+        let allowed = allowed_extension_ids(&config);
+        assert!(is_allowed(&Caller::Firefox("ext@leaxer.ai".to_string()), &allowed));
+        assert!(!is_allowed(&Caller::Firefox("evil@example.com".to_string()), &allowed));
+        assert!(!is_allowed(&Caller::Chrome(String::new()), &allowed_extension_ids(&json!({}))));
+    }
+
+    #[test]
+    fn framing_round_trips() {
+        let mut buf = Vec::new();
+        write_message(&mut buf, &json!({ "type": "ping" })).unwrap();
+        write_message(&mut buf, &json!({ "ok": true })).unwrap();
+
+        let mut reader = io::Cursor::new(buf);
+        assert_eq!(read_message(&mut reader).unwrap(), Some(json!({ "type": "ping" })));
+        assert_eq!(read_message(&mut reader).unwrap(), Some(json!({ "ok": true })));
+        assert_eq!(read_message(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn oversized_messages_are_refused() {
+        let mut reader = io::Cursor::new((MAX_INCOMING_BYTES as u32 + 1).to_ne_bytes().to_vec());
+        assert!(read_message(&mut reader).is_err());
+    }
+
+    #[test]
+    fn selections_become_quoted_chats() {
+        let message = json!({ "text": "line one\nline two", "url": "https://example.com", "title": "Example" });
+        let session = chat_session("send_selection", &message, 1000).unwrap();
+        assert_eq!(session["name"], "Example");
+        assert!(session["id"].as_str().unwrap().starts_with("chat_1000_"));
+        assert_eq!(
+            session["messages"][0]["content"],
+            "> line one\n> line two\n\nSource: [Example](https://example.com)"
+        );
+        assert!(chat_session("send_page", &json!({ "text": " " }), 1000).is_err());
+    }
+
+    #[test]
+    fn manifests_split_ids_by_browser() {
+        let ids = vec!["abcdefghijklmnopabcdefghijklmnop".to_string(), "ext@leaxer.ai".to_string()];
+        let exe = std::path::Path::new("/opt/leaxer");
+        assert_eq!(
+            chrome_manifest(exe, &ids)["allowed_origins"],
+            json!(["chrome-extension://abcdefghijklmnopabcdefghijklmnop/"])
+        );
+        assert_eq!(firefox_manifest(exe, &ids)["allowed_extensions"], json!(["ext@leaxer.ai"]));
+    }
+}
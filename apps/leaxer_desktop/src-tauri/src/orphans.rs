@@ -0,0 +1,194 @@
+//! Cleanup of backends left behind by a crashed session.
+//!
+//! While the backend runs its PID, port and shell token are kept in `backend.pid` in the
+//! user dir, and the file is removed when the backend stops. A file still present at launch means
+//! the last shell died without stopping its backend, which would keep the port and make
+//! the new one fail to bind. Such a VM is asked to shut down like any other (falling back
+//! to killing it) rather than adopted: we'd have no handle on its output, no job object
+//! and no way to tell which config it was started with. The PID only counts while it
+//! belongs to a backend VM started before the file was written, so a reused PID is left
+//! alone. Without a record nothing is stopped; a backend still holding the port is then a
+//! port conflict for the user to settle (see `ports`).
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::logging::log_to_file;
+
+const PID_FILE: &str = "backend.pid";
+
+/// How long an orphan gets to exit after being asked to
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(8);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Executable names of a backend VM, without `.exe`
+const BACKEND_EXECUTABLES: &[&str] = &["beam.smp", "erl", "leaxer_core"];
+
+fn pid_file() -> Option<PathBuf> {
+    crate::paths::get_leaxer_user_dir().map(|dir| dir.join(PID_FILE))
+}
+
+/// `"<pid> <port> [<shell token>]"`
+fn parse(contents: &str) -> Option<(u32, u16, Option<String>)> {
+    let mut fields = contents.split_whitespace();
+    let pid = fields.next()?.parse().ok()?;
+    let port = fields.next()?.parse().ok()?;
+    Some((pid, port, fields.next().map(str::to_string)))
+}
+
+/// Remember the running backend
+pub fn record(pid: u32, port: u16, token: Option<&str>) {
+    if let Some(path) = pid_file() {
+        let contents = format!("{} {} {}", pid, port, token.unwrap_or_default());
+        let written = std::fs::write(&path, format!("{}\n", contents.trim_end()));
+        #[cfg(unix)]
+        let written = written.and_then(|_| {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+        });
+        if let Err(e) = written {
+            log_to_file(&format!("[Leaxer] Failed to write {:?}: {}", path, e));
+        }
+    }
+}
+
+/// The backend stopped
+pub fn clear() {
+    if let Some(path) = pid_file() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Whether `pid` is a running Leaxer backend VM
+pub(crate) fn is_backend(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    sys.process(pid)
+        .is_some_and(|process| is_backend_executable(&process.name().to_string_lossy()))
+}
+
+/// Whether `pid` is a backend VM that was already running at `recorded_at`, i.e. the one
+/// recorded then rather than a later process that got the same PID
+fn is_recorded_backend(pid: u32, recorded_at: SystemTime) -> bool {
+    let Ok(recorded_at) = recorded_at.duration_since(SystemTime::UNIX_EPOCH) else {
+        return false;
+    };
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    sys.process(pid).is_some_and(|process| {
+        is_backend_executable(&process.name().to_string_lossy()) && process.start_time() <= recorded_at.as_secs()
+    })
+}
+
+fn is_backend_executable(name: &str) -> bool {
+    let name = name.to_lowercase();
+    let name = name.strip_suffix(".exe").unwrap_or(&name);
+    BACKEND_EXECUTABLES.contains(&name)
+}
+
+fn kill(pid: u32) {
+    // The backend leads its own process group; take erl_child_setup and ports with it.
+    // Anything else only loses the one process.
+    #[cfg(unix)]
+    unsafe {
+        let pid = pid as libc::pid_t;
+        if libc::getpgid(pid) == pid {
+            libc::killpg(pid, libc::SIGKILL);
+        } else {
+            libc::kill(pid, libc::SIGKILL);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let pid = Pid::from_u32(pid);
+        let mut sys = System::new();
+        sys.refresh_processes(ProcessesToUpdate::All, true);
+        for process in sys.processes().values() {
+            if process.pid() == pid || process.parent() == Some(pid) {
+                process.kill();
+            }
+        }
+    }
+}
+
+async fn wait_until(mut done: impl FnMut() -> bool) -> bool {
+    let poll = async {
+        while !done() {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    };
+    tokio::time::timeout(SHUTDOWN_TIMEOUT, poll).await.is_ok()
+}
+
+async fn request_shutdown(port: u16, token: Option<&str>) -> bool {
+    let timeout = Duration::from_secs(2);
+    crate::net::request_http_with_token(port, "POST", "/api/system/shutdown", token, timeout).await == Some(200)
+}
+
+/// Stop a backend that isn't ours to control: ask it to shut down, kill it if it doesn't
+/// exit in time. Without its shell token the request is refused and it is killed.
+pub async fn stop(pid: u32, port: u16, token: Option<&str>) {
+    request_shutdown(port, token).await;
+    if !wait_until(|| !is_backend(pid)).await {
+        log_to_file(&format!("[Leaxer] Backend (PID {}) did not exit in time, killing it", pid));
+        kill(pid);
+    }
+}
+
+/// Stop a backend left running by a previous session before spawning a new one on `port`
+pub async fn reap(port: u16) {
+    let recorded = pid_file().and_then(|path| {
+        let recorded_at = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+        let (pid, port, token) = parse(&std::fs::read_to_string(&path).ok()?)?;
+        Some((pid, port, token, recorded_at))
+    });
+
+    match recorded {
+        Some((pid, old_port, token, recorded_at)) if is_recorded_backend(pid, recorded_at) => {
+            log_to_file(&format!(
+                "[Leaxer] Backend from a previous session still running (PID {}), stopping it",
+                pid
+            ));
+            stop(pid, old_port, token.as_deref()).await;
+        }
+        Some((pid, ..)) => {
+            log_to_file(&format!("[Leaxer] Recorded backend (PID {}) is no longer running", pid));
+        }
+        None => {
+            // Without its token a backend refuses to shut down, and killing whatever holds
+            // the port could hit anything; the port conflict check asks the user instead
+            if std::net::TcpListener::bind(("127.0.0.1", port)).is_err() {
+                log_to_file(&format!("[Leaxer] Port {} is in use and no backend of ours was recorded", port));
+            }
+        }
+    }
+    clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pid_and_port() {
+        assert_eq!(parse("1234 4000\n"), Some((1234, 4000, None)));
+        assert_eq!(parse("1234 4000 abc\n"), Some((1234, 4000, Some("abc".to_string()))));
+        assert_eq!(parse("1234"), None);
+        assert_eq!(parse("garbage 4000"), None);
+    }
+
+    #[test]
+    fn matches_backend_executables_exactly() {
+        for name in ["beam.smp", "erl", "erl.exe", "leaxer_core", "Leaxer_Core.exe"] {
+            assert!(is_backend_executable(name), "{}", name);
+        }
+        for name in ["perl", "beam.smp.old", "erlang-ls", "leaxer_core_helper"] {
+            assert!(!is_backend_executable(name), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+//! Pairing other devices with a backend exposed on the network.
+//!
+//! With `network_exposure_enabled` (and `network_pairing`, on by default) the backend only
+//! answers other machines that present a device token. To pair one, the user starts pairing
+//! here and gets a six-digit PIN valid for a few minutes. The device sends the PIN with
+//! `POST /api/pairing` to the backend, which relays it over the control channel (`pair`);
+//! the shell checks it and issues the device its token. Paired devices are kept with the
+//! encrypted secrets, and the backend only learns SHA-256 hashes of their tokens:
+//! `LEAXER_DEVICE_TOKENS` when it starts, and `set_device_tokens` whenever the list changes.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tauri::Manager;
+
+use crate::logging::log_to_file;
+use crate::secrets::{random_bytes, SecretStore};
+
+/// `required` makes the backend refuse other machines without a device token
+pub const PAIRING_ENV: &str = "LEAXER_PAIRING";
+/// Comma-separated SHA-256 hashes (hex) of the paired devices' tokens
+pub const DEVICE_TOKENS_ENV: &str = "LEAXER_DEVICE_TOKENS";
+
+/// Secret holding the paired devices as JSON
+const DEVICES_SECRET: &str = "leaxer.paired_devices";
+
+const PIN_DIGITS: usize = 6;
+const PIN_TTL: Duration = Duration::from_secs(5 * 60);
+/// Wrong PINs accepted before the PIN is dropped
+const MAX_ATTEMPTS: u32 = 5;
+const TOKEN_BYTES: usize = 32;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PairedDevice {
+    pub id: String,
+    pub name: String,
+    /// Unix time in seconds
+    pub paired: u64,
+    pub token_sha256: String,
+}
+
+/// A PIN shown to the user
+#[derive(serde::Serialize)]
+pub struct PairingPin {
+    pin: String,
+    expires_in_secs: u64,
+}
+
+/// What a device gets for a correct PIN
+#[derive(serde::Serialize)]
+pub struct DeviceToken {
+    id: String,
+    token: String,
+}
+
+struct PendingPin {
+    pin: String,
+    expires: Instant,
+    attempts_left: u32,
+}
+
+impl PendingPin {
+    fn new(now: Instant) -> Self {
+        let mut pin = String::with_capacity(PIN_DIGITS);
+        while pin.len() < PIN_DIGITS {
+            // 250 is the largest multiple of 10 a byte holds; bytes above it would favour 0-5
+            let byte = random_bytes(1)[0];
+            if byte < 250 {
+                pin.push(char::from(b'0' + byte % 10));
+            }
+        }
+        PendingPin {
+            pin,
+            expires: now + PIN_TTL,
+            attempts_left: MAX_ATTEMPTS,
+        }
+    }
+
+    /// Check `pin`; a wrong one uses up an attempt
+    fn check(&mut self, pin: &str, now: Instant) -> Result<(), String> {
+        if now >= self.expires {
+            return Err("The pairing PIN has expired".to_string());
+        }
+        if self.attempts_left == 0 {
+            return Err("Too many wrong PINs, start pairing again".to_string());
+        }
+        if crate::webhook::token_matches(pin.trim(), &self.pin) {
+            return Ok(());
+        }
+        self.attempts_left -= 1;
+        Err("Wrong pairing PIN".to_string())
+    }
+}
+
+/// Managed state: the PIN of the pairing in progress, if any
+#[derive(Default)]
+pub struct Pairing {
+    pending: Mutex<Option<PendingPin>>,
+}
+
+impl Pairing {
+    fn start(&self) -> PairingPin {
+        let pending = PendingPin::new(Instant::now());
+        let pin = PairingPin {
+            pin: pending.pin.clone(),
+            expires_in_secs: PIN_TTL.as_secs(),
+        };
+        *self.pending.lock().unwrap() = Some(pending);
+        pin
+    }
+
+    fn cancel(&self) {
+        *self.pending.lock().unwrap() = None;
+    }
+
+    /// Check `pin` against the pairing in progress, using it up when `consume` is set
+    fn check(&self, pin: &str, consume: bool) -> Result<(), String> {
+        let mut pending = self.pending.lock().unwrap();
+        let result = pending.as_mut().ok_or("No pairing in progress")?.check(pin, Instant::now());
+        if result.is_ok() && consume {
+            *pending = None;
+        }
+        result
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn token_hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The paired devices. A list that can't be read is an error, never an empty list: saving
+/// over it would unpair every device.
+fn devices(secrets: &SecretStore) -> Result<Vec<PairedDevice>, String> {
+    match secrets.get(DEVICES_SECRET)? {
+        Some(json) => serde_json::from_str(&json).map_err(|e| format!("The paired devices are unreadable: {}", e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn save_devices(secrets: &SecretStore, devices: &[PairedDevice]) -> Result<(), String> {
+    let json = serde_json::to_string(devices).map_err(|e| e.to_string())?;
+    secrets.set(DEVICES_SECRET, Some(&json))
+}
+
+fn token_hashes(devices: &[PairedDevice]) -> Vec<String> {
+    devices.iter().map(|device| device.token_sha256.clone()).collect()
+}
+
+/// Have a backend about to start refuse unpaired devices, when it will listen on the network
+/// with pairing required
+pub fn configure(cmd: &mut std::process::Command, secrets: &SecretStore, enabled: bool) {
+    if enabled {
+        // Without the list no device gets in, which is the safe side
+        let paired = devices(secrets).unwrap_or_else(|e| {
+            log_to_file(&format!("[Leaxer] Paired devices unavailable: {}", e));
+            Vec::new()
+        });
+        cmd.env(PAIRING_ENV, "required")
+            .env(DEVICE_TOKENS_ENV, token_hashes(&paired).join(","));
+    }
+}
+
+/// Tell the running backend the current list of tokens
+async fn push_tokens(app: &tauri::AppHandle) {
+    let hashes = match devices(&app.state::<SecretStore>()) {
+        Ok(paired) => token_hashes(&paired),
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Paired devices unavailable: {}", e));
+            return;
+        }
+    };
+    let args = json!({ "hashes": hashes });
+    if let Err(e) = crate::control::request_with("set_device_tokens", args, Duration::from_secs(5)).await {
+        // It gets them from the environment when it next starts
+        log_to_file(&format!("[Leaxer] Failed to update the backend's device tokens: {}", e));
+    }
+}
+
+/// Check `pin` and issue a token to the device called `name`
+pub async fn pair(app: &tauri::AppHandle, pin: &str, name: &str) -> Result<DeviceToken, String> {
+    let secrets = app.state::<SecretStore>();
+    // Before the PIN is used up, so it can be tried again once the list is readable
+    let mut paired = devices(&secrets)?;
+    let checked = app.state::<Pairing>().check(pin, true);
+    crate::audit::record("device_pair", Some(name), &checked);
+    checked?;
+
+    let engine = base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    let token = engine.encode(random_bytes(TOKEN_BYTES));
+    let device = PairedDevice {
+        id: random_bytes(8).iter().map(|byte| format!("{:02x}", byte)).collect(),
+        name: Some(name.trim()).filter(|name| !name.is_empty()).unwrap_or("Device").to_string(),
+        paired: now_secs(),
+        token_sha256: token_hash(&token),
+    };
+    paired.push(device.clone());
+    save_devices(&secrets, &paired)?;
+    push_tokens(app).await;
+
+    log_to_file(&format!("[Leaxer] Paired device {:?} ({})", device.name, device.id));
+    Ok(DeviceToken { id: device.id, token })
+}
+
+/// Handle a `pair` request relayed by the backend
+pub async fn pair_request(app: &tauri::AppHandle, args: &Value) -> Result<Value, String> {
+    let pin = args["pin"].as_str().ok_or("A PIN is required")?;
+    let token = pair(app, pin, args["name"].as_str().unwrap_or_default()).await?;
+    serde_json::to_value(token).map_err(|e| e.to_string())
+}
+
+/// Start pairing a device, replacing any PIN shown before
+#[tauri::command]
+pub fn start_pairing(pairing: tauri::State<'_, Pairing>) -> PairingPin {
+    log_to_file("[Leaxer] Pairing started");
+    pairing.start()
+}
+
+#[tauri::command]
+pub fn cancel_pairing(pairing: tauri::State<'_, Pairing>) {
+    pairing.cancel();
+}
+
+/// Check a PIN without using it up; a wrong one still counts against the attempts
+#[tauri::command]
+pub fn verify_pairing_pin(pairing: tauri::State<'_, Pairing>, pin: String) -> Result<(), String> {
+    pairing.check(&pin, false)
+}
+
+/// Pair a device whose token will be entered by hand
+#[tauri::command]
+pub async fn pair_device(app: tauri::AppHandle, pin: String, name: String) -> Result<DeviceToken, String> {
+    pair(&app, &pin, &name).await
+}
+
+#[tauri::command]
+pub fn list_paired_devices(secrets: tauri::State<'_, SecretStore>) -> Result<Vec<PairedDevice>, String> {
+    devices(&secrets)
+}
+
+/// Forget a device; the backend refuses its token from then on
+#[tauri::command]
+pub async fn unpair_device(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let secrets = app.state::<SecretStore>();
+    let mut paired = devices(&secrets)?;
+    let before = paired.len();
+    paired.retain(|device| device.id != id);
+    let result = if paired.len() == before {
+        Err(format!("No paired device {}", id))
+    } else {
+        save_devices(&secrets, &paired)
+    };
+    crate::audit::record("device_unpair", Some(&id), &result);
+    result?;
+    push_tokens(&app).await;
+    log_to_file(&format!("[Leaxer] Unpaired device {}", id));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_expire_and_run_out_of_attempts() {
+        let now = Instant::now();
+        let mut pending = PendingPin::new(now);
+        assert_eq!(pending.pin.len(), PIN_DIGITS);
+        assert!(pending.pin.chars().all(|c| c.is_ascii_digit()));
+
+        let pin = pending.pin.clone();
+        assert!(pending.check(&pin, now).is_ok());
+        assert!(pending.check(&pin, now + PIN_TTL).is_err());
+
+        for _ in 0..MAX_ATTEMPTS {
+            assert!(pending.check("not a pin", now).is_err());
+        }
+        assert!(pending.check(&pin, now).is_err());
+    }
+
+    #[test]
+    fn tokens_are_hashed_as_hex() {
+        assert_eq!(token_hash("abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    }
+}
@@ -0,0 +1,271 @@
+//! Leaxer user directory and the filesystem locations commands may act on.
+//!
+//! The Leaxer dir is in the platform's data location: `AppData\Local\Leaxer` on Windows,
+//! `~/Library/Application Support/Leaxer` on macOS and the XDG data dir on Linux. Older
+//! versions used `Documents/Leaxer` on Windows and macOS, which OneDrive and iCloud
+//! redirect and sync; `migrate_legacy_dir` moves it once at launch.
+//!
+//! In portable mode (`--portable`, or a `portable.flag` file beside the executable) the
+//! Leaxer dir is `data` beside the executable instead, so the app can run from a USB
+//! stick without leaving data on the machine.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use tauri::Manager;
+
+/// Overrides the default Leaxer user dir, e.g. for managed or scripted installs
+pub const DATA_DIR_ENV: &str = "LEAXER_DATA_DIR";
+
+/// Command-line flag that turns on portable mode
+pub const PORTABLE_FLAG: &str = "--portable";
+
+/// File beside the executable that turns on portable mode
+const PORTABLE_MARKER: &str = "portable.flag";
+
+static PORTABLE_DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Data dir of the active profile when it isn't the default one
+static PROFILE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Extra filesystem locations (besides the Leaxer user dir) that commands may touch,
+/// e.g. files the user exported through a save dialog
+pub struct AllowedPaths {
+    pub paths: Vec<PathBuf>,
+}
+
+/// Get the Leaxer user data directory path of the active profile
+pub fn get_leaxer_user_dir() -> Option<PathBuf> {
+    PROFILE_DIR.read().unwrap().clone().or_else(base_leaxer_dir)
+}
+
+/// Point the shell at a profile's data dir (`None` for the default profile)
+pub fn set_profile_dir(dir: Option<PathBuf>) {
+    *PROFILE_DIR.write().unwrap() = dir;
+}
+
+/// `Documents/Leaxer`, where Windows and macOS installs used to keep their data
+fn legacy_leaxer_dir() -> Option<PathBuf> {
+    if cfg!(any(target_os = "windows", target_os = "macos")) {
+        dirs::document_dir().map(|p| p.join("Leaxer"))
+    } else {
+        None
+    }
+}
+
+/// The Leaxer dir in the platform's data location. Models are large, so Windows uses the
+/// local (not roaming) AppData.
+fn platform_leaxer_dir() -> Option<PathBuf> {
+    if cfg!(target_os = "windows") {
+        dirs::data_local_dir().map(|p| p.join("Leaxer"))
+    } else {
+        dirs::data_dir().map(|p| p.join("Leaxer"))
+    }
+}
+
+fn detect_portable(exe_dir: &Path, args: &[String]) -> Option<PathBuf> {
+    let portable = args.iter().any(|arg| arg == PORTABLE_FLAG) || exe_dir.join(PORTABLE_MARKER).is_file();
+    portable.then(|| exe_dir.join("data"))
+}
+
+/// `data` beside the executable when running in portable mode
+pub fn portable_dir() -> Option<PathBuf> {
+    PORTABLE_DIR
+        .get_or_init(|| {
+            let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+            detect_portable(&exe_dir, &std::env::args().collect::<Vec<_>>())
+        })
+        .clone()
+}
+
+/// In the home Leaxer dir, points at the folder the data was moved to (`data_dir`)
+pub const LOCATION_FILE: &str = "data_dir.json";
+
+/// Where the Leaxer dir is unless it was moved: the platform data location, or a legacy
+/// dir that couldn't be moved yet
+pub fn home_leaxer_dir() -> Option<PathBuf> {
+    let dir = platform_leaxer_dir()?;
+    match legacy_leaxer_dir() {
+        Some(legacy) if !dir.exists() && legacy.is_dir() => Some(legacy),
+        _ => Some(dir),
+    }
+}
+
+/// The `data_dir` the Leaxer dir was moved to, whether or not it is reachable
+pub fn configured_data_dir(home: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(home.join(LOCATION_FILE)).ok()?;
+    let location: serde_json::Value = serde_json::from_str(&content).ok()?;
+    location.get("data_dir")?.as_str().filter(|dir| !dir.is_empty()).map(PathBuf::from)
+}
+
+/// The default Leaxer user dir, which also holds the profile list
+pub fn base_leaxer_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV).filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = portable_dir() {
+        return Some(dir);
+    }
+    let home = home_leaxer_dir()?;
+    // A moved dir on a drive that isn't connected falls back to the home dir
+    match configured_data_dir(&home) {
+        Some(dir) if dir.is_dir() => Some(dir),
+        _ => Some(home),
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Move `from` to `to`: a rename on the same volume, else a copy into a staging dir that
+/// is renamed into place once complete, so a half-copied dir is never used
+fn move_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    let staging = to.with_extension("migrating");
+    let _ = std::fs::remove_dir_all(&staging);
+    if let Err(e) = copy_dir(from, &staging).and_then(|_| std::fs::rename(&staging, to)) {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+    // The copy is complete; a leftover original only wastes space
+    let _ = std::fs::remove_dir_all(from);
+    Ok(())
+}
+
+/// Move a `Documents/Leaxer` dir from an older version to the platform data location.
+/// Runs before anything is logged or opened in it. Returns the move made, if any; after a
+/// failure the old dir stays in use and the move is tried again next launch.
+pub fn migrate_legacy_dir() -> Result<Option<(PathBuf, PathBuf)>, String> {
+    if std::env::var_os(DATA_DIR_ENV).is_some_and(|dir| !dir.is_empty()) || portable_dir().is_some() {
+        return Ok(None);
+    }
+    let (Some(legacy), Some(dir)) = (legacy_leaxer_dir(), platform_leaxer_dir()) else {
+        return Ok(None);
+    };
+    if dir.exists() || !legacy.is_dir() {
+        return Ok(None);
+    }
+    move_dir(&legacy, &dir).map_err(|e| format!("Failed to move {:?} to {:?}: {}", legacy, dir, e))?;
+    Ok(Some((legacy, dir)))
+}
+
+/// Check whether `path` resolves to a location inside one of `roots`.
+/// Both sides are canonicalized, so symlinks and `..` can't escape a root.
+pub fn path_within_roots(path: &Path, roots: &[PathBuf]) -> bool {
+    let path = match path.canonicalize() {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+
+    roots
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| path.starts_with(&root))
+}
+
+/// Check whether a path lies inside the Leaxer user dir or a location the user chose explicitly
+pub fn is_path_allowed(app: &tauri::AppHandle, path: &Path) -> bool {
+    let mut roots: Vec<PathBuf> = get_leaxer_user_dir().into_iter().collect();
+    roots.extend(app.state::<Mutex<AllowedPaths>>().lock().unwrap().paths.iter().cloned());
+    path_within_roots(path, &roots)
+}
+
+/// Remember a user-chosen location so later commands (reveal, share, ...) may act on it
+pub fn allow_path(app: &tauri::AppHandle, path: PathBuf) {
+    let state = app.state::<Mutex<AllowedPaths>>();
+    let mut guard = state.lock().unwrap();
+    if !guard.paths.contains(&path) {
+        guard.paths.push(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn portable_mode_uses_data_beside_the_executable() {
+        let exe_dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_portable(exe_dir.path(), &["leaxer".to_string()]), None);
+        assert_eq!(
+            detect_portable(exe_dir.path(), &["leaxer".to_string(), PORTABLE_FLAG.to_string()]),
+            Some(exe_dir.path().join("data"))
+        );
+        fs::write(exe_dir.path().join(PORTABLE_MARKER), "").unwrap();
+        assert_eq!(detect_portable(exe_dir.path(), &[]), Some(exe_dir.path().join("data")));
+    }
+
+    #[test]
+    fn copies_and_moves_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let from = root.path().join("Documents").join("Leaxer");
+        fs::create_dir_all(from.join("models")).unwrap();
+        fs::write(from.join("config.json"), "{}").unwrap();
+        fs::write(from.join("models").join("a.gguf"), "weights").unwrap();
+
+        let copy = root.path().join("copy");
+        copy_dir(&from, &copy).unwrap();
+        assert_eq!(fs::read_to_string(copy.join("models").join("a.gguf")).unwrap(), "weights");
+
+        let to = root.path().join("AppData").join("Leaxer");
+        move_dir(&from, &to).unwrap();
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(to.join("config.json")).unwrap(), "{}");
+        assert!(!to.with_extension("migrating").exists());
+    }
+
+    #[test]
+    fn accepts_paths_inside_a_root() {
+        let root = tempfile::tempdir().unwrap();
+        let file = root.path().join("outputs").join("image.png");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, b"png").unwrap();
+
+        assert!(path_within_roots(&file, &[root.path().to_path_buf()]));
+        assert!(path_within_roots(root.path(), &[root.path().to_path_buf()]));
+    }
+
+    #[test]
+    fn rejects_paths_outside_roots() {
+        let root = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let file = other.path().join("secret.txt");
+        fs::write(&file, b"secret").unwrap();
+
+        assert!(!path_within_roots(&file, &[root.path().to_path_buf()]));
+        assert!(!path_within_roots(&file, &[]));
+    }
+
+    #[test]
+    fn rejects_traversal_out_of_a_root() {
+        let parent = tempfile::tempdir().unwrap();
+        let root = parent.path().join("root");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(parent.path().join("outside.txt"), b"x").unwrap();
+
+        let sneaky = root.join("..").join("outside.txt");
+        assert!(!path_within_roots(&sneaky, &[root]));
+    }
+
+    #[test]
+    fn rejects_missing_paths() {
+        let root = tempfile::tempdir().unwrap();
+        assert!(!path_within_roots(&root.path().join("missing.png"), &[root.path().to_path_buf()]));
+    }
+}
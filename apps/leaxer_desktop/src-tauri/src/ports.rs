@@ -0,0 +1,221 @@
+//! Finding out what holds the backend's port.
+//!
+//! `net::allocate_backend_port` moves the backend off a busy port before the UI is built.
+//! The program listening there is looked up (/proc on Linux, lsof on macOS, the TCP
+//! table on Windows), and before the backend is spawned the user is told which one it
+//! is. If it is another Leaxer backend they can stop it and relaunch on the usual port.
+
+use std::sync::Mutex;
+
+use sysinfo::{Pid, ProcessesToUpdate, System};
+
+use crate::logging::log_to_file;
+
+/// Process listening on a port
+#[derive(Clone, Debug)]
+pub struct PortHolder {
+    pub pid: u32,
+    pub name: String,
+}
+
+/// Port the backend was moved away from at launch, and what held it
+static CONFLICT: Mutex<Option<(u16, Option<PortHolder>)>> = Mutex::new(None);
+
+/// Inode of the socket listening on `port` in a /proc/net/tcp table
+#[cfg(any(target_os = "linux", test))]
+fn listening_inode(table: &str, port: u16) -> Option<u64> {
+    // 0A is TCP_LISTEN
+    const LISTEN: &str = "0A";
+    table.lines().skip(1).find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let local_port = u16::from_str_radix(fields.get(1)?.rsplit(':').next()?, 16).ok()?;
+        if local_port != port || *fields.get(3)? != LISTEN {
+            return None;
+        }
+        fields.get(9)?.parse().ok()
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn listener_pid(port: u16) -> Option<u32> {
+    let inode = ["/proc/net/tcp", "/proc/net/tcp6"]
+        .iter()
+        .find_map(|path| listening_inode(&std::fs::read_to_string(path).ok()?, port))?;
+    let socket = format!("socket:[{}]", inode);
+    std::fs::read_dir("/proc").ok()?.flatten().find_map(|entry| {
+        let pid = entry.file_name().to_str()?.parse().ok()?;
+        let mut fds = std::fs::read_dir(entry.path().join("fd")).ok()?.flatten();
+        fds.any(|fd| std::fs::read_link(fd.path()).is_ok_and(|link| link.as_os_str() == socket.as_str()))
+            .then_some(pid)
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn listener_pid(port: u16) -> Option<u32> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", "-t", &format!("-iTCP:{}", port), "-sTCP:LISTEN"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout).lines().next()?.trim().parse().ok()
+}
+
+#[cfg(target_os = "windows")]
+fn listener_pid(port: u16) -> Option<u32> {
+    use windows::Win32::Foundation::FALSE;
+    use windows::Win32::NetworkManagement::IpHelper::{
+        GetExtendedTcpTable, MIB_TCPTABLE_OWNER_PID, TCP_TABLE_OWNER_PID_LISTENER,
+    };
+    use windows::Win32::Networking::WinSock::AF_INET;
+
+    let mut size = 0u32;
+    // The first call only reports the size the table needs
+    unsafe { GetExtendedTcpTable(None, &mut size, FALSE, AF_INET.0 as u32, TCP_TABLE_OWNER_PID_LISTENER, 0) };
+    let mut buffer = vec![0u8; size as usize];
+    let result = unsafe {
+        GetExtendedTcpTable(
+            Some(buffer.as_mut_ptr().cast()),
+            &mut size,
+            FALSE,
+            AF_INET.0 as u32,
+            TCP_TABLE_OWNER_PID_LISTENER,
+            0,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    let table = unsafe { &*(buffer.as_ptr() as *const MIB_TCPTABLE_OWNER_PID) };
+    let rows = unsafe { std::slice::from_raw_parts(table.table.as_ptr(), table.dwNumEntries as usize) };
+    // Ports are stored in network byte order
+    rows.iter()
+        .find(|row| u16::from_be(row.dwLocalPort as u16) == port)
+        .map(|row| row.dwOwningPid)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn listener_pid(_port: u16) -> Option<u32> {
+    None
+}
+
+/// The process listening on a local port, if it can be found
+pub fn holder(port: u16) -> Option<PortHolder> {
+    let pid = listener_pid(port)?;
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[Pid::from_u32(pid)]), true);
+    let name = sys
+        .process(Pid::from_u32(pid))
+        .map(|process| process.name().to_string_lossy().to_string())
+        .unwrap_or_else(|| "an unknown program".to_string());
+    Some(PortHolder { pid, name })
+}
+
+fn describe(holder: Option<&PortHolder>) -> String {
+    match holder {
+        Some(holder) => format!("{} (PID {})", holder.name, holder.pid),
+        None => "another program".to_string(),
+    }
+}
+
+/// Remember that the backend had to leave `port`, and who holds it
+pub fn record_conflict(port: u16) {
+    let holder = holder(port);
+    log_to_file(&format!("[Leaxer] Port {} is used by {}", port, describe(holder.as_ref())));
+    *CONFLICT.lock().unwrap() = Some((port, holder));
+}
+
+enum Choice {
+    UseOtherPort,
+    StopHolder,
+    Quit,
+}
+
+#[cfg(feature = "dialog")]
+async fn ask(app: &tauri::AppHandle, port: u16, holder: Option<&PortHolder>, leaxer: bool) -> Choice {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+
+    const STOP: &str = "Stop it and restart";
+    const QUIT: &str = "Quit";
+    let other_port = format!("Use port {}", crate::net::backend_port());
+
+    let mut message = format!(
+        "Port {} is in use by {}. Leaxer can run its server on port {} instead.",
+        port,
+        describe(holder),
+        crate::net::backend_port()
+    );
+    let buttons = if leaxer {
+        message.push_str("\n\nIt looks like another Leaxer server. Stop it to use the usual port.");
+        MessageDialogButtons::YesNoCancelCustom(STOP.to_string(), other_port.clone(), QUIT.to_string())
+    } else {
+        MessageDialogButtons::OkCancelCustom(other_port.clone(), QUIT.to_string())
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    app.dialog()
+        .message(message)
+        .title("Port already in use")
+        .kind(MessageDialogKind::Warning)
+        .buttons(buttons)
+        .show_with_result(move |result| {
+            let _ = tx.send(result);
+        });
+    match rx.await {
+        Ok(MessageDialogResult::Custom(label)) if label == STOP => Choice::StopHolder,
+        Ok(MessageDialogResult::Yes) if leaxer => Choice::StopHolder,
+        Ok(MessageDialogResult::Custom(label)) if label == other_port => Choice::UseOtherPort,
+        Ok(MessageDialogResult::Ok | MessageDialogResult::No) => Choice::UseOtherPort,
+        // Quit, or the dialog was dismissed
+        _ => Choice::Quit,
+    }
+}
+
+/// Without a dialog the backend just runs on the free port
+#[cfg(not(feature = "dialog"))]
+async fn ask(_app: &tauri::AppHandle, _port: u16, _holder: Option<&PortHolder>, _leaxer: bool) -> Choice {
+    Choice::UseOtherPort
+}
+
+/// Tell the user about the port conflict found at launch, before the backend is spawned.
+/// Returns false when the app is quitting or restarting instead.
+pub async fn resolve_conflict(app: &tauri::AppHandle) -> bool {
+    let Some((port, holder)) = CONFLICT.lock().unwrap().take() else {
+        return true;
+    };
+    if std::net::TcpListener::bind(("127.0.0.1", port)).is_ok() {
+        // Reaping a backend of ours freed it; the UI already knows the other port
+        log_to_file(&format!("[Leaxer] Port {} is free again, staying on port {}", port, crate::net::backend_port()));
+        return true;
+    }
+
+    let leaxer = holder.as_ref().is_some_and(|holder| crate::orphans::is_backend(holder.pid));
+    match ask(app, port, holder.as_ref(), leaxer).await {
+        Choice::UseOtherPort => true,
+        Choice::StopHolder => {
+            if let Some(holder) = holder {
+                log_to_file(&format!("[Leaxer] Stopping the Leaxer backend on port {} (PID {})", port, holder.pid));
+                crate::orphans::stop(holder.pid, port, None).await;
+            }
+            // The UI was built against the other port, so start over
+            app.restart();
+        }
+        Choice::Quit => {
+            log_to_file("[Leaxer] Quitting because the backend port is in use");
+            app.exit(1);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_listening_socket() {
+        let table = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 0100007F:0FA0 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 48213 1 0000000000000000 100 0 0 10 0
+   1: 0100007F:0FA0 0100007F:D2F4 01 00000000:00000000 00:00000000 00000000  1000        0 48977 1 0000000000000000 20 4 30 10 -1";
+        assert_eq!(listening_inode(table, 4000), Some(48213));
+        assert_eq!(listening_inode(table, 4001), None);
+    }
+}
@@ -0,0 +1,341 @@
+//! Startup preflight checks.
+//!
+//! Port availability, free disk space, backend integrity and hardware detection are
+//! independent, so they run concurrently, each with its own time limit, and are
+//! collected into a single startup report instead of adding up one after another.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+use crate::checksum::{sha256_file, to_hex};
+use crate::commands::system::{available_space_at, cached_gpus};
+use crate::config::ConfigStore;
+use crate::logging::log_to_file;
+use crate::net::backend_port;
+use crate::paths::get_leaxer_user_dir;
+use crate::process::locate_backend;
+
+/// Upper bound for any single check
+const PREFLIGHT_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Warn when less than this is free on the disk holding the Leaxer user dir
+const MIN_FREE_DISK_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Optional checksum manifest in the release root: `<sha256>  <relative path>` per line
+const BACKEND_CHECKSUM_FILE: &str = "checksums.sha256";
+
+/// Release files read by `releases/<vsn>/` to boot the VM; with `bin/` these are what
+/// preflight verifies. Hashing the whole release (ERTS, every app) doesn't fit the time limit.
+const BOOT_FILES: &[&str] = &["start.boot", "start_erl.data", "sys.config", "vm.args"];
+
+#[derive(Clone, Copy, PartialEq, Debug, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Passed,
+    Warning,
+    Failed,
+    Skipped,
+    TimedOut,
+}
+
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct PreflightCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub message: String,
+    pub duration_ms: u64,
+}
+
+/// Aggregated result of all preflight checks for this launch
+#[derive(Clone, Default, serde::Serialize)]
+pub struct StartupReport {
+    pub checks: Vec<PreflightCheck>,
+    pub total_ms: u64,
+}
+
+impl StartupReport {
+    /// Whether a check of the given name passed
+    pub fn passed(&self, name: &str) -> bool {
+        self.checks
+            .iter()
+            .any(|check| check.name == name && check.status == CheckStatus::Passed)
+    }
+}
+
+type CheckResult = (CheckStatus, String);
+
+/// The backend port must be free before we spawn, on every address it will bind
+fn check_port(port: u16, exposed: bool) -> CheckResult {
+    let hosts: &[&str] = if exposed { &["127.0.0.1", "0.0.0.0"] } else { &["127.0.0.1"] };
+    for host in hosts {
+        if let Err(e) = std::net::TcpListener::bind((*host, port)) {
+            return (
+                CheckStatus::Failed,
+                format!("Port {} is unavailable on {}: {}", port, host, e),
+            );
+        }
+    }
+    (CheckStatus::Passed, format!("Port {} is free", port))
+}
+
+fn check_disk_space(dir: Option<&Path>) -> CheckResult {
+    let dir = match dir {
+        Some(dir) => dir,
+        None => return (CheckStatus::Skipped, "No user data directory".to_string()),
+    };
+    match available_space_at(dir) {
+        Some(free) if free < MIN_FREE_DISK_BYTES => (
+            CheckStatus::Warning,
+            format!("Only {} MB free on the data disk", free / (1024 * 1024)),
+        ),
+        Some(free) => (
+            CheckStatus::Passed,
+            format!("{} MB free on the data disk", free / (1024 * 1024)),
+        ),
+        None => (
+            CheckStatus::Skipped,
+            "Could not determine free space".to_string(),
+        ),
+    }
+}
+
+/// Launchers in `bin/` and the boot files of `releases/`
+fn is_boot_file(relative: &str) -> bool {
+    let relative = relative.replace('\\', "/");
+    if relative.starts_with("bin/") {
+        return true;
+    }
+    let name = relative.rsplit('/').next().unwrap_or_default();
+    relative.starts_with("releases/") && (BOOT_FILES.contains(&name) || name.ends_with(".boot"))
+}
+
+/// Verify the launcher and boot files listed in the release's checksum manifest
+pub fn check_backend_integrity(release_root: Option<&Path>) -> CheckResult {
+    let root = match release_root {
+        Some(root) => root,
+        None => {
+            return (
+                CheckStatus::Skipped,
+                "No bundled backend (dev mode)".to_string(),
+            )
+        }
+    };
+    let manifest = match std::fs::read_to_string(root.join(BACKEND_CHECKSUM_FILE)) {
+        Ok(manifest) => manifest,
+        Err(_) => {
+            return (
+                CheckStatus::Skipped,
+                "Release has no checksum manifest".to_string(),
+            )
+        }
+    };
+
+    let mut verified = 0;
+    for line in manifest.lines().filter(|l| !l.trim().is_empty()) {
+        let (expected, relative) = match line.split_once(char::is_whitespace) {
+            Some((hash, path)) => (hash.trim(), path.trim().trim_start_matches('*')),
+            None => {
+                return (
+                    CheckStatus::Failed,
+                    format!("Malformed manifest line: {}", line),
+                )
+            }
+        };
+        if !is_boot_file(relative) {
+            continue;
+        }
+        match sha256_file(&root.join(relative)) {
+            Ok(actual) if to_hex(&actual).eq_ignore_ascii_case(expected) => verified += 1,
+            Ok(_) => {
+                return (
+                    CheckStatus::Failed,
+                    format!("Checksum mismatch: {}", relative),
+                )
+            }
+            Err(e) => {
+                return (
+                    CheckStatus::Failed,
+                    format!("Cannot read {}: {}", relative, e),
+                )
+            }
+        }
+    }
+    (CheckStatus::Passed, format!("{} launcher and boot files verified", verified))
+}
+
+fn check_hardware() -> CheckResult {
+    let gpus = cached_gpus();
+    if gpus.is_empty() {
+        (
+            CheckStatus::Warning,
+            "No GPU detected, generation will run on CPU".to_string(),
+        )
+    } else {
+        (CheckStatus::Passed, gpus.join(", "))
+    }
+}
+
+/// Run a blocking check on the blocking pool with a time limit
+async fn timed_check<F>(name: &'static str, check: F) -> PreflightCheck
+where
+    F: FnOnce() -> CheckResult + Send + 'static,
+{
+    let started = Instant::now();
+    let result = tokio::time::timeout(
+        PREFLIGHT_CHECK_TIMEOUT,
+        tauri::async_runtime::spawn_blocking(move || {
+            let _span = crate::profiling::span(name);
+            check()
+        }),
+    )
+    .await;
+
+    let (status, message) = match result {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) => (CheckStatus::Failed, format!("Check panicked: {}", e)),
+        Err(_) => (
+            CheckStatus::TimedOut,
+            format!("Did not finish within {:?}", PREFLIGHT_CHECK_TIMEOUT),
+        ),
+    };
+
+    PreflightCheck {
+        name,
+        status,
+        message,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }
+}
+
+/// Run all preflight checks concurrently, store the report and emit `preflight-complete`
+pub async fn run_preflight(app: &tauri::AppHandle) -> StartupReport {
+    let started = Instant::now();
+    let user_dir = get_leaxer_user_dir();
+    let release_root = locate_backend(app).and_then(|exe| {
+        exe.parent()
+            .and_then(|bin| bin.parent())
+            .map(|p| p.to_path_buf())
+    });
+
+    let exposed = app.state::<ConfigStore>().network_exposure_enabled();
+
+    let (port, disk, integrity, hardware) = tokio::join!(
+        timed_check("port", move || check_port(backend_port(), exposed)),
+        timed_check("disk_space", move || check_disk_space(user_dir.as_deref())),
+        timed_check("backend_integrity", move || check_backend_integrity(
+            release_root.as_deref()
+        )),
+        timed_check("hardware", check_hardware),
+    );
+
+    let report = StartupReport {
+        checks: vec![port, disk, integrity, hardware],
+        total_ms: started.elapsed().as_millis() as u64,
+    };
+
+    for check in &report.checks {
+        log_to_file(&format!(
+            "[Leaxer] Preflight {}: {:?} - {} ({} ms)",
+            check.name, check.status, check.message, check.duration_ms
+        ));
+    }
+
+    *app.state::<Mutex<StartupReport>>().lock().unwrap() = report.clone();
+    let _ = app.emit("preflight-complete", &report);
+    report
+}
+
+/// Results of this launch's preflight checks
+#[tauri::command]
+pub fn get_startup_report(app: tauri::AppHandle) -> StartupReport {
+    app.state::<Mutex<StartupReport>>().lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn port_check_detects_listeners() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(check_port(port, false).0, CheckStatus::Failed);
+
+        drop(listener);
+        assert_eq!(check_port(port, false).0, CheckStatus::Passed);
+    }
+
+    #[test]
+    fn port_check_covers_all_interfaces_when_exposed() {
+        let listener = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_eq!(check_port(port, true).0, CheckStatus::Failed);
+    }
+
+    #[test]
+    fn integrity_check_verifies_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("bin")).unwrap();
+        fs::write(root.path().join("bin").join("leaxer_core"), b"hello").unwrap();
+        // sha256("hello")
+        fs::write(
+            root.path().join(BACKEND_CHECKSUM_FILE),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  bin/leaxer_core\n",
+        )
+        .unwrap();
+        assert_eq!(
+            check_backend_integrity(Some(root.path())).0,
+            CheckStatus::Passed
+        );
+
+        fs::write(root.path().join("bin").join("leaxer_core"), b"tampered").unwrap();
+        assert_eq!(
+            check_backend_integrity(Some(root.path())).0,
+            CheckStatus::Failed
+        );
+    }
+
+    #[test]
+    fn integrity_check_only_hashes_boot_files() {
+        for path in [
+            "bin/leaxer_core",
+            "releases/0.1.0/start.boot",
+            "releases/0.1.0/vm.args",
+            "releases/start_erl.data",
+        ] {
+            assert!(is_boot_file(path), "{}", path);
+        }
+        for path in [
+            "lib/leaxer_core-0.1.0/ebin/leaxer_core.beam",
+            "erts-15.0/bin/beam.smp",
+            "releases/0.1.0/notes.txt",
+        ] {
+            assert!(!is_boot_file(path), "{}", path);
+        }
+
+        // Files outside the boot set are neither read nor required
+        let root = tempfile::tempdir().unwrap();
+        fs::write(
+            root.path().join(BACKEND_CHECKSUM_FILE),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  lib/missing.beam\n",
+        )
+        .unwrap();
+        assert_eq!(
+            check_backend_integrity(Some(root.path())).0,
+            CheckStatus::Passed
+        );
+    }
+
+    #[test]
+    fn integrity_check_skips_without_manifest() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(
+            check_backend_integrity(Some(root.path())).0,
+            CheckStatus::Skipped
+        );
+        assert_eq!(check_backend_integrity(None).0, CheckStatus::Skipped);
+    }
+}
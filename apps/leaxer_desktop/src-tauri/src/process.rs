@@ -0,0 +1,809 @@
+//! Locating, spawning, supervising and stopping the bundled leaxer_core backend.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tokio::sync::{mpsc, oneshot};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::config::ConfigStore;
+use crate::logging::log_to_file;
+use crate::process_handle::ProcessHandle;
+
+#[cfg(target_os = "windows")]
+pub const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Path of the backend launcher relative to a release location
+pub fn backend_relative_path() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    let launcher = "leaxer_core.bat";
+    #[cfg(not(target_os = "windows"))]
+    let launcher = "leaxer_core";
+
+    PathBuf::from("leaxer_core").join("bin").join(launcher)
+}
+
+/// Find the backend launcher. Tries multiple locations:
+/// 1. Bundled resources (for installer builds)
+/// 2. Next to executable (for portable builds)
+pub fn find_backend_exe(resource_dir: Option<&Path>, exe_dir: Option<&Path>) -> Option<PathBuf> {
+    let backend_filename = backend_relative_path();
+
+    resource_dir
+        .map(|p| p.join(&backend_filename))
+        .filter(|p| p.exists())
+        .or_else(|| exe_dir.map(|p| p.join("resources").join(&backend_filename)).filter(|p| p.exists()))
+        .or_else(|| exe_dir.map(|p| p.join(&backend_filename)).filter(|p| p.exists()))
+}
+
+/// ERTS and release version of the release at `root`, from `releases/start_erl.data`
+pub fn release_versions(root: &Path) -> Option<(String, String)> {
+    // "<erts version> <release version>"
+    let start_erl = std::fs::read_to_string(root.join("releases").join("start_erl.data")).ok()?;
+    let mut versions = start_erl.split_whitespace();
+    Some((versions.next()?.to_string(), versions.next()?.to_string()))
+}
+
+/// Invoke the release's `erl` directly with the arguments and environment
+/// `bin/leaxer_core.bat start` would pass. None when `root` isn't a release with ERTS.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub(crate) fn release_start_command(root: &Path) -> Option<Command> {
+    let releases = root.join("releases");
+    let (erts_vsn, rel_vsn) = release_versions(root)?;
+    let erl = root
+        .join(format!("erts-{}", erts_vsn))
+        .join("bin")
+        .join(if cfg!(target_os = "windows") { "erl.exe" } else { "erl" });
+    if !erl.exists() {
+        return None;
+    }
+    let vsn_dir = releases.join(&rel_vsn);
+    let cookie = std::fs::read_to_string(releases.join("COOKIE")).unwrap_or_default();
+    crate::logging::register_secret(&cookie);
+
+    let mut cmd = Command::new(erl);
+    cmd.args(["-elixir", "ansi_enabled", "true", "-noshell", "-s", "elixir", "start_cli", "-mode", "embedded"])
+        .arg("-config")
+        .arg(vsn_dir.join("sys"))
+        .arg("-boot")
+        .arg(vsn_dir.join("start"))
+        .args(["-boot_var", "RELEASE_LIB"])
+        .arg(root.join("lib"))
+        .arg("-args_file")
+        .arg(vsn_dir.join("vm.args"))
+        .args(["-extra", "--no-halt"]);
+    cmd.env("RELEASE_ROOT", root)
+        .env("RELEASE_NAME", "leaxer_core")
+        .env("RELEASE_VSN", &rel_vsn)
+        .env("RELEASE_COMMAND", "start")
+        .env("RELEASE_PROG", "leaxer_core.bat")
+        .env("RELEASE_MODE", "embedded")
+        .env("RELEASE_NODE", "leaxer_core")
+        .env("RELEASE_COOKIE", cookie.trim())
+        .env("RELEASE_TMP", root.join("tmp"))
+        .env("RELEASE_SYS_CONFIG", vsn_dir.join("sys"))
+        .env("RELEASE_VM_ARGS", vsn_dir.join("vm.args"))
+        .env("RELEASE_BOOT_SCRIPT", "start")
+        .env("RELEASE_BOOT_SCRIPT_CLEAN", "start_clean");
+    Some(cmd)
+}
+
+/// Build the command that starts the backend release with the environment Phoenix needs
+pub fn backend_command(backend_exe: &Path, network_enabled: bool, keys: &crate::secrets::BackendKeys) -> Command {
+    // Get the release root directory (parent of bin/)
+    let release_root = backend_exe.parent()
+        .and_then(|p| p.parent())
+        .map(|p| p.to_path_buf());
+
+    // Start the VM directly rather than through the .bat and cmd.exe, so the PID we
+    // track is the backend itself
+    #[cfg(target_os = "windows")]
+    let mut cmd = release_root.as_deref().and_then(release_start_command).unwrap_or_else(|| {
+        log_to_file("[Leaxer] Release layout not recognized, starting the backend through its launcher");
+        // std quotes batch file arguments itself, spaces in the path included
+        let mut cmd = Command::new(backend_exe);
+        cmd.arg("start");
+        cmd
+    });
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW); // Hide console window
+
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = Command::new(backend_exe);
+    #[cfg(not(target_os = "windows"))]
+    cmd.arg("start");
+
+    if let Some(ref root) = release_root {
+        cmd.current_dir(root);
+    }
+
+    // Set required environment variables for Phoenix
+    cmd.env("PHX_SERVER", "true");
+    cmd.env("PHX_HOST", "localhost");
+    cmd.env("SECRET_KEY_BASE", &keys.secret_key_base);
+    cmd.env("SIGNING_SALT", &keys.signing_salt);
+    // The shell never attaches to the node, and without distribution erl starts no epmd
+    // daemon, which would otherwise detach from the process tree and outlive us
+    cmd.env("RELEASE_DISTRIBUTION", "none");
+    cmd.env(crate::logging::LOG_LEVEL_ENV, crate::logging::backend_log_level());
+    // Each profile runs its backend on its own port, against its own data dir
+    let port = crate::net::backend_port();
+    cmd.env("PORT", port.to_string());
+    cmd.env("CORS_ORIGINS", crate::net::cors_origins(port, &[]));
+    if let Some(dir) = crate::paths::get_leaxer_user_dir() {
+        cmd.env("LEAXER_USER_DIR", dir);
+    }
+
+    if network_enabled {
+        cmd.env("LEAXER_BIND_ALL_INTERFACES", "true");
+    }
+
+    cmd
+}
+
+/// Find the backend launcher for this installation: an installed backend update, else
+/// the bundled resources or portable layout
+pub fn locate_backend(app: &tauri::AppHandle) -> Option<PathBuf> {
+    if let Some(updated) = crate::backend_update::installed_backend() {
+        return Some(updated);
+    }
+    let resource_path = app.path().resource_dir().ok();
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_path_buf()));
+
+    find_backend_exe(resource_path.as_deref(), exe_dir.as_deref())
+}
+
+/// Locate the backend and spawn it.
+/// Returns `None` when no bundled backend exists (dev mode against localhost:4000) or spawning failed.
+fn spawn_backend(app: &tauri::AppHandle) -> Option<ProcessHandle> {
+    let _span = crate::profiling::span("backend_spawn");
+    let _spawn = tracing::info_span!("backend_spawn").entered();
+    log_to_file("[Leaxer] Looking for backend...");
+
+    if crate::net::external_backend().is_some() {
+        log_to_file("[Leaxer] Using an external backend, not spawning one");
+        return None;
+    }
+
+    let resolve_started = std::time::Instant::now();
+    let backend_exe = locate_backend(app);
+    crate::metrics::record(crate::metrics::Phase::ResolveBackend, resolve_started.elapsed());
+    let backend_exe = match backend_exe {
+        Some(path) => path,
+        None => {
+            log_to_file("[Leaxer] Backend not found, running in dev mode (connect to localhost:4000)");
+            return None;
+        }
+    };
+
+    log_to_file(&format!("[Leaxer] Found backend at: {:?}", backend_exe));
+    let spawn_started = std::time::Instant::now();
+
+    // Check if network exposure is enabled and set env var
+    let network_enabled = app.state::<ConfigStore>().network_exposure_enabled();
+    if network_enabled {
+        log_to_file("[Leaxer] Network exposure enabled, binding to all interfaces");
+    }
+    crate::net::set_lan_exposed(network_enabled);
+
+    let keys = app.state::<crate::secrets::SecretStore>().backend_keys();
+    let mut cmd = backend_command(&backend_exe, network_enabled, &keys);
+    // Only extensions that are up; the rest follow over the control channel as they pass
+    // their health checks
+    let extensions = app.state::<crate::extensions::Extensions>().available();
+    cmd.env(crate::extensions::BACKEND_ENV, crate::extensions::backend_env(&extensions));
+    cmd.envs(app.state::<crate::secrets::SecretStore>().backend_env());
+    cmd.env(crate::net::SHELL_TOKEN_ENV, crate::net::new_shell_token());
+    let tls = network_enabled && app.state::<ConfigStore>().get().network_tls;
+    crate::tls::configure(&mut cmd, crate::net::backend_port(), tls);
+    let pairing = network_enabled && app.state::<ConfigStore>().get().network_pairing;
+    crate::pairing::configure(&mut cmd, &app.state::<crate::secrets::SecretStore>(), pairing);
+    // Worked out on every spawn, so a restart picks up a new port, LAN address or TLS listener
+    let mut origins = crate::net::get_backend_url().lan_urls;
+    if cfg!(debug_assertions) {
+        origins.extend(app.config().build.dev_url.as_ref().map(|url| url.origin().ascii_serialization()));
+    }
+    cmd.env("CORS_ORIGINS", crate::net::cors_origins(crate::net::backend_port(), &origins));
+    crate::mdns::refresh(app);
+    // The profile's overrides go last so they win
+    cmd.envs(crate::profiles::active_env(app));
+
+    log_to_file("[Leaxer] Spawning command...");
+    tracing::debug!("Backend command: {:?}", cmd);
+
+    if let Some(socket) = crate::transport::socket_path() {
+        // A socket file left by a backend that didn't shut down keeps the new one from binding
+        let _ = std::fs::remove_file(&socket);
+        cmd.env(crate::transport::SOCKET_ENV, socket);
+    }
+    cmd.env(crate::control::CONTROL_ENV, "stdio");
+    cmd.stdin(std::process::Stdio::piped());
+    cmd.stdout(std::process::Stdio::piped());
+    cmd.stderr(std::process::Stdio::piped());
+
+    match ProcessHandle::spawn(cmd) {
+        Ok(mut process) => {
+            crate::metrics::record(crate::metrics::Phase::Spawn, spawn_started.elapsed());
+            if let Some(stdin) = process.take_stdin() {
+                crate::control::attach(app, stdin);
+            }
+            log_to_file(&format!("[Leaxer] Backend started with PID: {:?}", process.id()));
+            if let Some(pid) = process.id() {
+                crate::orphans::record(pid, crate::net::backend_port(), crate::net::shell_token().as_deref());
+            }
+            crate::logging::capture_backend_output(app, &mut process);
+            Some(process)
+        }
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Failed to start backend: {}", e));
+            crate::crash::record_spawn_failure(&e);
+            None
+        }
+    }
+}
+
+/// Requests handled by the backend supervisor task
+pub enum BackendRequest {
+    /// Locate and spawn the backend unless it is already running
+    Start,
+    /// Stop the backend; `done` fires once the process has exited
+    Stop { done: Option<oneshot::Sender<()>> },
+    /// Report the PID of the running backend
+    Pid { reply: oneshot::Sender<Option<u32>> },
+    /// Report what the supervisor knows about the process
+    Status { reply: oneshot::Sender<SupervisorStatus> },
+    /// Pause the backend while the window is hidden (warm standby)
+    Standby,
+    /// Wake the backend from standby
+    Resume,
+}
+
+/// The supervisor's view of the backend process
+#[derive(Clone, Debug, Default)]
+pub struct SupervisorStatus {
+    pub pid: Option<u32>,
+    pub started: Option<std::time::SystemTime>,
+    pub standby: bool,
+    /// Waiting to respawn after a crash
+    pub restarting: bool,
+    /// Crashed too often and won't be restarted
+    pub gave_up: bool,
+    pub restarts: u32,
+}
+
+/// Cheap, cloneable handle to the supervisor task that owns the backend process.
+/// All process operations happen on that task, so callers never block on them.
+#[derive(Clone)]
+pub struct BackendHandle {
+    tx: mpsc::UnboundedSender<BackendRequest>,
+}
+
+impl BackendHandle {
+    /// Ask the supervisor to spawn the backend
+    pub fn start(&self) {
+        let _ = self.tx.send(BackendRequest::Start);
+    }
+
+    /// Stop the backend and wait (up to `timeout`) for it to exit
+    pub async fn stop(&self, timeout: Duration) {
+        let (done, rx) = oneshot::channel();
+        if self.tx.send(BackendRequest::Stop { done: Some(done) }).is_ok() {
+            let _ = tokio::time::timeout(timeout, rx).await;
+        }
+    }
+
+    /// Put the backend into low-resource standby so reopening the window is instant
+    pub fn standby(&self) {
+        let _ = self.tx.send(BackendRequest::Standby);
+    }
+
+    /// Wake the backend from standby (no-op if it is running normally)
+    pub fn resume(&self) {
+        let _ = self.tx.send(BackendRequest::Resume);
+    }
+
+    /// PID of the running backend, if any
+    pub async fn pid(&self) -> Option<u32> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(BackendRequest::Pid { reply }).ok()?;
+        rx.await.ok().flatten()
+    }
+
+    pub async fn status(&self) -> Option<SupervisorStatus> {
+        let (reply, rx) = oneshot::channel();
+        self.tx.send(BackendRequest::Status { reply }).ok()?;
+        rx.await.ok()
+    }
+}
+
+/// How long the backend gets to shut down cleanly before it is killed
+const GRACEFUL_STOP_TIMEOUT: Duration = Duration::from_secs(8);
+
+/// Ask the backend to stop itself, so pending database writes are flushed
+async fn request_shutdown() {
+    if crate::control::request("shutdown", Duration::from_secs(2)).await.is_ok() {
+        return;
+    }
+    let status =
+        crate::net::request_http(crate::net::backend_port(), "POST", "/api/system/shutdown", Duration::from_secs(2)).await;
+    if status != Some(200) {
+        log_to_file(&format!("[Leaxer] Backend shutdown request not accepted: {:?}", status));
+    }
+}
+
+/// Restarts after unexpected exits before the supervisor gives up
+const MAX_RESTARTS: u32 = 5;
+
+/// A backend that stayed up this long is considered healthy again, resetting the count
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+
+/// Delay before restart number `attempt` (1-based): 1 s, 2 s, 4 s, ... capped at 30 s
+fn restart_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1 << attempt.saturating_sub(1).min(5)).min(Duration::from_secs(30))
+}
+
+/// Payload of the `backend-status` event
+#[derive(Clone, serde::Serialize)]
+pub struct BackendStatusEvent {
+    /// "restarting" (after `delay_ms`), "running" (respawned) or "failed" (gave up)
+    state: &'static str,
+    attempt: u32,
+    max_attempts: u32,
+    delay_ms: Option<u64>,
+    exit_code: Option<i32>,
+}
+
+fn emit_status(app: &tauri::AppHandle, state: &'static str, attempt: u32, delay: Option<Duration>, exit_code: Option<i32>) {
+    let _ = app.emit(
+        "backend-status",
+        BackendStatusEvent {
+            state,
+            attempt,
+            max_attempts: MAX_RESTARTS,
+            delay_ms: delay.map(|d| d.as_millis() as u64),
+            exit_code,
+        },
+    );
+}
+
+async fn wait_for_exit(child: &mut Option<ProcessHandle>) -> std::io::Result<std::process::ExitStatus> {
+    match child {
+        Some(process) => process.wait().await,
+        None => std::future::pending().await,
+    }
+}
+
+async fn sleep_until(at: Option<tokio::time::Instant>) {
+    match at {
+        Some(at) => tokio::time::sleep_until(at).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Start the supervisor task owning the backend process and return a handle to it.
+/// The task also watches the process and respawns it, with backoff, if it exits on its own.
+pub fn start_supervisor(app: tauri::AppHandle) -> BackendHandle {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    tauri::async_runtime::spawn(async move {
+        let mut child: Option<ProcessHandle> = None;
+        let mut in_standby = false;
+        let mut started_at = tokio::time::Instant::now();
+        let mut restarts = 0;
+        let mut restart_at: Option<tokio::time::Instant> = None;
+        let mut started: Option<std::time::SystemTime> = None;
+        let mut gave_up = false;
+
+        loop {
+            let request = tokio::select! {
+                request = rx.recv() => match request {
+                    Some(request) => request,
+                    None => break,
+                },
+                status = wait_for_exit(&mut child) => {
+                    child = None;
+                    in_standby = false;
+                    started = None;
+                    crate::orphans::clear();
+                    let exit_code = status.as_ref().ok().and_then(|s| s.code());
+                    log_to_file(&format!("[Leaxer] Backend exited unexpectedly: {:?}", status));
+                    if started_at.elapsed() >= STABLE_AFTER {
+                        restarts = 0;
+                    }
+                    if restarts >= MAX_RESTARTS {
+                        log_to_file(&format!("[Leaxer] Backend crashed {} times in a row, giving up", restarts));
+                        emit_status(&app, "failed", restarts, None, exit_code);
+                        gave_up = true;
+                        continue;
+                    }
+                    restarts += 1;
+                    let delay = restart_delay(restarts);
+                    log_to_file(&format!(
+                        "[Leaxer] Restarting backend in {:?} (attempt {}/{})",
+                        delay, restarts, MAX_RESTARTS
+                    ));
+                    emit_status(&app, "restarting", restarts, Some(delay), exit_code);
+                    restart_at = Some(tokio::time::Instant::now() + delay);
+                    continue;
+                },
+                _ = sleep_until(restart_at) => {
+                    restart_at = None;
+                    child = spawn_backend(&app);
+                    started_at = tokio::time::Instant::now();
+                    started = child.as_ref().map(|_| std::time::SystemTime::now());
+                    if child.is_some() {
+                        emit_status(&app, "running", restarts, None, None);
+                    } else {
+                        emit_status(&app, "failed", restarts, None, None);
+                        gave_up = true;
+                    }
+                    continue;
+                },
+            };
+
+            match request {
+                BackendRequest::Start => {
+                    // An explicit start replaces a pending restart and starts the count over
+                    restart_at = None;
+                    restarts = 0;
+                    gave_up = false;
+                    if child.is_none() {
+                        child = spawn_backend(&app);
+                        started_at = tokio::time::Instant::now();
+                        started = child.as_ref().map(|_| std::time::SystemTime::now());
+                    }
+                }
+                BackendRequest::Stop { done } => {
+                    restart_at = None;
+                    started = None;
+                    if let Some(process) = child.take() {
+                        log_to_file("[Leaxer] Stopping backend...");
+                        // A stopped VM can't answer; terminating continues the group first
+                        if !in_standby {
+                            request_shutdown().await;
+                        }
+                        in_standby = false;
+                        if !process.stop_gracefully(GRACEFUL_STOP_TIMEOUT).await {
+                            log_to_file("[Leaxer] Backend did not exit in time, killed it");
+                        }
+                        crate::orphans::clear();
+                    }
+                    if let Some(done) = done {
+                        let _ = done.send(());
+                    }
+                }
+                BackendRequest::Pid { reply } => {
+                    let _ = reply.send(child.as_ref().and_then(|c| c.id()));
+                }
+                BackendRequest::Status { reply } => {
+                    let _ = reply.send(SupervisorStatus {
+                        pid: child.as_ref().and_then(|c| c.id()),
+                        started,
+                        standby: in_standby,
+                        restarting: restart_at.is_some(),
+                        gave_up,
+                        restarts,
+                    });
+                }
+                BackendRequest::Standby | BackendRequest::Resume => {
+                    let standby = matches!(request, BackendRequest::Standby);
+                    if standby == in_standby {
+                        continue;
+                    }
+                    if let Some(process) = child.as_ref() {
+                        if process.set_suspended(standby) {
+                            in_standby = standby;
+                            log_to_file(&format!(
+                                "[Leaxer] Backend {}",
+                                if standby { "entered standby" } else { "resumed from standby" }
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    BackendHandle { tx }
+}
+
+#[derive(serde::Serialize)]
+pub struct BackendStatus {
+    /// "starting", "running", "standby", "restarting", "failed", "stopped", or "external"
+    /// (not spawned by us, e.g. a dev server, but answering on the port)
+    state: &'static str,
+    pid: Option<u32>,
+    port: u16,
+    /// Unix time in seconds
+    started_at: Option<u64>,
+    uptime_secs: Option<u64>,
+    /// Resident memory of the backend VM
+    memory_bytes: Option<u64>,
+    /// Crash restarts since the backend was last started on purpose
+    restarts: u32,
+    last_health: Option<crate::readiness::HealthCheck>,
+}
+
+fn state_name(status: &SupervisorStatus, ready: bool, healthy: bool) -> &'static str {
+    if status.gave_up {
+        "failed"
+    } else if status.restarting {
+        "restarting"
+    } else if status.pid.is_none() {
+        if healthy {
+            "external"
+        } else {
+            "stopped"
+        }
+    } else if status.standby {
+        "standby"
+    } else if !ready {
+        "starting"
+    } else {
+        "running"
+    }
+}
+
+fn resident_memory(pid: u32) -> Option<u64> {
+    use sysinfo::{Pid, ProcessesToUpdate, System};
+
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_processes(ProcessesToUpdate::Some(&[pid]), true);
+    sys.process(pid).map(|process| process.memory())
+}
+
+/// Report the backend's state, PID, uptime, memory and health for status indicators and
+/// the diagnostics panel. Checks health once more so the answer is current.
+#[tauri::command]
+pub async fn get_backend_status(app: tauri::AppHandle) -> Result<BackendStatus, String> {
+    let status = app.state::<BackendHandle>().status().await.unwrap_or_default();
+    let port = crate::net::backend_port();
+    // In standby the VM is stopped and can't answer
+    let healthy = !status.standby && crate::readiness::check_health(port).await;
+    let ready = app.state::<crate::readiness::BackendReadiness>().get() == crate::readiness::Readiness::Ready;
+
+    let memory_bytes = match status.pid {
+        Some(pid) => tauri::async_runtime::spawn_blocking(move || resident_memory(pid))
+            .await
+            .map_err(|e| e.to_string())?,
+        None => None,
+    };
+    let since_epoch = |time: std::time::SystemTime| time.duration_since(std::time::UNIX_EPOCH).ok();
+    Ok(BackendStatus {
+        state: state_name(&status, ready || healthy, healthy),
+        pid: status.pid,
+        port,
+        started_at: status.started.and_then(since_epoch).map(|d| d.as_secs()),
+        uptime_secs: status.started.and_then(|t| t.elapsed().ok()).map(|d| d.as_secs()),
+        memory_bytes,
+        restarts: status.restarts,
+        last_health: crate::readiness::last_health(),
+    })
+}
+
+/// A restart is in progress
+static RESTARTING: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Whether `restart_backend` is running
+pub fn restarting() -> bool {
+    RESTARTING.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+fn emit_restart(app: &tauri::AppHandle, state: &str, error: Option<&str>) {
+    let _ = app.emit("backend-restart", serde_json::json!({ "state": state, "error": error }));
+}
+
+/// Stop the backend and start it again with the current settings, so settings that only
+/// apply at launch (network exposure, extensions, ...) take effect without quitting.
+/// Emits `backend-restart` with `state` "stopping", "starting", "ready" or "failed".
+#[tauri::command]
+pub async fn restart_backend(app: tauri::AppHandle) -> Result<(), String> {
+    use std::sync::atomic::Ordering;
+
+    if crate::mock_backend::enabled(&std::env::args().collect::<Vec<_>>()) {
+        return Err("The mock backend can't be restarted".to_string());
+    }
+    if crate::net::external_backend().is_some() {
+        return Err("An external backend can't be restarted from here".to_string());
+    }
+    if RESTARTING.swap(true, Ordering::SeqCst) {
+        return Err("The backend is already restarting".to_string());
+    }
+    log_to_file("[Leaxer] Restarting backend on request");
+    let backend = app.state::<BackendHandle>().inner().clone();
+
+    emit_restart(&app, "stopping", None);
+    backend.stop(crate::BACKEND_STOP_TIMEOUT).await;
+    emit_restart(&app, "starting", None);
+    backend.start();
+    let timeout = crate::readiness::startup_timeout(&app.state::<ConfigStore>().load());
+    let ready = crate::readiness::wait_until_healthy(crate::net::backend_port(), timeout).await;
+    RESTARTING.store(false, Ordering::SeqCst);
+
+    let result = if ready {
+        emit_restart(&app, "ready", None);
+        Ok(())
+    } else {
+        let error = "The backend did not come back up";
+        log_to_file(&format!("[Leaxer] {}", error));
+        emit_restart(&app, "failed", Some(error));
+        Err(error.to_string())
+    };
+    crate::audit::record("backend_restart", None, &result);
+    result
+}
+
+/// Stopping the inference server during a cleanup waits for it to exit
+const CLEANUP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Have the backend free VRAM and temporary files. The route only takes requests that
+/// carry the shell token, so the UI goes through here.
+#[tauri::command]
+pub async fn cleanup_backend() -> Result<(), String> {
+    let status = crate::net::request_http_to(
+        &crate::net::backend_host(),
+        crate::net::backend_port(),
+        "POST",
+        "/api/system/cleanup",
+        CLEANUP_TIMEOUT,
+    )
+    .await;
+    let result = match status {
+        Some(200..=299) => Ok(()),
+        Some(status) => Err(format!("The backend refused the cleanup ({})", status)),
+        None => Err("The backend did not answer".to_string()),
+    };
+    crate::audit::record("backend_cleanup", None, &result);
+    result
+}
+
+/// Turn network exposure on or off and restart the backend with the new binding. Emits
+/// `network-exposure-changed` with the backend's address, LAN URLs included.
+#[tauri::command]
+pub async fn set_network_exposure(app: tauri::AppHandle, enabled: bool) -> Result<crate::net::BackendAddress, String> {
+    let config = app.state::<ConfigStore>();
+    config.set("network_exposure_enabled", serde_json::Value::Bool(enabled));
+    // On disk before the restart, since the backend reads config.json as it starts
+    let mut result = config.flush().map_err(|e| format!("Failed to write config.json: {}", e));
+    if result.is_ok() && config.network_exposure_enabled() != enabled {
+        result = Err("Network exposure is set by LEAXER_NETWORK_EXPOSURE".to_string());
+    }
+    crate::audit::record("network_exposure", Some(if enabled { "on" } else { "off" }), &result);
+    result?;
+
+    log_to_file(&format!("[Leaxer] Network exposure turned {}", if enabled { "on" } else { "off" }));
+    restart_backend(app.clone()).await?;
+    let address = crate::net::get_backend_url();
+    let _ = app.emit("network-exposure-changed", &address);
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use std::fs;
+
+    fn create_launcher(base: &Path) -> PathBuf {
+        let launcher = base.join(backend_relative_path());
+        fs::create_dir_all(launcher.parent().unwrap()).unwrap();
+        fs::write(&launcher, "").unwrap();
+        launcher
+    }
+
+    #[test]
+    fn prefers_bundled_resources() {
+        let resources = tempfile::tempdir().unwrap();
+        let exe_dir = tempfile::tempdir().unwrap();
+        let bundled = create_launcher(resources.path());
+        create_launcher(exe_dir.path());
+
+        let found = find_backend_exe(Some(resources.path()), Some(exe_dir.path()));
+        assert_eq!(found, Some(bundled));
+    }
+
+    #[test]
+    fn falls_back_to_portable_locations() {
+        let exe_dir = tempfile::tempdir().unwrap();
+        let next_to_exe = create_launcher(exe_dir.path());
+        assert_eq!(find_backend_exe(None, Some(exe_dir.path())), Some(next_to_exe));
+
+        let in_resources = create_launcher(&exe_dir.path().join("resources"));
+        assert_eq!(find_backend_exe(None, Some(exe_dir.path())), Some(in_resources));
+    }
+
+    #[test]
+    fn missing_backend_means_dev_mode() {
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(find_backend_exe(Some(empty.path()), Some(empty.path())), None);
+        assert_eq!(find_backend_exe(None, None), None);
+    }
+
+    #[test]
+    fn command_sets_phoenix_environment() {
+        let release = tempfile::tempdir().unwrap();
+        let launcher = create_launcher(release.path());
+
+        let keys = crate::secrets::BackendKeys {
+            secret_key_base: "k".repeat(86),
+            signing_salt: "s".repeat(32),
+        };
+        let cmd = backend_command(&launcher, false, &keys);
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(OsStr::new("PHX_SERVER"), Some(OsStr::new("true")))));
+        assert!(envs.contains(&(OsStr::new("SECRET_KEY_BASE"), Some(OsStr::new(&keys.secret_key_base)))));
+        assert!(envs.contains(&(OsStr::new("RELEASE_DISTRIBUTION"), Some(OsStr::new("none")))));
+        assert!(envs.contains(&(OsStr::new("PORT"), Some(OsStr::new("4000")))));
+        assert!(!envs.iter().any(|(key, _)| *key == "LEAXER_BIND_ALL_INTERFACES"));
+        assert_eq!(cmd.get_current_dir(), Some(release.path().join("leaxer_core").as_path()));
+
+        let exposed = backend_command(&launcher, true, &keys);
+        assert!(exposed
+            .get_envs()
+            .any(|(key, value)| key == "LEAXER_BIND_ALL_INTERFACES" && value == Some(OsStr::new("true"))));
+    }
+
+    #[test]
+    fn starts_the_release_vm_directly() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        assert!(release_start_command(root).is_none());
+
+        fs::create_dir_all(root.join("releases")).unwrap();
+        fs::write(root.join("releases").join("start_erl.data"), "15.2 0.1.0\n").unwrap();
+        fs::write(root.join("releases").join("COOKIE"), "secret\n").unwrap();
+        // No ERTS bundled: not something we can start directly
+        assert!(release_start_command(root).is_none());
+
+        let erl = root
+            .join("erts-15.2")
+            .join("bin")
+            .join(if cfg!(target_os = "windows") { "erl.exe" } else { "erl" });
+        fs::create_dir_all(erl.parent().unwrap()).unwrap();
+        fs::write(&erl, "").unwrap();
+
+        let cmd = release_start_command(root).unwrap();
+        assert_eq!(cmd.get_program(), erl.as_os_str());
+        let args: Vec<_> = cmd.get_args().collect();
+        let boot = root.join("releases").join("0.1.0").join("start");
+        assert!(args.windows(2).any(|pair| pair[0] == "-boot" && pair[1] == boot.as_os_str()));
+        assert_eq!(args[args.len() - 2..], [OsStr::new("-extra"), OsStr::new("--no-halt")]);
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(OsStr::new("RELEASE_VSN"), Some(OsStr::new("0.1.0")))));
+        assert!(envs.contains(&(OsStr::new("RELEASE_COOKIE"), Some(OsStr::new("secret")))));
+    }
+
+    #[test]
+    fn status_names_follow_the_supervisor() {
+        let running = SupervisorStatus {
+            pid: Some(42),
+            ..Default::default()
+        };
+        assert_eq!(state_name(&running, false, false), "starting");
+        assert_eq!(state_name(&running, true, true), "running");
+        assert_eq!(state_name(&SupervisorStatus { standby: true, ..running.clone() }, true, false), "standby");
+        assert_eq!(state_name(&SupervisorStatus::default(), false, false), "stopped");
+        assert_eq!(state_name(&SupervisorStatus::default(), false, true), "external");
+        let crashed = SupervisorStatus {
+            restarting: true,
+            ..Default::default()
+        };
+        assert_eq!(state_name(&crashed, false, false), "restarting");
+        assert_eq!(state_name(&SupervisorStatus { gave_up: true, ..crashed }, false, false), "failed");
+    }
+
+    #[test]
+    fn restart_delay_backs_off_to_a_cap() {
+        assert_eq!(restart_delay(1), Duration::from_secs(1));
+        assert_eq!(restart_delay(2), Duration::from_secs(2));
+        assert_eq!(restart_delay(4), Duration::from_secs(8));
+        assert_eq!(restart_delay(6), Duration::from_secs(30));
+        assert_eq!(restart_delay(40), Duration::from_secs(30));
+    }
+}
@@ -0,0 +1,403 @@
+//! Child processes whose whole tree is torn down with them.
+//!
+//! The backend launcher starts erl, which in turn starts inference workers, so killing the
+//! launcher PID alone leaves the rest running. On unix each child leads its own process
+//! group and is signalled as a group; on Windows it is placed in a Job Object that the OS
+//! kills when the last handle closes, which also covers crashes and logout.
+//!
+//! `kill_all` is the last line of defence for paths that skip destructors: the panic
+//! hook, the exit event and the main window going away.
+
+use std::io;
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Process groups that are still alive, so a panic or signal can reap them
+#[cfg(unix)]
+static LIVE_GROUPS: Mutex<Vec<i32>> = Mutex::new(Vec::new());
+
+/// Raw handles of open job objects. The kernel closes them when we die; this is for
+/// exits that should not leave the tree running until then.
+#[cfg(target_os = "windows")]
+static LIVE_JOBS: Mutex<Vec<isize>> = Mutex::new(Vec::new());
+
+#[cfg(unix)]
+fn signal_group(pgid: i32, signal: i32) -> bool {
+    unsafe { libc::killpg(pgid, signal) == 0 }
+}
+
+/// Kill every process tree we still own. Safe to call from a panic hook or signal handler.
+pub fn kill_all() {
+    #[cfg(unix)]
+    {
+        // try_lock: never deadlock if we panicked while holding the lock
+        if let Ok(mut groups) = LIVE_GROUPS.try_lock() {
+            for pgid in groups.drain(..) {
+                signal_group(pgid, libc::SIGCONT);
+                signal_group(pgid, libc::SIGKILL);
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HANDLE;
+        use windows::Win32::System::JobObjects::TerminateJobObject;
+
+        if let Ok(jobs) = LIVE_JOBS.try_lock() {
+            for &job in jobs.iter() {
+                unsafe {
+                    let _ = TerminateJobObject(HANDLE(job as *mut std::ffi::c_void), 1);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+struct JobObject(windows::Win32::Foundation::HANDLE);
+
+// A job handle is a plain kernel handle and may be used from any thread
+#[cfg(target_os = "windows")]
+unsafe impl Send for JobObject {}
+#[cfg(target_os = "windows")]
+unsafe impl Sync for JobObject {}
+
+#[cfg(target_os = "windows")]
+impl JobObject {
+    /// Create a job that kills all its processes when its last handle is closed
+    fn new() -> io::Result<Self> {
+        use windows::core::PCWSTR;
+        use windows::Win32::System::JobObjects::{
+            CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        unsafe {
+            let job = CreateJobObjectW(None, PCWSTR::null()).map_err(io::Error::other)?;
+            let job = JobObject(job);
+            let mut info = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+            info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            SetInformationJobObject(
+                job.0,
+                JobObjectExtendedLimitInformation,
+                &info as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+            .map_err(io::Error::other)?;
+            LIVE_JOBS.lock().unwrap().push(job.0 .0 as isize);
+            Ok(job)
+        }
+    }
+
+    fn assign(&self, process: std::os::windows::io::RawHandle) -> io::Result<()> {
+        use windows::Win32::Foundation::HANDLE;
+        use windows::Win32::System::JobObjects::AssignProcessToJobObject;
+
+        unsafe { AssignProcessToJobObject(self.0, HANDLE(process)).map_err(io::Error::other) }
+    }
+
+    fn terminate(&self) {
+        use windows::Win32::System::JobObjects::TerminateJobObject;
+
+        unsafe {
+            let _ = TerminateJobObject(self.0, 1);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for JobObject {
+    fn drop(&mut self) {
+        // Unregistered before closing, so kill_all never uses a closed handle
+        LIVE_JOBS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).retain(|&job| job != self.0 .0 as isize);
+        unsafe {
+            let _ = windows::Win32::Foundation::CloseHandle(self.0);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+const CREATE_SUSPENDED: u32 = 0x00000004;
+
+/// Resume the threads of a process created with `CREATE_SUSPENDED` (only its main thread)
+#[cfg(target_os = "windows")]
+fn resume_threads(pid: u32) -> io::Result<()> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Thread32First, Thread32Next, TH32CS_SNAPTHREAD, THREADENTRY32,
+    };
+    use windows::Win32::System::Threading::{OpenThread, ResumeThread, THREAD_SUSPEND_RESUME};
+
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPTHREAD, 0).map_err(io::Error::other)?;
+        let mut entry = THREADENTRY32 {
+            dwSize: std::mem::size_of::<THREADENTRY32>() as u32,
+            ..Default::default()
+        };
+        let mut resumed = false;
+        let mut more = Thread32First(snapshot, &mut entry).is_ok();
+        while more {
+            if entry.th32OwnerProcessID == pid {
+                if let Ok(thread) = OpenThread(THREAD_SUSPEND_RESUME, false, entry.th32ThreadID) {
+                    resumed |= ResumeThread(thread) != u32::MAX;
+                    let _ = CloseHandle(thread);
+                }
+            }
+            more = Thread32Next(snapshot, &mut entry).is_ok();
+        }
+        let _ = CloseHandle(snapshot);
+        if resumed {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!("could not resume process {}", pid)))
+        }
+    }
+}
+
+/// A spawned child together with everything it starts. Dropping the handle kills the tree.
+pub struct ProcessHandle {
+    child: tokio::process::Child,
+    #[cfg(unix)]
+    pgid: Option<i32>,
+    #[cfg(target_os = "windows")]
+    job: Option<JobObject>,
+}
+
+impl ProcessHandle {
+    /// Spawn `cmd` as the root of a new process tree
+    pub fn spawn(mut cmd: Command) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            cmd.process_group(0);
+        }
+
+        // Started suspended so it can't start anything before it is in the job. This
+        // replaces the caller's creation flags; none of our children want a console.
+        #[cfg(target_os = "windows")]
+        {
+            use std::os::windows::process::CommandExt;
+            cmd.creation_flags(crate::process::CREATE_NO_WINDOW | CREATE_SUSPENDED);
+        }
+
+        let child = tokio::process::Command::from(cmd).spawn()?;
+
+        #[cfg(unix)]
+        let pgid = child.id().map(|pid| pid as i32);
+        #[cfg(unix)]
+        if let Some(pgid) = pgid {
+            LIVE_GROUPS.lock().unwrap().push(pgid);
+        }
+
+        // The launcher is assigned while still suspended, so every process it starts
+        // inherits the job
+        #[cfg(target_os = "windows")]
+        let job = match (JobObject::new(), child.raw_handle()) {
+            (Ok(job), Some(process)) => match job.assign(process) {
+                Ok(()) => Some(job),
+                Err(e) => {
+                    crate::logging::log_to_file(&format!("[Leaxer] Failed to assign job object: {}", e));
+                    None
+                }
+            },
+            (Err(e), _) => {
+                crate::logging::log_to_file(&format!("[Leaxer] Failed to create job object: {}", e));
+                None
+            }
+            (Ok(_), None) => None,
+        };
+        #[cfg(target_os = "windows")]
+        let mut child = child;
+        #[cfg(target_os = "windows")]
+        if let Err(e) = child.id().map_or(Ok(()), resume_threads) {
+            let _ = child.start_kill();
+            return Err(e);
+        }
+
+        Ok(Self {
+            child,
+            #[cfg(unix)]
+            pgid,
+            #[cfg(target_os = "windows")]
+            job,
+        })
+    }
+
+    /// PID of the root process, if it is still running
+    pub fn id(&self) -> Option<u32> {
+        self.child.id()
+    }
+
+    /// Pause (`true`) or resume the whole tree.
+    /// On unix the group is stopped with SIGSTOP so it uses no CPU at all; Windows has no
+    /// supported way to suspend another process, so the root is dropped to idle priority.
+    pub fn set_suspended(&self, suspended: bool) -> bool {
+        #[cfg(unix)]
+        {
+            let signal = if suspended { libc::SIGSTOP } else { libc::SIGCONT };
+            self.pgid.map(|pgid| signal_group(pgid, signal)).unwrap_or(false)
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::Foundation::CloseHandle;
+            use windows::Win32::System::Threading::{
+                OpenProcess, SetPriorityClass, IDLE_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS,
+                PROCESS_SET_INFORMATION,
+            };
+
+            let pid = match self.id() {
+                Some(pid) => pid,
+                None => return false,
+            };
+            let class = if suspended { IDLE_PRIORITY_CLASS } else { NORMAL_PRIORITY_CLASS };
+            unsafe {
+                match OpenProcess(PROCESS_SET_INFORMATION, false, pid) {
+                    Ok(handle) => {
+                        let ok = SetPriorityClass(handle, class).is_ok();
+                        let _ = CloseHandle(handle);
+                        ok
+                    }
+                    Err(_) => false,
+                }
+            }
+        }
+    }
+
+    /// Kill every process in the tree without waiting
+    pub fn kill_tree(&mut self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.pgid.take() {
+            // A stopped group can't act on the kill until it is continued
+            signal_group(pgid, libc::SIGCONT);
+            signal_group(pgid, libc::SIGKILL);
+            LIVE_GROUPS.lock().unwrap().retain(|&g| g != pgid);
+        }
+
+        #[cfg(target_os = "windows")]
+        if let Some(job) = self.job.take() {
+            job.terminate();
+        }
+
+        let _ = self.child.start_kill();
+    }
+
+    /// Take the root's stdin pipe (None unless spawned with it piped)
+    pub fn take_stdin(&mut self) -> Option<tokio::process::ChildStdin> {
+        self.child.stdin.take()
+    }
+
+    /// Take the root's stdout and stderr pipes (None unless spawned with them piped)
+    pub fn take_output(&mut self) -> (Option<tokio::process::ChildStdout>, Option<tokio::process::ChildStderr>) {
+        (self.child.stdout.take(), self.child.stderr.take())
+    }
+
+    /// Wait for the root process to exit on its own
+    pub async fn wait(&mut self) -> io::Result<std::process::ExitStatus> {
+        self.child.wait().await
+    }
+
+    /// Ask the tree to exit on its own: SIGTERM to the group on unix (erl stops its
+    /// applications in order on SIGTERM). Windows has no equivalent for windowless
+    /// processes, so callers there rely on the backend's shutdown endpoint.
+    pub fn terminate(&self) {
+        #[cfg(unix)]
+        if let Some(pgid) = self.pgid {
+            signal_group(pgid, libc::SIGCONT);
+            signal_group(pgid, libc::SIGTERM);
+        }
+    }
+
+    /// Terminate the tree, give the root up to `grace` to exit, then kill whatever is
+    /// left. Returns whether it exited in time.
+    pub async fn stop_gracefully(mut self, grace: std::time::Duration) -> bool {
+        self.terminate();
+        let exited = matches!(tokio::time::timeout(grace, self.child.wait()).await, Ok(Ok(_)));
+        // Also reaps stragglers the root left behind
+        self.shutdown().await;
+        exited
+    }
+
+    /// Kill the tree and wait for the root process to exit
+    pub async fn shutdown(mut self) {
+        self.kill_tree();
+        let _ = self.child.wait().await;
+    }
+}
+
+impl Drop for ProcessHandle {
+    fn drop(&mut self) {
+        self.kill_tree();
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Running and not a zombie awaiting reaping
+    fn is_alive(pid: i32) -> bool {
+        std::fs::read_to_string(format!("/proc/{}/stat", pid))
+            .map(|stat| !stat.split(") ").nth(1).unwrap_or("").starts_with('Z'))
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn shutdown_kills_grandchildren() {
+        tauri::async_runtime::block_on(shutdown_kills_grandchildren_async());
+    }
+
+    async fn shutdown_kills_grandchildren_async() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("grandchild.pid");
+
+        // The launcher backgrounds a grandchild, like erl starting a worker
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(format!("sleep 30 & echo $! > {}; wait", pid_file.display()));
+        let handle = ProcessHandle::spawn(cmd).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let grandchild = loop {
+            if let Some(pid) = std::fs::read_to_string(&pid_file)
+                .ok()
+                .and_then(|s| s.trim().parse::<i32>().ok())
+            {
+                break pid;
+            }
+            assert!(Instant::now() < deadline, "grandchild never started");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        };
+        assert!(is_alive(grandchild));
+
+        handle.shutdown().await;
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while is_alive(grandchild) && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(!is_alive(grandchild), "grandchild survived shutdown");
+    }
+
+    #[test]
+    fn stop_gracefully_lets_the_root_clean_up() {
+        tauri::async_runtime::block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let marker = dir.path().join("cleaned-up");
+
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(format!(
+                "trap 'echo done > {}; exit 0' TERM; while true; do sleep 0.1; done",
+                marker.display()
+            ));
+            let handle = ProcessHandle::spawn(cmd).unwrap();
+            // Let the shell install its trap
+            tokio::time::sleep(Duration::from_millis(200)).await;
+
+            assert!(handle.stop_gracefully(Duration::from_secs(5)).await);
+            assert!(marker.exists());
+        });
+    }
+}
@@ -0,0 +1,531 @@
+//! Profiles: separate Leaxer setups, each with its own data dir, config.json, port and
+//! backend process.
+//!
+//! The list lives in `profiles.json` in the default Leaxer dir. The default profile uses
+//! that dir and the usual port; the others get `<Leaxer dir>/profiles/<id>` (or a data
+//! dir of the user's choosing) and a port of their own. Each profile can also carry
+//! environment overrides for its backend. Switching stops the old profile's backend and
+//! extensions, then starts the new profile's, reporting each step to the UI as a
+//! `profile-status` event. `--profile <id>` picks the profile at launch.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::json;
+use tauri::{Emitter, Manager};
+
+use crate::logging::log_to_file;
+use crate::paths::base_leaxer_dir;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+pub const PROFILE_FLAG: &str = "--profile";
+
+/// Set by the shell itself; a profile can't override them
+pub(crate) const RESERVED_ENV: &[&str] = &[
+    "PORT",
+    "LEAXER_USER_DIR",
+    "LEAXER_CONTROL",
+    "LEAXER_SOCKET",
+    "LEAXER_LOG_LEVEL",
+    "LEAXER_SHELL_TOKEN",
+    "LEAXER_PAIRING",
+    "LEAXER_DEVICE_TOKENS",
+    "LEAXER_ENV_FILES",
+    "RELEASE_DISTRIBUTION",
+];
+
+/// Ports handed to new profiles count up from here
+const FIRST_PROFILE_PORT: u16 = 4200;
+
+/// How long a switch waits for the new backend to accept connections
+const READY_TIMEOUT: Duration = Duration::from_secs(90);
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub port: u16,
+    /// Data dir to use instead of `<Leaxer dir>/profiles/<id>`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+    /// Extra environment for this profile's backend
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ProfileList {
+    pub active: String,
+    pub profiles: Vec<Profile>,
+}
+
+impl Default for ProfileList {
+    fn default() -> Self {
+        ProfileList {
+            active: DEFAULT_PROFILE.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE.to_string(),
+                name: "Default".to_string(),
+                port: crate::net::BACKEND_PORT,
+                data_dir: None,
+                env: BTreeMap::new(),
+            }],
+        }
+    }
+}
+
+impl ProfileList {
+    fn get(&self, id: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.id == id)
+    }
+
+    pub fn active_profile(&self) -> &Profile {
+        self.get(&self.active).unwrap_or(&self.profiles[0])
+    }
+
+    /// A file-name-safe id derived from the name, unique within the list
+    fn new_id(&self, name: &str) -> String {
+        let slug: String = name
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let slug = slug.trim_matches('-');
+        let slug = if slug.is_empty() { "profile" } else { slug };
+        let mut id = slug.to_string();
+        let mut n = 2;
+        while self.get(&id).is_some() {
+            id = format!("{}-{}", slug, n);
+            n += 1;
+        }
+        id
+    }
+
+    fn next_port(&self) -> u16 {
+        self.profiles
+            .iter()
+            .map(|p| p.port)
+            .filter(|&port| port >= FIRST_PROFILE_PORT)
+            .max()
+            .map_or(FIRST_PROFILE_PORT, |port| port + 1)
+    }
+}
+
+/// Data dir of a profile
+pub fn profile_dir(base: &Path, id: &str) -> PathBuf {
+    if id == DEFAULT_PROFILE {
+        base.to_path_buf()
+    } else {
+        base.join("profiles").join(id)
+    }
+}
+
+fn load(path: &Path) -> ProfileList {
+    let mut list: ProfileList = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    // The default profile always exists, and comes first
+    if list.get(DEFAULT_PROFILE).is_none() {
+        list.profiles.insert(0, ProfileList::default().profiles.remove(0));
+    }
+    list
+}
+
+/// Write through a temporary file, so a crash mid-write can't lose the list
+fn save(path: &Path, list: &ProfileList) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(list)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Managed profile list
+pub struct ProfileStore {
+    path: Option<PathBuf>,
+    list: Mutex<ProfileList>,
+    /// Held for the duration of a switch
+    switching: tokio::sync::Mutex<()>,
+}
+
+impl ProfileStore {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let list = path.as_deref().map(load).unwrap_or_default();
+        ProfileStore {
+            path,
+            list: Mutex::new(list),
+            switching: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// The list in `profiles.json` in the default Leaxer dir
+    pub fn open() -> Self {
+        Self::new(base_leaxer_dir().map(|dir| dir.join("profiles.json")))
+    }
+
+    pub fn list(&self) -> ProfileList {
+        self.list.lock().unwrap().clone()
+    }
+
+    fn update<T>(&self, change: impl FnOnce(&mut ProfileList) -> Result<T, String>) -> Result<T, String> {
+        let mut list = self.list.lock().unwrap();
+        let mut updated = list.clone();
+        let result = change(&mut updated)?;
+        if let Some(path) = &self.path {
+            save(path, &updated).map_err(|e| format!("Failed to save profiles: {}", e))?;
+        }
+        *list = updated;
+        Ok(result)
+    }
+
+    /// Point paths and the backend port at the active profile, or at the one picked with
+    /// `--profile` (which then becomes the active one). Runs before anything reads them.
+    pub fn apply_active(&self, args: &[String]) {
+        if let Some(id) = launch_profile(args) {
+            if let Err(e) = self.set_active(&id) {
+                log_to_file(&format!("[Leaxer] Ignoring {} {}: {}", PROFILE_FLAG, id, e));
+            }
+        }
+        let list = self.list();
+        apply(list.active_profile());
+    }
+
+    pub fn create(&self, name: &str) -> Result<Profile, String> {
+        let name = valid_name(name)?;
+        self.update(|list| {
+            let profile = Profile {
+                id: list.new_id(name),
+                name: name.to_string(),
+                port: list.next_port(),
+                data_dir: None,
+                env: BTreeMap::new(),
+            };
+            list.profiles.push(profile.clone());
+            Ok(profile)
+        })
+    }
+
+    pub fn rename(&self, id: &str, name: &str) -> Result<Profile, String> {
+        let name = valid_name(name)?;
+        self.update(|list| {
+            let profile = list
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == id)
+                .ok_or_else(|| format!("No profile {}", id))?;
+            profile.name = name.to_string();
+            Ok(profile.clone())
+        })
+    }
+
+    /// Change a profile's port, data dir and backend environment. Changes to the active
+    /// profile take effect the next time its backend starts.
+    pub fn configure(
+        &self,
+        id: &str,
+        port: u16,
+        data_dir: Option<PathBuf>,
+        env: BTreeMap<String, String>,
+    ) -> Result<Profile, String> {
+        if port < 1024 {
+            return Err("Ports below 1024 are reserved".to_string());
+        }
+        if let Some(key) = env.keys().find(|key| key.is_empty() || key.contains('=')) {
+            return Err(format!("Invalid environment variable name: {:?}", key));
+        }
+        if let Some(key) = env.keys().find(|key| RESERVED_ENV.contains(&key.as_str())) {
+            return Err(format!("{} is set by Leaxer and can't be overridden", key));
+        }
+        if let Some(dir) = &data_dir {
+            if id == DEFAULT_PROFILE {
+                return Err("The default profile always uses the Leaxer folder".to_string());
+            }
+            if !dir.is_absolute() {
+                return Err("The data folder must be an absolute path".to_string());
+            }
+        }
+        self.update(|list| {
+            if list.profiles.iter().any(|p| p.id != id && p.port == port) {
+                return Err(format!("Port {} is already used by another profile", port));
+            }
+            let profile = list
+                .profiles
+                .iter_mut()
+                .find(|p| p.id == id)
+                .ok_or_else(|| format!("No profile {}", id))?;
+            profile.port = port;
+            profile.data_dir = data_dir;
+            profile.env = env;
+            Ok(profile.clone())
+        })
+    }
+
+    pub fn delete(&self, id: &str) -> Result<Profile, String> {
+        self.update(|list| {
+            if id == DEFAULT_PROFILE {
+                return Err("The default profile can't be deleted".to_string());
+            }
+            if id == list.active {
+                return Err("Switch to another profile before deleting this one".to_string());
+            }
+            let index = list
+                .profiles
+                .iter()
+                .position(|p| p.id == id)
+                .ok_or_else(|| format!("No profile {}", id))?;
+            Ok(list.profiles.remove(index))
+        })
+    }
+
+    fn set_active(&self, id: &str) -> Result<Profile, String> {
+        self.update(|list| {
+            let profile = list.get(id).cloned().ok_or_else(|| format!("No profile {}", id))?;
+            list.active = profile.id.clone();
+            Ok(profile)
+        })
+    }
+}
+
+fn valid_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > 64 {
+        return Err("Profile names must be 1 to 64 characters".to_string());
+    }
+    Ok(name)
+}
+
+/// `--profile <id>` or `--profile=<id>`
+pub fn launch_profile(args: &[String]) -> Option<String> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == PROFILE_FLAG {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(PROFILE_FLAG).and_then(|rest| rest.strip_prefix('=')).map(str::to_string)
+        }
+    })
+}
+
+/// Environment overrides of the active profile, for its backend
+pub fn active_env(app: &tauri::AppHandle) -> BTreeMap<String, String> {
+    app.state::<ProfileStore>().list().active_profile().env.clone()
+}
+
+fn apply(profile: &Profile) {
+    let dir = match (profile.id.as_str(), &profile.data_dir) {
+        (DEFAULT_PROFILE, _) => None,
+        (_, Some(dir)) => Some(dir.clone()),
+        (id, None) => base_leaxer_dir().map(|base| profile_dir(&base, id)),
+    };
+    crate::paths::set_profile_dir(dir);
+    crate::net::set_backend_port(crate::net::port_override().unwrap_or(profile.port));
+}
+
+fn emit_status(app: &tauri::AppHandle, profile: &Profile, state: &str, error: Option<&str>) {
+    let _ = app.emit(
+        "profile-status",
+        json!({ "profile": profile.id, "port": profile.port, "state": state, "error": error }),
+    );
+}
+
+/// Wait until something accepts connections on the port
+async fn wait_until_listening(port: u16) -> bool {
+    let poll = async {
+        while tokio::net::TcpStream::connect(("127.0.0.1", port)).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    };
+    tokio::time::timeout(READY_TIMEOUT, poll).await.is_ok()
+}
+
+async fn switch(app: &tauri::AppHandle, id: &str) -> Result<Profile, String> {
+    let store = app.state::<ProfileStore>();
+    let _switching = store
+        .switching
+        .try_lock()
+        .map_err(|_| "A profile switch is already in progress".to_string())?;
+    let current = store.list().active_profile().clone();
+    if current.id == id {
+        return Ok(current);
+    }
+    let profile = store.list().get(id).cloned().ok_or_else(|| format!("No profile {}", id))?;
+    log_to_file(&format!("[Leaxer] Switching profile {} -> {}", current.id, profile.id));
+
+    emit_status(app, &current, "stopping", None);
+    crate::extensions::stop_all(app);
+    let backend = app.state::<crate::process::BackendHandle>().inner().clone();
+    backend.stop(crate::BACKEND_STOP_TIMEOUT).await;
+
+    store.set_active(&profile.id)?;
+    apply(&profile);
+    let config_path = crate::config::config_path();
+    if let Err(e) = app.state::<crate::config::ConfigStore>().switch_to(config_path) {
+        log_to_file(&format!("[Leaxer] Failed to switch config.json: {}", e));
+    }
+
+    emit_status(app, &profile, "starting", None);
+    backend.start();
+    crate::extensions::start(app);
+    if wait_until_listening(profile.port).await {
+        log_to_file(&format!("[Leaxer] Profile {} is ready on port {}", profile.id, profile.port));
+        emit_status(app, &profile, "ready", None);
+    } else {
+        log_to_file(&format!("[Leaxer] Backend for profile {} did not come up", profile.id));
+        emit_status(app, &profile, "failed", Some("The backend did not start in time"));
+    }
+    crate::commands::window::refresh_tray_menu(app);
+    Ok(profile)
+}
+
+/// Switch from the tray menu; errors only go to the log
+pub fn switch_in_background(app: &tauri::AppHandle, id: String) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = switch(&app, &id).await {
+            log_to_file(&format!("[Leaxer] Profile switch failed: {}", e));
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_profiles(store: tauri::State<'_, ProfileStore>) -> ProfileList {
+    store.list()
+}
+
+#[tauri::command]
+pub fn create_profile(app: tauri::AppHandle, name: String) -> Result<Profile, String> {
+    let profile = app.state::<ProfileStore>().create(&name)?;
+    crate::commands::window::refresh_tray_menu(&app);
+    Ok(profile)
+}
+
+#[tauri::command]
+pub fn rename_profile(app: tauri::AppHandle, id: String, name: String) -> Result<Profile, String> {
+    let profile = app.state::<ProfileStore>().rename(&id, &name)?;
+    crate::commands::window::refresh_tray_menu(&app);
+    Ok(profile)
+}
+
+/// Set a profile's port, data dir (`None` for the default location) and backend environment
+#[tauri::command]
+pub fn configure_profile(
+    app: tauri::AppHandle,
+    id: String,
+    port: u16,
+    data_dir: Option<String>,
+    env: BTreeMap<String, String>,
+) -> Result<Profile, String> {
+    let data_dir = data_dir.filter(|dir| !dir.trim().is_empty()).map(PathBuf::from);
+    app.state::<ProfileStore>().configure(&id, port, data_dir, env)
+}
+
+/// Remove a profile from the list; its data dir is only removed when `delete_data` is set,
+/// and never when it is a folder the user picked
+#[tauri::command]
+pub fn delete_profile(app: tauri::AppHandle, id: String, delete_data: bool) -> Result<(), String> {
+    let profile = app.state::<ProfileStore>().delete(&id)?;
+    crate::commands::window::refresh_tray_menu(&app);
+    if let Some(dir) = profile.data_dir.as_ref().filter(|_| delete_data) {
+        log_to_file(&format!("[Leaxer] Keeping the custom data dir {} of deleted profile {}", dir.display(), profile.id));
+    } else if delete_data {
+        if let Some(base) = base_leaxer_dir() {
+            let dir = profile_dir(&base, &profile.id);
+            if dir.exists() {
+                std::fs::remove_dir_all(&dir).map_err(|e| format!("Failed to delete {}: {}", dir.display(), e))?;
+            }
+        }
+    }
+    log_to_file(&format!("[Leaxer] Deleted profile {}", profile.id));
+    Ok(())
+}
+
+/// Stop the current profile's backend and start the selected one's; resolves once the
+/// switch is done (progress arrives as `profile-status` events meanwhile)
+#[tauri::command]
+pub async fn switch_profile(app: tauri::AppHandle, id: String) -> Result<Profile, String> {
+    switch(&app, &id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_list_has_only_the_default_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = ProfileStore::new(Some(dir.path().join("profiles.json")));
+        let list = store.list();
+        assert_eq!(list.active, DEFAULT_PROFILE);
+        assert_eq!(list.active_profile().port, crate::net::BACKEND_PORT);
+    }
+
+    #[test]
+    fn new_profiles_get_unique_ids_and_ports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.json");
+        let store = ProfileStore::new(Some(path.clone()));
+
+        let work = store.create("Work / Clients").unwrap();
+        let again = store.create("work clients").unwrap();
+        assert_eq!(work.id, "work---clients");
+        assert_eq!(again.id, "work-clients");
+        assert_eq!((work.port, again.port), (FIRST_PROFILE_PORT, FIRST_PROFILE_PORT + 1));
+        assert!(store.create("   ").is_err());
+
+        store.rename(&work.id, "Clients").unwrap();
+        assert_eq!(ProfileStore::new(Some(path)).list().get(&work.id).unwrap().name, "Clients");
+    }
+
+    #[test]
+    fn default_and_active_profiles_cannot_be_deleted() {
+        let store = ProfileStore::new(None);
+        let other = store.create("Other").unwrap();
+        assert!(store.delete(DEFAULT_PROFILE).is_err());
+
+        store.set_active(&other.id).unwrap();
+        assert!(store.delete(&other.id).is_err());
+        store.set_active(DEFAULT_PROFILE).unwrap();
+        assert_eq!(store.delete(&other.id).unwrap(), other);
+    }
+
+    #[test]
+    fn configures_ports_dirs_and_env() {
+        let store = ProfileStore::new(None);
+        let other = store.create("Other").unwrap();
+        let env = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        let dir = std::env::temp_dir().join("leaxer-other");
+
+        let configured = store
+            .configure(&other.id, 4300, Some(dir.clone()), env(&[("LEAXER_LOG_LEVEL", "debug")]))
+            .unwrap();
+        assert_eq!((configured.port, configured.data_dir), (4300, Some(dir)));
+        assert_eq!(configured.env.get("LEAXER_LOG_LEVEL").map(String::as_str), Some("debug"));
+
+        assert!(store.configure(&other.id, crate::net::BACKEND_PORT, None, env(&[])).is_err());
+        assert!(store.configure(&other.id, 4300, None, env(&[("PORT", "1")])).is_err());
+        assert!(store.configure(&other.id, 4300, Some(PathBuf::from("relative")), env(&[])).is_err());
+        assert!(store
+            .configure(DEFAULT_PROFILE, 4000, Some(std::env::temp_dir()), env(&[]))
+            .is_err());
+    }
+
+    #[test]
+    fn launch_flag_selects_a_profile() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(launch_profile(&args(&["leaxer", "--profile", "work"])).as_deref(), Some("work"));
+        assert_eq!(launch_profile(&args(&["leaxer", "--profile=work"])).as_deref(), Some("work"));
+        assert_eq!(launch_profile(&args(&["leaxer"])), None);
+    }
+
+    #[test]
+    fn profiles_live_next_to_the_default_data() {
+        let base = Path::new("/data/Leaxer");
+        assert_eq!(profile_dir(base, DEFAULT_PROFILE), base);
+        assert_eq!(profile_dir(base, "work"), base.join("profiles").join("work"));
+    }
+}
@@ -0,0 +1,256 @@
+//! Backend readiness gate for the main window.
+//!
+//! The window starts hidden. Once the page has loaded, the UI awaits `wait_for_backend`
+//! and only then shows the window, so nobody sees connection errors while Phoenix is
+//! still booting. The probe polls `/api/health` on the active port. If it hasn't answered
+//! within the startup timeout (`backend_startup_timeout_secs` in config.json), a native
+//! dialog shows the end of the log and offers to retry, open the logs folder or quit.
+//! Without the dialog plugin the state becomes `failed` and the UI shows the window with
+//! an error instead of waiting forever.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+use tokio::sync::watch;
+
+use crate::logging::log_to_file;
+
+/// First boots of the release (and slow disks) can take a while
+pub(crate) const READY_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Bounds for a configured startup timeout, in seconds
+pub(crate) const TIMEOUT_RANGE: std::ops::RangeInclusive<u64> = 10..=600;
+
+/// Log lines shown in the startup error dialog
+#[cfg(feature = "dialog")]
+const DIALOG_LOG_LINES: usize = 12;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(tag = "state", rename_all = "lowercase")]
+pub enum Readiness {
+    Starting,
+    Ready,
+    Failed { error: String },
+}
+
+/// Managed readiness state
+pub struct BackendReadiness(watch::Sender<Readiness>);
+
+impl Default for BackendReadiness {
+    fn default() -> Self {
+        BackendReadiness(watch::channel(Readiness::Starting).0)
+    }
+}
+
+impl BackendReadiness {
+    pub fn get(&self) -> Readiness {
+        self.0.borrow().clone()
+    }
+
+    fn set(&self, app: &tauri::AppHandle, readiness: Readiness) {
+        self.0.send_replace(readiness.clone());
+        let _ = app.emit("backend-readiness", readiness);
+    }
+}
+
+/// Result of the most recent health check
+#[derive(Clone, Copy, Debug, serde::Serialize)]
+pub struct HealthCheck {
+    pub healthy: bool,
+    /// Unix time in seconds
+    pub checked_at: u64,
+}
+
+static LAST_HEALTH: Mutex<Option<HealthCheck>> = Mutex::new(None);
+
+pub fn last_health() -> Option<HealthCheck> {
+    *LAST_HEALTH.lock().unwrap()
+}
+
+/// Check the backend's health endpoint once and remember the result
+pub async fn check_health(port: u16) -> bool {
+    let status =
+        crate::net::request_http_to(&crate::net::backend_host(), port, "GET", "/api/health", PROBE_TIMEOUT).await;
+    let healthy = matches!(status, Some(200..=299));
+    let checked_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    *LAST_HEALTH.lock().unwrap() = Some(HealthCheck { healthy, checked_at });
+    healthy
+}
+
+/// How long to wait for the backend, from `backend_startup_timeout_secs` in config.json
+pub fn startup_timeout(config: &serde_json::Value) -> Duration {
+    config
+        .get("backend_startup_timeout_secs")
+        .and_then(serde_json::Value::as_u64)
+        .map(|secs| Duration::from_secs(secs.clamp(*TIMEOUT_RANGE.start(), *TIMEOUT_RANGE.end())))
+        .unwrap_or(READY_TIMEOUT)
+}
+
+/// Poll the health endpoint until it answers or `timeout` passes
+pub async fn wait_until_healthy(port: u16, timeout: Duration) -> bool {
+    let poll = async {
+        while !check_health(port).await {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    };
+    tokio::time::timeout(timeout, poll).await.is_ok()
+}
+
+/// The end of the backend log, or of startup.log when the backend wrote nothing
+#[cfg(feature = "dialog")]
+fn log_tail() -> String {
+    crate::logging::flush();
+    [crate::logging::backend_log_path(), crate::logging::startup_log_path()]
+        .into_iter()
+        .flatten()
+        .filter_map(|path| crate::logging::read_tail(&path, DIALOG_LOG_LINES))
+        .find(|tail| !tail.trim().is_empty())
+        .unwrap_or_default()
+}
+
+#[cfg(feature = "dialog")]
+fn open_logs_folder(app: &tauri::AppHandle) {
+    let Some(dir) = crate::paths::get_leaxer_user_dir() else {
+        return;
+    };
+    let logs = dir.join("logs");
+    let path = if logs.exists() { logs } else { dir };
+    if let Err(e) = crate::commands::files::reveal_path(app.clone(), path.to_string_lossy().to_string()) {
+        log_to_file(&format!("[Leaxer] Failed to open the logs folder: {}", e));
+    }
+}
+
+/// Ask the user what to do about a backend that didn't start. Returns true to retry;
+/// quitting exits the app.
+#[cfg(feature = "dialog")]
+async fn ask_retry(app: &tauri::AppHandle, error: &str) -> bool {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult};
+
+    const RETRY: &str = "Retry";
+    const OPEN_LOGS: &str = "Open logs folder";
+    const QUIT: &str = "Quit";
+
+    let tail = log_tail();
+    loop {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        app.dialog()
+            .message(format!("{}.\n\nLast log lines:\n{}", error, tail))
+            .title("Leaxer could not start")
+            .kind(MessageDialogKind::Error)
+            .buttons(MessageDialogButtons::YesNoCancelCustom(
+                RETRY.to_string(),
+                OPEN_LOGS.to_string(),
+                QUIT.to_string(),
+            ))
+            .show_with_result(move |result| {
+                let _ = tx.send(result);
+            });
+        match rx.await {
+            Ok(MessageDialogResult::Yes) => return true,
+            Ok(MessageDialogResult::Custom(label)) if label == RETRY => return true,
+            Ok(MessageDialogResult::No) => open_logs_folder(app),
+            Ok(MessageDialogResult::Custom(label)) if label == OPEN_LOGS => open_logs_folder(app),
+            // Quit, or the dialog was dismissed
+            _ => {
+                log_to_file("[Leaxer] Quitting after the backend failed to start");
+                app.exit(1);
+                return false;
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "dialog"))]
+async fn ask_retry(_app: &tauri::AppHandle, _error: &str) -> bool {
+    false
+}
+
+/// Probe the freshly started backend and record whether it came up
+pub async fn probe(app: &tauri::AppHandle) {
+    let _span = crate::profiling::span("backend_ready");
+    let port = crate::net::backend_port();
+    loop {
+        let timeout = startup_timeout(&app.state::<crate::config::ConfigStore>().load());
+        crate::splash::set_status(app, "Waiting for server...");
+        if wait_until_healthy(port, timeout).await {
+            crate::metrics::mark(crate::metrics::Phase::BackendReady);
+            log_to_file(&format!("[Leaxer] Backend is ready on port {}", port));
+            crate::splash::set_status(app, "Ready");
+            app.state::<BackendReadiness>().set(app, Readiness::Ready);
+            crate::config_backup::save_last_good(&app.state::<crate::config::ConfigStore>());
+            return;
+        }
+
+        log_to_file(&format!("[Leaxer] Backend did not become ready within {:?}", timeout));
+        let error = format!("The backend did not respond within {} seconds", timeout.as_secs());
+        crate::splash::set_error(app, &error);
+        if !ask_retry(app, &error).await {
+            app.state::<BackendReadiness>().set(app, Readiness::Failed { error });
+            return;
+        }
+
+        log_to_file("[Leaxer] Retrying backend start");
+        let backend = app.state::<crate::process::BackendHandle>().inner().clone();
+        backend.stop(crate::BACKEND_STOP_TIMEOUT).await;
+        backend.start();
+    }
+}
+
+/// Give up on the backend before it was started, e.g. because it can't run
+pub fn fail(app: &tauri::AppHandle, error: String) {
+    crate::splash::set_error(app, &error);
+    app.state::<BackendReadiness>().set(app, Readiness::Failed { error });
+}
+
+/// Resolves once the backend is ready or has failed to start
+#[tauri::command]
+pub async fn wait_for_backend(readiness: tauri::State<'_, BackendReadiness>) -> Result<Readiness, String> {
+    let mut rx = readiness.0.subscribe();
+    let readiness = rx
+        .wait_for(|readiness| *readiness != Readiness::Starting)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(readiness.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_up_when_nothing_answers() {
+        let port = std::net::TcpListener::bind(("127.0.0.1", 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let ready = tauri::async_runtime::block_on(wait_until_healthy(port, Duration::from_millis(600)));
+        assert!(!ready);
+    }
+
+    #[test]
+    fn startup_timeout_is_configurable_within_bounds() {
+        assert_eq!(startup_timeout(&serde_json::json!({})), READY_TIMEOUT);
+        let config = |secs: u64| serde_json::json!({ "backend_startup_timeout_secs": secs });
+        assert_eq!(startup_timeout(&config(45)), Duration::from_secs(45));
+        assert_eq!(startup_timeout(&config(1)), Duration::from_secs(10));
+        assert_eq!(startup_timeout(&config(100_000)), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn readiness_serializes_with_its_state() {
+        let failed = Readiness::Failed { error: "timeout".to_string() };
+        assert_eq!(
+            serde_json::to_value(failed).unwrap(),
+            serde_json::json!({ "state": "failed", "error": "timeout" })
+        );
+    }
+}
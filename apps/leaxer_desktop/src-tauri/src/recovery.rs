@@ -0,0 +1,183 @@
+//! Detection of and recovery from a crashed or hung main webview.
+//!
+//! Renderer crashes aren't reported the same way (or at all) on every platform, so the
+//! UI sends `ui_heartbeat` every few seconds with a deep link to what it is showing.
+//! When the visible main window stops beating, the page is reloaded; if that brings no
+//! heartbeat either, the window is destroyed and created again from its config. The
+//! saved link is queued so the new page reopens the same workflow or chat, and its
+//! first heartbeat learns that it was recovered so the UI can say so.
+//!
+//! The check runs on the `MonitorSignal`, so it stops while the app is hidden in the tray.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::Manager;
+
+use crate::deep_link::{self, PendingDeepLinks};
+use crate::logging::log_to_file;
+use crate::monitor::MonitorSignal;
+
+/// How often the heartbeat is checked while the window is visible
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Silence after which the page counts as dead. The UI beats every 5 s; timers in a
+/// visible page are never throttled that far.
+const UNRESPONSIVE_AFTER: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+struct HeartbeatState {
+    /// Last beat from the current page; None until it has loaded
+    last: Option<Instant>,
+    /// Deep link to what the UI was showing at the last beat
+    route: Option<String>,
+    /// Recovery attempts since the last beat
+    attempts: u32,
+    /// Set when recovering, reported to (and cleared by) the next page's first beat
+    recovered: bool,
+}
+
+/// Managed heartbeat tracking
+#[derive(Default)]
+pub struct UiHeartbeat {
+    state: Mutex<HeartbeatState>,
+    /// The main window is being recreated; closing it must not quit the app
+    recreating: AtomicBool,
+}
+
+impl UiHeartbeat {
+    pub fn recreating(&self) -> bool {
+        self.recreating.load(Ordering::SeqCst)
+    }
+}
+
+/// What to do about a page that has been silent since `last`
+#[derive(Debug, PartialEq)]
+enum Action {
+    Nothing,
+    Reload,
+    Recreate,
+}
+
+fn action(last: Option<Instant>, attempts: u32, now: Instant) -> Action {
+    match last {
+        Some(last) if now.duration_since(last) >= UNRESPONSIVE_AFTER => {
+            if attempts == 0 {
+                Action::Reload
+            } else {
+                Action::Recreate
+            }
+        }
+        _ => Action::Nothing,
+    }
+}
+
+fn recreate_main_window(app: &tauri::AppHandle) -> Result<(), String> {
+    let heartbeat = app.state::<UiHeartbeat>();
+    heartbeat.recreating.store(true, Ordering::SeqCst);
+    let result = (|| {
+        if let Some(window) = app.get_webview_window("main") {
+            window.destroy().map_err(|e| e.to_string())?;
+        }
+        crate::commands::window::build_main_window(app)?;
+        crate::commands::window::show_main_window(app);
+        Ok(())
+    })();
+    heartbeat.recreating.store(false, Ordering::SeqCst);
+    result
+}
+
+fn recover(app: &tauri::AppHandle, action: Action) {
+    let route = {
+        let heartbeat = app.state::<UiHeartbeat>();
+        let mut state = heartbeat.state.lock().unwrap();
+        state.last = Some(Instant::now());
+        state.attempts += 1;
+        state.recovered = true;
+        state.route.clone()
+    };
+    // The fresh page opens whatever was on screen
+    if let Some(link) = route.as_deref().and_then(deep_link::parse) {
+        app.state::<PendingDeepLinks>().push(link);
+    }
+
+    match action {
+        Action::Reload => {
+            log_to_file("[Leaxer] Interface stopped responding, reloading it");
+            if let Some(window) = app.get_webview_window("main") {
+                if let Err(e) = window.reload() {
+                    log_to_file(&format!("[Leaxer] Reload failed: {}", e));
+                }
+            }
+        }
+        Action::Recreate => {
+            log_to_file("[Leaxer] Interface still not responding, recreating the window");
+            if let Err(e) = recreate_main_window(app) {
+                log_to_file(&format!("[Leaxer] Failed to recreate the window: {}", e));
+            }
+        }
+        Action::Nothing => {}
+    }
+}
+
+/// Watch the heartbeat of the main window while it is on screen
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut seen = 0;
+        let mut was_visible = false;
+        loop {
+            app.state::<MonitorSignal>().wait(&mut seen, CHECK_INTERVAL);
+            let visible = app
+                .get_webview_window("main")
+                .map(|window| window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false))
+                .unwrap_or(false);
+            let heartbeat = app.state::<UiHeartbeat>();
+            let next = {
+                let mut state = heartbeat.state.lock().unwrap();
+                // Pages hidden in the tray or minimized may be throttled; count silence
+                // only from when the window is back on screen
+                if visible && !was_visible {
+                    state.last = state.last.map(|_| Instant::now());
+                }
+                if visible {
+                    action(state.last, state.attempts, Instant::now())
+                } else {
+                    Action::Nothing
+                }
+            };
+            was_visible = visible;
+            if next != Action::Nothing {
+                recover(&app, next);
+            }
+        }
+    });
+}
+
+/// Called by the UI every few seconds with a `leaxer://open/...` link to what it shows.
+/// Returns true once after the interface was recovered from a crash.
+#[tauri::command]
+pub fn ui_heartbeat(heartbeat: tauri::State<'_, UiHeartbeat>, route: Option<String>) -> bool {
+    let mut state = heartbeat.state.lock().unwrap();
+    state.last = Some(Instant::now());
+    state.attempts = 0;
+    state.route = route;
+    std::mem::take(&mut state.recovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reloads_first_then_recreates() {
+        let now = Instant::now();
+        assert_eq!(action(None, 0, now), Action::Nothing);
+        assert_eq!(action(Some(now), 0, now), Action::Nothing);
+
+        let later = now + UNRESPONSIVE_AFTER;
+        assert_eq!(action(Some(now), 0, later), Action::Reload);
+        assert_eq!(action(Some(now), 1, later), Action::Recreate);
+    }
+}
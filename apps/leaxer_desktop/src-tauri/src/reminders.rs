@@ -0,0 +1,267 @@
+//! Reminders that fire as OS notifications at a set time.
+//!
+//! The frontend registers them (including ones the backend asks for), and the shell
+//! keeps them in `reminders.json` in the Leaxer dir, so they survive backend restarts and
+//! app restarts alike. A scheduler thread sleeps until the next one is due; reminders
+//! that came due while Leaxer wasn't running fire right after the next start.
+//!
+//! OS notifications need the `notification` feature; without it reminders still reach
+//! the UI as `reminder-fired` events.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tauri::{Emitter, Manager};
+
+use crate::logging::log_to_file;
+
+/// Upper bound on a single sleep, so a changed system clock is noticed eventually
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub body: Option<String>,
+    /// When to fire, in milliseconds since the Unix epoch
+    pub at: u64,
+    /// Deep link opened when the reminder is acted on in the UI
+    #[serde(default)]
+    pub link: Option<String>,
+}
+
+struct Schedule {
+    reminders: Vec<Reminder>,
+    next_id: u64,
+}
+
+/// Managed reminder store; cheap to clone
+#[derive(Clone)]
+pub struct Reminders {
+    path: Option<PathBuf>,
+    schedule: Arc<(Mutex<Schedule>, Condvar)>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Remove and return the reminders due at `now`, earliest first
+fn take_due(reminders: &mut Vec<Reminder>, now: u64) -> Vec<Reminder> {
+    let (mut due, pending): (Vec<_>, Vec<_>) = reminders.drain(..).partition(|r| r.at <= now);
+    *reminders = pending;
+    due.sort_by_key(|r| r.at);
+    due
+}
+
+fn load(path: &Path) -> Vec<Reminder> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Write through a temporary file, so a crash mid-write can't lose every reminder
+fn save(path: &Path, reminders: &[Reminder]) -> std::io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(reminders)?)?;
+    std::fs::rename(&tmp, path)
+}
+
+impl Reminders {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let reminders = path.as_deref().map(load).unwrap_or_default();
+        Reminders {
+            path,
+            schedule: Arc::new((
+                Mutex::new(Schedule {
+                    reminders,
+                    next_id: now_ms(),
+                }),
+                Condvar::new(),
+            )),
+        }
+    }
+
+    fn persist(&self, reminders: &[Reminder]) {
+        if let Some(path) = &self.path {
+            if let Err(e) = save(path, reminders) {
+                log_to_file(&format!("[Leaxer] Failed to save reminders: {}", e));
+            }
+        }
+    }
+
+    /// Add a reminder and wake the scheduler in case it is now the next one due
+    pub fn add(&self, title: String, body: Option<String>, at: u64, link: Option<String>) -> Reminder {
+        let (lock, cond) = &*self.schedule;
+        let mut schedule = lock.lock().unwrap();
+        schedule.next_id += 1;
+        let reminder = Reminder {
+            id: format!("reminder_{}", schedule.next_id),
+            title,
+            body,
+            at,
+            link,
+        };
+        schedule.reminders.push(reminder.clone());
+        self.persist(&schedule.reminders);
+        cond.notify_all();
+        reminder
+    }
+
+    /// Remove a reminder; returns whether it existed
+    pub fn cancel(&self, id: &str) -> bool {
+        let (lock, cond) = &*self.schedule;
+        let mut schedule = lock.lock().unwrap();
+        let before = schedule.reminders.len();
+        schedule.reminders.retain(|r| r.id != id);
+        let removed = schedule.reminders.len() != before;
+        if removed {
+            self.persist(&schedule.reminders);
+            cond.notify_all();
+        }
+        removed
+    }
+
+    pub fn list(&self) -> Vec<Reminder> {
+        let mut reminders = self.schedule.0.lock().unwrap().reminders.clone();
+        reminders.sort_by_key(|r| r.at);
+        reminders
+    }
+
+    /// Block until at least one reminder is due, then remove and return the due ones
+    fn wait_due(&self) -> Vec<Reminder> {
+        let (lock, cond) = &*self.schedule;
+        let mut schedule = lock.lock().unwrap();
+        loop {
+            let now = now_ms();
+            let due = take_due(&mut schedule.reminders, now);
+            if !due.is_empty() {
+                self.persist(&schedule.reminders);
+                return due;
+            }
+            let sleep = schedule
+                .reminders
+                .iter()
+                .map(|r| Duration::from_millis(r.at - now))
+                .min()
+                .unwrap_or(MAX_SLEEP)
+                .min(MAX_SLEEP);
+            schedule = cond.wait_timeout(schedule, sleep).unwrap().0;
+        }
+    }
+}
+
+fn fire(app: &tauri::AppHandle, reminder: &Reminder) {
+    log_to_file(&format!("[Leaxer] Reminder due: {}", reminder.id));
+
+    #[cfg(feature = "notification")]
+    {
+        use tauri_plugin_notification::NotificationExt;
+
+        let mut notification = app.notification().builder().title(&reminder.title);
+        if let Some(body) = &reminder.body {
+            notification = notification.body(body);
+        }
+        if let Err(e) = notification.show() {
+            log_to_file(&format!("[Leaxer] Failed to show reminder: {}", e));
+        }
+    }
+    let _ = app.emit("reminder-fired", reminder);
+}
+
+/// Fire reminders as they come due, for the lifetime of the app
+pub fn start_scheduler(app: &tauri::AppHandle) {
+    let reminders = app.state::<Reminders>().inner().clone();
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        for reminder in reminders.wait_due() {
+            fire(&app, &reminder);
+        }
+    });
+}
+
+/// Schedule a notification for `at` (milliseconds since the Unix epoch)
+#[tauri::command]
+pub fn schedule_reminder(
+    reminders: tauri::State<'_, Reminders>,
+    title: String,
+    body: Option<String>,
+    at: u64,
+    link: Option<String>,
+) -> Result<Reminder, String> {
+    if title.trim().is_empty() {
+        return Err("A reminder needs a title".to_string());
+    }
+    if let Some(link) = &link {
+        if crate::deep_link::parse(link).is_none() {
+            return Err(format!("Not a Leaxer link: {}", link));
+        }
+    }
+    Ok(reminders.add(title, body, at, link))
+}
+
+#[tauri::command]
+pub fn cancel_reminder(reminders: tauri::State<'_, Reminders>, id: String) -> bool {
+    reminders.cancel(&id)
+}
+
+/// Pending reminders, earliest first
+#[tauri::command]
+pub fn list_reminders(reminders: tauri::State<'_, Reminders>) -> Vec<Reminder> {
+    reminders.list()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reminder(id: &str, at: u64) -> Reminder {
+        Reminder {
+            id: id.to_string(),
+            title: id.to_string(),
+            body: None,
+            at,
+            link: None,
+        }
+    }
+
+    #[test]
+    fn takes_only_due_reminders_in_order() {
+        let mut reminders = vec![reminder("later", 300), reminder("b", 200), reminder("a", 100)];
+        let due = take_due(&mut reminders, 250);
+        assert_eq!(due.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(reminders, vec![reminder("later", 300)]);
+    }
+
+    #[test]
+    fn reminders_survive_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reminders.json");
+
+        let store = Reminders::new(Some(path.clone()));
+        let kept = store.add("Review".to_string(), Some("the upscale run".to_string()), u64::MAX, None);
+        let dropped = store.add("Other".to_string(), None, u64::MAX, None);
+        assert_ne!(kept.id, dropped.id);
+        assert!(store.cancel(&dropped.id));
+        assert!(!store.cancel(&dropped.id));
+
+        assert_eq!(Reminders::new(Some(path)).list(), vec![kept]);
+    }
+
+    #[test]
+    fn missed_reminders_are_due_immediately() {
+        let store = Reminders::new(None);
+        store.add("Missed".to_string(), None, 1, None);
+        store.add("Future".to_string(), None, u64::MAX, None);
+        let due = store.wait_due();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].title, "Missed");
+        assert_eq!(store.list().len(), 1);
+    }
+}
@@ -0,0 +1,405 @@
+//! Publish saved workflows and chats to the OS search index.
+//!
+//! The backend stores them as files in the Leaxer dir (`workflows/*.lxr`,
+//! `chats/*.chat`); the shell watches those folders and republishes when they change.
+//! - macOS: items go into Core Spotlight, and activating one opens it through a deep link
+//! - Windows: an Internet Shortcut per item is kept in `Documents/Leaxer Search`, since the
+//!   Windows Search index covers the user's folders but not AppData, where the Leaxer dir
+//!   is; opening one launches the `leaxer://` link
+//!
+//! Set `search_indexing` to false in config.json to keep Leaxer content out of system
+//! search; published items are removed on the next start.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::Manager;
+
+use crate::config::ConfigStore;
+use crate::deep_link::{link_for, ItemKind};
+use crate::logging::log_to_file;
+
+/// Quiet period after a change in the watched folders before republishing
+const REINDEX_DEBOUNCE: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchItem {
+    kind: ItemKind,
+    id: String,
+    title: String,
+}
+
+impl SearchItem {
+    fn link(&self) -> String {
+        link_for(self.kind, &self.id)
+    }
+}
+
+/// Keeps the folder watcher alive and serializes republishing
+pub struct SearchIndexer {
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    publishing: Mutex<()>,
+}
+
+/// Saved workflows and chats under `leaxer_dir`, ordered by kind and id
+pub fn scan_items(leaxer_dir: &Path) -> Vec<SearchItem> {
+    let list = |dir: &str, ext: &str| -> Vec<(String, PathBuf)> {
+        std::fs::read_dir(leaxer_dir.join(dir))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter_map(|path| {
+                let stem = path.file_stem()?.to_str()?.to_string();
+                (path.extension()? == ext).then_some((stem, path))
+            })
+            .collect()
+    };
+
+    let mut items: Vec<SearchItem> = list("workflows", "lxr")
+        .into_iter()
+        .map(|(name, _)| SearchItem {
+            kind: ItemKind::Workflow,
+            title: name.clone(),
+            id: name,
+        })
+        .collect();
+    // Chat files carry their display name; the file name is the id
+    items.extend(list("chats", "chat").into_iter().map(|(id, path)| {
+        let title = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|chat| chat.get("name").and_then(|n| n.as_str()).map(str::to_string))
+            .unwrap_or_else(|| id.clone());
+        SearchItem {
+            kind: ItemKind::Chat,
+            id,
+            title,
+        }
+    }));
+    items.sort_by(|a, b| (a.kind as u8, &a.id).cmp(&(b.kind as u8, &b.id)));
+    items
+}
+
+#[cfg(target_os = "macos")]
+use spotlight::publish;
+#[cfg(target_os = "windows")]
+use shortcuts::publish;
+
+/// Linux has no system-wide index to publish to
+#[cfg(target_os = "linux")]
+fn publish(_items: &[SearchItem]) -> Result<(), String> {
+    Ok(())
+}
+
+fn reindex(app: &tauri::AppHandle, enabled: bool) {
+    let Some(leaxer_dir) = crate::paths::get_leaxer_user_dir() else {
+        return;
+    };
+    let items = if enabled { scan_items(&leaxer_dir) } else { Vec::new() };
+
+    let indexer = app.state::<SearchIndexer>();
+    let _publishing = indexer.publishing.lock().unwrap();
+    match publish(&items) {
+        Ok(()) => log_to_file(&format!("[Leaxer] Published {} items to system search", items.len())),
+        Err(e) => log_to_file(&format!("[Leaxer] Failed to update system search: {}", e)),
+    }
+}
+
+/// Publish current content and keep it up to date while the app runs
+pub fn start(app: &tauri::AppHandle) {
+    use notify::{RecursiveMode, Watcher};
+
+    if cfg!(target_os = "linux") {
+        return;
+    }
+    app.manage(SearchIndexer {
+        watcher: Mutex::new(None),
+        publishing: Mutex::new(()),
+    });
+    #[cfg(target_os = "macos")]
+    spotlight::handle_activations(app);
+
+    let enabled = app.state::<ConfigStore>().get().search_indexing;
+
+    let handle = app.clone();
+    std::thread::spawn(move || reindex(&handle, enabled));
+    if !enabled {
+        return;
+    }
+
+    let Some(leaxer_dir) = crate::paths::get_leaxer_user_dir() else {
+        return;
+    };
+    let (tx, rx) = std::sync::mpsc::channel::<()>();
+    let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if event.is_ok() {
+            let _ = tx.send(());
+        }
+    });
+    let mut watcher = match watcher {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Failed to watch content for search: {}", e));
+            return;
+        }
+    };
+    for dir in ["workflows", "chats"] {
+        let dir = leaxer_dir.join(dir);
+        let _ = std::fs::create_dir_all(&dir);
+        if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            log_to_file(&format!("[Leaxer] Failed to watch {:?}: {}", dir, e));
+        }
+    }
+    *app.state::<SearchIndexer>().watcher.lock().unwrap() = Some(watcher);
+
+    // Saving a workflow can touch the file several times; republish once it settles
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        while rx.recv().is_ok() {
+            while rx.recv_timeout(REINDEX_DEBOUNCE).is_ok() {}
+            reindex(&handle, true);
+        }
+    });
+}
+
+/// Core Spotlight, reached through the Objective-C runtime
+#[cfg(target_os = "macos")]
+mod spotlight {
+    use std::ffi::c_void;
+    use std::sync::OnceLock;
+
+    use objc2::rc::Id;
+    use objc2::runtime::{AnyClass, AnyObject, Bool, Sel};
+    use objc2::{class, msg_send, msg_send_id, sel, Encode};
+    use objc2_foundation::{NSArray, NSString};
+
+    use super::SearchItem;
+    use crate::logging::log_to_file;
+
+    /// Groups Leaxer's items so they can be told apart from other apps' in the index
+    const DOMAIN: &str = "org.leaxer.ai.content";
+
+    #[link(name = "CoreSpotlight", kind = "framework")]
+    extern "C" {
+        static CSSearchableItemActivityIdentifier: &'static NSString;
+    }
+
+    static APP: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+    fn default_index() -> Id<AnyObject> {
+        unsafe { msg_send_id![class!(CSSearchableIndex), defaultSearchableIndex] }
+    }
+
+    fn searchable_item(item: &SearchItem) -> Id<AnyObject> {
+        unsafe {
+            let content_type = NSString::from_str("public.content");
+            let attributes: Id<AnyObject> = msg_send_id![
+                msg_send_id![class!(CSSearchableItemAttributeSet), alloc],
+                initWithItemContentType: &*content_type
+            ];
+            let title = NSString::from_str(&item.title);
+            let description = NSString::from_str(match item.kind {
+                crate::deep_link::ItemKind::Workflow => "Leaxer workflow",
+                crate::deep_link::ItemKind::Chat => "Leaxer chat",
+            });
+            let _: () = msg_send![&attributes, setTitle: &*title];
+            let _: () = msg_send![&attributes, setContentDescription: &*description];
+
+            // The identifier is the deep link, so an activation says what to open
+            let identifier = NSString::from_str(&item.link());
+            let domain = NSString::from_str(DOMAIN);
+            msg_send_id![
+                msg_send_id![class!(CSSearchableItem), alloc],
+                initWithUniqueIdentifier: &*identifier,
+                domainIdentifier: &*domain,
+                attributeSet: &*attributes
+            ]
+        }
+    }
+
+    /// Replace what is published with `items`. The whole domain is cleared first, so
+    /// items from an earlier run or a failed update can't linger.
+    pub fn publish(items: &[SearchItem]) -> Result<(), String> {
+        let index = default_index();
+        let items: Vec<Id<AnyObject>> = items.iter().map(searchable_item).collect();
+
+        unsafe {
+            let domains = NSArray::from_vec(vec![NSString::from_str(DOMAIN)]);
+            let _: () = msg_send![
+                &index,
+                deleteSearchableItemsWithDomainIdentifiers: &*domains,
+                completionHandler: std::ptr::null::<c_void>()
+            ];
+            if !items.is_empty() {
+                let items = NSArray::from_vec(items);
+                let _: () = msg_send![
+                    &index,
+                    indexSearchableItems: &*items,
+                    completionHandler: std::ptr::null::<c_void>()
+                ];
+            }
+        }
+        Ok(())
+    }
+
+    /// `application:continueUserActivity:restorationHandler:`, called when a Spotlight
+    /// result is opened
+    extern "C" fn continue_user_activity(
+        _this: &AnyObject,
+        _cmd: Sel,
+        _application: &AnyObject,
+        activity: &AnyObject,
+        _restoration_handler: *mut c_void,
+    ) -> Bool {
+        let identifier: Option<Id<NSString>> = unsafe {
+            let user_info: Option<Id<AnyObject>> = msg_send_id![activity, userInfo];
+            match user_info {
+                Some(info) => msg_send_id![&info, objectForKey: CSSearchableItemActivityIdentifier],
+                None => None,
+            }
+        };
+        match (identifier, APP.get()) {
+            (Some(identifier), Some(app)) => {
+                crate::deep_link::open(app, &identifier.to_string());
+                Bool::YES
+            }
+            _ => Bool::NO,
+        }
+    }
+
+    /// Teach the app delegate to handle Spotlight activations; tao's delegate doesn't
+    pub fn handle_activations(app: &tauri::AppHandle) {
+        let _ = APP.set(app.clone());
+        unsafe {
+            let ns_app: *mut AnyObject = msg_send![class!(NSApplication), sharedApplication];
+            let delegate: *mut AnyObject = msg_send![ns_app, delegate];
+            let Some(delegate) = delegate.as_ref() else {
+                log_to_file("[Leaxer] No app delegate, Spotlight results won't open items");
+                return;
+            };
+            let class: *const AnyClass = delegate.class();
+            let types = format!("{}@:@@?\0", Bool::ENCODING);
+            let imp: unsafe extern "C" fn() = std::mem::transmute(
+                continue_user_activity
+                    as extern "C" fn(&AnyObject, Sel, &AnyObject, &AnyObject, *mut c_void) -> Bool,
+            );
+            objc2::ffi::class_addMethod(
+                class as *mut _,
+                sel!(application:continueUserActivity:restorationHandler:).as_ptr(),
+                Some(imp),
+                types.as_ptr().cast(),
+            );
+        }
+    }
+}
+
+/// Internet Shortcuts in an indexed folder, for Windows Search
+#[cfg(target_os = "windows")]
+mod shortcuts {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use super::SearchItem;
+
+    /// Where the shortcuts go; the Leaxer dir itself isn't indexed
+    fn shortcut_dir() -> Option<PathBuf> {
+        dirs::document_dir().map(|dir| dir.join("Leaxer Search"))
+    }
+
+    /// Characters Windows doesn't allow in file names
+    const RESERVED: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+    fn file_names(items: &[SearchItem]) -> Vec<String> {
+        let mut taken: HashMap<String, usize> = HashMap::new();
+        items
+            .iter()
+            .map(|item| {
+                let base: String = item
+                    .title
+                    .chars()
+                    .map(|c| if RESERVED.contains(&c) || c.is_control() { '_' } else { c })
+                    .collect();
+                let base = match base.trim_end_matches(['.', ' ']) {
+                    "" => item.id.clone(),
+                    trimmed => trimmed.to_string(),
+                };
+                let count = taken.entry(base.to_lowercase()).or_insert(0);
+                *count += 1;
+                match *count {
+                    1 => format!("{}.url", base),
+                    n => format!("{} ({}).url", base, n),
+                }
+            })
+            .collect()
+    }
+
+    /// Replace what is published with `items`; unchanged shortcuts are left alone so the indexer
+    /// only sees real changes
+    pub fn publish(items: &[SearchItem]) -> Result<(), String> {
+        let dir = shortcut_dir().ok_or("No Documents folder")?;
+        std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        // Left behind by versions that kept the shortcuts in the Leaxer dir
+        if let Some(old) = crate::paths::get_leaxer_user_dir().map(|dir| dir.join("search")) {
+            let _ = std::fs::remove_dir_all(old);
+        }
+
+        let wanted: HashMap<String, String> = file_names(items)
+            .into_iter()
+            .zip(items)
+            .map(|(name, item)| (name, format!("[InternetShortcut]\r\nURL={}\r\n", item.link())))
+            .collect();
+
+        for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !wanted.contains_key(&name) {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
+        for (name, content) in &wanted {
+            let path = dir.join(name);
+            if std::fs::read_to_string(&path).ok().as_deref() != Some(content.as_str()) {
+                std::fs::write(&path, content).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_workflows_and_chats() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("workflows")).unwrap();
+        std::fs::create_dir_all(dir.path().join("chats")).unwrap();
+        std::fs::write(dir.path().join("workflows/upscale.lxr"), "{}").unwrap();
+        std::fs::write(dir.path().join("workflows/notes.txt"), "").unwrap();
+        std::fs::write(dir.path().join("chats/chat_1_ab.chat"), r#"{"name":"Trip ideas"}"#).unwrap();
+        std::fs::write(dir.path().join("chats/chat_2_cd.chat"), "not json").unwrap();
+
+        let items = scan_items(dir.path());
+        let summary: Vec<(ItemKind, &str, &str)> = items
+            .iter()
+            .map(|item| (item.kind, item.id.as_str(), item.title.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ItemKind::Workflow, "upscale", "upscale"),
+                (ItemKind::Chat, "chat_1_ab", "Trip ideas"),
+                (ItemKind::Chat, "chat_2_cd", "chat_2_cd"),
+            ]
+        );
+        assert_eq!(items[1].link(), "leaxer://open/chat/chat%5F1%5Fab");
+    }
+
+    #[test]
+    fn missing_folders_have_no_items() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(scan_items(dir.path()).is_empty());
+    }
+}
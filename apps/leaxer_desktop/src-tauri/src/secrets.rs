@@ -0,0 +1,418 @@
+//! API keys and tokens, kept out of config.json.
+//!
+//! Secrets are one JSON object encrypted with ChaCha20-Poly1305 into `secrets.enc` in the
+//! base Leaxer dir, shared by all profiles. The key lives in the OS keychain; where there
+//! is none (a Linux box without a secret service) it falls back to `secrets.key` beside
+//! the blob, readable only by the user. Everything is decrypted on first use and kept in
+//! memory. While the keychain holding the key of existing secrets can't be reached, the
+//! secrets stay locked and untouched; access is retried on the next use.
+//!
+//! Secrets named like environment variables (`HF_TOKEN`, `OPENAI_API_KEY`) are also passed
+//! to the backend when it starts. Names starting with `leaxer.` belong to the shell, such
+//! as the backend's `SECRET_KEY_BASE` and `SIGNING_SALT`, generated once per installation
+//! and replaced by `rotate_secrets`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+
+use crate::logging::log_to_file;
+
+pub(crate) const KEYRING_SERVICE: &str = "Leaxer";
+const KEYRING_KEY_ENTRY: &str = "secrets-key";
+
+const SECRETS_FILE: &str = "secrets.enc";
+/// Holds the key when the keychain can't
+const KEY_FILE: &str = "secrets.key";
+
+/// Prefix of the shell's own secrets, which the UI can't read or change
+const RESERVED_PREFIX: &str = "leaxer.";
+const SECRET_KEY_BASE: &str = "leaxer.secret_key_base";
+const SIGNING_SALT: &str = "leaxer.signing_salt";
+
+/// Random bytes behind a generated `SECRET_KEY_BASE`; Phoenix wants at least 64
+const SECRET_KEY_BASE_BYTES: usize = 64;
+const SIGNING_SALT_BYTES: usize = 24;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Bytes from the OS's random number generator
+pub fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).expect("the OS random number generator failed");
+    bytes
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn decode_key(encoded: &str) -> Option<[u8; KEY_LEN]> {
+    let bytes = base64::engine::general_purpose::STANDARD.decode(encoded.trim()).ok()?;
+    bytes.try_into().ok()
+}
+
+fn seal(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(key));
+    let nonce = random_bytes(NONCE_LEN);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| "Failed to encrypt the secrets".to_string())?;
+    Ok([nonce, ciphertext].concat())
+}
+
+fn unseal(key: &[u8; KEY_LEN], sealed: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    ChaCha20Poly1305::new(Key::from_slice(key)).decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+}
+
+/// Write `bytes` to `path` in one step, readable only by the user
+pub(crate) fn write_private(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    std::fs::write(&temp, bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&temp, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::rename(&temp, path)
+}
+
+fn new_key() -> [u8; KEY_LEN] {
+    let mut key = [0u8; KEY_LEN];
+    key.copy_from_slice(&random_bytes(KEY_LEN));
+    key
+}
+
+fn read_key_file(dir: &Path) -> Option<[u8; KEY_LEN]> {
+    decode_key(&std::fs::read_to_string(dir.join(KEY_FILE)).ok()?)
+}
+
+/// The key from the key file in `dir`, created if there is none
+fn file_key(dir: &Path) -> Result<[u8; KEY_LEN], String> {
+    if let Some(key) = read_key_file(dir) {
+        return Ok(key);
+    }
+    let key = new_key();
+    write_private(&dir.join(KEY_FILE), encode(&key).as_bytes())
+        .map_err(|e| format!("Failed to save the secrets key: {}", e))?;
+    Ok(key)
+}
+
+/// The key to use when the keychain can't be reached: a key file, but only a new one when
+/// there are no secrets yet that a new key would leave unreadable
+fn fallback_key(dir: &Path, keychain_error: &str) -> Result<[u8; KEY_LEN], String> {
+    if dir.join(SECRETS_FILE).exists() {
+        return Err(format!("The keychain holding the secrets key is unavailable: {}", keychain_error));
+    }
+    log_to_file(&format!("[Leaxer] Keychain unavailable ({}), keeping the secrets key in a file", keychain_error));
+    file_key(dir)
+}
+
+/// The encryption key: from the key file if secrets were written while the keychain was
+/// unavailable, else from the keychain, created on first use
+fn load_key(dir: &Path) -> Result<[u8; KEY_LEN], String> {
+    if let Some(key) = read_key_file(dir) {
+        return Ok(key);
+    }
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_KEY_ENTRY).map_err(|e| e.to_string());
+    let stored = entry.as_ref().map_err(Clone::clone).and_then(|entry| match entry.get_password() {
+        Ok(encoded) => Ok(Some(encoded)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    });
+    match stored {
+        Ok(Some(encoded)) => return decode_key(&encoded).ok_or_else(|| "The keychain holds an invalid key".to_string()),
+        Ok(None) => {}
+        Err(e) => return fallback_key(dir, &e),
+    }
+    let key = new_key();
+    match entry.and_then(|entry| entry.set_password(&encode(&key)).map_err(|e| e.to_string())) {
+        Ok(()) => Ok(key),
+        Err(e) => {
+            log_to_file(&format!("[Leaxer] Failed to store the secrets key in the keychain ({}), using a file", e));
+            file_key(dir)
+        }
+    }
+}
+
+/// A name beside `path` that no earlier unreadable file has taken
+fn set_aside_path(path: &Path) -> PathBuf {
+    (0..)
+        .map(|n| match n {
+            0 => path.with_extension("enc.unreadable"),
+            n => path.with_extension(format!("enc.unreadable.{}", n)),
+        })
+        .find(|candidate| !candidate.exists())
+        .expect("an unused name")
+}
+
+/// Secrets at `path` decrypted with `key`. A file that doesn't decrypt, e.g. because the
+/// keychain entry was deleted, is set aside rather than overwritten; one that can't be
+/// read at all is an error, leaving it in place.
+fn read_secrets(path: &Path, key: &[u8; KEY_LEN]) -> Result<BTreeMap<String, String>, String> {
+    let sealed = match std::fs::read(path) {
+        Ok(sealed) => sealed,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(format!("Failed to read the secrets: {}", e)),
+    };
+    match unseal(key, &sealed).and_then(|plain| serde_json::from_slice(&plain).ok()) {
+        Some(secrets) => Ok(secrets),
+        None => {
+            let aside = set_aside_path(path);
+            log_to_file(&format!("[Leaxer] Secrets could not be decrypted, moved to {:?}", aside));
+            std::fs::rename(path, &aside).map_err(|e| format!("Failed to set the unreadable secrets aside: {}", e))?;
+            Ok(BTreeMap::new())
+        }
+    }
+}
+
+/// Values the backend signs and encrypts sessions with, unique to this installation
+#[derive(Clone, Debug)]
+pub struct BackendKeys {
+    pub secret_key_base: String,
+    pub signing_salt: String,
+}
+
+struct Unlocked {
+    key: [u8; KEY_LEN],
+    secrets: BTreeMap<String, String>,
+}
+
+/// Managed store of the encrypted secrets
+pub struct SecretStore {
+    dir: Option<PathBuf>,
+    /// Loaded on first use, so the keychain is only asked when a secret is needed
+    unlocked: Mutex<Option<Unlocked>>,
+}
+
+impl SecretStore {
+    pub fn open() -> Self {
+        SecretStore {
+            dir: crate::paths::base_leaxer_dir(),
+            unlocked: Mutex::new(None),
+        }
+    }
+
+    fn with_unlocked<T>(&self, f: impl FnOnce(&mut Unlocked) -> Result<T, String>) -> Result<T, String> {
+        let dir = self.dir.as_deref().ok_or("No data directory")?;
+        let mut unlocked = self.unlocked.lock().unwrap();
+        if unlocked.is_none() {
+            let key = load_key(dir)?;
+            let secrets = read_secrets(&dir.join(SECRETS_FILE), &key)?;
+            secrets.values().for_each(|value| crate::logging::register_secret(value));
+            *unlocked = Some(Unlocked { key, secrets });
+        }
+        f(unlocked.as_mut().expect("unlocked above"))
+    }
+
+    fn save(&self, unlocked: &Unlocked) -> Result<(), String> {
+        let dir = self.dir.as_deref().ok_or("No data directory")?;
+        let plain = serde_json::to_vec(&unlocked.secrets).map_err(|e| e.to_string())?;
+        write_private(&dir.join(SECRETS_FILE), &seal(&unlocked.key, &plain)?)
+            .map_err(|e| format!("Failed to save the secrets: {}", e))
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<String>, String> {
+        self.with_unlocked(|unlocked| Ok(unlocked.secrets.get(name).cloned()))
+    }
+
+    /// Store `value` under `name`, or remove it for `None`
+    pub fn set(&self, name: &str, value: Option<&str>) -> Result<(), String> {
+        self.with_unlocked(|unlocked| {
+            match value {
+                Some(value) => {
+                    crate::logging::register_secret(value);
+                    unlocked.secrets.insert(name.to_string(), value.to_string())
+                }
+                None => unlocked.secrets.remove(name),
+            };
+            self.save(unlocked)
+        })
+    }
+
+    /// The secret under `name`, storing `create()` there first if there is none
+    pub fn get_or_create(&self, name: &str, create: impl FnOnce() -> String) -> Result<String, String> {
+        self.with_unlocked(|unlocked| {
+            if let Some(value) = unlocked.secrets.get(name) {
+                return Ok(value.clone());
+            }
+            let value = create();
+            crate::logging::register_secret(&value);
+            unlocked.secrets.insert(name.to_string(), value.clone());
+            self.save(unlocked)?;
+            Ok(value)
+        })
+    }
+
+    /// A random value of `bytes` bytes stored under `name` the first time. If it can't be
+    /// stored it only lasts this run.
+    fn generated(&self, name: &str, bytes: usize) -> String {
+        let create = || base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(random_bytes(bytes));
+        self.get_or_create(name, create).unwrap_or_else(|e| {
+            log_to_file(&format!("[Leaxer] Failed to keep {}, using a new one for this run: {}", name, e));
+            let value = create();
+            crate::logging::register_secret(&value);
+            value
+        })
+    }
+
+    pub fn backend_keys(&self) -> BackendKeys {
+        BackendKeys {
+            secret_key_base: self.generated(SECRET_KEY_BASE, SECRET_KEY_BASE_BYTES),
+            signing_salt: self.generated(SIGNING_SALT, SIGNING_SALT_BYTES),
+        }
+    }
+
+    /// Replace the backend's keys; it uses them once restarted
+    pub fn rotate_backend_keys(&self) -> Result<(), String> {
+        self.set(SECRET_KEY_BASE, None)?;
+        self.set(SIGNING_SALT, None)?;
+        self.backend_keys();
+        Ok(())
+    }
+
+    /// Secrets named like environment variables, for the backend
+    pub fn backend_env(&self) -> Vec<(String, String)> {
+        let secrets = self.with_unlocked(|unlocked| Ok(unlocked.secrets.clone()));
+        let secrets = secrets.unwrap_or_else(|e| {
+            log_to_file(&format!("[Leaxer] Not passing secrets to the backend: {}", e));
+            BTreeMap::new()
+        });
+        secrets
+            .into_iter()
+            .filter(|(name, _)| is_env_name(name) && !crate::profiles::RESERVED_ENV.contains(&name.as_str()))
+            .collect()
+    }
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("A secret needs a name".to_string());
+    }
+    if name.starts_with(RESERVED_PREFIX) {
+        return Err(format!("{} is reserved for Leaxer itself", name));
+    }
+    Ok(())
+}
+
+fn is_env_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// A secret, `None` if it isn't set
+#[tauri::command]
+pub fn secret_get(secrets: tauri::State<'_, SecretStore>, name: String) -> Result<Option<String>, String> {
+    let result = check_name(&name).and_then(|_| secrets.get(&name));
+    crate::audit::record("secret_get", Some(&name), &result);
+    result
+}
+
+/// Set a secret, or remove it when `value` is empty or missing. Secrets for the backend
+/// reach it when it next starts.
+#[tauri::command]
+pub fn secret_set(secrets: tauri::State<'_, SecretStore>, name: String, value: Option<String>) -> Result<(), String> {
+    let value = value.as_deref().filter(|value| !value.is_empty());
+    let result = check_name(&name).and_then(|_| secrets.set(&name, value));
+    crate::audit::record(if value.is_some() { "secret_set" } else { "secret_delete" }, Some(&name), &result);
+    result
+}
+
+/// Give the backend a new `SECRET_KEY_BASE` and `SIGNING_SALT` and restart it, signing
+/// out every browser and device with a session
+#[tauri::command]
+pub async fn rotate_secrets(app: tauri::AppHandle) -> Result<(), String> {
+    use tauri::Manager;
+
+    let rotated = app.state::<SecretStore>().rotate_backend_keys();
+    crate::audit::record("secrets_rotate", None, &rotated);
+    rotated?;
+    log_to_file("[Leaxer] Backend secrets rotated");
+    crate::process::restart_backend(app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unlocked_store(dir: &Path, key: [u8; KEY_LEN]) -> SecretStore {
+        let secrets = read_secrets(&dir.join(SECRETS_FILE), &key).unwrap();
+        SecretStore {
+            dir: Some(dir.to_path_buf()),
+            unlocked: Mutex::new(Some(Unlocked { key, secrets })),
+        }
+    }
+
+    #[test]
+    fn secrets_are_encrypted_at_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = unlocked_store(dir.path(), [7; KEY_LEN]);
+        store.set("HF_TOKEN", Some("hf_abcdefgh")).unwrap();
+        store.set("webhook_token", Some("0123456789")).unwrap();
+        assert_eq!(store.get_or_create("webhook_token", || "other".to_string()).unwrap(), "0123456789");
+
+        let sealed = std::fs::read(dir.path().join(SECRETS_FILE)).unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("hf_abcdefgh"));
+
+        let reopened = unlocked_store(dir.path(), [7; KEY_LEN]);
+        assert_eq!(reopened.get("HF_TOKEN").unwrap().as_deref(), Some("hf_abcdefgh"));
+        assert_eq!(reopened.backend_env(), vec![("HF_TOKEN".to_string(), "hf_abcdefgh".to_string())]);
+    }
+
+    #[test]
+    fn backend_keys_stay_until_rotated() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = unlocked_store(dir.path(), [3; KEY_LEN]);
+        let keys = store.backend_keys();
+        assert!(keys.secret_key_base.len() >= 64);
+        assert_eq!(store.backend_keys().secret_key_base, keys.secret_key_base);
+        assert!(store.backend_env().is_empty());
+
+        store.rotate_backend_keys().unwrap();
+        let rotated = store.backend_keys();
+        assert_ne!(rotated.secret_key_base, keys.secret_key_base);
+        assert_ne!(rotated.signing_salt, keys.signing_salt);
+    }
+
+    #[test]
+    fn a_wrong_key_sets_the_file_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        unlocked_store(dir.path(), [1; KEY_LEN]).set("a", Some("b")).unwrap();
+
+        let other = unlocked_store(dir.path(), [2; KEY_LEN]);
+        assert_eq!(other.get("a").unwrap(), None);
+        assert!(dir.path().join("secrets.enc.unreadable").exists());
+
+        // A later unreadable file doesn't replace the first one
+        other.set("c", Some("d")).unwrap();
+        unlocked_store(dir.path(), [3; KEY_LEN]);
+        assert!(dir.path().join("secrets.enc.unreadable.1").exists());
+    }
+
+    #[test]
+    fn an_unreachable_keychain_never_replaces_the_key_of_existing_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(SECRETS_FILE), b"sealed").unwrap();
+        assert!(fallback_key(dir.path(), "locked").is_err());
+        assert!(!dir.path().join(KEY_FILE).exists());
+        assert_eq!(std::fs::read(dir.path().join(SECRETS_FILE)).unwrap(), b"sealed");
+
+        let fresh = tempfile::tempdir().unwrap();
+        let key = fallback_key(fresh.path(), "locked").unwrap();
+        assert_eq!(read_key_file(fresh.path()), Some(key));
+    }
+}
@@ -0,0 +1,91 @@
+//! Small loading window shown while the backend boots.
+//!
+//! The page (`splash.html`, a static file of the UI build) polls `splash_state` for the
+//! phase the startup is in. The UI closes the splash with `close_splash` once the main
+//! window is on screen, which only happens after the backend answered its health check.
+//! If it never does, the splash switches to an error with buttons to reveal the backend
+//! log or open the main window anyway.
+
+use std::sync::Mutex;
+
+use tauri::Manager;
+
+use crate::logging::log_to_file;
+
+pub const SPLASH_LABEL: &str = "splash";
+
+#[derive(Clone, Default, serde::Serialize)]
+pub struct SplashState {
+    status: String,
+    error: Option<String>,
+}
+
+/// Managed splash state
+#[derive(Default)]
+pub struct Splash(Mutex<SplashState>);
+
+/// Show the splash window
+pub fn open(app: &tauri::AppHandle) {
+    set_status(app, "Starting...");
+    let result = tauri::WebviewWindowBuilder::new(app, SPLASH_LABEL, tauri::WebviewUrl::App("splash.html".into()))
+        .title("Leaxer")
+        .inner_size(360.0, 220.0)
+        .resizable(false)
+        .decorations(false)
+        .center()
+        .devtools(crate::commands::window::devtools_enabled(app))
+        .build();
+    if let Err(e) = result {
+        log_to_file(&format!("[Leaxer] Failed to open splash window: {}", e));
+    }
+}
+
+pub fn set_status(app: &tauri::AppHandle, status: &str) {
+    *app.state::<Splash>().0.lock().unwrap() = SplashState {
+        status: status.to_string(),
+        error: None,
+    };
+}
+
+pub fn set_error(app: &tauri::AppHandle, error: &str) {
+    app.state::<Splash>().0.lock().unwrap().error = Some(error.to_string());
+}
+
+fn close(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window(SPLASH_LABEL) {
+        let _ = window.destroy();
+    }
+}
+
+/// Current phase, polled by the splash page
+#[tauri::command]
+pub fn splash_state(splash: tauri::State<'_, Splash>) -> SplashState {
+    splash.0.lock().unwrap().clone()
+}
+
+/// Called by the UI once the main window is showing
+#[tauri::command]
+pub fn close_splash(app: tauri::AppHandle) {
+    crate::metrics::mark(crate::metrics::Phase::WindowShown);
+    close(&app);
+}
+
+/// "Open anyway" on the error screen
+#[tauri::command]
+pub fn dismiss_splash(app: tauri::AppHandle) {
+    crate::commands::window::show_main_window(&app);
+    crate::metrics::mark(crate::metrics::Phase::WindowShown);
+    close(&app);
+}
+
+/// "View log" on the error screen
+#[tauri::command]
+pub fn reveal_backend_log(app: tauri::AppHandle) -> Result<(), String> {
+    let log = crate::logging::backend_log_path().ok_or("No data directory")?;
+    // Before the backend ever wrote anything, show where the logs go
+    let path = match log.parent() {
+        Some(dir) if !log.exists() => dir.to_path_buf(),
+        _ => log,
+    };
+    crate::commands::files::reveal_path(app, path.to_string_lossy().to_string())
+}
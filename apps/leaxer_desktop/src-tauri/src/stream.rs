@@ -0,0 +1,360 @@
+//! `leaxer-stream://` protocol for handing large files to the webview.
+//!
+//! Sending images and exports through command results means base64 JSON that is held in
+//! memory several times over. Instead, commands return a URL on this protocol and the
+//! webview fetches the bytes directly, with HTTP Range support so media elements and
+//! chunked readers only load what they need. Each response holds at most one chunk,
+//! read with a single positional read straight into the body, so even multi-gigabyte
+//! model weights never sit in memory whole.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use tauri::http::{header, Method, Request, Response, StatusCode};
+
+use crate::logging::log_to_file;
+use crate::paths::is_path_allowed;
+
+/// Scheme registered with the webview
+pub const STREAM_SCHEME: &str = "leaxer-stream";
+
+/// Largest body returned for a single request; bigger files must be read with Range requests
+const MAX_CHUNK_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Build the URL the webview uses to fetch a local file through the stream protocol
+pub fn file_url(path: &Path) -> String {
+    let encoded = percent_encoding::utf8_percent_encode(
+        &path.to_string_lossy(),
+        percent_encoding::NON_ALPHANUMERIC,
+    )
+    .to_string();
+
+    // Windows webviews only accept custom schemes in the http://<scheme>.localhost form
+    #[cfg(target_os = "windows")]
+    let url = format!("http://{}.localhost/file/{}", STREAM_SCHEME, encoded);
+    #[cfg(not(target_os = "windows"))]
+    let url = format!("{}://localhost/file/{}", STREAM_SCHEME, encoded);
+    url
+}
+
+/// Parse a single `Range: bytes=...` header into an inclusive byte range within `len`
+pub fn parse_range(value: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    // Multi-range requests are answered with the first range only
+    let spec = spec.split(',').next()?.trim();
+    let (start, end) = spec.split_once('-')?;
+
+    if len == 0 {
+        return None;
+    }
+
+    let (start, end) = if start.is_empty() {
+        // Suffix range: the last N bytes
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 {
+            return None;
+        }
+        (len.saturating_sub(suffix), len - 1)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() { len - 1 } else { end.parse::<u64>().ok()?.min(len - 1) };
+        (start, end)
+    };
+
+    if start > end || start >= len {
+        return None;
+    }
+    Some((start, end))
+}
+
+fn content_type(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    match ext.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        "m4a" => "audio/mp4",
+        "opus" => "audio/opus",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "json" => "application/json",
+        "txt" | "log" | "md" => "text/plain; charset=utf-8",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(header::CONTENT_TYPE, "text/plain")
+        .body(message.as_bytes().to_vec())
+        .unwrap()
+}
+
+/// Fill `buf` from `offset` with positional reads, leaving the file cursor alone
+#[cfg(unix)]
+pub fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buf, offset)
+}
+
+/// Fill `buf` from `offset` with positional reads, leaving the file cursor alone
+#[cfg(windows)]
+pub fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> std::io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset)? {
+            0 => return Err(std::io::ErrorKind::UnexpectedEof.into()),
+            n => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read `[start, end]` of a file into a body buffer of exactly that size
+fn read_slice(path: &Path, start: u64, end: u64) -> std::io::Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let mut buf = vec![0u8; (end - start + 1) as usize];
+    read_exact_at(&file, &mut buf, start)?;
+    Ok(buf)
+}
+
+/// Weak validator from size and modification time, so the webview can revalidate
+/// cached media without the file being read again
+fn entity_tag(meta: &std::fs::Metadata) -> String {
+    let modified = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("W/\"{:x}-{:x}\"", meta.len(), modified)
+}
+
+/// Serve a `/file/<percent-encoded path>` request, honoring Range, HEAD and If-None-Match
+fn serve_file(path: PathBuf, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let meta = match std::fs::metadata(&path) {
+        Ok(meta) if meta.is_file() => meta,
+        _ => return error_response(StatusCode::NOT_FOUND, "File not found"),
+    };
+    let len = meta.len();
+    let etag = entity_tag(&meta);
+
+    let header_value = |name| request.headers().get(name).and_then(|v| v.to_str().ok());
+
+    if header_value(header::IF_NONE_MATCH).is_some_and(|tags| tags.split(',').any(|t| t.trim() == etag)) {
+        return Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, etag)
+            .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+            .body(Vec::new())
+            .unwrap();
+    }
+
+    let range = header_value(header::RANGE);
+
+    let requested = match range {
+        Some(value) => match parse_range(value, len) {
+            Some(range) => Some(range),
+            None => {
+                return Response::builder()
+                    .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                    .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+                    .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+                    .body(Vec::new())
+                    .unwrap();
+            }
+        },
+        None => None,
+    };
+
+    // Files that fit in one chunk are returned whole unless a range was asked for;
+    // anything larger is always answered with a partial response
+    let (start, end, partial) = match requested {
+        Some((start, end)) => (start, end.min(start + MAX_CHUNK_BYTES - 1), true),
+        None if len <= MAX_CHUNK_BYTES => (0, len.saturating_sub(1), false),
+        None => (0, MAX_CHUNK_BYTES - 1, true),
+    };
+
+    // HEAD lets players probe size and range support without reading anything
+    let body = if len == 0 || request.method() == Method::HEAD {
+        Vec::new()
+    } else {
+        match read_slice(&path, start, end) {
+            Ok(body) => body,
+            Err(e) => {
+                log_to_file(&format!("[Leaxer] Failed to stream {:?}: {}", path, e));
+                return error_response(StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file");
+            }
+        }
+    };
+
+    let mut response = Response::builder()
+        .header(header::CONTENT_TYPE, content_type(&path))
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, if len == 0 { 0 } else { end - start + 1 })
+        .header(header::ETAG, &etag)
+        .header(header::CACHE_CONTROL, "no-cache")
+        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+        .header(header::ACCESS_CONTROL_EXPOSE_HEADERS, "Content-Range, Content-Length, Accept-Ranges, ETag");
+
+    if partial {
+        response = response
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, len));
+    } else {
+        response = response.status(StatusCode::OK);
+    }
+
+    response.body(body).unwrap()
+}
+
+/// Handle a request on the stream protocol. Only paths the shell allows are served.
+pub fn handle_request(app: &tauri::AppHandle, request: &Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let encoded = match request.uri().path().strip_prefix("/file/") {
+        Some(encoded) => encoded,
+        None => return error_response(StatusCode::NOT_FOUND, "Unknown resource"),
+    };
+    let path = PathBuf::from(
+        percent_encoding::percent_decode_str(encoded)
+            .decode_utf8_lossy()
+            .to_string(),
+    );
+
+    if !is_path_allowed(app, &path) {
+        log_to_file(&format!("[Leaxer] Refused to stream path outside allowed roots: {:?}", path));
+        return error_response(StatusCode::FORBIDDEN, "Path is outside the allowed locations");
+    }
+
+    serve_file(path, request)
+}
+
+/// Return a stream URL for a file the frontend wants to display or read
+#[tauri::command]
+pub fn get_file_url(app: tauri::AppHandle, path: String) -> Result<String, String> {
+    let path = PathBuf::from(path);
+    if !path.is_file() || !is_path_allowed(&app, &path) {
+        return Err(format!("Path cannot be streamed: {:?}", path));
+    }
+    Ok(file_url(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(range: Option<&str>) -> Request<Vec<u8>> {
+        let mut request = Request::builder().uri("leaxer-stream://localhost/file/x");
+        if let Some(range) = range {
+            request = request.header(header::RANGE, range);
+        }
+        request.body(Vec::new()).unwrap()
+    }
+
+    #[test]
+    fn parses_byte_ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=500-", 1000), Some((500, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=900-5000", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=0-1, 5-6", 1000), Some((0, 1)));
+    }
+
+    #[test]
+    fn rejects_unsatisfiable_ranges() {
+        assert_eq!(parse_range("bytes=1000-", 1000), None);
+        assert_eq!(parse_range("bytes=50-10", 1000), None);
+        assert_eq!(parse_range("bytes=-0", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=0-1", 0), None);
+    }
+
+    #[test]
+    fn serves_requested_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        std::fs::write(&path, b"0123456789").unwrap();
+
+        let response = serve_file(path.clone(), &get(Some("bytes=2-5")));
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body(), b"2345");
+        assert_eq!(response.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+
+        let response = serve_file(path, &get(None));
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), b"0123456789");
+    }
+
+    #[test]
+    fn large_files_are_served_in_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("model.safetensors");
+        let file = File::create(&path).unwrap();
+        file.set_len(MAX_CHUNK_BYTES * 3).unwrap();
+
+        let response = serve_file(path.clone(), &get(None));
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body().len() as u64, MAX_CHUNK_BYTES);
+
+        let response = serve_file(path, &get(Some("bytes=100-")));
+        assert_eq!(response.body().len() as u64, MAX_CHUNK_BYTES);
+        assert_eq!(
+            response.headers()[header::CONTENT_RANGE],
+            format!("bytes 100-{}/{}", 100 + MAX_CHUNK_BYTES - 1, MAX_CHUNK_BYTES * 3)
+        );
+    }
+
+    #[test]
+    fn head_and_conditional_requests_skip_the_body() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clip.mp3");
+        std::fs::write(&path, b"0123456789").unwrap();
+
+        let head = Request::builder().method(Method::HEAD).body(Vec::new()).unwrap();
+        let response = serve_file(path.clone(), &head);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.body().is_empty());
+        assert_eq!(response.headers()[header::CONTENT_LENGTH], "10");
+
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+        let conditional = Request::builder()
+            .header(header::IF_NONE_MATCH, etag)
+            .body(Vec::new())
+            .unwrap();
+        assert_eq!(serve_file(path, &conditional).status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[test]
+    fn missing_files_are_not_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let response = serve_file(dir.path().join("missing.png"), &get(None));
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn file_urls_round_trip() {
+        let path = Path::new("/tmp/My Outputs/img #1.png");
+        let url = file_url(path);
+        let encoded = url.split("/file/").nth(1).unwrap();
+        assert!(!encoded.contains(' ') && !encoded.contains('#'));
+        let decoded = percent_encoding::percent_decode_str(encoded).decode_utf8_lossy();
+        assert_eq!(decoded, "/tmp/My Outputs/img #1.png");
+    }
+}
@@ -0,0 +1,137 @@
+//! HTTPS for other devices when the backend is exposed on the network.
+//!
+//! With `network_exposure_enabled` (and `network_tls`, on by default) the backend keeps
+//! plain HTTP on loopback for this machine and listens for other devices with TLS on its
+//! port + 443. The certificate is self-signed, generated once into `<Leaxer dir>/tls/`
+//! and kept, so its SHA-256 fingerprint stays the same for clients that pinned it. The
+//! UI shows the fingerprint next to the LAN URLs.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use crate::logging::log_to_file;
+
+pub const CERT_ENV: &str = "LEAXER_TLS_CERTFILE";
+pub const KEY_ENV: &str = "LEAXER_TLS_KEYFILE";
+pub const PORT_ENV: &str = "LEAXER_TLS_PORT";
+pub const FINGERPRINT_ENV: &str = "LEAXER_TLS_FINGERPRINT";
+
+const CERT_FILE: &str = "cert.pem";
+const KEY_FILE: &str = "key.pem";
+
+/// The TLS listener's port is the backend's plus this, so 4000 gets 4443
+const PORT_OFFSET: u16 = 443;
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct TlsInfo {
+    pub port: u16,
+    /// SHA-256 of the certificate, as colon-separated hex
+    pub fingerprint: String,
+}
+
+/// TLS settings of the running backend
+static ACTIVE: RwLock<Option<TlsInfo>> = RwLock::new(None);
+
+fn tls_dir() -> Option<PathBuf> {
+    crate::paths::get_leaxer_user_dir().map(|dir| dir.join("tls"))
+}
+
+pub fn tls_port(port: u16) -> u16 {
+    port.saturating_add(PORT_OFFSET)
+}
+
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der).iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(":")
+}
+
+/// DER bytes of the first certificate in a PEM file
+fn pem_to_der(pem: &str) -> Option<Vec<u8>> {
+    let body: String = pem
+        .lines()
+        .skip_while(|line| !line.starts_with("-----BEGIN CERTIFICATE-----"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("-----END"))
+        .collect();
+    base64::engine::general_purpose::STANDARD.decode(body).ok()
+}
+
+/// Names the certificate is issued for
+fn subject_names() -> Vec<String> {
+    let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string()];
+    names.extend(sysinfo::System::host_name());
+    names.extend(crate::net::lan_ip().map(|ip| ip.to_string()));
+    names
+}
+
+/// The certificate and key in `dir`, generating them if either is missing. Returns the
+/// certificate's fingerprint.
+fn ensure_certificate(dir: &Path, names: Vec<String>) -> Result<String, String> {
+    let (cert_path, key_path) = (dir.join(CERT_FILE), dir.join(KEY_FILE));
+    if key_path.is_file() {
+        if let Some(der) = std::fs::read_to_string(&cert_path).ok().as_deref().and_then(pem_to_der) {
+            return Ok(fingerprint(&der));
+        }
+    }
+    let generated =
+        rcgen::generate_simple_self_signed(names).map_err(|e| format!("Failed to create a certificate: {}", e))?;
+    crate::secrets::write_private(&key_path, generated.key_pair.serialize_pem().as_bytes())
+        .and_then(|_| std::fs::write(&cert_path, generated.cert.pem()))
+        .map_err(|e| format!("Failed to save the certificate: {}", e))?;
+    log_to_file(&format!("[Leaxer] Created a TLS certificate in {:?}", dir));
+    Ok(fingerprint(generated.cert.der()))
+}
+
+/// Give a backend about to start on `port` its certificate when it will listen on the
+/// network with TLS, and remember the outcome for `active`
+pub fn configure(cmd: &mut std::process::Command, port: u16, enabled: bool) {
+    let info = if enabled { prepare(cmd, port) } else { Ok(None) };
+    let info = info.unwrap_or_else(|e| {
+        log_to_file(&format!("[Leaxer] Serving the network without TLS: {}", e));
+        None
+    });
+    *ACTIVE.write().unwrap() = info;
+}
+
+fn prepare(cmd: &mut std::process::Command, port: u16) -> Result<Option<TlsInfo>, String> {
+    let dir = tls_dir().ok_or("No Leaxer user directory")?;
+    let fingerprint = ensure_certificate(&dir, subject_names())?;
+    cmd.env(CERT_ENV, dir.join(CERT_FILE))
+        .env(KEY_ENV, dir.join(KEY_FILE))
+        .env(PORT_ENV, tls_port(port).to_string())
+        .env(FINGERPRINT_ENV, &fingerprint);
+    Ok(Some(TlsInfo {
+        port: tls_port(port),
+        fingerprint,
+    }))
+}
+
+/// TLS settings of the running backend, if it serves the network with TLS
+pub fn active() -> Option<TlsInfo> {
+    ACTIVE.read().unwrap().clone()
+}
+
+/// The backend's TLS port and certificate fingerprint, or `None` when it serves plain HTTP
+#[tauri::command]
+pub fn get_tls_info() -> Option<TlsInfo> {
+    active()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn certificate_is_created_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = ensure_certificate(dir.path(), vec!["localhost".to_string()]).unwrap();
+        assert_eq!(first.len(), 32 * 3 - 1);
+        assert!(dir.path().join(KEY_FILE).is_file());
+
+        let pem = std::fs::read_to_string(dir.path().join(CERT_FILE)).unwrap();
+        assert_eq!(fingerprint(&pem_to_der(&pem).unwrap()), first);
+        assert_eq!(ensure_certificate(dir.path(), vec!["other".to_string()]).unwrap(), first);
+    }
+}
@@ -0,0 +1,243 @@
+//! Unix socket transport: the backend listens on a socket file in the user dir instead of
+//! a TCP port, so nothing else on the machine can connect to it.
+//!
+//! Enabled with `"backend_transport": "unix"` in config.json. The backend gets the path
+//! as `LEAXER_SOCKET` and binds its endpoint to it. The webview can't open unix sockets,
+//! so the shell proxies for it: `leaxer-backend://localhost/...` requests are forwarded
+//! over the socket, and the UI long-polls through that protocol since custom protocols
+//! can't carry WebSockets. The control channel already runs over stdin/stdout.
+//!
+//! Unix only: the backend's HTTP server can't listen on a Windows named pipe, so there the
+//! setting is logged and ignored and the backend keeps its loopback port. Shell-side
+//! downloads, MCP clients, extensions and the browser's native messaging host need a TCP
+//! backend and are refused in this mode (`net::backend_http_url`).
+
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use tauri::http::{Request, Response, StatusCode};
+
+use crate::logging::log_to_file;
+
+/// Scheme the webview reaches a socket-bound backend through
+pub const BACKEND_SCHEME: &str = "leaxer-backend";
+
+/// Tells the backend which socket to listen on
+pub const SOCKET_ENV: &str = "LEAXER_SOCKET";
+
+/// Longest socket path the platforms accept (`sun_path` is 104 bytes on macOS)
+const MAX_SOCKET_PATH: usize = 100;
+
+/// Largest response the proxy buffers; outputs bigger than this need TCP mode
+const MAX_RESPONSE_BYTES: usize = 512 * 1024 * 1024;
+
+static SOCKET: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Socket the backend listens on, None in TCP mode
+pub fn socket_path() -> Option<PathBuf> {
+    SOCKET.read().unwrap().clone()
+}
+
+/// Switch to the socket transport when config.json asks for it
+pub fn init(config: &serde_json::Value) {
+    if config.get("backend_transport").and_then(serde_json::Value::as_str) != Some("unix") {
+        return;
+    }
+    if cfg!(not(unix)) {
+        log_to_file("[Leaxer] backend_transport \"unix\" is not supported on this platform, using TCP");
+        return;
+    }
+    let Some(path) = crate::paths::get_leaxer_user_dir().map(|dir| dir.join("backend.sock")) else {
+        return;
+    };
+    if path.as_os_str().len() > MAX_SOCKET_PATH {
+        log_to_file(&format!("[Leaxer] Socket path {:?} is too long, using TCP", path));
+        return;
+    }
+    log_to_file(&format!("[Leaxer] Backend will listen on {:?}", path));
+    *SOCKET.write().unwrap() = Some(path);
+}
+
+/// Base URL of the proxy, in place of `http://localhost:<port>`
+pub fn proxy_url() -> String {
+    format!("{}://localhost", BACKEND_SCHEME)
+}
+
+/// Serialize a request for the backend; the connection closes after one exchange
+fn encode_request(request: &Request<Vec<u8>>) -> Vec<u8> {
+    let target = request.uri().path_and_query().map(|p| p.as_str()).unwrap_or("/");
+    let mut head = format!("{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n", request.method(), target);
+    for (name, value) in request.headers() {
+        // The proxy sets these itself
+        if matches!(name.as_str(), "host" | "connection" | "content-length" | "transfer-encoding") {
+            continue;
+        }
+        if let Ok(value) = value.to_str() {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+    }
+    head.push_str(&format!("Content-Length: {}\r\n\r\n", request.body().len()));
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(request.body());
+    bytes
+}
+
+/// Decode a `Transfer-Encoding: chunked` body
+fn dechunk(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(out);
+        }
+        out.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+/// Parse a complete HTTP/1.1 response read until the backend closed the connection
+fn parse_response(raw: &[u8]) -> Option<Response<Vec<u8>>> {
+    let head_end = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&raw[..head_end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let status: u16 = lines.next()?.split_whitespace().nth(1)?.parse().ok()?;
+
+    let mut builder = Response::builder().status(status);
+    let mut chunked = false;
+    for line in lines {
+        let (name, value) = line.split_once(':')?;
+        let (name, value) = (name.trim(), value.trim());
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.eq_ignore_ascii_case("chunked");
+            continue;
+        }
+        if name.eq_ignore_ascii_case("connection") || name.eq_ignore_ascii_case("content-length") {
+            continue;
+        }
+        builder = builder.header(name, value);
+    }
+
+    let body = &raw[head_end + 4..];
+    let body = if chunked { dechunk(body)? } else { body.to_vec() };
+    builder.body(body).ok()
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response<Vec<u8>> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "text/plain")
+        .body(message.as_bytes().to_vec())
+        .unwrap()
+}
+
+/// Read until the backend closes the connection; a response over `limit` is an error
+/// rather than a silently truncated body
+#[cfg(unix)]
+async fn read_response(stream: impl tokio::io::AsyncRead + Unpin, limit: usize) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let mut response = Vec::new();
+    stream.take(limit as u64 + 1).read_to_end(&mut response).await?;
+    if response.len() > limit {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("response exceeds {} MB", limit / (1024 * 1024)),
+        ));
+    }
+    Ok(response)
+}
+
+#[cfg(unix)]
+async fn exchange(socket: &std::path::Path, request: &[u8]) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut stream = tokio::net::UnixStream::connect(socket).await?;
+    stream.write_all(request).await?;
+    read_response(stream, MAX_RESPONSE_BYTES).await
+}
+
+#[cfg(not(unix))]
+async fn exchange(_socket: &std::path::Path, _request: &[u8]) -> std::io::Result<Vec<u8>> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+/// Send a bodyless request over the socket; the response status, if one came within `timeout`
+pub async fn request_status(
+    method: &str,
+    path: &str,
+    token: Option<&str>,
+    timeout: std::time::Duration,
+) -> Option<u16> {
+    let socket = socket_path()?;
+    let request = format!(
+        "{} {} HTTP/1.1\r\nHost: localhost\r\n{}Content-Length: 0\r\nConnection: close\r\n\r\n",
+        method,
+        path,
+        crate::net::shell_token_header(token)
+    );
+    let response = tokio::time::timeout(timeout, exchange(&socket, request.as_bytes())).await.ok()?.ok()?;
+    parse_response(&response).map(|response| response.status().as_u16())
+}
+
+/// Forward a webview request on `BACKEND_SCHEME` to the backend's socket
+pub async fn proxy(request: Request<Vec<u8>>) -> Response<Vec<u8>> {
+    let Some(socket) = socket_path() else {
+        return error_response(StatusCode::NOT_FOUND, "The backend is not listening on a socket");
+    };
+    match exchange(&socket, &encode_request(&request)).await {
+        Ok(raw) => parse_response(&raw)
+            .unwrap_or_else(|| error_response(StatusCode::BAD_GATEWAY, "Malformed response from the backend")),
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+            error_response(StatusCode::BAD_GATEWAY, &format!("Backend response too large: {}", e))
+        }
+        Err(e) => error_response(StatusCode::BAD_GATEWAY, &format!("Backend unreachable: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_chunked_responses() {
+        let plain = parse_response(b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 2\r\n\r\n{}").unwrap();
+        assert_eq!(plain.status(), 200);
+        assert_eq!(plain.headers()["content-type"], "application/json");
+        assert_eq!(plain.body(), b"{}");
+
+        let chunked =
+            parse_response(b"HTTP/1.1 404 Not Found\r\ntransfer-encoding: chunked\r\n\r\n4\r\nnone\r\n4\r\n yet\r\n0\r\n\r\n")
+                .unwrap();
+        assert_eq!(chunked.status(), 404);
+        assert_eq!(chunked.body(), b"none yet");
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n").is_none());
+    }
+
+    #[test]
+    fn forwards_the_request_target_and_body() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("leaxer-backend://localhost/api/workflows?limit=5")
+            .header("Content-Type", "application/json")
+            .header("Host", "localhost")
+            .body(b"{\"a\":1}".to_vec())
+            .unwrap();
+        let encoded = String::from_utf8(encode_request(&request)).unwrap();
+        assert!(encoded.starts_with("POST /api/workflows?limit=5 HTTP/1.1\r\n"));
+        assert!(encoded.contains("content-type: application/json\r\n"));
+        assert_eq!(encoded.matches("Host:").count() + encoded.matches("host:").count(), 1);
+        assert!(encoded.ends_with("Content-Length: 7\r\n\r\n{\"a\":1}"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn oversized_responses_are_errors_not_truncated() {
+        let raw: &[u8] = b"HTTP/1.1 200 OK\r\n\r\n0123456789";
+        let read = |limit| tauri::async_runtime::block_on(read_response(raw, limit));
+        assert_eq!(read(raw.len()).unwrap(), raw);
+        assert_eq!(read(raw.len() - 1).unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+}
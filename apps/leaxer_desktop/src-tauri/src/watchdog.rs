@@ -0,0 +1,96 @@
+//! Watchdog for a backend that is alive but no longer answering.
+//!
+//! The supervisor only notices a backend that exits. A wedged VM keeps its process, so
+//! this task pings `/api/health` while the backend should be serving. After
+//! `FAILURES_BEFORE_UNRESPONSIVE` misses in a row it emits `backend-unresponsive` and,
+//! if `backend_watchdog_restart` is set in config.json, restarts the backend. Pings run on
+//! their own fixed interval, not the `MonitorSignal`: a backend left serving from the tray
+//! still needs watching, and window focus must not bunch pings up into a false hang.
+
+use std::time::Duration;
+
+use tauri::{Emitter, Manager};
+
+use crate::config::ConfigStore;
+use crate::logging::log_to_file;
+use crate::process::BackendHandle;
+use crate::readiness::{BackendReadiness, Readiness};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Consecutive failed pings before the backend counts as hung
+const FAILURES_BEFORE_UNRESPONSIVE: u32 = 3;
+
+/// Whether a ping result makes the backend unresponsive now (only on the failure that
+/// crosses the threshold, so the event fires once per episode)
+fn record(failures: &mut u32, healthy: bool) -> bool {
+    if healthy {
+        *failures = 0;
+        return false;
+    }
+    *failures += 1;
+    *failures == FAILURES_BEFORE_UNRESPONSIVE
+}
+
+/// Ping the backend once if it is supposed to be up, restarting it when that makes it hung
+async fn check(app: &tauri::AppHandle, failures: &mut u32) {
+    // Only a backend we spawned, that finished booting and isn't paused, restarting
+    // or being replaced is expected to answer
+    let status = app.state::<BackendHandle>().status().await.unwrap_or_default();
+    let ready = app.state::<BackendReadiness>().get() == Readiness::Ready;
+    if status.pid.is_none() || status.standby || status.restarting || !ready || crate::process::restarting() {
+        *failures = 0;
+        return;
+    }
+
+    let healthy = crate::readiness::check_health(crate::net::backend_port()).await;
+    if !record(failures, healthy) {
+        return;
+    }
+
+    log_to_file(&format!("[Leaxer] Backend stopped answering ({} failed health checks)", failures));
+    let auto_restart = app.state::<ConfigStore>().get().backend_watchdog_restart;
+    let _ = app.emit(
+        "backend-unresponsive",
+        serde_json::json!({ "failures": *failures, "restarting": auto_restart }),
+    );
+    if auto_restart {
+        if let Err(e) = crate::process::restart_backend(app.clone()).await {
+            log_to_file(&format!("[Leaxer] Watchdog restart failed: {}", e));
+        }
+        *failures = 0;
+    }
+}
+
+/// Ping the backend periodically while it is supposed to be up
+pub fn start(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        // A check that overran (e.g. a restart) shouldn't be followed by a burst of pings
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes at once; the first ping waits a full interval
+        interval.tick().await;
+        let mut failures = 0;
+        loop {
+            interval.tick().await;
+            check(&app, &mut failures).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_once_after_consecutive_failures() {
+        let mut failures = 0;
+        assert!(!record(&mut failures, false));
+        assert!(!record(&mut failures, true));
+        let reports: Vec<bool> = (0..5).map(|_| record(&mut failures, false)).collect();
+        assert_eq!(reports, [false, false, true, false, false]);
+        assert!(!record(&mut failures, true));
+        assert_eq!(failures, 0);
+    }
+}
@@ -0,0 +1,353 @@
+//! Opt-in localhost webhook endpoint for scripts and other apps' plugins.
+//!
+//! Listens on 127.0.0.1 on its own port (`webhook_port`, default 4010) while
+//! `webhook_enabled` is set in config.json. Every request needs
+//! `Authorization: Bearer <webhook_token>`, a token kept with the encrypted secrets.
+//! - `POST /chat` with `{title, text, url}` saves a new chat on the backend
+//! - `POST /event/<name>` with any JSON forwards it to the UI as a `webhook` event
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tauri::{Emitter, Manager};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+use crate::config::ConfigStore;
+use crate::logging::log_to_file;
+use crate::secrets::SecretStore;
+
+pub const WEBHOOK_ENABLED_KEY: &str = "webhook_enabled";
+const WEBHOOK_PORT_KEY: &str = "webhook_port";
+const WEBHOOK_TOKEN_KEY: &str = "webhook_token";
+
+const DEFAULT_WEBHOOK_PORT: u16 = 4010;
+
+/// Requests with a larger body are refused
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Requests with more header lines than this are refused
+const MAX_HEADERS: usize = 64;
+
+/// Longest request or header line accepted, in bytes
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// How long a client gets to send its whole request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The running listener, if any
+#[derive(Default)]
+pub struct WebhookServer {
+    task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+}
+
+#[derive(serde::Serialize)]
+pub struct WebhookInfo {
+    enabled: bool,
+    url: String,
+    token: String,
+}
+
+#[derive(Debug, PartialEq)]
+struct Request {
+    method: String,
+    path: String,
+    token: Option<String>,
+    body: Vec<u8>,
+}
+
+/// 32 random bytes from the OS, as hex
+fn new_token() -> String {
+    crate::secrets::random_bytes(32).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Compare without returning early, so response timing doesn't reveal the token
+pub(crate) fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn port(config: &Value) -> u16 {
+    config
+        .get(WEBHOOK_PORT_KEY)
+        .and_then(Value::as_u64)
+        .and_then(|port| u16::try_from(port).ok())
+        .unwrap_or(DEFAULT_WEBHOOK_PORT)
+}
+
+/// The token, creating one the first time. One left in config.json by earlier versions
+/// moves to the secrets.
+fn token(app: &tauri::AppHandle) -> String {
+    let config = app.state::<ConfigStore>();
+    let secrets = app.state::<SecretStore>();
+    let plaintext = config.load_file().get(WEBHOOK_TOKEN_KEY).and_then(Value::as_str).map(str::to_string);
+    let token = match plaintext.filter(|token| !token.is_empty()) {
+        Some(token) => secrets.set(WEBHOOK_TOKEN_KEY, Some(&token)).map(|_| {
+            config.remove(WEBHOOK_TOKEN_KEY);
+            token
+        }),
+        None => secrets.get_or_create(WEBHOOK_TOKEN_KEY, new_token),
+    };
+    token.unwrap_or_else(|e| {
+        log_to_file(&format!("[Leaxer] Webhook token unavailable, using one for this run: {}", e));
+        secrets.get(WEBHOOK_TOKEN_KEY).ok().flatten().unwrap_or_else(new_token)
+    })
+}
+
+/// Read one line into `line`, refusing with `too_long` a line over `MAX_LINE_BYTES`
+async fn read_line<R: AsyncBufRead + Unpin>(reader: &mut R, line: &mut String, too_long: u16) -> Result<(), u16> {
+    let read = reader.take(MAX_LINE_BYTES as u64 + 1).read_line(line).await.map_err(|_| 400u16)?;
+    if read > MAX_LINE_BYTES {
+        return Err(too_long);
+    }
+    Ok(())
+}
+
+/// Read one HTTP/1.1 request; `Err` holds the status to answer with
+async fn read_request<R: tokio::io::AsyncRead + Unpin>(reader: R) -> Result<Request, u16> {
+    let mut reader = BufReader::new(reader);
+    let mut line = String::new();
+    read_line(&mut reader, &mut line, 414).await?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Err(400),
+    };
+
+    let mut content_length = 0usize;
+    let mut token = None;
+    for _ in 0..=MAX_HEADERS {
+        line.clear();
+        read_line(&mut reader, &mut line, 431).await?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            let mut body = vec![0u8; content_length];
+            reader.read_exact(&mut body).await.map_err(|_| 400u16)?;
+            return Ok(Request {
+                method,
+                path,
+                token,
+                body,
+            });
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(400);
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.parse().map_err(|_| 400u16)?;
+            if content_length > MAX_BODY_BYTES {
+                return Err(413);
+            }
+        } else if name.eq_ignore_ascii_case("authorization") {
+            token = value.strip_prefix("Bearer ").map(str::to_string);
+        }
+    }
+    Err(431)
+}
+
+/// Act on an authenticated request; returns the status and JSON body of the response
+async fn route(app: &tauri::AppHandle, request: &Request) -> (u16, Value) {
+    if request.method != "POST" {
+        return (405, json!({ "error": "Only POST is supported" }));
+    }
+    let payload: Value = match serde_json::from_slice(&request.body) {
+        Ok(payload) => payload,
+        Err(_) => return (400, json!({ "error": "Body must be JSON" })),
+    };
+
+    if request.path == "/chat" {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        let session = match crate::native_messaging::chat_session("send_page", &payload, now) {
+            Ok(session) => session,
+            Err(e) => return (400, json!({ "error": e })),
+        };
+        return match crate::native_messaging::save_chat(&session).await {
+            Ok(()) => (201, json!({ "chat_id": session["id"] })),
+            Err(e) => (502, json!({ "error": e })),
+        };
+    }
+
+    match request.path.strip_prefix("/event/") {
+        Some(name) if !name.is_empty() && !name.contains('/') => {
+            let _ = app.emit("webhook", json!({ "name": name, "payload": payload }));
+            (202, json!({ "ok": true }))
+        }
+        _ => (404, json!({ "error": "Unknown endpoint" })),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        201 => "Created",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        431 => "Request Header Fields Too Large",
+        _ => "Bad Gateway",
+    }
+}
+
+async fn handle_connection(app: tauri::AppHandle, mut stream: tokio::net::TcpStream, expected_token: String) {
+    let (reader, mut writer) = stream.split();
+    // A client that stalls would otherwise hold its connection open forever
+    let request = tokio::time::timeout(READ_TIMEOUT, read_request(reader)).await.unwrap_or(Err(408));
+    let (status, body) = match request {
+        Err(status) => (status, json!({ "error": reason(status) })),
+        Ok(request) if !request.token.as_deref().is_some_and(|t| token_matches(t, &expected_token)) => {
+            (401, json!({ "error": "Missing or wrong token" }))
+        }
+        Ok(request) => {
+            let response = route(&app, &request).await;
+            log_to_file(&format!("[Leaxer] Webhook {} {} -> {}", request.method, request.path, response.0));
+            response
+        }
+    };
+
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    );
+    let _ = writer.write_all(response.as_bytes()).await;
+    let _ = writer.shutdown().await;
+}
+
+/// Start listening if enabled in config (and not already running)
+pub fn start(app: &tauri::AppHandle) {
+    let config = app.state::<ConfigStore>();
+    if !config.get_bool(WEBHOOK_ENABLED_KEY) {
+        return;
+    }
+    let server = app.state::<WebhookServer>();
+    let mut task = server.task.lock().unwrap();
+    if task.is_some() {
+        return;
+    }
+
+    let port = port(&config.load());
+    let token = token(app);
+    let app = app.clone();
+    *task = Some(tauri::async_runtime::spawn(async move {
+        let listener = match tokio::net::TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log_to_file(&format!("[Leaxer] Webhook listener could not bind port {}: {}", port, e));
+                return;
+            }
+        };
+        log_to_file(&format!("[Leaxer] Webhook listener on 127.0.0.1:{}", port));
+        while let Ok((stream, _)) = listener.accept().await {
+            tauri::async_runtime::spawn(handle_connection(app.clone(), stream, token.clone()));
+        }
+    }));
+}
+
+fn stop(app: &tauri::AppHandle) {
+    if let Some(task) = app.state::<WebhookServer>().task.lock().unwrap().take() {
+        task.abort();
+        log_to_file("[Leaxer] Webhook listener stopped");
+    }
+}
+
+fn info(app: &tauri::AppHandle) -> WebhookInfo {
+    let config = app.state::<ConfigStore>();
+    WebhookInfo {
+        enabled: config.get_bool(WEBHOOK_ENABLED_KEY),
+        url: format!("http://127.0.0.1:{}", port(&config.load())),
+        token: token(app),
+    }
+}
+
+/// Where integrations should send requests, and the token they need
+#[tauri::command]
+pub fn get_webhook_info(app: tauri::AppHandle) -> WebhookInfo {
+    info(&app)
+}
+
+#[tauri::command]
+pub fn set_webhook_enabled(app: tauri::AppHandle, enabled: bool) -> WebhookInfo {
+    app.state::<ConfigStore>().set(WEBHOOK_ENABLED_KEY, json!(enabled));
+    if enabled {
+        start(&app);
+    } else {
+        stop(&app);
+    }
+    info(&app)
+}
+
+/// Replace the token, cutting off every integration that used the old one
+#[tauri::command]
+pub fn rotate_webhook_token(app: tauri::AppHandle) -> WebhookInfo {
+    if let Err(e) = app.state::<SecretStore>().set(WEBHOOK_TOKEN_KEY, Some(&new_token())) {
+        log_to_file(&format!("[Leaxer] Failed to replace the webhook token: {}", e));
+    }
+    // The listener holds the token it was started with
+    stop(&app);
+    start(&app);
+    info(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(raw: &str) -> Result<Request, u16> {
+        tauri::async_runtime::block_on(read_request(raw.as_bytes()))
+    }
+
+    #[test]
+    fn parses_requests_with_bodies() {
+        let request = parse(
+            "POST /event/obsidian HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer abc\r\nContent-Length: 2\r\n\r\n{}",
+        )
+        .unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/event/obsidian");
+        assert_eq!(request.token.as_deref(), Some("abc"));
+        assert_eq!(request.body, b"{}");
+    }
+
+    #[test]
+    fn refuses_oversized_or_malformed_requests() {
+        let too_big = format!("POST /chat HTTP/1.1\r\nContent-Length: {}\r\n\r\n", MAX_BODY_BYTES + 1);
+        assert_eq!(parse(&too_big), Err(413));
+        assert_eq!(parse("garbage\r\n\r\n"), Err(400));
+        assert_eq!(parse("POST /chat HTTP/1.1\r\nno colon here\r\n\r\n"), Err(400));
+        let long_header = format!("POST /chat HTTP/1.1\r\nX-Filler: {}\r\n\r\n", "a".repeat(MAX_LINE_BYTES));
+        assert_eq!(parse(&long_header), Err(431));
+        assert_eq!(parse(&format!("POST /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE_BYTES))), Err(414));
+    }
+
+    #[test]
+    fn tokens_are_random_and_compared_exactly() {
+        let token = new_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, new_token());
+        assert!(token_matches(&token, &token));
+        assert!(!token_matches(&token[..63], &token));
+        assert!(!token_matches("", &token));
+    }
+
+    #[test]
+    fn port_falls_back_to_default() {
+        assert_eq!(port(&json!({ "webhook_port": 5005 })), 5005);
+        assert_eq!(port(&json!({ "webhook_port": 70000 })), DEFAULT_WEBHOOK_PORT);
+        assert_eq!(port(&json!({})), DEFAULT_WEBHOOK_PORT);
+    }
+}
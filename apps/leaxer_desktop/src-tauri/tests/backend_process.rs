@@ -0,0 +1,66 @@
+//! Spawns a fake backend release to check discovery and launch end to end.
+#![cfg(unix)]
+
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use leaxer_desktop_lib::process::{backend_command, backend_relative_path, find_backend_exe};
+use leaxer_desktop_lib::secrets::BackendKeys;
+
+/// Write a launcher script that records how it was started, then idles like a server would
+fn install_fake_backend(resources: &Path) -> PathBuf {
+    let launcher = resources.join(backend_relative_path());
+    fs::create_dir_all(launcher.parent().unwrap()).unwrap();
+    fs::write(
+        &launcher,
+        "#!/bin/sh\n\
+         {\n\
+           echo \"args=$*\"\n\
+           echo \"phx_server=$PHX_SERVER\"\n\
+           echo \"bind_all=$LEAXER_BIND_ALL_INTERFACES\"\n\
+         } > fake_backend.out.tmp\n\
+         mv fake_backend.out.tmp fake_backend.out\n\
+         exec sleep 30\n",
+    )
+    .unwrap();
+    fs::set_permissions(&launcher, fs::Permissions::from_mode(0o755)).unwrap();
+    launcher
+}
+
+fn wait_for_file(path: &Path) -> String {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if let Ok(content) = fs::read_to_string(path) {
+            return content;
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    panic!("fake backend never wrote {:?}", path);
+}
+
+#[test]
+fn spawns_discovered_backend_from_release_root() {
+    let resources = tempfile::tempdir().unwrap();
+    let launcher = install_fake_backend(resources.path());
+
+    let found = find_backend_exe(Some(resources.path()), None).expect("backend should be found");
+    assert_eq!(found, launcher);
+
+    let keys = BackendKeys {
+        secret_key_base: "k".repeat(86),
+        signing_salt: "s".repeat(32),
+    };
+    let mut child = backend_command(&found, true, &keys).spawn().unwrap();
+
+    // The launcher runs from the release root (parent of bin/)
+    let output = wait_for_file(&resources.path().join("leaxer_core").join("fake_backend.out"));
+    assert!(output.contains("args=start"));
+    assert!(output.contains("phx_server=true"));
+    assert!(output.contains("bind_all=true"));
+
+    assert!(child.try_wait().unwrap().is_none(), "backend exited early");
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
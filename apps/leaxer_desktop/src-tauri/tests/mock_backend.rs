@@ -0,0 +1,66 @@
+//! Talks to the built-in mock backend over real sockets, the way the UI would.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+
+use leaxer_desktop_lib::mock_backend::serve;
+
+fn start_mock() -> u16 {
+    tauri::async_runtime::block_on(async {
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tauri::async_runtime::spawn(serve(listener));
+        port
+    })
+}
+
+#[test]
+fn answers_health_checks() {
+    let port = start_mock();
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream
+        .write_all(b"GET /api/health HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with(r#"{"status":"healthy"}"#));
+}
+
+#[test]
+fn replies_to_channel_joins_over_websocket() {
+    let port = start_mock();
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream
+        .write_all(
+            b"GET /socket/websocket?vsn=2.0.0 HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+              Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .unwrap();
+
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert!(line.starts_with("HTTP/1.1 101"));
+    while line != "\r\n" {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+    }
+
+    // Client frames are masked
+    let join = br#"["1","1","graph:main","phx_join",{}]"#;
+    let mask = [1u8, 2, 3, 4];
+    let mut frame = vec![0x81, 0x80 | join.len() as u8];
+    frame.extend_from_slice(&mask);
+    frame.extend(join.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    stream.write_all(&frame).unwrap();
+
+    let mut header = [0u8; 2];
+    reader.read_exact(&mut header).unwrap();
+    assert_eq!(header[0], 0x81);
+    let mut reply = vec![0u8; header[1] as usize];
+    reader.read_exact(&mut reply).unwrap();
+    let reply: serde_json::Value = serde_json::from_slice(&reply).unwrap();
+    assert_eq!(reply[3], "phx_reply");
+    assert_eq!(reply[4]["status"], "ok");
+}
@@ -26,6 +26,9 @@ import { useSettingsStore } from './stores/settingsStore';
 import { useLogStore } from './stores/logStore';
 import { useUIStore } from './stores/uiStore';
 import { useViewStore } from './stores/viewStore';
+import { useChatStore } from './stores/chatStore';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { NodeSpecsProvider } from './contexts/NodeSpecsContext';
 import { unlockAudio, playStartSound, playCompleteSound, playStopSound, playSound } from './lib/sounds';
 import {
@@ -105,6 +108,9 @@ function App() {
   // Stopping state - tracks when stop is in progress
   const [isStopping, setIsStopping] = useState(false);
 
+  // The desktop shell is waiting for the backend to exit before closing the window
+  const [shuttingDown, setShuttingDown] = useState(false);
+
   // Track disconnect notification ID for dismissal on reconnect
   const disconnectNotificationRef = useRef<string | null>(null);
 
@@ -452,6 +458,180 @@ function App() {
     }
   }, [loadWorkflowFromContent, addRecentFile]);
 
+  // Items opened from system search arrive as deep links queued by the desktop shell
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+
+    const openDeepLinks = async () => {
+      const links = await invoke<Array<{ kind: 'workflow' | 'chat'; id: string }>>('take_deep_links');
+      for (const link of links) {
+        if (link.kind === 'workflow') {
+          useViewStore.getState().setCurrentView('node');
+          await handleOpenRecentFile(link.id);
+        } else {
+          useViewStore.getState().setCurrentView('chat');
+          useChatStore.getState().setActiveSession(link.id);
+        }
+      }
+    };
+
+    openDeepLinks();
+    const unlisten = listen('deep-link', openDeepLinks);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [handleOpenRecentFile]);
+
+  // Reminders scheduled through the desktop shell also show up in the notification center
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+
+    const unlisten = listen<{ title: string; body?: string | null }>('reminder-fired', (event) => {
+      notify.info(event.payload.title, { description: event.payload.body ?? undefined });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Downloads cut off by a crash or power loss continue once the backend is reachable
+  const resumedInterrupted = useRef(false);
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+    if (!connected || resumedInterrupted.current) return;
+    resumedInterrupted.current = true;
+
+    invoke<{ id: number; kind: string; dest: string }[]>('list_interrupted')
+      .then((entries) => {
+        for (const entry of entries.filter((e) => e.kind === 'download')) {
+          notify.info('Resuming interrupted download', { description: entry.dest, silent: true });
+          invoke<string>('resume_interrupted', { id: entry.id }).catch((e) =>
+            notify.error('Could not resume download', { description: String(e) })
+          );
+        }
+      })
+      .catch(() => {});
+  }, [connected]);
+
+  // Input keeps the kiosk idle timer from resetting the app (no-op outside kiosk mode)
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+
+    let lastReport = 0;
+    const onActivity = () => {
+      const now = Date.now();
+      if (now - lastReport < 10_000) return;
+      lastReport = now;
+      invoke('report_activity').catch(() => {});
+    };
+    const events = ['pointerdown', 'keydown', 'wheel'] as const;
+    events.forEach((name) => window.addEventListener(name, onActivity, { passive: true }));
+    return () => {
+      events.forEach((name) => window.removeEventListener(name, onActivity));
+    };
+  }, []);
+
+  // The shell reloads or recreates the window when these stop, reopening the item sent along
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+
+    const beat = () => {
+      let route: string | null = null;
+      const view = useViewStore.getState().currentView;
+      const filePath = useWorkflowStore.getState().getActiveTab()?.filePath;
+      const sessionId = useChatStore.getState().activeSessionId;
+      if (view === 'node' && filePath) {
+        route = `leaxer://open/workflow/${encodeURIComponent(filePath)}`;
+      } else if (view === 'chat' && sessionId) {
+        route = `leaxer://open/chat/${encodeURIComponent(sessionId)}`;
+      }
+      invoke<boolean>('ui_heartbeat', { route })
+        .then((recovered) => {
+          if (recovered) {
+            notify.warning('The interface crashed and was recovered', {
+              description: 'Your work on the backend was not affected.',
+            });
+          }
+        })
+        .catch(() => {});
+    };
+    beat();
+    const interval = window.setInterval(beat, 5_000);
+    return () => window.clearInterval(interval);
+  }, []);
+
+  // The shell respawns a crashed backend; the socket reconnects on its own once it is back
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+
+    const unlisten = listen<{ state: string; attempt: number; max_attempts: number; delay_ms?: number | null }>(
+      'backend-status',
+      (event) => {
+        const { state, attempt, max_attempts, delay_ms } = event.payload;
+        if (state === 'restarting') {
+          notify.warning('Backend stopped unexpectedly, restarting...', {
+            description: `Attempt ${attempt} of ${max_attempts} in ${Math.round((delay_ms ?? 0) / 1000)}s`,
+          });
+        } else if (state === 'failed') {
+          notify.error('Backend keeps crashing', {
+            description: 'Restart Leaxer, or check the logs for details.',
+          });
+        }
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+
+    const unlisten = listen('app-shutting-down', () => setShuttingDown(true));
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // The shell's watchdog noticed the backend stopped answering health checks
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+
+    const unlisten = listen<{ failures: number; restarting: boolean }>('backend-unresponsive', (event) => {
+      notify.warning('Backend is not responding', {
+        description: event.payload.restarting
+          ? 'Restarting it...'
+          : 'Restart the backend from the hardware monitor if it does not recover.',
+      });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Switching profiles (tray menu or settings) moves the backend to the profile's port
+  useEffect(() => {
+    if (!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) return;
+
+    const unlisten = listen<{ profile: string; port: number; state: string; error?: string | null }>(
+      'profile-status',
+      (event) => {
+        const { profile, port, state, error } = event.payload;
+        if (state === 'ready') {
+          useSettingsStore.getState().setBackendUrl(`ws://localhost:${port}/socket`);
+          window.location.reload();
+        } else if (state === 'failed') {
+          notify.error(`Profile "${profile}" failed to start`, { description: error ?? undefined });
+        } else if (state === 'stopping') {
+          notify.info('Switching profile...');
+        }
+      }
+    );
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   const handleSaveFile = useCallback(async () => {
     const tab = getActiveTab();
     if (!tab) return;
@@ -666,6 +846,18 @@ function App() {
           onSave={handleSaveAsConfirm}
           onCancel={() => setSaveAsDialogOpen(false)}
         />
+
+        {/* Shutdown indicator while the backend exits */}
+        {shuttingDown && (
+          <div
+            className="absolute inset-0 z-[9999] flex items-center justify-center"
+            style={{ backgroundColor: 'rgba(0, 0, 0, 0.6)' }}
+          >
+            <div className="text-sm" style={{ color: 'var(--color-text)' }}>
+              Shutting down...
+            </div>
+          </div>
+        )}
       </div>
     </NodeSpecsProvider>
   );
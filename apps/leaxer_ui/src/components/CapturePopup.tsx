@@ -0,0 +1,108 @@
+import { useCallback, useEffect, useState } from 'react';
+import ReactMarkdown from 'react-markdown';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { useChatWebSocket } from '@/hooks/useChatWebSocket';
+import { useChatStore } from '@/stores/chatStore';
+import { DEFAULT_CHAT_SETTINGS, type ChatCompletionMessage } from '@/types/chat';
+
+interface Capture {
+  text: string;
+  action: 'summarize' | 'translate';
+  truncated: boolean;
+}
+
+/** Instruction sent ahead of the captured text for each action */
+function promptFor(capture: Capture): ChatCompletionMessage[] {
+  const instruction =
+    capture.action === 'translate'
+      ? `Translate the following text into ${navigator.language}. Reply with the translation only.`
+      : 'Summarize the following text in a few sentences.';
+  return [
+    { role: 'system', content: instruction },
+    { role: 'user', content: capture.text },
+  ];
+}
+
+/**
+ * Popup shown by the desktop shell's "capture selection" hotkey.
+ * Runs the configured action on the captured text and streams the result.
+ */
+export function CapturePopup() {
+  const selectedModel = useChatStore((state) => state.selectedModel);
+  const [capture, setCapture] = useState<Capture | null>(null);
+  const [result, setResult] = useState('');
+  const [error, setError] = useState<string | null>(null);
+  const [running, setRunning] = useState(false);
+
+  const { connected, sendMessage } = useChatWebSocket({
+    onStreamChunk: (chunk) => setResult((prev) => prev + chunk.content),
+    onGenerationComplete: () => setRunning(false),
+    onGenerationError: (payload) => {
+      setError(payload.error);
+      setRunning(false);
+    },
+  });
+
+  const takeCapture = useCallback(() => {
+    invoke<Capture | null>('take_capture').then((next) => {
+      if (next) {
+        setCapture(next);
+        setResult('');
+        setError(null);
+      }
+    });
+  }, []);
+
+  // The shell stores the capture before opening or focusing this window
+  useEffect(() => {
+    takeCapture();
+    const unlisten = listen('capture-ready', takeCapture);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [takeCapture]);
+
+  useEffect(() => {
+    if (!capture || !connected || running || result || error) return;
+    if (!selectedModel) {
+      setError('Select a chat model in Leaxer first.');
+      return;
+    }
+    setRunning(true);
+    sendMessage(promptFor(capture), selectedModel, DEFAULT_CHAT_SETTINGS).catch((e: Error) => {
+      setError(e.message);
+      setRunning(false);
+    });
+    // Only start once per capture
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [capture, connected, selectedModel]);
+
+  useEffect(() => {
+    const handleKeyDown = (e: KeyboardEvent) => {
+      if (e.key === 'Escape') {
+        e.preventDefault();
+        window.close();
+      }
+    };
+    window.addEventListener('keydown', handleKeyDown);
+    return () => window.removeEventListener('keydown', handleKeyDown);
+  }, []);
+
+  return (
+    <div
+      className="h-screen overflow-y-auto p-4 text-sm"
+      style={{ backgroundColor: 'var(--color-surface-0)', color: 'var(--color-text)' }}
+    >
+      <h3 className="text-xs font-semibold mb-2 capitalize" style={{ color: 'var(--color-text-muted)' }}>
+        {capture ? capture.action : 'Waiting for selection'}
+        {capture?.truncated && ' (selection truncated)'}
+      </h3>
+      {error ? (
+        <p style={{ color: 'var(--color-error)' }}>{error}</p>
+      ) : (
+        <ReactMarkdown>{result || (running ? '…' : '')}</ReactMarkdown>
+      )}
+    </div>
+  );
+}
@@ -1,6 +1,7 @@
 import { useState, useMemo, useCallback } from 'react';
 import { Cpu, MemoryStick, Gauge, RotateCw, Trash2, Terminal } from 'lucide-react';
 import { cn } from '@/lib/utils';
+import { invoke } from '@tauri-apps/api/core';
 import { apiFetch } from '@/lib/fetch';
 import { useHardwareChannel } from '@/hooks/useHardwareChannel';
 import { useQueueStore } from '@/stores/queueStore';
@@ -156,6 +157,15 @@ export function HardwareMonitor() {
     setServerRestarting(true);
     clearJobs();
 
+    // The desktop shell respawns the process, which also applies launch-time settings
+    if ((window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) {
+      invoke('restart_backend').catch((e) => {
+        log.error('Backend restart failed', e);
+        setServerRestarting(false);
+      });
+      return;
+    }
+
     try {
       const apiBaseUrl = getApiBaseUrl();
       await apiFetch(`${apiBaseUrl}/api/system/restart`, { method: 'POST' });
@@ -178,8 +188,13 @@ export function HardwareMonitor() {
     setIsCleaningUp(true);
 
     try {
-      const apiBaseUrl = getApiBaseUrl();
-      await apiFetch(`${apiBaseUrl}/api/system/cleanup`, { method: 'POST' });
+      // Behind a desktop shell the backend only takes cleanups the shell sends
+      if ((window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) {
+        await invoke('cleanup_backend');
+      } else {
+        const apiBaseUrl = getApiBaseUrl();
+        await apiFetch(`${apiBaseUrl}/api/system/cleanup`, { method: 'POST' });
+      }
       log.debug('System cleanup completed');
     } catch {
       log.debug('System cleanup initiated');
@@ -521,11 +521,23 @@ export function SettingsModal({ isOpen, onClose }: SettingsModalProps) {
                             </p>
                             <p className="text-[11px] mt-1" style={{ color: 'var(--color-text-muted)' }}>Backend URL (set in Settings):</p>
                             <p className="text-[12px] font-mono" style={{ color: 'var(--color-text)' }}>
-                              ws://{ip}:{networkInfo.backend_port}/socket
+                              {networkInfo.tls
+                                ? `wss://${ip}:${networkInfo.tls.port}/socket`
+                                : `ws://${ip}:${networkInfo.backend_port}/socket`}
                             </p>
                           </div>
                         ))}
                       </div>
+                      {networkInfo.tls?.fingerprint && (
+                        <div className="mt-2">
+                          <p className="text-[11px]" style={{ color: 'var(--color-text-muted)' }}>
+                            Certificate fingerprint (SHA-256), check it when a device asks to trust the certificate:
+                          </p>
+                          <p className="text-[11px] font-mono break-all" style={{ color: 'var(--color-text)' }}>
+                            {networkInfo.tls.fingerprint}
+                          </p>
+                        </div>
+                      )}
                     </div>
                   </div>
                 </div>
@@ -2,6 +2,7 @@ import { useEffect, useRef, useCallback, useState } from 'react';
 import { Socket, Channel } from 'phoenix';
 import { useSettingsStore } from '@/stores/settingsStore';
 import { useChatStore } from '@/stores/chatStore';
+import { socketTransportOptions } from '@/lib/backendTransport';
 import type {
   ChatCompletionMessage,
   ChatSettings,
@@ -128,6 +129,7 @@ export function useChatWebSocket(options: UseChatWebSocketOptions = {}) {
     log.debug('Connecting to chat WebSocket at:', url);
 
     const socket = new Socket(url, {
+      ...socketTransportOptions(url),
       reconnectAfterMs: (tries: number) => Math.min(1000 * Math.pow(2, tries - 1), 10000),
       heartbeatIntervalMs: 30000,
     });
@@ -3,6 +3,7 @@ import { Socket, Channel } from 'phoenix';
 import { useDownloadStore, type ActiveDownload, type DownloadStatus } from '../stores/downloadStore';
 import { useSettingsStore } from '../stores/settingsStore';
 import { createLogger } from '../lib/logger';
+import { socketTransportOptions } from '../lib/backendTransport';
 
 const log = createLogger('downloads');
 
@@ -60,7 +61,7 @@ export function useDownloadChannel() {
       .replace(/\/socket\/?$/, '')      // remove trailing /socket if present
       .replace(/\/$/, '');              // remove trailing slash
     wsUrl = `${wsUrl}/socket`;          // add /socket
-    const socket = new Socket(wsUrl);
+    const socket = new Socket(wsUrl, socketTransportOptions(wsUrl));
 
     socket.connect();
     socketRef.current = socket;
@@ -1,6 +1,7 @@
 import { useEffect, useRef, useState, useCallback } from 'react';
 import { Socket, Channel } from 'phoenix';
 import { createLogger } from '../lib/logger';
+import { socketTransportOptions } from '../lib/backendTransport';
 
 const log = createLogger('HardwareChannel');
 
@@ -56,7 +57,7 @@ export function useHardwareChannel(options: UseHardwareChannelOptions = {}) {
   useEffect(() => {
     if (!enabled) return;
 
-    const socket = new Socket(url);
+    const socket = new Socket(url, socketTransportOptions(url));
     socket.connect();
     socketRef.current = socket;
 
@@ -4,6 +4,7 @@ import type { ExecutionProgress, ExecutionComplete, ExecutionError, StepProgress
 import type { LogEntry, LogBatch, LogChannelJoinResponse } from '../types/logs';
 import type { QueueUpdatedPayload, JobCompletedPayload, JobErrorPayload, WorkflowSnapshot } from '../types/queue';
 import { createLogger } from '../lib/logger';
+import { socketTransportOptions } from '../lib/backendTransport';
 
 const log = createLogger('WebSocket');
 
@@ -128,6 +129,7 @@ export function useWebSocket(options: UseWebSocketOptions = {}) {
     document.addEventListener('visibilitychange', handleVisibilityChange);
 
     const socket = new Socket(url, {
+      ...socketTransportOptions(url),
       // Reconnection settings for better reliability
       reconnectAfterMs: (tries: number) => {
         // Exponential backoff: 1s, 2s, 4s, 8s, then cap at 10s
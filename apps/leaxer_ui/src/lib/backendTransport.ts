@@ -0,0 +1,13 @@
+/**
+ * The desktop shell can run the backend on a unix socket and proxy requests to it through
+ * the `leaxer-backend:` protocol. Custom protocols can't carry WebSockets, so sockets on
+ * that protocol long-poll instead.
+ */
+
+import { LongPoll } from 'phoenix';
+
+export const isProxiedBackendUrl = (url: string): boolean => url.startsWith('leaxer-backend:');
+
+/** Extra Phoenix Socket options for a backend at `url` */
+export const socketTransportOptions = (url: string): { transport?: typeof LongPoll } =>
+  isProxiedBackendUrl(url) ? { transport: LongPoll } : {};
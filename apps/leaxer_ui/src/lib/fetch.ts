@@ -4,6 +4,7 @@
  */
 
 import { fetch as tauriFetch } from '@tauri-apps/plugin-http';
+import { isProxiedBackendUrl } from './backendTransport';
 
 // Check if we're running in Tauri
 const isTauri = typeof window !== 'undefined' && '__TAURI_INTERNALS__' in window;
@@ -16,7 +17,9 @@ export async function apiFetch(
   input: string | URL | Request,
   init?: RequestInit
 ): Promise<Response> {
-  if (isTauri) {
+  const url = input instanceof Request ? input.url : input.toString();
+  // The shell's socket proxy is a webview protocol, out of reach of the HTTP plugin
+  if (isTauri && !isProxiedBackendUrl(url)) {
     // Use Tauri's HTTP plugin which bypasses browser restrictions
     return tauriFetch(input, init);
   }
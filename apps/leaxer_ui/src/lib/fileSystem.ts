@@ -7,12 +7,14 @@
  */
 
 import { apiFetch } from '@/lib/fetch';
+import { useSettingsStore } from '@/stores/settingsStore';
 
 // ============================================================================
 // API Configuration
 // ============================================================================
 
-const API_BASE = import.meta.env.VITE_API_URL || 'http://localhost:4000';
+// Follows the settings, which point at the active desktop profile's backend
+const apiBase = (): string => import.meta.env.VITE_API_URL || useSettingsStore.getState().getApiBaseUrl();
 
 // ============================================================================
 // Types
@@ -48,7 +50,7 @@ export const isTauri = () => {
  */
 export async function listWorkflows(): Promise<WorkflowFile[]> {
   try {
-    const response = await apiFetch(`${API_BASE}/api/workflows`);
+    const response = await apiFetch(`${apiBase()}/api/workflows`);
     if (!response.ok) {
       throw new Error(`Failed to list workflows: ${response.statusText}`);
     }
@@ -64,7 +66,7 @@ export async function listWorkflows(): Promise<WorkflowFile[]> {
  * Load a workflow by name from the backend
  */
 export async function loadWorkflow(name: string): Promise<unknown> {
-  const response = await apiFetch(`${API_BASE}/api/workflows/${encodeURIComponent(name)}`);
+  const response = await apiFetch(`${apiBase()}/api/workflows/${encodeURIComponent(name)}`);
   if (!response.ok) {
     if (response.status === 404) {
       throw new Error(`Workflow '${name}' not found`);
@@ -78,7 +80,7 @@ export async function loadWorkflow(name: string): Promise<unknown> {
  * Save a workflow to the backend (saves to ~/Documents/Leaxer/workflows)
  */
 export async function saveWorkflow(name: string, workflow: unknown): Promise<SaveWorkflowResult> {
-  const response = await apiFetch(`${API_BASE}/api/workflows`, {
+  const response = await apiFetch(`${apiBase()}/api/workflows`, {
     method: 'POST',
     headers: {
       'Content-Type': 'application/json',
@@ -98,7 +100,7 @@ export async function saveWorkflow(name: string, workflow: unknown): Promise<Sav
  * Delete a workflow from the backend
  */
 export async function deleteWorkflow(name: string): Promise<void> {
-  const response = await apiFetch(`${API_BASE}/api/workflows/${encodeURIComponent(name)}`, {
+  const response = await apiFetch(`${apiBase()}/api/workflows/${encodeURIComponent(name)}`, {
     method: 'DELETE',
   });
 
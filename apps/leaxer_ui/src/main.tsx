@@ -1,17 +1,72 @@
 import { StrictMode } from 'react'
 import { createRoot } from 'react-dom/client'
-import { getCurrentWindow } from '@tauri-apps/api/window'
+import { getCurrentWindow, Window as TauriWindow } from '@tauri-apps/api/window'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import { notify } from './lib/notify'
 import '@xyflow/react/dist/style.css'
 import './index.css'
 import App from './App.tsx'
+import { CapturePopup } from './components/CapturePopup'
 
-// Show window after content is fully loaded (prevents white flash on Tauri)
+// Show window after content is fully loaded (prevents white flash on Tauri) and the
+// backend answers its health check (prevents connection errors while it boots).
 // Only run in Tauri environment
 if ((window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__) {
+  // The shell knows the backend's actual port (it may have moved off 4000) and host.
+  // Comparison windows are told about their own backend by the shell instead.
+  if (getCurrentWindow().label === 'main') {
+    invoke<{ ws_url: string }>('get_backend_url')
+      .then(({ ws_url }) => {
+        (window as Window & { __LEAXER_BACKEND_URL__?: string }).__LEAXER_BACKEND_URL__ = ws_url
+      })
+      .catch(() => {})
+  }
+
+  // Live server output for the devtools console; the shell only sends it in dev builds
+  // and developer mode
+  if (getCurrentWindow().label === 'main') {
+    listen<{ level: string; message: string }>('backend-log', ({ payload }) => {
+      const write =
+        payload.level === 'error' ? console.error
+        : payload.level === 'warning' ? console.warn
+        : payload.level === 'debug' ? console.debug
+        : console.info
+      write('[backend]', payload.message)
+    })
+  }
+
   window.addEventListener('load', () => {
-    setTimeout(() => {
-      getCurrentWindow().show()
-    }, 100)
+    const show = (then?: () => void) => {
+      setTimeout(() => getCurrentWindow().show().then(then), 100)
+    }
+    // Popups don't depend on the backend
+    if (getCurrentWindow().label !== 'main') {
+      show()
+      return
+    }
+    invoke<{ state: 'ready' } | { state: 'failed'; error: string }>('wait_for_backend')
+      .then(async (readiness) => {
+        if (readiness.state === 'failed') {
+          // The splash window shows the error and lets the user open the app anyway
+          if (await TauriWindow.getByLabel('splash')) return
+          notify.error('Backend failed to start', { description: readiness.error, duration: 0 })
+        }
+        // The main window replaces the splash
+        show(() => invoke('close_splash').catch(() => {}))
+      })
+      .catch(() => show())
+  })
+
+  // F12 / Ctrl+Shift+I open devtools (the shell rejects this unless developer_mode is enabled)
+  window.addEventListener('keydown', (event) => {
+    const isDevtoolsShortcut =
+      event.key === 'F12' ||
+      (event.ctrlKey && event.shiftKey && event.key.toLowerCase() === 'i')
+    if (isDevtoolsShortcut) {
+      event.preventDefault()
+      invoke('open_devtools').catch(() => {})
+    }
   })
 }
 
@@ -39,9 +94,14 @@ const cleanupOldLocalStorage = () => {
 
 cleanupOldLocalStorage();
 
+// The shell's capture hotkey opens a popup window that only shows the capture result
+const isCaptureWindow =
+  !!(window as Window & { __TAURI_INTERNALS__?: unknown }).__TAURI_INTERNALS__ &&
+  getCurrentWindow().label === 'capture'
+
 createRoot(document.getElementById('root')!).render(
   <StrictMode>
-    <App />
+    {isCaptureWindow ? <CapturePopup /> : <App />}
   </StrictMode>,
 )
 
@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
+import { invoke } from '@tauri-apps/api/core';
 import {
   type SoundName,
   DEFAULT_SOUNDS,
@@ -97,6 +98,10 @@ const getDefaultBackendUrl = (): string => {
   return `ws://${hostname}:4000/socket`;
 };
 
+// Comparison windows opened by the desktop shell talk to their own temporary backend
+const getBackendOverride = (): string | undefined =>
+  (window as Window & { __LEAXER_BACKEND_URL__?: string }).__LEAXER_BACKEND_URL__;
+
 // Get stored backend URL or compute default
 const getStoredBackendUrl = (): string => {
   try {
@@ -122,6 +127,8 @@ export interface NetworkInfo {
   current_binding: string;
   backend_port: number;
   frontend_port: number;
+  /** HTTPS listener for other devices, when the desktop shell provides a certificate */
+  tls?: { port: number; fingerprint: string | null } | null;
 }
 
 interface SettingsState {
@@ -199,7 +206,7 @@ export const useSettingsStore = create<SettingsState>()(
       backendUrl: getStoredBackendUrl(),
       setBackendUrl: (backendUrl) => set({ backendUrl }),
       getApiBaseUrl: () => {
-        const wsUrl = get().backendUrl;
+        const wsUrl = getBackendOverride() ?? get().backendUrl;
         // Convert ws://host:port/socket to http://host:port
         let baseUrl = wsUrl.replace(/\/socket$/, '').replace(/^ws/, 'http');
 
@@ -228,7 +235,7 @@ export const useSettingsStore = create<SettingsState>()(
         return baseUrl;
       },
       getBackendWsUrl: () => {
-        const wsUrl = get().backendUrl;
+        const wsUrl = getBackendOverride() ?? get().backendUrl;
 
         // If the stored URL uses localhost but we're accessing from a different host,
         // dynamically substitute the current hostname (for LAN access)
@@ -386,6 +393,14 @@ export const useSettingsStore = create<SettingsState>()(
 
       setNetworkExposureEnabled: async (enabled: boolean) => {
         try {
+          // The desktop app restarts its backend with the new binding right away
+          if ('__TAURI_INTERNALS__' in window) {
+            await invoke('set_network_exposure', { enabled });
+            set({ networkExposureEnabled: enabled, networkRestartRequired: false });
+            await get().fetchNetworkInfo();
+            return;
+          }
+
           const apiBaseUrl = get().getApiBaseUrl();
           const response = await fetch(`${apiBaseUrl}/api/settings`, {
             method: 'PUT',
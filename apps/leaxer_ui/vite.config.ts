@@ -13,10 +13,17 @@ function getLeaxerUserDir(): string {
   const platform = os.platform()
   const homeDir = os.homedir()
 
+  // Older versions kept data in Documents/Leaxer; the desktop app moves it on launch
+  const withLegacyFallback = (dir: string) => {
+    const legacy = path.join(homeDir, 'Documents', 'Leaxer')
+    return !fs.existsSync(dir) && fs.existsSync(legacy) ? legacy : dir
+  }
+
   if (platform === 'win32') {
-    return path.join(homeDir, 'Documents', 'Leaxer')
+    const localAppData = process.env.LOCALAPPDATA || path.join(homeDir, 'AppData', 'Local')
+    return withLegacyFallback(path.join(localAppData, 'Leaxer'))
   } else if (platform === 'darwin') {
-    return path.join(homeDir, 'Documents', 'Leaxer')
+    return withLegacyFallback(path.join(homeDir, 'Library', 'Application Support', 'Leaxer'))
   } else {
     // Linux - use XDG spec
     const xdgData = process.env.XDG_DATA_HOME || path.join(homeDir, '.local', 'share')
@@ -50,8 +50,35 @@ defmodule RuntimeHelpers do
       end
     end
   end
+
+  # A backend run as a system service by the desktop shell gets its settings and keys
+  # from files listed in LEAXER_ENV_FILES (separated like PATH). Variables that are
+  # already set win.
+  def load_env_files do
+    separator = if match?({:win32, _}, :os.type()), do: ";", else: ":"
+
+    (System.get_env("LEAXER_ENV_FILES") || "")
+    |> String.split(separator, trim: true)
+    |> Enum.flat_map(fn path ->
+      case File.read(path) do
+        {:ok, content} -> String.split(content, ["\r\n", "\n"], trim: true)
+        _ -> []
+      end
+    end)
+    |> Enum.each(fn line ->
+      case String.split(line, "=", parts: 2) do
+        [name, value] when name != "" ->
+          if System.get_env(name) == nil, do: System.put_env(name, value)
+
+        _ ->
+          :ok
+      end
+    end)
+  end
 end
 
+RuntimeHelpers.load_env_files()
+
 # ## Using releases
 #
 # If you use `mix release`, you need to explicitly enable the server
@@ -123,6 +150,8 @@ network_exposure_enabled = RuntimeHelpers.network_exposure_enabled?()
 # In development, this uses sensible defaults for common dev server ports
 # In production, set CORS_ORIGINS env var to a comma-separated list of allowed origins
 # Example: CORS_ORIGINS="https://app.example.com,https://admin.example.com"
+# The desktop shell sets it on every spawn from the backend's port, its LAN URLs when
+# exposed and the app's own origins.
 #
 # When network exposure is enabled, allow private network IP ranges
 cors_origins =
@@ -206,8 +235,15 @@ if config_env() == :prod do
   # Bind to all interfaces when:
   # 1. LEAXER_BIND_ALL_INTERFACES=true env var is set (for Docker, k8s, etc.)
   # 2. network_exposure_enabled is true in config.json (user enabled LAN access)
+  #
+  # With a certificate from the desktop shell, other devices are served over HTTPS on
+  # LEAXER_TLS_PORT and plain HTTP stays on loopback.
+  tls_certfile = System.get_env("LEAXER_TLS_CERTFILE")
+  tls_keyfile = System.get_env("LEAXER_TLS_KEYFILE")
+  tls_enabled = network_exposure_enabled and is_binary(tls_certfile) and is_binary(tls_keyfile)
+
   ip_binding =
-    if network_exposure_enabled do
+    if network_exposure_enabled and not tls_enabled do
       {0, 0, 0, 0}
     else
       {127, 0, 0, 1}
@@ -228,6 +264,20 @@ if config_env() == :prod do
       ]
     ]
 
+  if tls_enabled do
+    config :leaxer_core, LeaxerCoreWeb.Endpoint,
+      https: [
+        ip: {0, 0, 0, 0},
+        port: String.to_integer(System.get_env("LEAXER_TLS_PORT", "4443")),
+        cipher_suite: :strong,
+        certfile: tls_certfile,
+        keyfile: tls_keyfile,
+        thousand_island_options: [
+          read_timeout: 300_000
+        ]
+      ]
+  end
+
   # ## SSL Support
   #
   # To get SSL working, you will need to add the `https` key
@@ -260,3 +310,16 @@ if config_env() == :prod do
   #
   # Check `Plug.SSL` for all available options in `force_ssl`.
 end
+
+# The desktop shell can have the backend listen on a unix socket instead of a port
+# (`backend_transport` "unix" in config.json) and proxy the webview's requests to it
+if socket = System.get_env("LEAXER_SOCKET") do
+  config :leaxer_core, LeaxerCoreWeb.Endpoint, http: [ip: {:local, socket}, port: 0]
+end
+
+# Verbosity chosen in the desktop shell (`log_level` in config.json or LEAXER_LOG_LEVEL)
+if level = System.get_env("LEAXER_LOG_LEVEL") do
+  if level in ~w(debug info warning error) do
+    config :logger, level: String.to_atom(level)
+  end
+end